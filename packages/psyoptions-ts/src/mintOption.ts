@@ -48,6 +48,11 @@ export const mintOptionsTx = async (
       isSigner: false,
     });
   }
+  // Pausable instructions take the PauseConfig PDA whether or not it exists
+  const [pauseConfig] = await PublicKey.findProgramAddress(
+    [new TextEncoder().encode('pauseConfig')],
+    program.programId,
+  );
   await program.rpc.mintOption(size, {
     accounts: {
       userAuthority: minter.publicKey,
@@ -65,6 +70,7 @@ export const mintOptionsTx = async (
       clock: SYSVAR_CLOCK_PUBKEY,
       rent: SYSVAR_RENT_PUBKEY,
       systemProgram: SystemProgram.programId,
+      pauseConfig,
     },
     remainingAccounts,
     signers: [minter],
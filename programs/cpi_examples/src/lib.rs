@@ -3,10 +3,9 @@ use anchor_lang::prelude::*;
 use anchor_lang::InstructionData;
 use anchor_spl::dex::Dex;
 use anchor_spl::token::{self, Mint, TokenAccount, Token, Transfer};
-use anchor_spl::dex::serum_dex;
 use anchor_spl::dex::serum_dex::{instruction::SelfTradeBehavior as SerumSelfTradeBehavior, matching::{OrderType as SerumOrderType, Side as SerumSide}};
 use psy_american::cpi::accounts::{ExerciseOption, MintOptionV2};
-use psy_american::OptionMarket;
+use psy_american::{proxy_cpi, OptionMarket};
 use std::num::NonZeroU64;
 use solana_program::msg;
 
@@ -81,7 +80,9 @@ pub mod cpi_examples {
             underlying_amount_per_contract,
             quote_amount_per_contract,
             expiration_unix_timestamp,
-            bump_seed
+            bump_seed,
+            // Whole contracts, one option token per contract
+            option_decimals: 0,
         };
         let mut cpi_accounts = vec![
             ctx.accounts.user.to_account_metas(Some(true))[0].clone(),
@@ -105,6 +106,16 @@ pub mod cpi_examples {
             ctx.accounts.rent.to_account_metas(Some(false))[0].clone(),
             ctx.accounts.system_program.to_account_metas(Some(false))[0].clone(),
             ctx.accounts.clock.to_account_metas(Some(false))[0].clone(),
+            // The listing PDAs that decide whether the market is official
+            ctx.accounts.listing_registry.to_account_metas(Some(false))[0].clone(),
+            ctx.accounts.listed_pair.to_account_metas(Some(false))[0].clone(),
+            ctx.accounts.lister.to_account_metas(Some(false))[0].clone(),
+            // The pair's series grid, if it has one
+            ctx.accounts.series_config.to_account_metas(Some(false))[0].clone(),
+            // The page of the pair's OptionChain index the market is added to
+            ctx.accounts.option_chain.to_account_metas(Some(false))[0].clone(),
+            // The PauseConfig, which may not exist
+            ctx.accounts.pause_config.to_account_metas(Some(false))[0].clone(),
         ];
         // msg!("cpi_accounts {:?}", cpi_accounts);
         let mut account_infos = vec![
@@ -122,6 +133,12 @@ pub mod cpi_examples {
             ctx.accounts.rent.to_account_info().clone(),
             ctx.accounts.system_program.to_account_info().clone(),
             ctx.accounts.clock.to_account_info().clone(),
+            ctx.accounts.listing_registry.to_account_info().clone(),
+            ctx.accounts.listed_pair.to_account_info().clone(),
+            ctx.accounts.lister.to_account_info().clone(),
+            ctx.accounts.series_config.to_account_info().clone(),
+            ctx.accounts.option_chain.to_account_info().clone(),
+            ctx.accounts.pause_config.to_account_info().clone(),
         ];
         for remaining_account in ctx.remaining_accounts {
             cpi_accounts.push(remaining_account.to_account_metas(Some(false))[0].clone());
@@ -208,6 +225,8 @@ pub mod cpi_examples {
             option_market: ctx.accounts.option_market.to_account_info(),
             // The rest are self explanatory, we can't spell everything out for you ;)
             token_program: ctx.accounts.token_program.to_account_info(),
+            // The PauseConfig, which may not exist
+            pause_config: ctx.accounts.pause_config.to_account_info(),
         };
         let key = ctx.accounts.underlying_asset_mint.key();
 
@@ -240,6 +259,13 @@ pub mod cpi_examples {
     ) -> Result<()> {
         // **optionally** create the open orders program with CPI to PsyOptions
         let cpi_program = ctx.accounts.psy_american_program.clone();
+        let vault_key = ctx.accounts.vault.key();
+        let vault_authority_seeds =  &[
+            vault_key.as_ref(),
+            b"vaultAuthority",
+            &[vault_authority_bump]
+        ];
+        let signer = &[&vault_authority_seeds[..]];
         if ctx.accounts.open_orders.data_is_empty() {
             // NOTE: Not sure if this is the best way to handle this. But the InitAccount::try_accounts
             //  was failing because the vault_authority did not have any SOL. 
@@ -256,119 +282,49 @@ pub mod cpi_examples {
                 ctx.accounts.system_program.to_account_info(),
             ],
             )?;
-    //////////////// The following is constructed following the JS client middleware for permissioned markets /////////
-            // Basic Serum DEX InitOpenOrders instruction
-            let mut ix = serum_dex::instruction::init_open_orders(
-                &ctx.accounts.dex_program.key,
-                ctx.accounts.open_orders.key,
-                ctx.accounts.vault_authority.key,
-                ctx.accounts.market.key,
-                Some(ctx.accounts.psy_market_authority.key),
-            ).map_err(|_x| CpiExampleErrors::ErrorCode::DexIxError)?;
-            ix.program_id = *cpi_program.key;
-            // TODO: Wrap the necessary Psy American middleware updates to the instruction. 
-            //  Note: only the OpenOrdersPda manipulates this instruction
-            // Override the open orders account and market authority.
-            ix.accounts[0].pubkey = ctx.accounts.open_orders.key();
-            ix.accounts[4].pubkey = ctx.accounts.psy_market_authority.key();
-            ix.accounts[4].is_signer = false;
-            // Writable because it must pay for the PDA initialization.
-            ix.accounts[1].is_writable = true;
-            // Prepend to the account list extra accounts needed for PDA initialization.
-            ix.accounts.insert(0, ctx.accounts.system_program.to_account_metas(Some(false))[0].clone());
-            ix.accounts.insert(0, ctx.accounts.dex_program.to_account_metas(Some(false))[0].clone());
-            // Prepend the ix discriminator, bump, and bumpInit to the instruction data,
-            // which saves the program compute by avoiding recalculating them in the
-            // program.
-            ix.data.insert(0, open_order_bump_init);
-            ix.data.insert(0, open_order_bump);
-            ix.data.insert(0, 0 as u8);
-            // PsyOptions Validation discriminator
-            ix.data.insert(0, 0 as u8);
-
-            // Handle the insertion of the dex program id one for time for the general proxy IX
-            ix.accounts.insert(0, ctx.accounts.dex_program.to_account_metas(Some(false))[0].clone());
-
-            let vault_key = ctx.accounts.vault.key();
-            let vault_authority_seeds =  &[
-                vault_key.as_ref(),
-                b"vaultAuthority",
-                &[vault_authority_bump]
-            ];
             // send initOpenOrders instruction to PsyOptions
-            solana_program::program::invoke_signed(
-                &ix,
-                &[
-                    ctx.accounts.psy_american_program.to_account_info(),
-                    ctx.accounts.dex_program.to_account_info(),
-                    ctx.accounts.open_orders.to_account_info(),
-                    ctx.accounts.vault_authority.to_account_info(),
-                    ctx.accounts.market.to_account_info(),
-                    ctx.accounts.psy_market_authority.to_account_info(),
-                    ctx.accounts.system_program.to_account_info(),
-                    ctx.accounts.rent.to_account_info()
-                ],
-                &[vault_authority_seeds],
-            )?;
+            let cpi_accounts = proxy_cpi::InitOpenOrders {
+                dex_program: ctx.accounts.dex_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                open_orders: ctx.accounts.open_orders.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+                market: ctx.accounts.market.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+                market_authority: ctx.accounts.psy_market_authority.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer);
+            proxy_cpi::init_open_orders(cpi_ctx, open_order_bump, open_order_bump_init)?;
         }
 
-
-        let mut new_order_ix = serum_dex::instruction::new_order(
-            ctx.accounts.market.key,
-            ctx.accounts.open_orders.key,
-            ctx.accounts.request_queue.key,
-            ctx.accounts.event_queue.key,
-            ctx.accounts.market_bids.key,
-            ctx.accounts.market_asks.key,
-            &ctx.accounts.vault.key(),
-            ctx.accounts.vault_authority.key,
-            ctx.accounts.coin_vault.key,
-            ctx.accounts.pc_vault.key,
-            ctx.accounts.token_program.key,
-            &ctx.accounts.rent.key(),
-            None, // optional Serum discount pubkey?
-            ctx.accounts.dex_program.key,
+        // send newOrderV3 instruction to PsyOptions
+        let cpi_accounts = proxy_cpi::NewOrderV3 {
+            dex_program: ctx.accounts.dex_program.to_account_info(),
+            price_band: ctx.accounts.price_band.to_account_info(),
+            price_oracle: ctx.accounts.price_oracle.to_account_info(),
+            market: ctx.accounts.market.to_account_info(),
+            open_orders: ctx.accounts.open_orders.to_account_info(),
+            request_queue: ctx.accounts.request_queue.to_account_info(),
+            event_queue: ctx.accounts.event_queue.to_account_info(),
+            market_bids: ctx.accounts.market_bids.to_account_info(),
+            market_asks: ctx.accounts.market_asks.to_account_info(),
+            order_payer_token_account: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.vault_authority.to_account_info(),
+            coin_vault: ctx.accounts.coin_vault.to_account_info(),
+            pc_vault: ctx.accounts.pc_vault.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        proxy_cpi::new_order_v3(
+            cpi_ctx,
             side.into(),
             NonZeroU64::new(limit_price).unwrap(),
             NonZeroU64::new(max_coin_qty).unwrap(),
+            NonZeroU64::new(max_native_pc_qty_including_fees).unwrap(),
+            self_trade_behavior.into(),
             order_type.into(),
             client_order_id,
-            self_trade_behavior.into(),
             limit,
-            NonZeroU64::new(max_native_pc_qty_including_fees).unwrap()
-        ).map_err(|_x| CpiExampleErrors::ErrorCode::DexIxError)?;
-        new_order_ix.program_id = *cpi_program.key;
-        // insert data for the OpenOrdersPDA middleware
-        new_order_ix.data.insert(0, 1 as u8);
-        // insert data for the PsyOptions Validation middleware
-        new_order_ix.data.insert(0, 1 as u8);
-        // Handle the insertion of the dex program id one for time for the general proxy IX
-        new_order_ix.accounts.insert(0, ctx.accounts.dex_program.to_account_metas(Some(false))[0].clone());
-        // execute the CPI
-        let vault_key = ctx.accounts.vault.key();
-        let vault_authority_seeds =  &[
-            vault_key.as_ref(),
-            b"vaultAuthority",
-            &[vault_authority_bump]
-        ];
-        // send initOpenOrders instruction to PsyOptions
-        solana_program::program::invoke_signed(
-            &new_order_ix,
-            &[
-                ctx.accounts.market.to_account_info(),
-                ctx.accounts.open_orders.to_account_info(),
-                ctx.accounts.request_queue.to_account_info(),
-                ctx.accounts.event_queue.to_account_info(),
-                ctx.accounts.market_bids.to_account_info(),
-                ctx.accounts.market_asks.to_account_info(),
-                ctx.accounts.vault.to_account_info(),
-                ctx.accounts.vault_authority.to_account_info(),
-                ctx.accounts.coin_vault.to_account_info(),
-                ctx.accounts.pc_vault.to_account_info(),
-                ctx.accounts.token_program.to_account_info(),
-                ctx.accounts.rent.to_account_info(),
-            ],
-            &[vault_authority_seeds],
         )?;
 
         Ok(())
@@ -379,47 +335,60 @@ pub mod cpi_examples {
 pub struct InitOptionMarket<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
-    /// CHECK: TODO
+    /// CHECK: Only the target of the CPI, which validates every account it is passed
     pub psy_american_program: AccountInfo<'info>,
     /////// Init OptionMarket accounts
     pub underlying_asset_mint: Box<Account<'info, Mint>>,
     pub quote_asset_mint: Box<Account<'info, Mint>>,
 
     #[account(mut)]
-    /// CHECK: TODO
+    /// CHECK: Initialized and validated by PsyOptions' seeds
     pub option_mint: AccountInfo<'info>,
     #[account(mut)]
-    /// CHECK: TODO
+    /// CHECK: Initialized and validated by PsyOptions' seeds
     pub writer_token_mint: AccountInfo<'info>,
     #[account(mut)]
-    /// CHECK: TODO
+    /// CHECK: Initialized and validated by PsyOptions' seeds
     pub quote_asset_pool: AccountInfo<'info>,
     #[account(mut)]
-    /// CHECK: TODO
+    /// CHECK: Initialized and validated by PsyOptions' seeds
     pub underlying_asset_pool: AccountInfo<'info>,
     #[account(mut)]
-    /// CHECK: TODO
+    /// CHECK: Initialized and validated by PsyOptions' seeds
     pub option_market: AccountInfo<'info>,
-    /// CHECK: TODO
+    /// CHECK: Validated by PsyOptions against the fee owner key
     pub fee_owner: AccountInfo<'info>,
 
     pub token_program: Program<'info, Token>,
-    /// CHECK: TODO
+    /// CHECK: Validated by PsyOptions' fee account constraints
     pub associated_token_program: AccountInfo<'info>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
     pub clock: Sysvar<'info, Clock>,
+    /// CHECK: Validated by PsyOptions' seeds
+    pub listing_registry: AccountInfo<'info>,
+    /// CHECK: Validated by PsyOptions' seeds
+    pub listed_pair: AccountInfo<'info>,
+    /// CHECK: Validated by PsyOptions against the ListedPair
+    pub lister: AccountInfo<'info>,
+    /// CHECK: Validated by PsyOptions' seeds
+    pub series_config: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: Validated by PsyOptions' seeds
+    pub option_chain: AccountInfo<'info>,
+    /// CHECK: Validated by PsyOptions' seeds
+    pub pause_config: AccountInfo<'info>,
 }
 
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(mut, signer)]
-    /// CHECK: TODO
+    /// CHECK: Constrained to be a signer
     pub authority: AccountInfo<'info>,
     #[account(mut)]
     pub option_source: Box<Account<'info, TokenAccount>>,
-    /// CHECK: TODO
+    /// CHECK: Initialized and validated by PsyOptions' seeds
     pub option_mint: AccountInfo<'info>,
     #[account(init,
         seeds = [&option_mint.key().to_bytes()[..], b"vault"],
@@ -429,7 +398,7 @@ pub struct Initialize<'info> {
         token::authority = vault_authority,
     )]
     pub vault: Box<Account<'info, TokenAccount>>,
-    /// CHECK: TODO
+    /// CHECK: The PDA signer for the vault, the signed CPIs fail unless it matches the seeds
     pub vault_authority: AccountInfo<'info>,
     pub token_program: Program<'info, Token>,
     pub rent: Sysvar<'info, Rent>,
@@ -439,12 +408,12 @@ pub struct Initialize<'info> {
 #[derive(Accounts)]
 pub struct Exercise<'info> {
     #[account(mut, signer)]
-    /// CHECK: TODO
+    /// CHECK: Constrained to be a signer
     pub authority: AccountInfo<'info>,
-    /// CHECK: TODO
+    /// CHECK: Only the target of the CPI, which validates every account it is passed
     pub psy_american_program: AccountInfo<'info>,
     #[account(mut)]
-    /// CHECK: TODO
+    /// CHECK: The PDA signer for the vault, the signed CPIs fail unless it matches the seeds
     pub vault_authority: AccountInfo<'info>,
     // Exercise CPI accounts
     option_market: Box<Account<'info, OptionMarket>>,
@@ -461,7 +430,7 @@ pub struct Exercise<'info> {
     #[account(mut)]
     quote_asset_src: Box<Account<'info, TokenAccount>>,
     #[account(mut)]
-    /// CHECK: TODO
+    /// CHECK: Validated by PsyOptions against the fee owner key
     fee_owner: AccountInfo<'info>,
 
     token_program: Program<'info, Token>,
@@ -473,7 +442,7 @@ pub struct Exercise<'info> {
 #[derive(Accounts)]
 pub struct InitMintVault<'info> {
     #[account(mut, signer)]
-    /// CHECK: TODO
+    /// CHECK: Constrained to be a signer
     pub authority: AccountInfo<'info>,
     pub underlying_asset: Box<Account<'info, Mint>>,
     #[account(init,
@@ -484,7 +453,7 @@ pub struct InitMintVault<'info> {
         token::authority = vault_authority,
     )]
     pub vault: Box<Account<'info, TokenAccount>>,
-    /// CHECK: TODO
+    /// CHECK: The PDA signer for the vault, the signed CPIs fail unless it matches the seeds
     pub vault_authority: AccountInfo<'info>,
 
     pub token_program: Program<'info, Token>,
@@ -495,20 +464,20 @@ pub struct InitMintVault<'info> {
 #[derive(Accounts)]
 pub struct MintCtx<'info> {
     #[account(mut, signer)]
-    /// CHECK: TODO
+    /// CHECK: Constrained to be a signer
     pub authority: AccountInfo<'info>,
-    /// CHECK: TODO
+    /// CHECK: Only the target of the CPI, which validates every account it is passed
     pub psy_american_program: AccountInfo<'info>,
     /// The vault where the underlying assets are held. This is the PsyAmerican 
     /// `underlying_asset_src`
     #[account(mut)]
     pub vault: Box<Account<'info, TokenAccount>>,
     #[account(mut)]
-    /// CHECK: TODO
+    /// CHECK: The PDA signer for the vault, the signed CPIs fail unless it matches the seeds
     pub vault_authority: AccountInfo<'info>,
 
     /// Mint CPI acounts
-    /// CHECK: TODO
+    /// CHECK: Validated by PsyOptions against the OptionMarket
     pub underlying_asset_mint: AccountInfo<'info>,
     #[account(mut)]
     pub underlying_asset_pool: Box<Account<'info, TokenAccount>>,
@@ -522,16 +491,18 @@ pub struct MintCtx<'info> {
     pub minted_writer_token_dest: Box<Account<'info, TokenAccount>>,
     pub option_market: Box<Account<'info, OptionMarket>>,
     #[account(mut)]
-    /// CHECK: TODO
+    /// CHECK: Validated by PsyOptions against the fee owner key
     pub fee_owner: AccountInfo<'info>,
 
 
     pub token_program: Program<'info, Token>,
-    /// CHECK: TODO
+    /// CHECK: Validated by PsyOptions' fee account constraints
     pub associated_token_program: AccountInfo<'info>,
     pub clock: Sysvar<'info, Clock>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
+    /// CHECK: Validated by PsyOptions' seeds
+    pub pause_config: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
@@ -547,7 +518,7 @@ pub struct InitNewOrderVault<'info> {
         token::authority = vault_authority,
     )]
     pub vault: Box<Account<'info, TokenAccount>>,
-    /// CHECK: TODO
+    /// CHECK: The PDA signer for the vault, the signed CPIs fail unless it matches the seeds
     pub vault_authority: AccountInfo<'info>,
 
     pub token_program: Program<'info, Token>,
@@ -560,48 +531,54 @@ pub struct PlaceOrder<'info> {
     /// The user who signed and sent the TX from the client
     pub user_authority: Signer<'info>,
     /// The PsyOptions American program ID
-    /// CHECK: TODO
+    /// CHECK: Only the target of the CPI, which validates every account it is passed
     pub psy_american_program: AccountInfo<'info>,
     /// The Serum DEX program ID
     pub dex_program: Program<'info, Dex>,
     /// The vault's OpenOrders account
     #[account(mut)]
-    /// CHECK: TODO
+    /// CHECK: Validated by the Serum DEX, and initialized through PsyOptions when empty
     pub open_orders: AccountInfo<'info>,
     /// The Serum Market
     #[account(mut)]
-    /// CHECK: TODO
+    /// CHECK: Validated by the Serum DEX
     pub market: AccountInfo<'info>,
     /// The Serum Market market authority
-    /// CHECK: TODO
+    /// CHECK: Validated by PsyOptions' seeds
     pub psy_market_authority: AccountInfo<'info>,
     /// The USDC vault account
     #[account(mut)]
     pub vault: Box<Account<'info, TokenAccount>>,
     /// The vault authority that also has authority over the OpenOrders account
-    /// CHECK: TODO
+    /// CHECK: The PDA signer for the vault, the signed CPIs fail unless it matches the seeds
     #[account(mut)]
     pub vault_authority: AccountInfo<'info>,
 
     //// other new_order accounts
-    /// CHECK: TODO
+    /// CHECK: Validated by the Serum DEX against the market
     #[account(mut)]
     pub request_queue: AccountInfo<'info>,
-    /// CHECK: TODO
+    /// CHECK: Validated by the Serum DEX against the market
     #[account(mut)]
     pub event_queue: AccountInfo<'info>,
-    /// CHECK: TODO
+    /// CHECK: Validated by the Serum DEX against the market
     #[account(mut)]
     pub market_bids: AccountInfo<'info>,
-    /// CHECK: TODO
+    /// CHECK: Validated by the Serum DEX against the market
     #[account(mut)]
     pub market_asks: AccountInfo<'info>,
-    /// CHECK: TODO
+    /// CHECK: Validated by the Serum DEX against the market
     #[account(mut)]
     pub coin_vault: AccountInfo<'info>,
-    /// CHECK: TODO
+    /// CHECK: Validated by the Serum DEX against the market
     #[account(mut)]
     pub pc_vault: AccountInfo<'info>,
+    /// The PsyOptions PriceBandConfig PDA for the Serum market
+    /// CHECK: Validated by PsyOptions
+    pub price_band: AccountInfo<'info>,
+    /// The oracle for the PriceBandConfig, or the PriceBandConfig key when the market has none
+    /// CHECK: Validated by PsyOptions
+    pub price_oracle: AccountInfo<'info>,
 
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
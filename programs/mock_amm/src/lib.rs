@@ -0,0 +1,144 @@
+//! A constant price AMM for testing psy_american's net exercise. It implements the swap
+//! interface psy_american calls, which has the layout of SPL Token Swap's `Swap`: a tag of 1
+//! followed by `amount_in` and `minimum_amount_out` as little endian u64s.
+//!
+//! Accounts for a swap:
+//! 0. `[]` The Pool
+//! 1. `[]` The Pool's authority PDA, seeded by the Pool's key
+//! 2. `[signer]` The owner of `source`
+//! 3. `[writable]` source
+//! 4. `[writable]` The Pool's vault for the source asset
+//! 5. `[writable]` The Pool's vault for the destination asset
+//! 6. `[writable]` destination
+//! 7. `[]` The SPL Token program
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    declare_id,
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use std::convert::TryInto;
+
+declare_id!("93vmWpNZwqa8xp7fdKTskU3CTwEAZMZ64iAizwwVbB9t");
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+/// Tag of the swap instruction
+pub const SWAP_TAG: u8 = 1;
+
+/// Error returned when the swap would return less than `minimum_amount_out`
+pub const EXCEEDED_SLIPPAGE: u32 = 0;
+
+/// A Pool swaps any amount in for `amount_in * price_numerator / price_denominator` out
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pool {
+    pub price_numerator: u64,
+    pub price_denominator: u64,
+    /// Bump seed for the Pool's authority PDA
+    pub authority_bump: u8,
+}
+
+impl Pool {
+    pub const LEN: usize = 17;
+
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(Self::LEN);
+        data.extend_from_slice(&self.price_numerator.to_le_bytes());
+        data.extend_from_slice(&self.price_denominator.to_le_bytes());
+        data.push(self.authority_bump);
+        data
+    }
+
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Pool {
+            price_numerator: u64::from_le_bytes(data[0..8].try_into().unwrap()),
+            price_denominator: u64::from_le_bytes(data[8..16].try_into().unwrap()),
+            authority_bump: data[16],
+        })
+    }
+
+    /// The amount out for `amount_in`, rounded down
+    pub fn amount_out(&self, amount_in: u64) -> Result<u64, ProgramError> {
+        let amount_out = (amount_in as u128 * self.price_numerator as u128)
+            .checked_div(self.price_denominator as u128)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        amount_out.try_into().map_err(|_| ProgramError::InvalidArgument)
+    }
+}
+
+/// The authority PDA that owns a Pool's vaults
+pub fn pool_authority(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[pool.as_ref()], &ID)
+}
+
+/// Instruction data for a swap
+pub fn swap_data(amount_in: u64, minimum_amount_out: u64) -> Vec<u8> {
+    let mut data = vec![SWAP_TAG];
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+    data
+}
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (tag, args) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+    if *tag != SWAP_TAG || args.len() != 16 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let amount_in = u64::from_le_bytes(args[0..8].try_into().unwrap());
+    let minimum_amount_out = u64::from_le_bytes(args[8..16].try_into().unwrap());
+
+    let account_info_iter = &mut accounts.iter();
+    let pool_info = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let user_transfer_authority = next_account_info(account_info_iter)?;
+    let source = next_account_info(account_info_iter)?;
+    let vault_in = next_account_info(account_info_iter)?;
+    let vault_out = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    if pool_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let pool = Pool::unpack(&pool_info.try_borrow_data()?)?;
+    let authority_seeds: &[&[u8]] = &[pool_info.key.as_ref(), &[pool.authority_bump]];
+    if Pubkey::create_program_address(authority_seeds, program_id)? != *authority.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let amount_out = pool.amount_out(amount_in)?;
+    if amount_out < minimum_amount_out {
+        return Err(ProgramError::Custom(EXCEEDED_SLIPPAGE));
+    }
+
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            source.key,
+            vault_in.key,
+            user_transfer_authority.key,
+            &[],
+            amount_in,
+        )?,
+        &[source.clone(), vault_in.clone(), user_transfer_authority.clone(), token_program.clone()],
+    )?;
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            vault_out.key,
+            destination.key,
+            authority.key,
+            &[],
+            amount_out,
+        )?,
+        &[vault_out.clone(), destination.clone(), authority.clone(), token_program.clone()],
+        &[authority_seeds],
+    )?;
+    Ok(())
+}
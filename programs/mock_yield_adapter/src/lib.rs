@@ -0,0 +1,190 @@
+//! A fixed rate yield adapter for testing psy_american's yield-bearing markets. It implements
+//! the adapter interface psy_american calls, see its `yield_vault` module: a tag followed by a
+//! little endian u64 amount.
+//!
+//! A Reserve holds the underlying in its vault and mints receipt tokens worth
+//! `rate_numerator / rate_denominator` of the underlying each. Accrue raises the rate to
+//! simulate yield, the vault must hold the underlying to pay it out.
+//!
+//! Accounts for a deposit:
+//! 0. `[]` The Reserve
+//! 1. `[]` The Reserve's authority PDA, seeded by the Reserve's key
+//! 2. `[signer]` The owner of `source`
+//! 3. `[writable]` source, an underlying token account
+//! 4. `[writable]` The Reserve's underlying vault
+//! 5. `[writable]` The receipt mint
+//! 6. `[writable]` destination, a receipt token account
+//! 7. `[]` The SPL Token program
+//!
+//! Accounts for a withdraw or redeem:
+//! 0. `[]` The Reserve
+//! 1. `[]` The Reserve's authority PDA, seeded by the Reserve's key
+//! 2. `[signer]` The owner of `source`
+//! 3. `[writable]` source, a receipt token account
+//! 4. `[writable]` The receipt mint
+//! 5. `[writable]` The Reserve's underlying vault
+//! 6. `[writable]` destination, an underlying token account
+//! 7. `[]` The SPL Token program
+//!
+//! Accounts for an accrue, whose amount is the new `rate_numerator`:
+//! 0. `[writable]` The Reserve
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    declare_id,
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use std::convert::TryInto;
+
+declare_id!("EyTLcgVpcpmJFSCUbNFMNwwjV4eFC7yzM8ZpFHRBZfeJ");
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+/// Deposit `amount` of the underlying for receipt tokens
+pub const DEPOSIT_TAG: u8 = 1;
+/// Burn receipt tokens for exactly `amount` of the underlying
+pub const WITHDRAW_TAG: u8 = 2;
+/// Burn `amount` receipt tokens for the underlying they are worth
+pub const REDEEM_TAG: u8 = 3;
+/// Set the Reserve's `rate_numerator` to `amount`
+pub const ACCRUE_TAG: u8 = 4;
+
+/// A Reserve's receipt tokens are each worth `rate_numerator / rate_denominator` underlying
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Reserve {
+    pub rate_numerator: u64,
+    pub rate_denominator: u64,
+    /// Bump seed for the Reserve's authority PDA
+    pub authority_bump: u8,
+}
+
+impl Reserve {
+    pub const LEN: usize = 17;
+
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(Self::LEN);
+        data.extend_from_slice(&self.rate_numerator.to_le_bytes());
+        data.extend_from_slice(&self.rate_denominator.to_le_bytes());
+        data.push(self.authority_bump);
+        data
+    }
+
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Reserve {
+            rate_numerator: u64::from_le_bytes(data[0..8].try_into().unwrap()),
+            rate_denominator: u64::from_le_bytes(data[8..16].try_into().unwrap()),
+            authority_bump: data[16],
+        })
+    }
+
+    /// The receipt tokens minted for depositing `underlying`, rounded down
+    pub fn receipts_for_deposit(&self, underlying: u64) -> Result<u64, ProgramError> {
+        mul_div(underlying, self.rate_denominator, self.rate_numerator, false)
+    }
+
+    /// The receipt tokens burned to withdraw exactly `underlying`, rounded up
+    pub fn receipts_for_withdrawal(&self, underlying: u64) -> Result<u64, ProgramError> {
+        mul_div(underlying, self.rate_denominator, self.rate_numerator, true)
+    }
+
+    /// The underlying paid for redeeming `receipts`, rounded down
+    pub fn underlying_for_receipts(&self, receipts: u64) -> Result<u64, ProgramError> {
+        mul_div(receipts, self.rate_numerator, self.rate_denominator, false)
+    }
+}
+
+fn mul_div(amount: u64, numerator: u64, denominator: u64, round_up: bool) -> Result<u64, ProgramError> {
+    if denominator == 0 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let product = amount as u128 * numerator as u128;
+    let denominator = denominator as u128;
+    let result = if round_up { (product + denominator - 1) / denominator } else { product / denominator };
+    result.try_into().map_err(|_| ProgramError::InvalidArgument)
+}
+
+/// The authority PDA that owns a Reserve's vault and mints its receipt tokens
+pub fn reserve_authority(reserve: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[reserve.as_ref()], &ID)
+}
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (tag, args) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+    if args.len() != 8 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let amount = u64::from_le_bytes(args.try_into().unwrap());
+
+    let account_info_iter = &mut accounts.iter();
+    let reserve_info = next_account_info(account_info_iter)?;
+    if reserve_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut reserve = Reserve::unpack(&reserve_info.try_borrow_data()?)?;
+    if *tag == ACCRUE_TAG {
+        reserve.rate_numerator = amount;
+        reserve_info.try_borrow_mut_data()?[..Reserve::LEN].copy_from_slice(&reserve.pack());
+        return Ok(());
+    }
+
+    let authority = next_account_info(account_info_iter)?;
+    let owner = next_account_info(account_info_iter)?;
+    let source = next_account_info(account_info_iter)?;
+    let authority_seeds: &[&[u8]] = &[reserve_info.key.as_ref(), &[reserve.authority_bump]];
+    if Pubkey::create_program_address(authority_seeds, program_id)? != *authority.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    match *tag {
+        DEPOSIT_TAG => {
+            let vault = next_account_info(account_info_iter)?;
+            let receipt_mint = next_account_info(account_info_iter)?;
+            let destination = next_account_info(account_info_iter)?;
+            let token_program = next_account_info(account_info_iter)?;
+            invoke(
+                &spl_token::instruction::transfer(token_program.key, source.key, vault.key, owner.key, &[], amount)?,
+                &[source.clone(), vault.clone(), owner.clone(), token_program.clone()],
+            )?;
+            invoke_signed(
+                &spl_token::instruction::mint_to(
+                    token_program.key,
+                    receipt_mint.key,
+                    destination.key,
+                    authority.key,
+                    &[],
+                    reserve.receipts_for_deposit(amount)?,
+                )?,
+                &[receipt_mint.clone(), destination.clone(), authority.clone(), token_program.clone()],
+                &[authority_seeds],
+            )?;
+        }
+        WITHDRAW_TAG | REDEEM_TAG => {
+            let receipt_mint = next_account_info(account_info_iter)?;
+            let vault = next_account_info(account_info_iter)?;
+            let destination = next_account_info(account_info_iter)?;
+            let token_program = next_account_info(account_info_iter)?;
+            let (receipts, underlying) = if *tag == WITHDRAW_TAG {
+                (reserve.receipts_for_withdrawal(amount)?, amount)
+            } else {
+                (amount, reserve.underlying_for_receipts(amount)?)
+            };
+            invoke(
+                &spl_token::instruction::burn(token_program.key, source.key, receipt_mint.key, owner.key, &[], receipts)?,
+                &[source.clone(), receipt_mint.clone(), owner.clone(), token_program.clone()],
+            )?;
+            invoke_signed(
+                &spl_token::instruction::transfer(token_program.key, vault.key, destination.key, authority.key, &[], underlying)?,
+                &[vault.clone(), destination.clone(), authority.clone(), token_program.clone()],
+                &[authority_seeds],
+            )?;
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    }
+    Ok(())
+}
@@ -0,0 +1,350 @@
+//! Drives random sequences of psy_american instructions, with random account substitutions,
+//! against an in-process bank and checks that the pools always cover the outstanding option
+//! and writer token supply.
+//!
+//! Each run starts from positions written directly into the bank with the integration test
+//! fixtures, and an unpaused PauseConfig so the sequences can mint.
+//!
+//! Run with `cargo fuzz run instructions` from `programs/psy_american`.
+#![no_main]
+
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+use arbitrary::Arbitrary;
+use common::*;
+use libfuzzer_sys::fuzz_target;
+use psy_american::{fees, PauseFlags};
+use solana_program::{instruction::AccountMeta, system_program};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const USERS: usize = 3;
+/// Options and WriterTokens each user starts with, and the contracts their underlying can mint
+const POSITION: u64 = 20;
+const MAX_INSTRUCTIONS: usize = 32;
+
+#[derive(Arbitrary, Debug)]
+struct FuzzInput {
+    /// Underlying per contract, offset by 1 so the market is valid
+    underlying_amount_per_contract: u32,
+    /// Quote per contract, offset by 1 so the market is valid
+    quote_amount_per_contract: u32,
+    instructions: Vec<FuzzInstruction>,
+}
+
+#[derive(Arbitrary, Debug)]
+enum FuzzInstruction {
+    MintOption { user: u8, size: u64, substitution: Option<Substitution> },
+    MintOptionV2 { user: u8, size: u64, substitution: Option<Substitution> },
+    ExerciseOption { user: u8, size: u64, substitution: Option<Substitution> },
+    ExerciseOptionV2 { user: u8, size: u64, substitution: Option<Substitution> },
+    CloseOptionPosition { user: u8, size: u64, substitution: Option<Substitution> },
+    ClosePostExpiration { user: u8, size: u64, substitution: Option<Substitution> },
+    BurnWriterForQuote { user: u8, size: u64, substitution: Option<Substitution> },
+    /// Transfer options from one user to another, so exercisers and writers diverge
+    TransferOptions { from: u8, to: u8, amount: u64 },
+    WarpPastExpiration,
+}
+
+/// Replace the account at `index` with one of the known accounts
+#[derive(Arbitrary, Debug)]
+struct Substitution {
+    index: u8,
+    replacement: u8,
+}
+
+struct State {
+    fixture: MarketFixture,
+    users: Vec<UserFixture>,
+    expired: bool,
+}
+
+impl State {
+    fn known_accounts(&self) -> Vec<Pubkey> {
+        let market = &self.fixture.market;
+        let mut accounts = vec![
+            self.fixture.key,
+            market.option_mint,
+            market.writer_token_mint,
+            market.underlying_asset_mint,
+            market.quote_asset_mint,
+            market.underlying_asset_pool,
+            market.quote_asset_pool,
+            market.mint_fee_account,
+            market.exercise_fee_account,
+            fees::fee_owner_key::ID,
+        ];
+        for user in &self.users {
+            accounts.extend_from_slice(&[
+                user.authority.pubkey(),
+                user.option_account,
+                user.writer_token_account,
+                user.underlying_account,
+                user.quote_account,
+            ]);
+        }
+        accounts
+    }
+
+    fn user(&self, index: u8) -> &UserFixture {
+        &self.users[index as usize % USERS]
+    }
+
+    fn substitute(&self, mut ix: Instruction, substitution: &Option<Substitution>) -> Instruction {
+        if let Some(substitution) = substitution {
+            let known_accounts = self.known_accounts();
+            let index = substitution.index as usize % ix.accounts.len();
+            let meta = &mut ix.accounts[index];
+            meta.pubkey = known_accounts[substitution.replacement as usize % known_accounts.len()];
+        }
+        ix
+    }
+}
+
+fn build(state: &State, fuzz_ix: &FuzzInstruction) -> Option<Instruction> {
+    let market = &state.fixture.market;
+    let (ix, substitution) = match fuzz_ix {
+        FuzzInstruction::MintOption { user, size, substitution } => {
+            let u = state.user(*user);
+            let mut ix = instruction(
+                psy_american::accounts::MintOption {
+                    user_authority: u.authority.pubkey(),
+                    underlying_asset_mint: market.underlying_asset_mint,
+                    underlying_asset_pool: market.underlying_asset_pool,
+                    underlying_asset_src: u.underlying_account,
+                    option_mint: market.option_mint,
+                    minted_option_dest: u.option_account,
+                    writer_token_mint: market.writer_token_mint,
+                    minted_writer_token_dest: u.writer_token_account,
+                    option_market: state.fixture.key,
+                    fee_owner: fees::fee_owner_key::ID,
+                    token_program: spl_token::ID,
+                    associated_token_program: anchor_spl::associated_token::ID,
+                    clock: clock_sysvar(),
+                    rent: rent_sysvar(),
+                    system_program: system_program::ID,
+                    pause_config: pause_config_key(),
+                },
+                psy_american::instruction::MintOption { size: *size },
+            );
+            ix.accounts.push(AccountMeta::new(market.mint_fee_account, false));
+            (ix, substitution)
+        }
+        FuzzInstruction::MintOptionV2 { user, size, substitution } => {
+            let u = state.user(*user);
+            let ix = instruction(
+                psy_american::accounts::MintOptionV2 {
+                    user_authority: u.authority.pubkey(),
+                    underlying_asset_mint: market.underlying_asset_mint,
+                    underlying_asset_pool: market.underlying_asset_pool,
+                    underlying_asset_src: u.underlying_account,
+                    option_mint: market.option_mint,
+                    minted_option_dest: u.option_account,
+                    writer_token_mint: market.writer_token_mint,
+                    minted_writer_token_dest: u.writer_token_account,
+                    option_market: state.fixture.key,
+                    token_program: spl_token::ID,
+                    pause_config: pause_config_key(),
+                },
+                psy_american::instruction::MintOptionV2 { size: *size },
+            );
+            (ix, substitution)
+        }
+        FuzzInstruction::ExerciseOption { user, size, substitution } => {
+            let u = state.user(*user);
+            let mut ix = instruction(
+                psy_american::accounts::ExerciseOption {
+                    user_authority: u.authority.pubkey(),
+                    option_authority: u.authority.pubkey(),
+                    option_market: state.fixture.key,
+                    option_mint: market.option_mint,
+                    exerciser_option_token_src: u.option_account,
+                    underlying_asset_pool: market.underlying_asset_pool,
+                    underlying_asset_dest: u.underlying_account,
+                    quote_asset_pool: market.quote_asset_pool,
+                    quote_asset_src: u.quote_account,
+                    fee_owner: fees::fee_owner_key::ID,
+                    token_program: spl_token::ID,
+                    system_program: system_program::ID,
+                    clock: clock_sysvar(),
+                },
+                psy_american::instruction::ExerciseOption { size: *size },
+            );
+            ix.accounts.push(AccountMeta::new(market.exercise_fee_account, false));
+            (ix, substitution)
+        }
+        FuzzInstruction::ExerciseOptionV2 { user, size, substitution } => {
+            let u = state.user(*user);
+            let ix = instruction(
+                psy_american::accounts::ExerciseOptionV2 {
+                    user_authority: u.authority.pubkey(),
+                    option_authority: u.authority.pubkey(),
+                    option_market: state.fixture.key,
+                    option_mint: market.option_mint,
+                    exerciser_option_token_src: u.option_account,
+                    underlying_asset_pool: market.underlying_asset_pool,
+                    underlying_asset_dest: u.underlying_account,
+                    quote_asset_pool: market.quote_asset_pool,
+                    quote_asset_src: u.quote_account,
+                    token_program: spl_token::ID,
+                },
+                psy_american::instruction::ExerciseOptionV2 { size: *size },
+            );
+            (ix, substitution)
+        }
+        FuzzInstruction::CloseOptionPosition { user, size, substitution } => {
+            let u = state.user(*user);
+            let ix = instruction(
+                psy_american::accounts::CloseOptionPosition {
+                    user_authority: u.authority.pubkey(),
+                    option_market: state.fixture.key,
+                    writer_token_mint: market.writer_token_mint,
+                    writer_token_src: u.writer_token_account,
+                    option_token_mint: market.option_mint,
+                    option_token_src: u.option_account,
+                    underlying_asset_pool: market.underlying_asset_pool,
+                    underlying_asset_dest: u.underlying_account,
+                    token_program: spl_token::ID,
+                },
+                psy_american::instruction::CloseOptionPosition { size: *size },
+            );
+            (ix, substitution)
+        }
+        FuzzInstruction::ClosePostExpiration { user, size, substitution } => {
+            let u = state.user(*user);
+            let ix = instruction(
+                psy_american::accounts::ClosePostExp {
+                    user_authority: u.authority.pubkey(),
+                    option_market: state.fixture.key,
+                    writer_token_mint: market.writer_token_mint,
+                    writer_token_src: u.writer_token_account,
+                    underlying_asset_pool: market.underlying_asset_pool,
+                    underlying_asset_dest: u.underlying_account,
+                    token_program: spl_token::ID,
+                    clock: clock_sysvar(),
+                },
+                psy_american::instruction::ClosePostExpiration { size: *size },
+            );
+            (ix, substitution)
+        }
+        FuzzInstruction::BurnWriterForQuote { user, size, substitution } => {
+            let u = state.user(*user);
+            let ix = instruction(
+                psy_american::accounts::BurnWriterForQuote {
+                    user_authority: u.authority.pubkey(),
+                    option_market: state.fixture.key,
+                    writer_token_mint: market.writer_token_mint,
+                    writer_token_src: u.writer_token_account,
+                    quote_asset_pool: market.quote_asset_pool,
+                    writer_quote_dest: u.quote_account,
+                    token_program: spl_token::ID,
+                },
+                psy_american::instruction::BurnWriterForQuote { size: *size },
+            );
+            (ix, substitution)
+        }
+        FuzzInstruction::TransferOptions { from, to, amount } => {
+            let from = state.user(*from);
+            let ix = spl_token::instruction::transfer(
+                &spl_token::ID,
+                &from.option_account,
+                &state.user(*to).option_account,
+                &from.authority.pubkey(),
+                &[],
+                *amount,
+            ).unwrap();
+            return Some(ix);
+        }
+        FuzzInstruction::WarpPastExpiration => return None,
+    };
+    Some(state.substitute(ix, substitution))
+}
+
+/// The users that must sign `ix`, or None if a substitution made an account without a known
+/// keypair a signer
+fn signers<'a>(state: &'a State, ix: &Instruction) -> Option<Vec<&'a Keypair>> {
+    let mut signers: Vec<&Keypair> = vec![];
+    for meta in ix.accounts.iter().filter(|meta| meta.is_signer) {
+        let user = state.users.iter().find(|user| user.authority.pubkey() == meta.pubkey)?;
+        if !signers.iter().any(|signer| signer.pubkey() == meta.pubkey) {
+            signers.push(&user.authority);
+        }
+    }
+    Some(signers)
+}
+
+/// The pools must cover every outstanding token:
+/// - before expiration, every option can be exercised for `underlying_amount_per_contract`
+/// - every WriterToken can be redeemed for either its underlying or its quote
+async fn check_invariants(context: &mut ProgramTestContext, state: &State) {
+    let market = &state.fixture.market;
+    let underlying_pool = token_balance(context, &market.underlying_asset_pool).await as u128;
+    let quote_pool = token_balance(context, &market.quote_asset_pool).await as u128;
+    let option_supply = mint_supply(context, &market.option_mint).await as u128;
+    let writer_supply = mint_supply(context, &market.writer_token_mint).await as u128;
+    let upc = market.underlying_amount_per_contract as u128;
+    let qpc = market.quote_amount_per_contract as u128;
+
+    if !state.expired {
+        assert!(
+            underlying_pool >= option_supply * upc,
+            "underlying pool {} does not cover {} options",
+            underlying_pool,
+            option_supply,
+        );
+    }
+    assert!(
+        underlying_pool * qpc + quote_pool * upc >= writer_supply * upc * qpc,
+        "pools ({} underlying, {} quote) do not cover {} writer tokens",
+        underlying_pool,
+        quote_pool,
+        writer_supply,
+    );
+}
+
+async fn run(input: FuzzInput) {
+    let mut program_test = program_test();
+    add_pause_config(&mut program_test, PauseFlags::default());
+    let fixture = MarketFixture::new(
+        input.underlying_amount_per_contract as u64 + 1,
+        input.quote_amount_per_contract as u64 + 1,
+        EXPIRATION,
+    );
+    fixture.add_to(&mut program_test, POSITION * USERS as u64, 0);
+    let underlying = POSITION * (fixture.market.underlying_amount_per_contract + fees::fee_amount(fixture.market.underlying_amount_per_contract).unwrap());
+    let quote = POSITION * (fixture.market.quote_amount_per_contract + fees::fee_amount(fixture.market.quote_amount_per_contract).unwrap());
+    let users = (0..USERS)
+        .map(|_| UserFixture::add_to(&mut program_test, &fixture, POSITION, POSITION, underlying, quote))
+        .collect();
+    let mut state = State { fixture, users, expired: false };
+    let mut context = program_test.start_with_context().await;
+
+    for fuzz_ix in input.instructions.iter().take(MAX_INSTRUCTIONS) {
+        match build(&state, fuzz_ix) {
+            Some(ix) => {
+                if let Some(signers) = signers(&state, &ix) {
+                    // Failures are expected, only the resulting state matters
+                    let _ = process(&mut context, ix, &signers).await;
+                }
+            }
+            None => {
+                warp_past(&mut context, EXPIRATION).await;
+                state.expired = true;
+            }
+        }
+        check_invariants(&mut context, &state).await;
+    }
+}
+
+fuzz_target!(|input: FuzzInput| {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(run(input));
+});
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use solana_program::{instruction::{AccountMeta, Instruction}, program::invoke};
+
+/// Tag of the swap instruction. The interface uses the layout of SPL Token Swap's `Swap`, so
+/// token-swap compatible AMMs can be called directly and other AMMs through a thin adapter
+/// program.
+pub const SWAP_TAG: u8 = 1;
+
+/// Build the swap instruction for `amm_program`. The AMM's accounts are passed through in
+/// order with the signer and writable flags they were given to the calling instruction.
+pub fn swap_instruction(amm_program: &Pubkey, accounts: &[AccountInfo], amount_in: u64, minimum_amount_out: u64) -> Instruction {
+    let mut data = vec![SWAP_TAG];
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+    Instruction {
+        program_id: *amm_program,
+        accounts: accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: *account.key,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data,
+    }
+}
+
+/// Swap `amount_in` for at least `minimum_amount_out` on `amm_program`. The AMM is not
+/// trusted, callers must check the balances it moved.
+pub fn swap<'info>(
+    amm_program: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    amount_in: u64,
+    minimum_amount_out: u64
+) -> Result<()> {
+    let ix = swap_instruction(amm_program.key, accounts, amount_in, minimum_amount_out);
+    let mut account_infos = accounts.to_vec();
+    account_infos.push(amm_program.clone());
+    invoke(&ix, &account_infos)?;
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+
+use crate::errors;
+
+/// The most decimals an option mint can have. A whole contract is `10^option_decimals` option
+/// tokens, and 10^9 keeps `amount_per_contract * size` well inside a u128.
+pub const MAX_OPTION_DECIMALS: u8 = 9;
+
+/// Which way to round an amount that is not a whole number of native units. Amounts paid into
+/// a pool round up and amounts paid out of a pool round down, so the pools never lose dust.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// The amount of an asset for `size` option tokens, where `amount_per_contract` is the amount
+/// for one whole contract of `10^option_decimals` option tokens.
+pub fn amount_for_size(amount_per_contract: u64, size: u64, option_decimals: u8, rounding: Rounding) -> Result<u64> {
+    if option_decimals > MAX_OPTION_DECIMALS {
+        return Err(errors::ErrorCode::OptionDecimalsTooLarge.into())
+    }
+    let tokens_per_contract = 10u128.pow(option_decimals as u32);
+    let total = amount_per_contract as u128 * size as u128;
+    let amount = match rounding {
+        Rounding::Down => total / tokens_per_contract,
+        Rounding::Up => (total + tokens_per_contract - 1) / tokens_per_contract,
+    };
+    if amount > u64::MAX as u128 {
+        return Err(errors::ErrorCode::NumberOverflow.into())
+    }
+    Ok(amount as u64)
+}
+
+/// The most option tokens `amount` of an asset pays out for, the inverse of `amount_for_size`
+/// rounded down. An `amount_per_contract` of 0 pays out for any size.
+pub fn size_for_amount(amount_per_contract: u64, amount: u64, option_decimals: u8) -> Result<u64> {
+    if option_decimals > MAX_OPTION_DECIMALS {
+        return Err(errors::ErrorCode::OptionDecimalsTooLarge.into())
+    }
+    if amount_per_contract == 0 {
+        return Ok(u64::MAX)
+    }
+    let size = amount as u128 * 10u128.pow(option_decimals as u32) / amount_per_contract as u128;
+    Ok(size.min(u64::MAX as u128) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn whole_contracts_match_plain_multiplication() {
+        assert_eq!(amount_for_size(100, 7, 0, Rounding::Down).unwrap(), 700);
+        assert_eq!(amount_for_size(100, 7, 0, Rounding::Up).unwrap(), 700);
+    }
+
+    #[test]
+    fn fractional_contracts_round_in_favor_of_the_pool() {
+        // 0.333 of a 100 unit contract
+        assert_eq!(amount_for_size(100, 333, 3, Rounding::Down).unwrap(), 33);
+        assert_eq!(amount_for_size(100, 333, 3, Rounding::Up).unwrap(), 34);
+        // 0.5 of a 100 unit contract is exact
+        assert_eq!(amount_for_size(100, 500, 3, Rounding::Down).unwrap(), 50);
+        assert_eq!(amount_for_size(100, 500, 3, Rounding::Up).unwrap(), 50);
+    }
+
+    #[test]
+    fn sizes_are_counted_in_option_tokens() {
+        assert_eq!(size_for_amount(100, 700, 0).unwrap(), 7);
+        // 33 units of a 100 unit contract pay out for 0.33 of it
+        assert_eq!(size_for_amount(100, 33, 3).unwrap(), 330);
+        assert_eq!(size_for_amount(0, 1, 3).unwrap(), u64::MAX);
+        assert!(size_for_amount(100, 1, MAX_OPTION_DECIMALS + 1).is_err());
+    }
+
+    #[test]
+    fn rejects_overflow_and_too_many_decimals() {
+        assert!(amount_for_size(u64::MAX, 2, 0, Rounding::Down).is_err());
+        assert!(amount_for_size(1, 1, MAX_OPTION_DECIMALS + 1, Rounding::Down).is_err());
+        assert!(amount_for_size(u64::MAX, u64::MAX, MAX_OPTION_DECIMALS, Rounding::Down).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn rounding_up_is_at_most_one_more(
+            amount_per_contract in 0..=u32::MAX as u64,
+            size in 0..=u32::MAX as u64,
+            option_decimals in 0..=MAX_OPTION_DECIMALS,
+        ) {
+            let down = amount_for_size(amount_per_contract, size, option_decimals, Rounding::Down).unwrap();
+            let up = amount_for_size(amount_per_contract, size, option_decimals, Rounding::Up).unwrap();
+            prop_assert!(up == down || up == down + 1);
+        }
+
+        #[test]
+        fn size_for_amount_never_pays_out_more_than_the_amount(
+            amount_per_contract in 1..=u32::MAX as u64,
+            amount in 0..=u32::MAX as u64,
+            option_decimals in 0..=MAX_OPTION_DECIMALS,
+        ) {
+            let size = size_for_amount(amount_per_contract, amount, option_decimals).unwrap();
+            prop_assert!(amount_for_size(amount_per_contract, size, option_decimals, Rounding::Down).unwrap() <= amount);
+            // One more option token is not covered by the amount
+            prop_assert!(amount_for_size(amount_per_contract, size + 1, option_decimals, Rounding::Up).unwrap() > amount);
+        }
+
+        #[test]
+        fn split_sizes_never_pay_out_more_than_they_paid_in(
+            amount_per_contract in 0..=u32::MAX as u64,
+            first in 0..=u32::MAX as u64,
+            second in 0..=u32::MAX as u64,
+            option_decimals in 0..=MAX_OPTION_DECIMALS,
+        ) {
+            // Minting in two pieces and exercising at once, or the reverse, never drains the pool
+            let paid_in = amount_for_size(amount_per_contract, first, option_decimals, Rounding::Up).unwrap()
+                + amount_for_size(amount_per_contract, second, option_decimals, Rounding::Up).unwrap();
+            let paid_out = amount_for_size(amount_per_contract, first + second, option_decimals, Rounding::Down).unwrap();
+            prop_assert!(paid_out <= paid_in);
+
+            let paid_in = amount_for_size(amount_per_contract, first + second, option_decimals, Rounding::Up).unwrap();
+            let paid_out = amount_for_size(amount_per_contract, first, option_decimals, Rounding::Down).unwrap()
+                + amount_for_size(amount_per_contract, second, option_decimals, Rounding::Down).unwrap();
+            prop_assert!(paid_out <= paid_in);
+        }
+    }
+}
@@ -58,4 +58,138 @@ pub enum ErrorCode {
   CannotPruneActiveMarket,
   #[msg("Numberical overflow")]
   NumberOverflow,
+  #[msg("Referral wallet must be owned by the referral authority or a registered Referrer")]
+  InvalidReferrer,
+  #[msg("Referrer share must be <= 10,000 bps")]
+  ReferrerShareTooLarge,
+  #[msg("Referral fee destination must be owned by the Referrer authority")]
+  ReferralDestMustBeOwnedByAuthority,
+  #[msg("Referral fee accounts must share the same mint")]
+  ReferralFeeMintMismatch,
+  #[msg("PriceBandConfig is not the PDA for the Serum market")]
+  PriceBandDoesNotMatchMarket,
+  #[msg("Trading is halted on this market")]
+  MarketHalted,
+  #[msg("Oracle does not match the value on the PriceBandConfig")]
+  OracleDoesNotMatchPriceBand,
+  #[msg("Oracle account is not a valid price account")]
+  InvalidOracle,
+  #[msg("Oracle price is not currently trading")]
+  OracleNotTrading,
+  #[msg("Order price is outside of the market's price band")]
+  OrderPriceOutsideBand,
+  #[msg("Lower band must be <= 10,000 bps")]
+  LowerBandTooWide,
+  #[msg("Either the underlying or quote asset must be the Serum pc mint")]
+  PcMintMustBeUnderlyingOrQuote,
+  #[msg("Underlying asset mint does not match the value on the OptionMarket")]
+  UnderlyingMintDoesNotMatchMarket,
+  #[msg("Quote asset mint does not match the value on the OptionMarket")]
+  QuoteMintDoesNotMatchMarket,
+  #[msg("Size must be at least one Serum coin lot")]
+  SizeSmallerThanLotSize,
+  #[msg("Limit price must be > 0")]
+  LimitPriceCantBeZero,
+  #[msg("Fewer options were filled than the minimum fill")]
+  FillBelowMinimum,
+  #[msg("Error creating a dex instruction")]
+  DexIxError,
+  #[msg("The underlying asset pool does not cover the outstanding options")]
+  UnderlyingPoolDoesNotCoverOptions,
+  #[msg("The underlying and quote asset pools do not cover the outstanding WriterTokens")]
+  PoolsDoNotCoverWriterTokens,
+  #[msg("The number of remaining accounts does not match the number of batch entries")]
+  BatchAccountsDoNotMatchEntries,
+  #[msg("The option token source does not hold enough options to exercise")]
+  NotEnoughOptionsToExercise,
+  #[msg("The quote asset source does not hold enough to exercise")]
+  NotEnoughQuoteToExercise,
+  #[msg("A position can only be rolled between OptionMarkets with the same underlying asset")]
+  RollUnderlyingAssetsDiffer,
+  #[msg("Option decimals must be <= 9")]
+  OptionDecimalsTooLarge,
+  #[msg("A position can only be rolled between OptionMarkets with the same option decimals")]
+  RollOptionDecimalsDiffer,
+  #[msg("Net exercise can only swap the underlying assets the exercise releases")]
+  NetExerciseSwapsTooMuchUnderlying,
+  #[msg("The swap did not return enough quote assets to pay the strike")]
+  SwapDidNotCoverStrike,
+  #[msg("Flash mints must be settled by a later flash_repay on the same OptionMarket")]
+  FlashMintNotRepaid,
+  #[msg("flash_repay must settle a preceding flash_mint on the same OptionMarket")]
+  FlashRepayWithoutMint,
+  #[msg("flash_repay must settle exactly the size of the flash_mint")]
+  FlashRepayDoesNotMatchMint,
+  #[msg("flash_mint and flash_repay must be top level instructions")]
+  FlashInstructionMustBeTopLevel,
+  #[msg("Flash instruction data could not be read")]
+  InvalidFlashInstruction,
+  #[msg("Only listed pairs or authorized listers can initialize markets")]
+  MarketNotListed,
+  #[msg("Series configs need a contract size, strike increment and an offset inside the expiration interval")]
+  InvalidSeriesConfig,
+  #[msg("The contract size does not match the pair's series config")]
+  SeriesContractSizeNotAllowed,
+  #[msg("The strike is not a multiple of the pair's strike increment")]
+  SeriesStrikeNotAllowed,
+  #[msg("The expiration is not on the pair's expiration schedule")]
+  SeriesExpirationNotAllowed,
+  #[msg("The OptionChain does not index the market's underlying and quote assets")]
+  OptionChainDoesNotMatchPair,
+  #[msg("The OptionChain page is full, use a page with room or extend the chain")]
+  OptionChainPageFull,
+  #[msg("The OptionChain can only be extended once its last page is full")]
+  OptionChainPageNotFull,
+  #[msg("The OptionChain can only be extended from its last page")]
+  OptionChainNotLastPage,
+  #[msg("The OptionMarket is not on this OptionChain page")]
+  OptionMarketNotInChain,
+  #[msg("The OptionMarket still has outstanding WriterTokens")]
+  OptionMarketHasOpenPositions,
+  #[msg("The OptionMarket already records its creator")]
+  MarketAlreadyUpgraded,
+  #[msg("Only the OptionMarket's creator can do this")]
+  NotMarketCreator,
+  #[msg("The OptionMarket already has the current account size")]
+  MarketAlreadyMigrated,
+  #[msg("The instruction is paused, only exits are enabled")]
+  InstructionPaused,
+  #[msg("Governance needs 1 to 10 unique members, a reachable threshold and a timelock of at least a day")]
+  InvalidGovernanceConfig,
+  #[msg("The Governance exists, changes need an approved proposal")]
+  GovernanceRequired,
+  #[msg("Only Governance members can do this")]
+  NotGovernanceMember,
+  #[msg("The member already approved the proposal")]
+  ProposalAlreadyApproved,
+  #[msg("The proposal has not reached the Governance's threshold")]
+  ProposalNotApproved,
+  #[msg("The proposal's timelock has not passed")]
+  ProposalTimelocked,
+  #[msg("The target account does not match the proposal's action")]
+  ProposalTargetDoesNotMatch,
+  #[msg("Only the proposer can cancel the proposal")]
+  NotProposer,
+  #[msg("The OptionMarket holds its collateral in a YieldVault, use the yield instructions")]
+  YieldMarketUnsupported,
+  #[msg("A YieldVault can only be added before any options are written")]
+  MarketHasWrittenOptions,
+  #[msg("Receipt pool does not match the value on the YieldVault")]
+  ReceiptPoolDoesNotMatchVault,
+  #[msg("Adapter program does not match the value on the YieldVault")]
+  YieldAdapterDoesNotMatchVault,
+  #[msg("The deposit does not cover the collateral and accrued yield of the options")]
+  YieldDepositTooSmall,
+  #[msg("The adapter withdrew less underlying than the exercise releases")]
+  YieldWithdrawalShort,
+  #[msg("The adapter redeemed more receipt tokens than the position's share")]
+  YieldAdapterOverdrew,
+  #[msg("Oracle price has not been published recently")]
+  OracleStale,
+  #[msg("Serum market lot sizes must be greater than 0")]
+  SerumMarketNotInitialized,
+  #[msg("Adapter reserve or vault does not match the value on the YieldVault")]
+  YieldAdapterAccountsDoNotMatchVault,
+  #[msg("The adapter program is not approved to hold market collateral")]
+  YieldAdapterNotApproved,
 }
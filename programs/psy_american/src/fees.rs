@@ -1,3 +1,7 @@
+use anchor_lang::prelude::*;
+
+use crate::errors;
+
 /// The fee_owner_key will own all of the associated accounts where token fees are paid to.
 /// In the future this should be a program derived address owned by a fully decentralized
 /// fee sweeping program.
@@ -13,15 +17,21 @@ pub const NFT_MINT_LAMPORTS: u64 = 1_000_000_000 / 2;
 
 /// Floating points are not ideal for the Solana runtime, so we need a integer type than
 /// can handle fraction parts for us. The highest 64 bits are the integer, the lower 64
-/// bits are the decimals.
+/// bits are the decimals. All arithmetic is checked and returns `NumberOverflow` rather
+/// than wrapping.
 #[repr(transparent)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 struct U64F64(u128);
 
 impl U64F64 {
   #[inline(always)]
-  const fn mul_u64(self, other: u64) -> U64F64 {
-    U64F64(self.0 * other as u128)
+  fn checked_add(self, other: U64F64) -> Result<U64F64> {
+    self.0.checked_add(other.0).map(U64F64).ok_or_else(|| errors::ErrorCode::NumberOverflow.into())
+  }
+
+  #[inline(always)]
+  fn checked_mul_u64(self, other: u64) -> Result<U64F64> {
+    self.0.checked_mul(other as u128).map(U64F64).ok_or_else(|| errors::ErrorCode::NumberOverflow.into())
   }
 
   #[inline(always)]
@@ -35,8 +45,11 @@ fn fee_bps(bps: u64) -> U64F64 {
   U64F64(((bps as u128) << 64) / 10_000)
 }
 
-fn fee_rate() -> U64F64 {
-  U64F64(fee_bps(5).0 + 1)
+/// 5bps rounded up by one ulp. The rate exceeds 5bps by less than 2^-64, so for any u64
+/// quantity the fee is between 5bps rounded down and 5bps rounded up, and exactly 5bps when
+/// that is a whole number.
+fn fee_rate() -> Result<U64F64> {
+  fee_bps(5).checked_add(U64F64(1))
 }
 
 /// Calculates the fee for Minting and Exercising.
@@ -44,7 +57,86 @@ fn fee_rate() -> U64F64 {
 /// NOTE: SPL Tokens have an arbitrary amount of decimals. So an option market
 /// for an NFT will have `underlying_amount_per_contract` and should return a
 /// mint fee of 0. This is something to keep in mind.
-pub fn fee_amount(asset_quantity: u64) -> u64 {
-  let rate = fee_rate();
-  rate.mul_u64(asset_quantity).floor()
+pub fn fee_amount(asset_quantity: u64) -> Result<u64> {
+  let rate = fee_rate()?;
+  Ok(rate.checked_mul_u64(asset_quantity)?.floor())
+}
+
+/// The share of Serum referral fees a newly registered frontend receives, in basis points.
+/// The remainder of the referral fees are swept to the protocol's fee owner.
+pub const DEFAULT_REFERRER_SHARE_BPS: u16 = 5_000;
+
+/// Splits collected referral fees into the frontend's share and the protocol's share.
+pub fn referrer_split(amount: u64, frontend_share_bps: u16) -> (u64, u64) {
+  let frontend_amount = (amount as u128 * frontend_share_bps as u128 / 10_000) as u64;
+  (frontend_amount, amount - frontend_amount)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use num_bigint::BigUint;
+  use proptest::prelude::*;
+
+  /// 5bps of the quantity as an exact rational, rounded (down, up)
+  fn reference_fee_bounds(asset_quantity: u64) -> (u64, u64) {
+    let numerator = BigUint::from(asset_quantity) * BigUint::from(5u8);
+    let denominator = BigUint::from(10_000u32);
+    let to_u64 = |value: BigUint| value.to_u64_digits().first().copied().unwrap_or(0);
+    let floor = &numerator / &denominator;
+    let ceil = (&numerator + &denominator - BigUint::from(1u8)) / &denominator;
+    (to_u64(floor), to_u64(ceil))
+  }
+
+  proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10_000))]
+
+    #[test]
+    fn fee_amount_is_5bps_within_rounding(asset_quantity in any::<u64>()) {
+      let fee = fee_amount(asset_quantity).unwrap();
+      let (floor, ceil) = reference_fee_bounds(asset_quantity);
+      prop_assert!(floor <= fee && fee <= ceil, "{} not in [{}, {}]", fee, floor, ceil);
+    }
+
+    #[test]
+    fn fee_amount_is_exact_on_whole_fees(fee in 0..u64::MAX / 2_000) {
+      // Every quantity whose 5bps fee is a whole number
+      let asset_quantity = fee * 2_000;
+      prop_assert_eq!(reference_fee_bounds(asset_quantity), (fee, fee));
+      prop_assert_eq!(fee_amount(asset_quantity).unwrap(), fee);
+    }
+
+    #[test]
+    fn fee_amount_is_monotonic(a in any::<u64>(), b in any::<u64>()) {
+      let (low, high) = if a <= b { (a, b) } else { (b, a) };
+      prop_assert!(fee_amount(low).unwrap() <= fee_amount(high).unwrap());
+    }
+
+    #[test]
+    fn checked_mul_u64_errors_instead_of_wrapping(value in any::<u128>(), other in any::<u64>()) {
+      let result = U64F64(value).checked_mul_u64(other);
+      match value.checked_mul(other as u128) {
+        Some(product) => prop_assert_eq!(result.unwrap(), U64F64(product)),
+        None => prop_assert!(result.is_err()),
+      }
+    }
+  }
+
+  #[test]
+  fn fee_amount_edge_cases() {
+    assert_eq!(fee_amount(0).unwrap(), 0);
+    // Anything under 2,000 is too small to pay a fee
+    assert_eq!(fee_amount(1_999).unwrap(), 0);
+    assert_eq!(fee_amount(2_000).unwrap(), 1);
+    assert_eq!(fee_amount(10_000).unwrap(), 5);
+    let (floor, ceil) = reference_fee_bounds(u64::MAX);
+    let fee = fee_amount(u64::MAX).unwrap();
+    assert!(floor <= fee && fee <= ceil);
+  }
+
+  #[test]
+  fn checked_add_errors_instead_of_wrapping() {
+    assert!(U64F64(u128::MAX).checked_add(U64F64(1)).is_err());
+    assert_eq!(U64F64(1).checked_add(U64F64(1)).unwrap(), U64F64(2));
+  }
 }
@@ -0,0 +1,100 @@
+use anchor_lang::{prelude::*, Discriminator};
+use solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use std::convert::TryInto;
+
+use crate::{errors, instruction};
+
+/// Index of the OptionMarket in the accounts of both `flash_mint` and `flash_repay`
+pub const FLASH_OPTION_MARKET_INDEX: usize = 1;
+
+/// A `flash_mint` or `flash_repay` read from the instructions sysvar
+enum FlashInstruction {
+    Mint { option_market: Pubkey, size: u64 },
+    Repay { option_market: Pubkey, size: u64 },
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    data.get(offset..offset + 8).map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Decode the instruction at `index` if it is a top level `flash_mint` or `flash_repay`
+fn load_flash_instruction(instructions: &AccountInfo, index: usize) -> Result<Option<FlashInstruction>> {
+    let ix = load_instruction_at_checked(index, instructions)?;
+    if ix.program_id != crate::ID || ix.data.len() < 8 {
+        return Ok(None)
+    }
+    let option_market = match ix.accounts.get(FLASH_OPTION_MARKET_INDEX) {
+        Some(meta) => meta.pubkey,
+        None => return Ok(None),
+    };
+    let discriminator = &ix.data[..8];
+    if discriminator == instruction::FlashMint::discriminator() {
+        let size = read_u64(&ix.data, 8).ok_or(errors::ErrorCode::InvalidFlashInstruction)?;
+        return Ok(Some(FlashInstruction::Mint { option_market, size }))
+    }
+    if discriminator == instruction::FlashRepay::discriminator() {
+        let burn_size = read_u64(&ix.data, 8).ok_or(errors::ErrorCode::InvalidFlashInstruction)?;
+        let deposit_size = read_u64(&ix.data, 16).ok_or(errors::ErrorCode::InvalidFlashInstruction)?;
+        let size = burn_size.checked_add(deposit_size).ok_or(errors::ErrorCode::NumberOverflow)?;
+        return Ok(Some(FlashInstruction::Repay { option_market, size }))
+    }
+    Ok(None)
+}
+
+/// The index of the executing instruction. Flash instructions cannot be called through CPI,
+/// otherwise one top level instruction could flash mint many times against a single repay.
+fn current_index(instructions: &AccountInfo, option_market: &Pubkey, is_mint: bool) -> Result<usize> {
+    let index = load_current_index_checked(instructions)? as usize;
+    match load_flash_instruction(instructions, index)? {
+        Some(FlashInstruction::Mint { option_market: key, .. }) if is_mint && key == *option_market => Ok(index),
+        Some(FlashInstruction::Repay { option_market: key, .. }) if !is_mint && key == *option_market => Ok(index),
+        _ => Err(errors::ErrorCode::FlashInstructionMustBeTopLevel.into()),
+    }
+}
+
+/// Validate a later instruction in the transaction is the `flash_repay` that settles this
+/// `flash_mint`. Flash mints on a market cannot be nested, so every `flash_mint` is paired
+/// with exactly one `flash_repay`.
+pub fn validate_repaid(instructions: &AccountInfo, option_market: &Pubkey, size: u64) -> Result<()> {
+    let mut index = current_index(instructions, option_market, true)? + 1;
+    loop {
+        let flash_instruction = match load_flash_instruction(instructions, index) {
+            Ok(flash_instruction) => flash_instruction,
+            // Past the last instruction
+            Err(_) => return Err(errors::ErrorCode::FlashMintNotRepaid.into()),
+        };
+        match flash_instruction {
+            Some(FlashInstruction::Mint { option_market: key, .. }) if key == *option_market => {
+                return Err(errors::ErrorCode::FlashMintNotRepaid.into())
+            }
+            Some(FlashInstruction::Repay { option_market: key, size: repaid }) if key == *option_market => {
+                if repaid != size {
+                    return Err(errors::ErrorCode::FlashRepayDoesNotMatchMint.into())
+                }
+                return Ok(())
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+}
+
+/// Validate an earlier instruction in the transaction is the `flash_mint` this `flash_repay`
+/// settles, so collateral is never burned or deposited without minting.
+pub fn validate_minted(instructions: &AccountInfo, option_market: &Pubkey, size: u64) -> Result<()> {
+    let mut index = current_index(instructions, option_market, false)?;
+    while index > 0 {
+        index -= 1;
+        match load_flash_instruction(instructions, index)? {
+            Some(FlashInstruction::Repay { option_market: key, .. }) if key == *option_market => break,
+            Some(FlashInstruction::Mint { option_market: key, size: minted }) if key == *option_market => {
+                if minted != size {
+                    return Err(errors::ErrorCode::FlashRepayDoesNotMatchMint.into())
+                }
+                return Ok(())
+            }
+            _ => {}
+        }
+    }
+    Err(errors::ErrorCode::FlashRepayWithoutMint.into())
+}
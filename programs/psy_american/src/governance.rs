@@ -0,0 +1,489 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{system_program, AccountsClose, AccountsExit};
+use anchor_spl::token::Mint;
+
+use crate::{
+    errors, listing, pause, price_band, series, yield_vault, Governance, GovernanceAction, ListedPair, Lister,
+    ListingRegistry, OptionMarket, PauseConfig, PriceBandConfig, Proposal, Referrer, SeriesConfig, YieldAdapter,
+};
+
+/// Seed of the Governance PDA. There is a single governance for the program.
+pub const GOVERNANCE_SEED: &[u8] = b"governance";
+/// Seed of the Proposal PDAs, followed by the little endian u64 proposal index
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+
+pub const MAX_GOVERNANCE_MEMBERS: usize = 10;
+/// Approved proposals wait at least a day before they can be executed, so users have notice
+/// of parameter changes. Proposals that only pause more or halt a Serum market are the
+/// exception, see `skips_timelock`.
+pub const MIN_TIMELOCK_SECONDS: i64 = 24 * 60 * 60;
+
+/// Account size of the Governance with every member
+pub const GOVERNANCE_LEN: usize = 8 + 4 + MAX_GOVERNANCE_MEMBERS * 32 + 1 + 8 + 8 + 1;
+/// Serialized size of the largest GovernanceAction, an UpdateGovernance with every member
+pub const GOVERNANCE_ACTION_LEN: usize = 1 + 4 + MAX_GOVERNANCE_MEMBERS * 32 + 1 + 8;
+/// Account size of a Proposal approved by every member
+pub const PROPOSAL_LEN: usize = 8 + 8 + 32 + GOVERNANCE_ACTION_LEN + 4 + MAX_GOVERNANCE_MEMBERS * 32 + 8 + 1;
+
+pub fn governance_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GOVERNANCE_SEED], &crate::ID)
+}
+
+pub fn proposal_address(index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROPOSAL_SEED, &index.to_le_bytes()], &crate::ID)
+}
+
+/// Validate the members are unique and can reach the threshold
+pub fn validate_config(members: &[Pubkey], threshold: u8, timelock_seconds: i64) -> Result<()> {
+    if members.is_empty() || members.len() > MAX_GOVERNANCE_MEMBERS {
+        return Err(errors::ErrorCode::InvalidGovernanceConfig.into())
+    }
+    if threshold == 0 || threshold as usize > members.len() || timelock_seconds < MIN_TIMELOCK_SECONDS {
+        return Err(errors::ErrorCode::InvalidGovernanceConfig.into())
+    }
+    for (i, member) in members.iter().enumerate() {
+        if members[..i].contains(member) {
+            return Err(errors::ErrorCode::InvalidGovernanceConfig.into())
+        }
+    }
+    Ok(())
+}
+
+/// Validate a proposed action could be executed
+pub fn validate_action(action: &GovernanceAction) -> Result<()> {
+    match action {
+        GovernanceAction::ListPair { underlying_asset_mint, quote_asset_mint } => {
+            if underlying_asset_mint == quote_asset_mint {
+                return Err(errors::ErrorCode::QuoteAndUnderlyingAssetMustDiffer.into())
+            }
+            Ok(())
+        }
+        GovernanceAction::SetReferrerShare { frontend_share_bps, .. } => crate::validate_referrer_share(*frontend_share_bps),
+        GovernanceAction::UpdateGovernance { members, threshold, timelock_seconds } => {
+            validate_config(members, *threshold, *timelock_seconds)
+        }
+        GovernanceAction::SetSeriesConfig {
+            underlying_amount_per_contract,
+            strike_increment,
+            expiration_interval,
+            expiration_offset,
+            ..
+        } => series::validate_config(*underlying_amount_per_contract, *strike_increment, *expiration_interval, *expiration_offset),
+        GovernanceAction::SetPriceBand { lower_band_bps, .. } => crate::validate_price_band(*lower_band_bps),
+        _ => Ok(()),
+    }
+}
+
+/// True when the action can be executed as soon as it is approved. Pausing more and halting a
+/// Serum market have to take effect in an emergency, while unpausing and every other change
+/// waits out the timelock. `target` is the action's validated target.
+pub fn skips_timelock(action: &GovernanceAction, target: &AccountInfo) -> Result<bool> {
+    match action {
+        GovernanceAction::SetPauseFlags { flags } => Ok(flags.pauses_at_least(&pause::current_flags(target)?)),
+        GovernanceAction::SetPriceBand { oracle, lower_band_bps, upper_band_bps, halted, .. } => {
+            price_band::only_halts(target, oracle, *lower_band_bps, *upper_band_bps, *halted)
+        }
+        _ => Ok(false),
+    }
+}
+
+impl Governance {
+    pub fn is_member(&self, key: &Pubkey) -> bool {
+        self.members.contains(key)
+    }
+
+    /// Approvals from current members. Approvals of members removed since no longer count.
+    pub fn approvals(&self, proposal: &Proposal) -> usize {
+        proposal.approvals.iter().filter(|approver| self.is_member(approver)).count()
+    }
+
+    pub fn is_approved(&self, proposal: &Proposal) -> bool {
+        self.approvals(proposal) >= self.threshold as usize
+    }
+
+    /// Start the proposal's timelock once it reaches the threshold. Returns the time it can be
+    /// executed when it is newly queued.
+    pub fn queue_if_approved(&self, proposal: &mut Proposal, now: i64) -> Result<Option<i64>> {
+        if proposal.is_queued() || !self.is_approved(proposal) {
+            return Ok(None)
+        }
+        proposal.eta = now.checked_add(self.timelock_seconds).ok_or(errors::ErrorCode::NumberOverflow)?;
+        Ok(Some(proposal.eta))
+    }
+}
+
+impl Proposal {
+    /// True once the proposal reached the threshold and its timelock started
+    pub fn is_queued(&self) -> bool {
+        self.eta != 0
+    }
+}
+
+impl GovernanceAction {
+    /// The account the action creates or changes
+    pub fn target(&self) -> Pubkey {
+        match self {
+            GovernanceAction::SetPauseFlags { .. } => {
+                Pubkey::find_program_address(&[pause::PAUSE_CONFIG_SEED], &crate::ID).0
+            }
+            GovernanceAction::SetListingEnforcement { .. } => {
+                Pubkey::find_program_address(&[listing::LISTING_REGISTRY_SEED], &crate::ID).0
+            }
+            GovernanceAction::ListPair { underlying_asset_mint, quote_asset_mint } |
+            GovernanceAction::DelistPair { underlying_asset_mint, quote_asset_mint } => {
+                Pubkey::find_program_address(
+                    &[listing::LISTED_PAIR_SEED, underlying_asset_mint.as_ref(), quote_asset_mint.as_ref()],
+                    &crate::ID,
+                ).0
+            }
+            GovernanceAction::AddLister { authority } | GovernanceAction::RemoveLister { authority } => {
+                Pubkey::find_program_address(&[listing::LISTER_SEED, authority.as_ref()], &crate::ID).0
+            }
+            GovernanceAction::SetReferrerShare { referrer, .. } => *referrer,
+            GovernanceAction::UpdateGovernance { .. } => governance_address().0,
+            GovernanceAction::SetMarketOfficial { option_market, .. } |
+            GovernanceAction::UpgradeMarket { option_market, .. } => *option_market,
+            GovernanceAction::SetSeriesConfig { underlying_asset_mint, quote_asset_mint, .. } |
+            GovernanceAction::CloseSeriesConfig { underlying_asset_mint, quote_asset_mint } => {
+                Pubkey::find_program_address(
+                    &[series::SERIES_CONFIG_SEED, underlying_asset_mint.as_ref(), quote_asset_mint.as_ref()],
+                    &crate::ID,
+                ).0
+            }
+            GovernanceAction::SetPriceBand { option_market, pc_mint, .. } => {
+                price_band::price_band_address(&price_band::serum_market_address(option_market, pc_mint)).0
+            }
+            GovernanceAction::ApproveYieldAdapter { adapter_program } |
+            GovernanceAction::RevokeYieldAdapter { adapter_program } => {
+                Pubkey::find_program_address(&[yield_vault::YIELD_ADAPTER_SEED, adapter_program.as_ref()], &crate::ID).0
+            }
+        }
+    }
+}
+
+/// Apply an action to its target, creating the PDA when the action adds it. `payer` funds
+/// created accounts and `rent_dest` receives the rent of closed ones. UpdateGovernance changes
+/// the Governance account itself and is applied by the caller. `remaining_accounts` are the
+/// accounts SetPriceBand reads, see `set_price_band`.
+pub fn apply<'info>(
+    action: &GovernanceAction,
+    target: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    rent_dest: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>]
+) -> Result<()> {
+    match action {
+        GovernanceAction::SetPauseFlags { flags } => {
+            if target.data_is_empty() {
+                let seeds: &[&[u8]] = &[pause::PAUSE_CONFIG_SEED];
+                create(payer, target, system_program, seeds, 8 + std::mem::size_of::<PauseConfig>(), |bump_seed| {
+                    PauseConfig { flags: *flags, bump_seed }
+                })
+            } else {
+                let mut pause_config = Account::<PauseConfig>::try_from(target)?;
+                pause_config.flags = *flags;
+                pause_config.exit(&crate::ID)
+            }
+        }
+        GovernanceAction::SetListingEnforcement { enforce } => {
+            if target.data_is_empty() {
+                let seeds: &[&[u8]] = &[listing::LISTING_REGISTRY_SEED];
+                create(payer, target, system_program, seeds, 8 + std::mem::size_of::<ListingRegistry>(), |bump_seed| {
+                    ListingRegistry { enforce: *enforce, bump_seed }
+                })
+            } else {
+                let mut listing_registry = Account::<ListingRegistry>::try_from(target)?;
+                listing_registry.enforce = *enforce;
+                listing_registry.exit(&crate::ID)
+            }
+        }
+        GovernanceAction::ListPair { underlying_asset_mint, quote_asset_mint } => {
+            let seeds: &[&[u8]] = &[listing::LISTED_PAIR_SEED, underlying_asset_mint.as_ref(), quote_asset_mint.as_ref()];
+            create(payer, target, system_program, seeds, 8 + std::mem::size_of::<ListedPair>(), |bump_seed| {
+                ListedPair {
+                    underlying_asset_mint: *underlying_asset_mint,
+                    quote_asset_mint: *quote_asset_mint,
+                    bump_seed,
+                }
+            })
+        }
+        GovernanceAction::DelistPair { .. } => {
+            Account::<ListedPair>::try_from(target)?.close(rent_dest.clone())
+        }
+        GovernanceAction::AddLister { authority } => {
+            let seeds: &[&[u8]] = &[listing::LISTER_SEED, authority.as_ref()];
+            create(payer, target, system_program, seeds, 8 + std::mem::size_of::<Lister>(), |bump_seed| {
+                Lister { authority: *authority, bump_seed }
+            })
+        }
+        GovernanceAction::RemoveLister { .. } => {
+            Account::<Lister>::try_from(target)?.close(rent_dest.clone())
+        }
+        GovernanceAction::SetReferrerShare { frontend_share_bps, .. } => {
+            let mut referrer = Account::<Referrer>::try_from(target)?;
+            referrer.frontend_share_bps = *frontend_share_bps;
+            referrer.exit(&crate::ID)
+        }
+        GovernanceAction::UpdateGovernance { .. } => Ok(()),
+        GovernanceAction::SetMarketOfficial { official, .. } => {
+            let mut option_market = Account::<OptionMarket>::try_from(target)?;
+            option_market.official = *official;
+            option_market.exit(&crate::ID)
+        }
+        GovernanceAction::UpgradeMarket { creator, created_at, .. } => {
+            let mut option_market = Account::<OptionMarket>::try_from(target)?;
+            if option_market.creator != Pubkey::default() {
+                return Err(errors::ErrorCode::MarketAlreadyUpgraded.into())
+            }
+            option_market.creator = *creator;
+            option_market.created_at = *created_at;
+            option_market.exit(&crate::ID)
+        }
+        GovernanceAction::SetSeriesConfig {
+            underlying_asset_mint,
+            quote_asset_mint,
+            underlying_amount_per_contract,
+            strike_increment,
+            expiration_interval,
+            expiration_offset,
+        } => {
+            if target.data_is_empty() {
+                let seeds: &[&[u8]] = &[series::SERIES_CONFIG_SEED, underlying_asset_mint.as_ref(), quote_asset_mint.as_ref()];
+                create(payer, target, system_program, seeds, 8 + std::mem::size_of::<SeriesConfig>(), |bump_seed| {
+                    SeriesConfig {
+                        underlying_asset_mint: *underlying_asset_mint,
+                        quote_asset_mint: *quote_asset_mint,
+                        underlying_amount_per_contract: *underlying_amount_per_contract,
+                        strike_increment: *strike_increment,
+                        expiration_interval: *expiration_interval,
+                        expiration_offset: *expiration_offset,
+                        bump_seed,
+                    }
+                })
+            } else {
+                let mut series_config = Account::<SeriesConfig>::try_from(target)?;
+                series_config.underlying_amount_per_contract = *underlying_amount_per_contract;
+                series_config.strike_increment = *strike_increment;
+                series_config.expiration_interval = *expiration_interval;
+                series_config.expiration_offset = *expiration_offset;
+                series_config.exit(&crate::ID)
+            }
+        }
+        GovernanceAction::CloseSeriesConfig { .. } => {
+            Account::<SeriesConfig>::try_from(target)?.close(rent_dest.clone())
+        }
+        GovernanceAction::SetPriceBand { .. } => set_price_band(action, target, payer, system_program, remaining_accounts),
+        GovernanceAction::ApproveYieldAdapter { adapter_program } => {
+            let seeds: &[&[u8]] = &[yield_vault::YIELD_ADAPTER_SEED, adapter_program.as_ref()];
+            create(payer, target, system_program, seeds, 8 + std::mem::size_of::<YieldAdapter>(), |bump_seed| {
+                YieldAdapter { adapter_program: *adapter_program, bump_seed }
+            })
+        }
+        GovernanceAction::RevokeYieldAdapter { .. } => {
+            Account::<YieldAdapter>::try_from(target)?.close(rent_dest.clone())
+        }
+    }
+}
+
+/// Apply a SetPriceBand. `remaining_accounts` start with the oracle, which must be a valid price
+/// account unless the action halts trading. Creating the PriceBandConfig also needs the
+/// OptionMarket, Serum market, underlying, quote and pc mints, in that order.
+fn set_price_band<'info>(
+    action: &GovernanceAction,
+    target: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>]
+) -> Result<()> {
+    let (option_market, pc_mint, oracle, lower_band_bps, upper_band_bps, halted) = match action {
+        GovernanceAction::SetPriceBand { option_market, pc_mint, oracle, lower_band_bps, upper_band_bps, halted } => {
+            (option_market, pc_mint, oracle, *lower_band_bps, *upper_band_bps, *halted)
+        }
+        _ => unreachable!(),
+    };
+    let oracle_info = remaining_accounts.get(0).ok_or(anchor_lang::error::ErrorCode::AccountNotEnoughKeys)?;
+    if oracle_info.key != oracle {
+        return Err(errors::ErrorCode::OracleDoesNotMatchPriceBand.into())
+    }
+    if !target.data_is_empty() {
+        // A halt must not depend on the oracle, which may be why trading is halted
+        if !halted {
+            price_band::load_oracle_price(oracle_info)?;
+        }
+        let mut config = Account::<PriceBandConfig>::try_from(target)?;
+        config.oracle = *oracle;
+        config.lower_band_bps = lower_band_bps;
+        config.upper_band_bps = upper_band_bps;
+        config.halted = halted;
+        return config.exit(&crate::ID)
+    }
+
+    let accounts = remaining_accounts.get(1..6).ok_or(anchor_lang::error::ErrorCode::AccountNotEnoughKeys)?;
+    let option_market_account = Account::<OptionMarket>::try_from(&accounts[0])?;
+    if option_market_account.key() != *option_market {
+        return Err(errors::ErrorCode::ProposalTargetDoesNotMatch.into())
+    }
+    let serum_market = &accounts[1];
+    if serum_market.key() != price_band::serum_market_address(option_market, pc_mint) {
+        return Err(errors::ErrorCode::PriceBandDoesNotMatchMarket.into())
+    }
+    let underlying_asset_mint = Account::<Mint>::try_from(&accounts[2])?;
+    let quote_asset_mint = Account::<Mint>::try_from(&accounts[3])?;
+    let pc_mint_account = Account::<Mint>::try_from(&accounts[4])?;
+    if pc_mint_account.key() != *pc_mint {
+        return Err(errors::ErrorCode::PcMintMustBeUnderlyingOrQuote.into())
+    }
+    price_band::validate_mints(
+        &option_market_account,
+        &underlying_asset_mint.key(),
+        &quote_asset_mint.key(),
+        pc_mint,
+    )?;
+    let config = price_band::new_config(
+        &option_market_account,
+        serum_market,
+        oracle_info,
+        &underlying_asset_mint,
+        &quote_asset_mint,
+        &pc_mint_account,
+        lower_band_bps,
+        upper_band_bps,
+        0,
+    )?;
+    let serum_market_key = serum_market.key();
+    let seeds: &[&[u8]] = &[serum_market_key.as_ref(), b"priceBand"];
+    create(payer, target, system_program, seeds, 8 + std::mem::size_of::<PriceBandConfig>(), |bump_seed| {
+        PriceBandConfig { halted, bump_seed, ..config }
+    })
+}
+
+/// Create the PDA at `seeds` and write the account built from its bump seed. Like Anchor's
+/// `init`, a PDA that was already sent lamports is topped up to rent exemption, allocated and
+/// assigned, since `create_account` fails on an account with lamports.
+fn create<'info, T: AccountSerialize>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    space: usize,
+    account: impl FnOnce(u8) -> T
+) -> Result<()> {
+    let (_, bump) = Pubkey::find_program_address(seeds, &crate::ID);
+    let bump_seed = [bump];
+    let mut signer_seeds = seeds.to_vec();
+    signer_seeds.push(&bump_seed);
+    let signer = &[&signer_seeds[..]];
+    let rent_exempt_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = target.lamports();
+    if current_lamports == 0 {
+        let cpi_accounts = system_program::CreateAccount {
+            from: payer.clone(),
+            to: target.clone(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer);
+        system_program::create_account(cpi_ctx, rent_exempt_lamports, space as u64, &crate::ID)?;
+    } else {
+        let shortfall = rent_exempt_lamports.saturating_sub(current_lamports);
+        if shortfall > 0 {
+            let cpi_accounts = system_program::Transfer {
+                from: payer.clone(),
+                to: target.clone(),
+            };
+            let cpi_ctx = CpiContext::new(system_program.clone(), cpi_accounts);
+            system_program::transfer(cpi_ctx, shortfall)?;
+        }
+        let cpi_accounts = system_program::Allocate {
+            account_to_allocate: target.clone(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer);
+        system_program::allocate(cpi_ctx, space as u64)?;
+        let cpi_accounts = system_program::Assign {
+            account_to_assign: target.clone(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer);
+        system_program::assign(cpi_ctx, &crate::ID)?;
+    }
+    let mut data = target.try_borrow_mut_data()?;
+    account(bump).try_serialize(&mut &mut data[..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn governance(members: Vec<Pubkey>, threshold: u8) -> Governance {
+        Governance {
+            members,
+            threshold,
+            timelock_seconds: MIN_TIMELOCK_SECONDS,
+            proposal_count: 0,
+            bump_seed: 255,
+        }
+    }
+
+    fn proposal(approvals: Vec<Pubkey>, action: GovernanceAction) -> Proposal {
+        Proposal {
+            index: 0,
+            proposer: approvals[0],
+            action,
+            approvals,
+            eta: 0,
+            bump_seed: 255,
+        }
+    }
+
+    fn enforce_listing() -> GovernanceAction {
+        GovernanceAction::SetListingEnforcement { enforce: true }
+    }
+
+    #[test]
+    fn config_threshold_must_be_reachable() {
+        let members: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        assert!(validate_config(&members, 2, MIN_TIMELOCK_SECONDS).is_ok());
+        assert!(validate_config(&members, 0, MIN_TIMELOCK_SECONDS).is_err());
+        assert!(validate_config(&members, 4, MIN_TIMELOCK_SECONDS).is_err());
+        assert!(validate_config(&members, 2, MIN_TIMELOCK_SECONDS - 1).is_err());
+        assert!(validate_config(&[], 0, MIN_TIMELOCK_SECONDS).is_err());
+        assert!(validate_config(&[members[0], members[0]], 1, MIN_TIMELOCK_SECONDS).is_err());
+        let too_many: Vec<_> = (0..=MAX_GOVERNANCE_MEMBERS).map(|_| Pubkey::new_unique()).collect();
+        assert!(validate_config(&too_many, 1, MIN_TIMELOCK_SECONDS).is_err());
+    }
+
+    #[test]
+    fn removed_members_approvals_do_not_count() {
+        let members: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let mut governance = governance(members.clone(), 2);
+        let proposal = proposal(vec![members[0], members[1]], enforce_listing());
+        assert!(governance.is_approved(&proposal));
+        governance.members.remove(1);
+        assert_eq!(governance.approvals(&proposal), 1);
+        assert!(!governance.is_approved(&proposal));
+    }
+
+    #[test]
+    fn proposals_queue_once_at_the_threshold() {
+        let members: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let governance = governance(members.clone(), 2);
+        let mut proposal = proposal(vec![members[0]], enforce_listing());
+        assert_eq!(governance.queue_if_approved(&mut proposal, 100).unwrap(), None);
+        proposal.approvals.push(members[2]);
+        assert_eq!(governance.queue_if_approved(&mut proposal, 100).unwrap(), Some(100 + MIN_TIMELOCK_SECONDS));
+        assert_eq!(governance.queue_if_approved(&mut proposal, 200).unwrap(), None);
+        assert_eq!(proposal.eta, 100 + MIN_TIMELOCK_SECONDS);
+    }
+
+    #[test]
+    fn largest_proposal_fits_the_account() {
+        let members: Vec<_> = (0..MAX_GOVERNANCE_MEMBERS).map(|_| Pubkey::new_unique()).collect();
+        let action = GovernanceAction::UpdateGovernance {
+            members: members.clone(),
+            threshold: 1,
+            timelock_seconds: MIN_TIMELOCK_SECONDS,
+        };
+        let proposal = proposal(members, action);
+        let mut data = vec![];
+        proposal.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), PROPOSAL_LEN);
+    }
+}
@@ -1,30 +1,81 @@
+pub mod amm;
+pub mod contracts;
 pub mod errors;
 pub mod fees;
+pub mod flash;
+pub mod governance;
+pub mod listing;
+pub mod migration;
+pub mod option_chain;
+pub mod pause;
+pub mod price_band;
+pub mod proxy_cpi;
+pub mod series;
 pub mod serum_proxy;
+pub mod yield_vault;
 
 use anchor_lang::{AccountsExit, Key, prelude::*};
 use anchor_spl::token::{self, Burn, Mint, MintTo, TokenAccount, Transfer, Token};
 use anchor_spl::dex::{initialize_market as init_serum_market_instruction, InitializeMarket as SerumInitMarket};
+use anchor_spl::dex::serum_dex::{instruction::SelfTradeBehavior, matching::{OrderType as SerumOrderType, Side as SerumSide}};
+use std::num::NonZeroU64;
 use spl_token::state::Account as SPLTokenAccount;
-use solana_program::{program::invoke, program_error::ProgramError, program_pack::Pack, system_instruction, system_program};
-use psyfi_serum_dex_permissioned::{MarketProxy, OpenOrdersPda, ReferralFees};
+use solana_program::{program::{invoke, set_return_data}, program_error::ProgramError, program_pack::Pack, system_instruction, system_program};
+use psyfi_serum_dex_permissioned::{MarketProxy, OpenOrdersPda};
+use contracts::Rounding;
 
 declare_id!("R2y9ip6mxmWUj4pt54jP2hz2dgvMozy9VTSwMWE7evs");
 
+/// The max number of orders to match against when placing an order on behalf of a user
+const SERUM_MATCH_LIMIT: u16 = u16::MAX;
+
+/// The number of remaining accounts for each entry of a batch_mint
+pub const BATCH_MINT_ACCOUNTS: usize = 7;
+/// The number of remaining accounts for each entry of a batch_exercise
+pub const BATCH_EXERCISE_ACCOUNTS: usize = 5;
+
+// The Serum types do not implement the BorshSerialize and BorshDeserialize that is required by Anchor.
+#[derive(Clone, Copy, Debug, AnchorSerialize, AnchorDeserialize)]
+pub enum OrderType {
+    Limit = 0,
+    ImmediateOrCancel = 1,
+    PostOnly = 2,
+}
+impl From<OrderType> for SerumOrderType {
+    fn from(order_type: OrderType) -> SerumOrderType {
+        match order_type {
+            OrderType::Limit => SerumOrderType::Limit,
+            OrderType::ImmediateOrCancel => SerumOrderType::ImmediateOrCancel,
+            OrderType::PostOnly => SerumOrderType::PostOnly,
+        }
+    }
+}
+
 #[program]
 pub mod psy_american {
     use super::*;
 
-    #[access_control(InitializeMarket::accounts(&ctx))]
-    /// Initialize a new PsyOptions market
+    #[access_control(
+        InitializeMarket::accounts(&ctx)
+        InitializeMarket::listing(&ctx)
+        InitializeMarket::option_chain(&ctx)
+        series::validate_market(&ctx.accounts.series_config, underlying_amount_per_contract, quote_amount_per_contract, expiration_unix_timestamp)
+        validate_option_decimals(option_decimals)
+        pause::check(&ctx.accounts.pause_config, pause::PauseGroup::InitializeMarket)
+    )]
+    /// Initialize a new PsyOptions market. A whole contract is `10^option_decimals` option
+    /// tokens, so markets with large contracts can be written and exercised fractionally.
+    /// Markets for a listed pair or created by an authorized lister are flagged official.
+    /// When the pair has a SeriesConfig the market must be on its expiration and strike grid.
+    /// The market is added to the pair's OptionChain index once the chain exists.
     pub fn initialize_market(
         ctx: Context<InitializeMarket>, 
         underlying_amount_per_contract: u64,
         quote_amount_per_contract: u64,
         expiration_unix_timestamp: i64,
-        bump_seed: u8
+        bump_seed: u8,
+        option_decimals: u8
     ) -> Result<()> {
-        panic!("Blocked instruction");
         // (nice to have) Validate the expiration is in the future
         if expiration_unix_timestamp < ctx.accounts.clock.unix_timestamp {
             return Err(errors::ErrorCode::ExpirationIsInThePast.into())
@@ -56,6 +107,27 @@ pub mod psy_american {
         option_market.mint_fee_account = fee_accounts.mint_fee_key;
         option_market.exercise_fee_account = fee_accounts.exercise_fee_key;
         option_market.expired = false;
+        option_market.option_decimals = option_decimals;
+        option_market.official = listing::is_official(&ctx.accounts.listed_pair, &ctx.accounts.lister);
+        option_market.version = OptionMarket::CURRENT_VERSION;
+        option_market.creator = ctx.accounts.authority.key();
+        option_market.created_at = ctx.accounts.clock.unix_timestamp;
+        option_market.label = [0; 32];
+        let option_market_key = option_market.key();
+        let option_chain = option_chain::load_page(
+            &ctx.accounts.option_chain,
+            &ctx.accounts.underlying_asset_mint.key(),
+            &ctx.accounts.quote_asset_mint.key(),
+        )?;
+        if let Some(mut option_chain) = option_chain {
+            option_chain.push(OptionChainEntry {
+                option_market: option_market_key,
+                underlying_amount_per_contract,
+                quote_amount_per_contract,
+                expiration_unix_timestamp,
+            })?;
+            option_chain.exit(&crate::ID)?;
+        }
 
         match ctx.bumps.get("option_market") {
             Some(bump) => {
@@ -70,9 +142,13 @@ pub mod psy_american {
         Ok(())
     }
 
-    #[access_control(MintOption::unexpired_market(&ctx) MintOption::accounts(&ctx) validate_size(size))]
+    #[access_control(
+        MintOption::unexpired_market(&ctx)
+        MintOption::accounts(&ctx)
+        validate_size(size)
+        pause::check(&ctx.accounts.pause_config, pause::PauseGroup::MintOption)
+    )]
     pub fn mint_option<'a, 'b, 'c, 'info>(ctx: Context<'a, 'b, 'c, 'info, MintOption<'info>>, size: u64) -> Result<()> {
-        panic!("Blocked instruction");
         let option_market = &ctx.accounts.option_market;
         let mint_fee_account = validate_mint_fee_acct(
             option_market,
@@ -80,7 +156,7 @@ pub mod psy_american {
         )?;
 
         // Take a mint fee
-        let mint_fee_amount_per_contract = fees::fee_amount(option_market.underlying_amount_per_contract);
+        let mint_fee_amount_per_contract = fees::fee_amount(option_market.underlying_amount_per_contract)?;
         if mint_fee_amount_per_contract > 0 {
             match mint_fee_account {
                 Some(account) => {
@@ -91,14 +167,14 @@ pub mod psy_american {
                     };
                     let cpi_token_program = ctx.accounts.token_program.clone();
                     let cpi_ctx = CpiContext::new(cpi_token_program.to_account_info(), cpi_accounts);
-                    let total_fee = mint_fee_amount_per_contract.checked_mul(size).ok_or(errors::ErrorCode::NumberOverflow)?;
+                    let total_fee = contracts::amount_for_size(mint_fee_amount_per_contract, size, option_market.option_decimals, Rounding::Up)?;
                     token::transfer(cpi_ctx, total_fee)?;
                 },
                 None => {}
             }
         } else {
             // Handle NFT case with SOL fee
-            let total_fee = fees::NFT_MINT_LAMPORTS.checked_mul(size).ok_or(errors::ErrorCode::NumberOverflow)?;
+            let total_fee = contracts::amount_for_size(fees::NFT_MINT_LAMPORTS, size, option_market.option_decimals, Rounding::Up)?;
             invoke(
                 &system_instruction::transfer(&ctx.accounts.user_authority.key, &fees::fee_owner_key::ID, total_fee),
             &[
@@ -117,7 +193,7 @@ pub mod psy_american {
         };
         let cpi_token_program = ctx.accounts.token_program.clone();
         let cpi_ctx = CpiContext::new(cpi_token_program.to_account_info(), cpi_accounts);
-        let underlying_transfer_amount = option_market.underlying_amount_per_contract.checked_mul(size).unwrap();
+        let underlying_transfer_amount = option_market.underlying_amount_in(size)?;
         token::transfer(cpi_ctx, underlying_transfer_amount)?;
 
         let seeds = &[
@@ -153,9 +229,13 @@ pub mod psy_american {
         Ok(())
     }
 
-    #[access_control(MintOptionV2::unexpired_market(&ctx) MintOptionV2::accounts(&ctx) validate_size(size))]
+    #[access_control(
+        MintOptionV2::unexpired_market(&ctx)
+        MintOptionV2::accounts(&ctx)
+        validate_size(size)
+        pause::check(&ctx.accounts.pause_config, pause::PauseGroup::MintOption)
+    )]
     pub fn mint_option_v2<'a, 'b, 'c, 'info>(ctx: Context<'a, 'b, 'c, 'info, MintOptionV2<'info>>, size: u64) -> Result<()> {
-        panic!("Blocked instruction");
         let option_market = &ctx.accounts.option_market;
 
         // Transfer the underlying assets to the underlying assets pool
@@ -166,7 +246,243 @@ pub mod psy_american {
         };
         let cpi_token_program = ctx.accounts.token_program.clone();
         let cpi_ctx = CpiContext::new(cpi_token_program.to_account_info(), cpi_accounts);
-        let underlying_transfer_amount = option_market.underlying_amount_per_contract.checked_mul(size).unwrap();
+        let underlying_transfer_amount = option_market.underlying_amount_in(size)?;
+        token::transfer(cpi_ctx, underlying_transfer_amount)?;
+
+        let seeds = &[
+            option_market.underlying_asset_mint.as_ref(),
+            option_market.quote_asset_mint.as_ref(),
+            &option_market.underlying_amount_per_contract.to_le_bytes(),
+            &option_market.quote_amount_per_contract.to_le_bytes(),
+            &option_market.expiration_unix_timestamp.to_le_bytes(),
+            &[option_market.bump_seed]
+        ];
+        let signer = &[&seeds[..]];
+
+        // Mint a new OptionToken(s)
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.option_mint.to_account_info(),
+            to: ctx.accounts.minted_option_dest.to_account_info(),
+            authority: ctx.accounts.option_market.to_account_info(),
+        };
+        let cpi_token_program = ctx.accounts.token_program.clone();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_token_program.to_account_info(), cpi_accounts, signer);
+        token::mint_to(cpi_ctx, size)?;
+
+        // Mint a new WriterToken(s)
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.writer_token_mint.to_account_info(),
+            to: ctx.accounts.minted_writer_token_dest.to_account_info(),
+            authority: ctx.accounts.option_market.to_account_info(),
+        };
+        let cpi_token_program = ctx.accounts.token_program.clone();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_token_program.to_account_info(), cpi_accounts, signer);
+        token::mint_to(cpi_ctx, size)?;
+
+        Ok(())
+    }
+
+    #[access_control(
+        BatchMint::accounts(&ctx, &sizes)
+        pause::check(&ctx.accounts.pause_config, pause::PauseGroup::CompositeMint)
+    )]
+    /// Mint options on several OptionMarkets in one instruction. Each entry of `sizes` has
+    /// BATCH_MINT_ACCOUNTS remaining accounts in the order of BatchMintEntry.
+    ///
+    /// Every OptionMarket holds its collateral in its own pool PDA, so markets on the same
+    /// underlying mint can not share a transfer. The underlying assets are transferred once per
+    /// source and pool instead, so repeated entries for a market cost a single transfer.
+    pub fn batch_mint<'a, 'b, 'c, 'info>(ctx: Context<'a, 'b, 'c, 'info, BatchMint<'info>>, sizes: Vec<u64>) -> Result<()> {
+        let clock = Clock::get()?;
+        let mut entries = Vec::with_capacity(sizes.len());
+        // (underlying_asset_src, underlying_asset_pool, amount)
+        let mut transfers: Vec<(AccountInfo<'info>, AccountInfo<'info>, u64)> = vec![];
+        for (accounts, size) in ctx.remaining_accounts.chunks(BATCH_MINT_ACCOUNTS).zip(sizes.iter()) {
+            let entry = BatchMintEntry::load(accounts)?;
+            entry.validate(clock.unix_timestamp)?;
+            validate_size(*size)?;
+
+            let underlying_transfer_amount = entry.option_market.underlying_amount_in(*size)?;
+            let src = entry.underlying_asset_src.to_account_info();
+            let pool = entry.underlying_asset_pool.to_account_info();
+            match transfers.iter_mut().find(|(s, p, _)| s.key == src.key && p.key == pool.key) {
+                Some((_, _, amount)) => {
+                    *amount = amount.checked_add(underlying_transfer_amount).ok_or(errors::ErrorCode::NumberOverflow)?;
+                }
+                None => transfers.push((src, pool, underlying_transfer_amount)),
+            }
+            entries.push((entry, *size));
+        }
+
+        // Transfer the underlying assets to the underlying assets pools
+        for (src, pool, amount) in transfers {
+            let cpi_accounts = Transfer {
+                from: src,
+                to: pool,
+                authority: ctx.accounts.user_authority.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            token::transfer(cpi_ctx, amount)?;
+        }
+
+        for (entry, size) in entries {
+            let option_market = &entry.option_market;
+            let seeds = &[
+                option_market.underlying_asset_mint.as_ref(),
+                option_market.quote_asset_mint.as_ref(),
+                &option_market.underlying_amount_per_contract.to_le_bytes(),
+                &option_market.quote_amount_per_contract.to_le_bytes(),
+                &option_market.expiration_unix_timestamp.to_le_bytes(),
+                &[option_market.bump_seed]
+            ];
+            let signer = &[&seeds[..]];
+
+            // Mint a new OptionToken(s)
+            let cpi_accounts = MintTo {
+                mint: entry.option_mint.to_account_info(),
+                to: entry.minted_option_dest.to_account_info(),
+                authority: entry.option_market.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token::mint_to(cpi_ctx, size)?;
+
+            // Mint a new WriterToken(s)
+            let cpi_accounts = MintTo {
+                mint: entry.writer_token_mint.to_account_info(),
+                to: entry.minted_writer_token_dest.to_account_info(),
+                authority: entry.option_market.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token::mint_to(cpi_ctx, size)?;
+        }
+
+        Ok(())
+    }
+
+    #[access_control(
+        FlashMint::accounts(&ctx)
+        FlashMint::unexpired_market(&ctx)
+        validate_size(size)
+        pause::check(&ctx.accounts.pause_config, pause::PauseGroup::CompositeMint)
+    )]
+    /// Mint `size` options and WriterTokens without collateral. A later top level `flash_repay`
+    /// on the same OptionMarket in the same transaction must burn or collateralize exactly
+    /// `size`, which is verified through the instructions sysvar.
+    pub fn flash_mint(ctx: Context<FlashMint>, size: u64) -> Result<()> {
+        flash::validate_repaid(&ctx.accounts.instructions, &ctx.accounts.option_market.key(), size)?;
+
+        let option_market = &ctx.accounts.option_market;
+        let seeds = &[
+            option_market.underlying_asset_mint.as_ref(),
+            option_market.quote_asset_mint.as_ref(),
+            &option_market.underlying_amount_per_contract.to_le_bytes(),
+            &option_market.quote_amount_per_contract.to_le_bytes(),
+            &option_market.expiration_unix_timestamp.to_le_bytes(),
+            &[option_market.bump_seed]
+        ];
+        let signer = &[&seeds[..]];
+
+        // Mint a new OptionToken(s)
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.option_mint.to_account_info(),
+            to: ctx.accounts.minted_option_dest.to_account_info(),
+            authority: ctx.accounts.option_market.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+        token::mint_to(cpi_ctx, size)?;
+
+        // Mint a new WriterToken(s)
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.writer_token_mint.to_account_info(),
+            to: ctx.accounts.minted_writer_token_dest.to_account_info(),
+            authority: ctx.accounts.option_market.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+        token::mint_to(cpi_ctx, size)?;
+
+        Ok(())
+    }
+
+    #[access_control(FlashRepay::accounts(&ctx))]
+    /// Settle the preceding `flash_mint` on the OptionMarket. `burn_size` options and WriterTokens
+    /// are burned and the underlying assets for `deposit_size` are deposited into the pool, which
+    /// leaves the deposited options collateralized as if they were minted normally.
+    pub fn flash_repay(ctx: Context<FlashRepay>, burn_size: u64, deposit_size: u64) -> Result<()> {
+        let size = burn_size.checked_add(deposit_size).ok_or(errors::ErrorCode::NumberOverflow)?;
+        flash::validate_minted(&ctx.accounts.instructions, &ctx.accounts.option_market.key(), size)?;
+
+        if burn_size > 0 {
+            // Burn the OptionTokens
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.option_mint.to_account_info(),
+                    from: ctx.accounts.option_token_src.to_account_info(),
+                    authority: ctx.accounts.user_authority.to_account_info(),
+                },
+            );
+            token::burn(cpi_ctx, burn_size)?;
+
+            // Burn the WriterTokens
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.writer_token_mint.to_account_info(),
+                    from: ctx.accounts.writer_token_src.to_account_info(),
+                    authority: ctx.accounts.user_authority.to_account_info(),
+                },
+            );
+            token::burn(cpi_ctx, burn_size)?;
+        }
+
+        if deposit_size > 0 {
+            // Transfer the underlying assets to the underlying assets pool
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.underlying_asset_src.to_account_info(),
+                to: ctx.accounts.underlying_asset_pool.to_account_info(),
+                authority: ctx.accounts.user_authority.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            let underlying_transfer_amount = ctx.accounts.option_market.underlying_amount_in(deposit_size)?;
+            token::transfer(cpi_ctx, underlying_transfer_amount)?;
+        }
+
+        Ok(())
+    }
+
+    #[access_control(
+        MintAndSell::unexpired_market(&ctx)
+        MintAndSell::accounts(&ctx)
+        validate_size(size)
+        pause::check(&ctx.accounts.pause_config, pause::PauseGroup::CompositeMint)
+    )]
+    /// Mint options and place an ask for them on the OptionMarket's Serum market in a single
+    /// instruction. The writer tokens are kept by the user. The user's OpenOrders account must
+    /// already be initialized through the proxy, and the ask is placed through the proxy so it
+    /// runs the same middleware as every other order.
+    pub fn mint_and_sell<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, MintAndSell<'info>>,
+        size: u64,
+        limit_price: u64,
+        order_type: OrderType,
+        client_order_id: u64
+    ) -> Result<()> {
+        let option_market = &ctx.accounts.option_market;
+
+        let (coin_lot_size, _pc_lot_size) = price_band::load_lot_sizes(&ctx.accounts.serum_market)?;
+        let coin_lots = size.checked_div(coin_lot_size).ok_or(errors::ErrorCode::SerumMarketNotInitialized)?;
+        let max_coin_qty = NonZeroU64::new(coin_lots).ok_or(errors::ErrorCode::SizeSmallerThanLotSize)?;
+        let limit_price = NonZeroU64::new(limit_price).ok_or(errors::ErrorCode::LimitPriceCantBeZero)?;
+
+        // Transfer the underlying assets to the underlying assets pool
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.underlying_asset_src.to_account_info(),
+            to: ctx.accounts.underlying_asset_pool.to_account_info(),
+            authority: ctx.accounts.user_authority.to_account_info().clone(),
+        };
+        let cpi_token_program = ctx.accounts.token_program.clone();
+        let cpi_ctx = CpiContext::new(cpi_token_program.to_account_info(), cpi_accounts);
+        let underlying_transfer_amount = option_market.underlying_amount_in(size)?;
         token::transfer(cpi_ctx, underlying_transfer_amount)?;
 
         let seeds = &[
@@ -199,6 +515,166 @@ pub mod psy_american {
         let cpi_ctx = CpiContext::new_with_signer(cpi_token_program.to_account_info(), cpi_accounts, signer);
         token::mint_to(cpi_ctx, size)?;
 
+        // Place the ask through the proxy, which validates the price band and signs for the
+        // user's OpenOrders PDA
+        let cpi_accounts = proxy_cpi::NewOrderV3 {
+            dex_program: ctx.accounts.dex_program.to_account_info(),
+            price_band: ctx.accounts.price_band.to_account_info(),
+            price_oracle: ctx.accounts.price_oracle.to_account_info(),
+            market: ctx.accounts.serum_market.to_account_info(),
+            open_orders: ctx.accounts.open_orders.to_account_info(),
+            request_queue: ctx.accounts.request_queue.to_account_info(),
+            event_queue: ctx.accounts.event_queue.to_account_info(),
+            market_bids: ctx.accounts.market_bids.to_account_info(),
+            market_asks: ctx.accounts.market_asks.to_account_info(),
+            order_payer_token_account: ctx.accounts.minted_option_dest.to_account_info(),
+            authority: ctx.accounts.user_authority.to_account_info(),
+            coin_vault: ctx.accounts.coin_vault.to_account_info(),
+            pc_vault: ctx.accounts.pc_vault.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.psy_american_program.to_account_info(), cpi_accounts);
+        proxy_cpi::new_order_v3(
+            cpi_ctx,
+            SerumSide::Ask,
+            limit_price,
+            max_coin_qty,
+            NonZeroU64::new(u64::MAX).unwrap(),
+            SelfTradeBehavior::DecrementTake,
+            order_type.into(),
+            client_order_id,
+            SERUM_MATCH_LIMIT,
+        )?;
+
+        Ok(())
+    }
+
+    #[access_control(
+        BuyAndExercise::accounts(&ctx)
+        BuyAndExercise::unexpired_market(&ctx)
+        validate_size(size)
+        pause::check(&ctx.accounts.pause_config, pause::PauseGroup::BuyAndExercise)
+    )]
+    /// Buy options with an IOC bid on the OptionMarket's Serum market and exercise the
+    /// filled amount in a single instruction. `max_price` is the bid's limit price and the
+    /// instruction fails if fewer than `min_fill` options are filled. The bid is placed and
+    /// settled through the proxy so it runs the same middleware as every other order.
+    pub fn buy_and_exercise<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, BuyAndExercise<'info>>,
+        size: u64,
+        max_price: u64,
+        max_native_pc_qty_including_fees: u64,
+        min_fill: u64
+    ) -> Result<()> {
+        let (coin_lot_size, _pc_lot_size) = price_band::load_lot_sizes(&ctx.accounts.serum_market)?;
+        let coin_lots = size.checked_div(coin_lot_size).ok_or(errors::ErrorCode::SerumMarketNotInitialized)?;
+        let max_coin_qty = NonZeroU64::new(coin_lots).ok_or(errors::ErrorCode::SizeSmallerThanLotSize)?;
+        let max_price = NonZeroU64::new(max_price).ok_or(errors::ErrorCode::LimitPriceCantBeZero)?;
+        let max_native_pc_qty = NonZeroU64::new(max_native_pc_qty_including_fees).ok_or(errors::ErrorCode::LimitPriceCantBeZero)?;
+
+        // Place the IOC bid through the proxy, which validates the price band and signs for the
+        // user's OpenOrders PDA. The fill is credited to the OpenOrders as free coin, which may
+        // already hold coin from earlier orders.
+        let coin_free_before = price_band::load_native_coin_free(&ctx.accounts.open_orders)?;
+        let cpi_accounts = proxy_cpi::NewOrderV3 {
+            dex_program: ctx.accounts.dex_program.to_account_info(),
+            price_band: ctx.accounts.price_band.to_account_info(),
+            price_oracle: ctx.accounts.price_oracle.to_account_info(),
+            market: ctx.accounts.serum_market.to_account_info(),
+            open_orders: ctx.accounts.open_orders.to_account_info(),
+            request_queue: ctx.accounts.request_queue.to_account_info(),
+            event_queue: ctx.accounts.event_queue.to_account_info(),
+            market_bids: ctx.accounts.market_bids.to_account_info(),
+            market_asks: ctx.accounts.market_asks.to_account_info(),
+            order_payer_token_account: ctx.accounts.pc_wallet.to_account_info(),
+            authority: ctx.accounts.user_authority.to_account_info(),
+            coin_vault: ctx.accounts.coin_vault.to_account_info(),
+            pc_vault: ctx.accounts.pc_vault.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.psy_american_program.to_account_info(), cpi_accounts);
+        proxy_cpi::new_order_v3(
+            cpi_ctx,
+            SerumSide::Bid,
+            max_price,
+            max_coin_qty,
+            max_native_pc_qty,
+            SelfTradeBehavior::DecrementTake,
+            SerumOrderType::ImmediateOrCancel,
+            0,
+            SERUM_MATCH_LIMIT,
+        )?;
+        let filled = price_band::load_native_coin_free(&ctx.accounts.open_orders)?
+            .checked_sub(coin_free_before)
+            .ok_or(errors::ErrorCode::NumberOverflow)?;
+        if filled == 0 || filled < min_fill {
+            return Err(errors::ErrorCode::FillBelowMinimum.into())
+        }
+
+        // Settle the filled options into the user's OptionToken account
+        let cpi_accounts = proxy_cpi::SettleFunds {
+            dex_program: ctx.accounts.dex_program.to_account_info(),
+            market: ctx.accounts.serum_market.to_account_info(),
+            open_orders: ctx.accounts.open_orders.to_account_info(),
+            authority: ctx.accounts.user_authority.to_account_info(),
+            coin_vault: ctx.accounts.coin_vault.to_account_info(),
+            pc_vault: ctx.accounts.pc_vault.to_account_info(),
+            coin_wallet: ctx.accounts.option_token_dest.to_account_info(),
+            pc_wallet: ctx.accounts.pc_wallet.to_account_info(),
+            vault_signer: ctx.accounts.vault_signer.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.psy_american_program.to_account_info(), cpi_accounts);
+        proxy_cpi::settle_funds(cpi_ctx)?;
+
+        // Exercise exactly the amount this order filled. Coin that was already free in the
+        // OpenOrders is settled to the user as well, but is not exercised.
+        let option_market = &ctx.accounts.option_market;
+        let seeds = &[
+            option_market.underlying_asset_mint.as_ref(),
+            option_market.quote_asset_mint.as_ref(),
+            &option_market.underlying_amount_per_contract.to_le_bytes(),
+            &option_market.quote_amount_per_contract.to_le_bytes(),
+            &option_market.expiration_unix_timestamp.to_le_bytes(),
+            &[option_market.bump_seed]
+        ];
+        let signer = &[&seeds[..]];
+        // Burn the filled option tokens
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info().clone(),
+            Burn {
+                mint: ctx.accounts.option_mint.to_account_info(),
+                from: ctx.accounts.option_token_dest.to_account_info(),
+                authority: ctx.accounts.user_authority.to_account_info(),
+            },
+            signer,
+        );
+        token::burn(cpi_ctx, filled)?;
+
+        // Transfer the quote assets to the pool
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.quote_asset_src.to_account_info(),
+            to: ctx.accounts.quote_asset_pool.to_account_info(),
+            authority: ctx.accounts.user_authority.to_account_info(),
+        };
+        let cpi_token_program = ctx.accounts.token_program.clone();
+        let cpi_ctx = CpiContext::new(cpi_token_program.to_account_info(), cpi_accounts);
+        let quote_transfer_amount = option_market.quote_amount_in(filled)?;
+        token::transfer(cpi_ctx, quote_transfer_amount)?;
+
+        // Transfer the underlying assets from the pool to the exerciser
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.underlying_asset_pool.to_account_info(),
+            to: ctx.accounts.underlying_asset_dest.to_account_info(),
+            authority: ctx.accounts.option_market.to_account_info(),
+        };
+        let cpi_token_program = ctx.accounts.token_program.clone();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_token_program.to_account_info(), cpi_accounts, signer);
+        let underlying_transfer_amount = option_market.underlying_amount_out(filled)?;
+        token::transfer(cpi_ctx, underlying_transfer_amount)?;
+
         Ok(())
     }
 
@@ -234,7 +710,7 @@ pub mod psy_american {
         };
         let cpi_token_program = ctx.accounts.token_program.clone();
         let cpi_ctx = CpiContext::new(cpi_token_program.to_account_info(), cpi_accounts);
-        let quote_transfer_amount = option_market.quote_amount_per_contract.checked_mul(size).unwrap();
+        let quote_transfer_amount = option_market.quote_amount_in(size)?;
         token::transfer(cpi_ctx, quote_transfer_amount)?;
 
         // Transfer the underlying assets from the pool to the exerciser
@@ -245,12 +721,12 @@ pub mod psy_american {
         };
         let cpi_token_program = ctx.accounts.token_program.clone();
         let cpi_ctx = CpiContext::new_with_signer(cpi_token_program.to_account_info(), cpi_accounts, signer);
-        let underlying_transfer_amount = option_market.underlying_amount_per_contract.checked_mul(size).unwrap();
+        let underlying_transfer_amount = option_market.underlying_amount_out(size)?;
         token::transfer(cpi_ctx, underlying_transfer_amount)?;
 
         // Transfer an exercise fee
         let exercise_fee_account = validate_exercise_fee_acct(&ctx.accounts.option_market, ctx.remaining_accounts)?;
-        let exercise_fee_amount_per_contract = fees::fee_amount(option_market.quote_amount_per_contract);
+        let exercise_fee_amount_per_contract = fees::fee_amount(option_market.quote_amount_per_contract)?;
         if exercise_fee_amount_per_contract > 0 {
             match exercise_fee_account {
                 Some(account) => {
@@ -261,14 +737,14 @@ pub mod psy_american {
                     };
                     let cpi_token_program = ctx.accounts.token_program.clone();
                     let cpi_ctx = CpiContext::new(cpi_token_program.to_account_info(), cpi_accounts);
-                    let total_fee = exercise_fee_amount_per_contract.checked_mul(size).ok_or(errors::ErrorCode::NumberOverflow)?;
+                    let total_fee = contracts::amount_for_size(exercise_fee_amount_per_contract, size, option_market.option_decimals, Rounding::Up)?;
                     token::transfer(cpi_ctx, total_fee)?;
                 },
                 None => {}
             }
         } else {
             // Handle NFT case with SOL fee
-            let total_fee = fees::NFT_MINT_LAMPORTS.checked_mul(size).ok_or(errors::ErrorCode::NumberOverflow)?;
+            let total_fee = contracts::amount_for_size(fees::NFT_MINT_LAMPORTS, size, option_market.option_decimals, Rounding::Up)?;
             invoke(
                 &system_instruction::transfer(&ctx.accounts.user_authority.key, &fees::fee_owner_key::ID, total_fee),
             &[
@@ -313,7 +789,7 @@ pub mod psy_american {
         };
         let cpi_token_program = ctx.accounts.token_program.clone();
         let cpi_ctx = CpiContext::new(cpi_token_program.to_account_info(), cpi_accounts);
-        let quote_transfer_amount = option_market.quote_amount_per_contract.checked_mul(size).unwrap();
+        let quote_transfer_amount = option_market.quote_amount_in(size)?;
         token::transfer(cpi_ctx, quote_transfer_amount)?;
 
         // Transfer the underlying assets from the pool to the exerciser
@@ -324,9 +800,168 @@ pub mod psy_american {
         };
         let cpi_token_program = ctx.accounts.token_program.clone();
         let cpi_ctx = CpiContext::new_with_signer(cpi_token_program.to_account_info(), cpi_accounts, signer);
-        let underlying_transfer_amount = option_market.underlying_amount_per_contract.checked_mul(size).unwrap();
+        let underlying_transfer_amount = option_market.underlying_amount_out(size)?;
+        token::transfer(cpi_ctx, underlying_transfer_amount)?;
+
+        Ok(())
+    }
+
+    #[access_control(NetExercise::accounts(&ctx) NetExercise::unexpired_market(&ctx) validate_size(size))]
+    /// Exercise in-the-money options without holding quote assets. The released underlying is
+    /// sent to `underlying_asset_dest`, `underlying_in` of it is swapped on `amm_program` for
+    /// quote, and the strike is paid into the quote asset pool from the proceeds. The holder
+    /// keeps the rest of the underlying and any quote beyond the strike. The AMM's swap
+    /// accounts are the remaining accounts, see `amm::swap`.
+    pub fn net_exercise<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, NetExercise<'info>>,
+        size: u64,
+        underlying_in: u64
+    ) -> Result<()> {
+        let option_market = &ctx.accounts.option_market;
+        let underlying_transfer_amount = option_market.underlying_amount_out(size)?;
+        let quote_transfer_amount = option_market.quote_amount_in(size)?;
+        // Validate only the released underlying is swapped
+        if underlying_in > underlying_transfer_amount {
+            return Err(errors::ErrorCode::NetExerciseSwapsTooMuchUnderlying.into())
+        }
+        let underlying_before = ctx.accounts.underlying_asset_dest.amount;
+        let quote_before = ctx.accounts.quote_asset_account.amount;
+
+        let seeds = &[
+            option_market.underlying_asset_mint.as_ref(),
+            option_market.quote_asset_mint.as_ref(),
+            &option_market.underlying_amount_per_contract.to_le_bytes(),
+            &option_market.quote_amount_per_contract.to_le_bytes(),
+            &option_market.expiration_unix_timestamp.to_le_bytes(),
+            &[option_market.bump_seed]
+        ];
+        let signer = &[&seeds[..]];
+        // Burn the size of option tokens
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.option_mint.to_account_info(),
+                from: ctx.accounts.exerciser_option_token_src.to_account_info(),
+                authority: ctx.accounts.user_authority.to_account_info(),
+            },
+        );
+        token::burn(cpi_ctx, size)?;
+
+        // Transfer the underlying assets from the pool to the exerciser
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.underlying_asset_pool.to_account_info(),
+            to: ctx.accounts.underlying_asset_dest.to_account_info(),
+            authority: ctx.accounts.option_market.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
         token::transfer(cpi_ctx, underlying_transfer_amount)?;
 
+        // Swap part of the underlying for the quote to pay the strike
+        amm::swap(&ctx.accounts.amm_program, ctx.remaining_accounts, underlying_in, quote_transfer_amount)?;
+
+        // Validate the AMM spent no more than the released underlying and returned the strike
+        ctx.accounts.underlying_asset_dest.reload()?;
+        ctx.accounts.quote_asset_account.reload()?;
+        if ctx.accounts.underlying_asset_dest.amount < underlying_before {
+            return Err(errors::ErrorCode::NetExerciseSwapsTooMuchUnderlying.into())
+        }
+        let quote_received = ctx.accounts.quote_asset_account.amount.saturating_sub(quote_before);
+        if quote_received < quote_transfer_amount {
+            return Err(errors::ErrorCode::SwapDidNotCoverStrike.into())
+        }
+
+        // Transfer the quote assets to the pool
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.quote_asset_account.to_account_info(),
+            to: ctx.accounts.quote_asset_pool.to_account_info(),
+            authority: ctx.accounts.user_authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, quote_transfer_amount)?;
+
+        Ok(())
+    }
+
+    #[access_control(BatchExercise::accounts(&ctx, &sizes))]
+    /// Exercise options on several OptionMarkets that share an underlying and quote asset in one
+    /// instruction, paying from one quote source into one underlying destination. Each entry of
+    /// `sizes` has BATCH_EXERCISE_ACCOUNTS remaining accounts in the order of BatchExerciseEntry.
+    /// When `all_or_nothing` is false, entries that fail validation or cannot be paid for are
+    /// skipped instead of failing the instruction.
+    pub fn batch_exercise<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, BatchExercise<'info>>,
+        sizes: Vec<u64>,
+        all_or_nothing: bool
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let mut quote_remaining = ctx.accounts.quote_asset_src.amount;
+        // (exerciser_option_token_src, options burned so far). Loaded balances are not refreshed
+        // by the burns, so entries that share a source need to account for earlier entries.
+        let mut options_burned: Vec<(Pubkey, u64)> = vec![];
+        for (index, (accounts, size)) in ctx.remaining_accounts.chunks(BATCH_EXERCISE_ACCOUNTS).zip(sizes.iter()).enumerate() {
+            let entry = match BatchExerciseEntry::load(accounts).and_then(|entry| {
+                let burned = options_burned.iter()
+                    .find(|(src, _)| *src == entry.exerciser_option_token_src.key())
+                    .map_or(0, |(_, burned)| *burned);
+                let options_remaining = entry.exerciser_option_token_src.amount.saturating_sub(burned);
+                entry.validate(&ctx.accounts, *size, options_remaining, quote_remaining, clock.unix_timestamp).map(|_| entry)
+            }) {
+                Ok(entry) => entry,
+                Err(err) if !all_or_nothing => {
+                    msg!("Skipping batch entry {}: {}", index, err);
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            let option_market = &entry.option_market;
+            let quote_transfer_amount = option_market.quote_amount_in(*size)?;
+            let underlying_transfer_amount = option_market.underlying_amount_out(*size)?;
+            quote_remaining -= quote_transfer_amount;
+            let src = entry.exerciser_option_token_src.key();
+            match options_burned.iter_mut().find(|(s, _)| *s == src) {
+                Some((_, burned)) => *burned += *size,
+                None => options_burned.push((src, *size)),
+            }
+
+            let seeds = &[
+                option_market.underlying_asset_mint.as_ref(),
+                option_market.quote_asset_mint.as_ref(),
+                &option_market.underlying_amount_per_contract.to_le_bytes(),
+                &option_market.quote_amount_per_contract.to_le_bytes(),
+                &option_market.expiration_unix_timestamp.to_le_bytes(),
+                &[option_market.bump_seed]
+            ];
+            let signer = &[&seeds[..]];
+            // Burn the size of option tokens
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: entry.option_mint.to_account_info(),
+                    from: entry.exerciser_option_token_src.to_account_info(),
+                    authority: ctx.accounts.option_authority.to_account_info(),
+                },
+            );
+            token::burn(cpi_ctx, *size)?;
+
+            // Transfer the quote assets to the pool
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.quote_asset_src.to_account_info(),
+                to: entry.quote_asset_pool.to_account_info(),
+                authority: ctx.accounts.user_authority.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            token::transfer(cpi_ctx, quote_transfer_amount)?;
+
+            // Transfer the underlying assets from the pool to the exerciser
+            let cpi_accounts = Transfer {
+                from: entry.underlying_asset_pool.to_account_info(),
+                to: ctx.accounts.underlying_asset_dest.to_account_info(),
+                authority: entry.option_market.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token::transfer(cpi_ctx, underlying_transfer_amount)?;
+        }
+
         Ok(())
     }
 
@@ -363,7 +998,7 @@ pub mod psy_american {
         };
         let cpi_token_program = ctx.accounts.token_program.clone();
         let cpi_ctx = CpiContext::new_with_signer(cpi_token_program.to_account_info(), cpi_accounts, signer);
-        let underlying_transfer_amount = option_market.underlying_amount_per_contract.checked_mul(size).unwrap();
+        let underlying_transfer_amount = option_market.underlying_amount_out(size)?;
         token::transfer(cpi_ctx, underlying_transfer_amount)?;
         Ok(())
     }
@@ -414,16 +1049,332 @@ pub mod psy_american {
         };
         let cpi_token_program = ctx.accounts.token_program.clone();
         let cpi_ctx = CpiContext::new_with_signer(cpi_token_program.to_account_info(), cpi_accounts, signer);
-        let underlying_transfer_amount = option_market.underlying_amount_per_contract.checked_mul(size).unwrap();
+        let underlying_transfer_amount = option_market.underlying_amount_out(size)?;
         token::transfer(cpi_ctx, underlying_transfer_amount)?;
         Ok(())
     }
 
-    #[access_control(BurnWriterForQuote::accounts(&ctx) BurnWriterForQuote::quotes_in_pool(&ctx, size))]
-    pub fn burn_writer_for_quote(ctx: Context<BurnWriterForQuote>, size: u64)  -> Result<()> {
+    #[access_control(InitYieldVault::accounts(&ctx))]
+    /// Hold the market's collateral as the receipt token of `adapter_program`, e.g. a liquid
+    /// staking or lending token, so it earns yield for the market's writers. The adapter is
+    /// trusted with the collateral, so it must be approved with a YieldAdapter. Only the
+    /// market's creator can call this, before any options are written, and the market then only
+    /// supports the yield instructions.
+    pub fn init_yield_vault(ctx: Context<InitYieldVault>) -> Result<()> {
+        let yield_vault = &mut ctx.accounts.yield_vault;
+        yield_vault.option_market = ctx.accounts.option_market.key();
+        yield_vault.adapter_program = ctx.accounts.adapter_program.key();
+        yield_vault.adapter_reserve = ctx.accounts.adapter_reserve.key();
+        yield_vault.adapter_vault = ctx.accounts.adapter_vault.key();
+        yield_vault.receipt_mint = ctx.accounts.receipt_mint.key();
+        yield_vault.receipt_pool = ctx.accounts.receipt_pool.key();
+        yield_vault.collateral_size = 0;
+        yield_vault.bump_seed = *ctx.bumps.get("yield_vault").unwrap();
+        ctx.accounts.option_market.yield_vault = yield_vault.key();
+        Ok(())
+    }
+
+    #[access_control(
+        MintOptionYield::unexpired_market(&ctx)
+        validate_size(size)
+        pause::check(&ctx.accounts.pause_config, pause::PauseGroup::MintOption)
+    )]
+    /// Write options on a market with a YieldVault. `underlying_in` is deposited through the
+    /// adapter and must cover the collateral for `size`. It must also buy in at the yield the
+    /// outstanding options accrued, so earlier writers keep their share. The adapter's deposit
+    /// accounts are the remaining accounts, see `yield_vault::deposit`.
+    pub fn mint_option_yield<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, MintOptionYield<'info>>,
+        size: u64,
+        underlying_in: u64
+    ) -> Result<()> {
         let option_market = &ctx.accounts.option_market;
-        let seeds = &[
-            option_market.underlying_asset_mint.as_ref(),
+        if underlying_in < option_market.underlying_amount_in(size)? {
+            return Err(errors::ErrorCode::YieldDepositTooSmall.into())
+        }
+        let receipts_before = ctx.accounts.receipt_pool.amount;
+        let receipts_required = ctx.accounts.yield_vault.receipts_required(receipts_before, size)?;
+
+        // Deposit the underlying for receipt tokens
+        yield_vault::deposit(&ctx.accounts.adapter_program, ctx.remaining_accounts, underlying_in, &ctx.accounts.yield_vault)?;
+
+        // Validate the adapter minted the receipt tokens the options need
+        ctx.accounts.receipt_pool.reload()?;
+        let receipts_minted = ctx.accounts.receipt_pool.amount.saturating_sub(receipts_before);
+        if receipts_minted == 0 || receipts_minted < receipts_required {
+            return Err(errors::ErrorCode::YieldDepositTooSmall.into())
+        }
+
+        let seeds = &[
+            option_market.underlying_asset_mint.as_ref(),
+            option_market.quote_asset_mint.as_ref(),
+            &option_market.underlying_amount_per_contract.to_le_bytes(),
+            &option_market.quote_amount_per_contract.to_le_bytes(),
+            &option_market.expiration_unix_timestamp.to_le_bytes(),
+            &[option_market.bump_seed]
+        ];
+        let signer = &[&seeds[..]];
+
+        // Mint a new OptionToken(s)
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.option_mint.to_account_info(),
+            to: ctx.accounts.minted_option_dest.to_account_info(),
+            authority: ctx.accounts.option_market.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+        token::mint_to(cpi_ctx, size)?;
+
+        // Mint a new WriterToken(s)
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.writer_token_mint.to_account_info(),
+            to: ctx.accounts.minted_writer_token_dest.to_account_info(),
+            authority: ctx.accounts.option_market.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+        token::mint_to(cpi_ctx, size)?;
+
+        let yield_vault = &mut ctx.accounts.yield_vault;
+        yield_vault.collateral_size = yield_vault.collateral_size.checked_add(size).ok_or(errors::ErrorCode::NumberOverflow)?;
+        Ok(())
+    }
+
+    #[access_control(ExerciseOptionYield::unexpired_market(&ctx) validate_size(size))]
+    /// Exercise options on a market with a YieldVault. The adapter withdraws the underlying the
+    /// options release from the receipt pool to `underlying_asset_dest`, and the yield their
+    /// collateral accrued stays with the writers. The adapter's withdraw accounts are the
+    /// remaining accounts, see `yield_vault::redeem`.
+    pub fn exercise_option_yield<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, ExerciseOptionYield<'info>>,
+        size: u64
+    ) -> Result<()> {
+        let option_market = &ctx.accounts.option_market;
+        // Burn the size of option tokens
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.option_mint.to_account_info(),
+                from: ctx.accounts.exerciser_option_token_src.to_account_info(),
+                authority: ctx.accounts.option_authority.to_account_info(),
+            },
+        );
+        token::burn(cpi_ctx, size)?;
+
+        // Transfer the quote assets to the pool
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.quote_asset_src.to_account_info(),
+            to: ctx.accounts.quote_asset_pool.to_account_info(),
+            authority: ctx.accounts.user_authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, option_market.quote_amount_in(size)?)?;
+
+        // Withdraw the underlying from the receipt pool to the exerciser
+        let underlying_transfer_amount = option_market.underlying_amount_out(size)?;
+        let underlying_before = ctx.accounts.underlying_asset_dest.amount;
+        let receipts_before = ctx.accounts.receipt_pool.amount;
+        let receipts = ctx.accounts.yield_vault.receipt_share(receipts_before, size)?;
+        yield_vault::redeem(
+            &ctx.accounts.adapter_program,
+            ctx.remaining_accounts,
+            yield_vault::WITHDRAW_TAG,
+            underlying_transfer_amount,
+            &ctx.accounts.yield_vault,
+        )?;
+        ctx.accounts.underlying_asset_dest.reload()?;
+        if ctx.accounts.underlying_asset_dest.amount.saturating_sub(underlying_before) < underlying_transfer_amount {
+            return Err(errors::ErrorCode::YieldWithdrawalShort.into())
+        }
+        // Validate the adapter took no more than the options' share, the rest backs the others
+        ctx.accounts.receipt_pool.reload()?;
+        if receipts_before.saturating_sub(ctx.accounts.receipt_pool.amount) > receipts {
+            return Err(errors::ErrorCode::YieldAdapterOverdrew.into())
+        }
+
+        let yield_vault = &mut ctx.accounts.yield_vault;
+        yield_vault.collateral_size = yield_vault.collateral_size.checked_sub(size).ok_or(errors::ErrorCode::NumberOverflow)?;
+        Ok(())
+    }
+
+    #[access_control(validate_size(size))]
+    /// Close a position on a market with a YieldVault by burning `size` options and
+    /// WriterTokens. The adapter redeems the position's share of the receipt pool, its
+    /// collateral and accrued yield, to `underlying_asset_dest`. The adapter's redeem accounts
+    /// are the remaining accounts, see `yield_vault::redeem_share`.
+    pub fn close_option_position_yield<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, CloseOptionPositionYield<'info>>,
+        size: u64
+    ) -> Result<()> {
+        // Burn the size of WriterTokens
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.writer_token_mint.to_account_info(),
+                from: ctx.accounts.writer_token_src.to_account_info(),
+                authority: ctx.accounts.user_authority.to_account_info(),
+            },
+        );
+        token::burn(cpi_ctx, size)?;
+
+        // Burn the OptionTokens
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.option_mint.to_account_info(),
+                from: ctx.accounts.option_token_src.to_account_info(),
+                authority: ctx.accounts.user_authority.to_account_info(),
+            },
+        );
+        token::burn(cpi_ctx, size)?;
+
+        yield_vault::redeem_share(
+            &mut ctx.accounts.yield_vault,
+            &mut ctx.accounts.receipt_pool,
+            &ctx.accounts.adapter_program,
+            ctx.remaining_accounts,
+            size,
+        )
+    }
+
+    #[access_control(ClosePostExpYield::expired_market(&ctx) validate_size(size))]
+    /// Burn WriterTokens of an expired market with a YieldVault. The adapter redeems their share
+    /// of the receipt pool, the collateral of the unexercised options and all the yield it
+    /// accrued, to `underlying_asset_dest`. The adapter's redeem accounts are the remaining
+    /// accounts, see `yield_vault::redeem_share`.
+    pub fn close_post_expiration_yield<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, ClosePostExpYield<'info>>,
+        size: u64
+    ) -> Result<()> {
+        // Burn the size of WriterTokens
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.writer_token_mint.to_account_info(),
+                from: ctx.accounts.writer_token_src.to_account_info(),
+                authority: ctx.accounts.user_authority.to_account_info(),
+            },
+        );
+        token::burn(cpi_ctx, size)?;
+
+        yield_vault::redeem_share(
+            &mut ctx.accounts.yield_vault,
+            &mut ctx.accounts.receipt_pool,
+            &ctx.accounts.adapter_program,
+            ctx.remaining_accounts,
+            size,
+        )
+    }
+
+    #[access_control(
+        RollPosition::accounts(&ctx)
+        RollPosition::unexpired_market(&ctx)
+        validate_size(size)
+        pause::check(&ctx.accounts.pause_config, pause::PauseGroup::CompositeMint)
+    )]
+    /// Close `size` options and WriterTokens in one OptionMarket and mint the same size in another
+    /// on the same underlying asset, moving the underlying directly from pool to pool. When the
+    /// contract sizes differ the difference is refunded to, or pulled from, `underlying_asset_account`.
+    pub fn roll_position(ctx: Context<RollPosition>, size: u64) -> Result<()> {
+        let from_market = &ctx.accounts.from_option_market;
+        let to_market = &ctx.accounts.to_option_market;
+        let from_seeds = &[
+            from_market.underlying_asset_mint.as_ref(),
+            from_market.quote_asset_mint.as_ref(),
+            &from_market.underlying_amount_per_contract.to_le_bytes(),
+            &from_market.quote_amount_per_contract.to_le_bytes(),
+            &from_market.expiration_unix_timestamp.to_le_bytes(),
+            &[from_market.bump_seed]
+        ];
+        let from_signer = &[&from_seeds[..]];
+        let to_seeds = &[
+            to_market.underlying_asset_mint.as_ref(),
+            to_market.quote_asset_mint.as_ref(),
+            &to_market.underlying_amount_per_contract.to_le_bytes(),
+            &to_market.quote_amount_per_contract.to_le_bytes(),
+            &to_market.expiration_unix_timestamp.to_le_bytes(),
+            &[to_market.bump_seed]
+        ];
+        let to_signer = &[&to_seeds[..]];
+
+        // Burn the size of WriterTokens
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.from_writer_token_mint.to_account_info(),
+                from: ctx.accounts.writer_token_src.to_account_info(),
+                authority: ctx.accounts.user_authority.to_account_info(),
+            },
+        );
+        token::burn(cpi_ctx, size)?;
+
+        // Burn the OptionTokens
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.from_option_mint.to_account_info(),
+                from: ctx.accounts.option_token_src.to_account_info(),
+                authority: ctx.accounts.user_authority.to_account_info(),
+            },
+        );
+        token::burn(cpi_ctx, size)?;
+
+        let released_amount = from_market.underlying_amount_out(size)?;
+        let required_amount = to_market.underlying_amount_in(size)?;
+
+        // Move the underlying assets both markets need from pool to pool
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.from_underlying_asset_pool.to_account_info(),
+            to: ctx.accounts.to_underlying_asset_pool.to_account_info(),
+            authority: ctx.accounts.from_option_market.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, from_signer);
+        token::transfer(cpi_ctx, released_amount.min(required_amount))?;
+
+        if released_amount > required_amount {
+            // Refund the excess underlying assets to the user
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.from_underlying_asset_pool.to_account_info(),
+                to: ctx.accounts.underlying_asset_account.to_account_info(),
+                authority: ctx.accounts.from_option_market.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, from_signer);
+            token::transfer(cpi_ctx, released_amount - required_amount)?;
+        } else if required_amount > released_amount {
+            // Pull the missing underlying assets from the user
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.underlying_asset_account.to_account_info(),
+                to: ctx.accounts.to_underlying_asset_pool.to_account_info(),
+                authority: ctx.accounts.user_authority.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            token::transfer(cpi_ctx, required_amount - released_amount)?;
+        }
+
+        // Mint a new OptionToken(s)
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.to_option_mint.to_account_info(),
+            to: ctx.accounts.minted_option_dest.to_account_info(),
+            authority: ctx.accounts.to_option_market.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, to_signer);
+        token::mint_to(cpi_ctx, size)?;
+
+        // Mint a new WriterToken(s)
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.to_writer_token_mint.to_account_info(),
+            to: ctx.accounts.minted_writer_token_dest.to_account_info(),
+            authority: ctx.accounts.to_option_market.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, to_signer);
+        token::mint_to(cpi_ctx, size)?;
+
+        Ok(())
+    }
+
+    #[access_control(BurnWriterForQuote::accounts(&ctx) BurnWriterForQuote::quotes_in_pool(&ctx, size))]
+    pub fn burn_writer_for_quote(ctx: Context<BurnWriterForQuote>, size: u64)  -> Result<()> {
+        let option_market = &ctx.accounts.option_market;
+        let seeds = &[
+            option_market.underlying_asset_mint.as_ref(),
             option_market.quote_asset_mint.as_ref(),
             &option_market.underlying_amount_per_contract.to_le_bytes(),
             &option_market.quote_amount_per_contract.to_le_bytes(),
@@ -452,16 +1403,18 @@ pub mod psy_american {
         };
         let cpi_token_program = ctx.accounts.token_program.clone();
         let cpi_ctx = CpiContext::new_with_signer(cpi_token_program.to_account_info(), cpi_accounts, signer);
-        let quote_transfer_amount = option_market.quote_amount_per_contract.checked_mul(size).unwrap();
+        let quote_transfer_amount = option_market.quote_amount_out(size)?;
         token::transfer(cpi_ctx, quote_transfer_amount)?;
         
 
         Ok(())
     }
 
-    #[access_control(InitSerumMarket::accounts(&ctx))]
+    #[access_control(
+        InitSerumMarket::accounts(&ctx)
+        pause::check(&ctx.accounts.pause_config, pause::PauseGroup::InitializeMarket)
+    )]
     pub fn init_serum_market(ctx: Context<InitSerumMarket>, _market_space: u64, vault_signer_nonce: u64, coin_lot_size: u64, pc_lot_size: u64, pc_dust_threshold: u64) -> Result<()> {
-        panic!("Blocked instruction");
         let init_market_ctx = SerumInitMarket {
             market: ctx.accounts.serum_market.to_account_info(),
             coin_mint: ctx.accounts.option_mint.to_account_info(),
@@ -490,129 +1443,573 @@ pub mod psy_american {
         )
     }
 
-    pub fn entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> Result<()> {
-        MarketProxy::new()
-            .middleware(&mut serum_proxy::Validation::new())
-            .middleware(&mut ReferralFees::new(serum_proxy::referral::ID))
-            .middleware(&mut OpenOrdersPda::new())
-            .run(program_id, accounts, data)
+    /// Register a frontend so it can earn referral fees on the PsyOptions Serum markets
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        let referrer = &mut ctx.accounts.referrer;
+        referrer.authority = ctx.accounts.authority.key();
+        referrer.frontend_share_bps = fees::DEFAULT_REFERRER_SHARE_BPS;
+        referrer.bump_seed = *ctx.bumps.get("referrer").unwrap();
+        Ok(())
     }
-}
-
-struct FeeAccounts {
-    mint_fee_key: Pubkey,
-    exercise_fee_key: Pubkey
-}
 
-/// Validate that the size is greater than 0
-fn validate_size(size: u64) -> Result<()> {
-    if size <= 0 {
-        return Err(errors::ErrorCode::SizeCantBeLessThanEqZero.into())
+    #[access_control(validate_referrer_share(frontend_share_bps))]
+    /// Update the share of referral fees the frontend receives. Only the fee owner can call this.
+    pub fn set_referrer_share(ctx: Context<SetReferrerShare>, frontend_share_bps: u16) -> Result<()> {
+        ctx.accounts.referrer.frontend_share_bps = frontend_share_bps;
+        Ok(())
     }
-    Ok(())
-}
 
-fn validate_fee_accounts<'info>(
-    remaining_accounts: &[AccountInfo],
-    underlying_asset_mint: &Pubkey,
-    quote_asset_mint: &Pubkey,
-    underlying_amount_per_contract: u64,
-    quote_amount_per_contract: u64
-) -> Result<FeeAccounts> {
-    let account_info_iter = &mut remaining_accounts.iter();
-    let mut fee_accounts = FeeAccounts {
-        mint_fee_key: fees::fee_owner_key::ID,
-        exercise_fee_key: fees::fee_owner_key::ID,
-    };
+    #[access_control(ClaimReferralFees::accounts(&ctx))]
+    /// Sweep the referral fees collected by a Referrer, splitting them between the frontend
+    /// and the protocol.
+    pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+        let referrer = &ctx.accounts.referrer;
+        let seeds = &[
+            b"referrer".as_ref(),
+            referrer.authority.as_ref(),
+            &[referrer.bump_seed]
+        ];
+        let signer = &[&seeds[..]];
+        let (frontend_amount, protocol_amount) = fees::referrer_split(
+            ctx.accounts.referral_fee_src.amount,
+            referrer.frontend_share_bps
+        );
 
-    // if the mint fee account is required, check that it exists and has the proper owner
-    if fees::fee_amount(underlying_amount_per_contract) > 0 {
-        let mint_fee_recipient = next_account_info(account_info_iter)?;
-        if mint_fee_recipient.owner != &spl_token::ID {
-            return Err(errors::ErrorCode::ExpectedSPLTokenProgramId.into())
-        }
-        let mint_fee_account = SPLTokenAccount::unpack_from_slice(&mint_fee_recipient.try_borrow_data()?)?;
-        if mint_fee_account.owner != fees::fee_owner_key::ID {
-            return Err(errors::ErrorCode::MintFeeMustBeOwnedByFeeOwner.into()) 
+        // Transfer the frontend's share
+        if frontend_amount > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.referral_fee_src.to_account_info(),
+                to: ctx.accounts.frontend_fee_dest.to_account_info(),
+                authority: ctx.accounts.referrer.to_account_info(),
+            };
+            let cpi_token_program = ctx.accounts.token_program.clone();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_token_program.to_account_info(), cpi_accounts, signer);
+            token::transfer(cpi_ctx, frontend_amount)?;
         }
-        // check that the mint fee recipient account's mint is also the underlying mint
-        if mint_fee_account.mint != *underlying_asset_mint {
-            return Err(errors::ErrorCode::MintFeeTokenMustMatchUnderlyingAsset.into())
+
+        // Transfer the protocol's share
+        if protocol_amount > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.referral_fee_src.to_account_info(),
+                to: ctx.accounts.protocol_fee_dest.to_account_info(),
+                authority: ctx.accounts.referrer.to_account_info(),
+            };
+            let cpi_token_program = ctx.accounts.token_program.clone();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_token_program.to_account_info(), cpi_accounts, signer);
+            token::transfer(cpi_ctx, protocol_amount)?;
         }
+        Ok(())
+    }
 
-        fee_accounts.mint_fee_key = *mint_fee_recipient.key;
+    #[access_control(InitPriceBand::accounts(&ctx) validate_price_band(lower_band_bps))]
+    /// Configure the price band and circuit breaker for an option's Serum market. Only the
+    /// fee owner can call this, until the Governance exists and configures bands instead.
+    pub fn init_price_band(ctx: Context<InitPriceBand>, lower_band_bps: u16, upper_band_bps: u16) -> Result<()> {
+        let config = price_band::new_config(
+            &ctx.accounts.option_market,
+            &ctx.accounts.serum_market,
+            &ctx.accounts.oracle,
+            &ctx.accounts.underlying_asset_mint,
+            &ctx.accounts.quote_asset_mint,
+            &ctx.accounts.pc_mint,
+            lower_band_bps,
+            upper_band_bps,
+            *ctx.bumps.get("price_band").unwrap(),
+        )?;
+        let price_band: &mut PriceBandConfig = &mut ctx.accounts.price_band;
+        *price_band = config;
+        Ok(())
     }
 
-    // if the exercise fee account is required, check that it exists and has the proper owner
-    if fees::fee_amount(quote_amount_per_contract) > 0 {
-        let exercise_fee_recipient = next_account_info(account_info_iter)?;
-        if exercise_fee_recipient.owner != &spl_token::ID {
-            return Err(errors::ErrorCode::ExpectedSPLTokenProgramId.into())
-        }
-        let exercise_fee_account = SPLTokenAccount::unpack_from_slice(&exercise_fee_recipient.try_borrow_data()?)?;
-        if exercise_fee_account.owner != fees::fee_owner_key::ID {
-            return Err(errors::ErrorCode::ExerciseFeeMustBeOwnedByFeeOwner.into()) 
-        }
-        // check that the exercise fee recipient account's mint is also the quote mint
-        if exercise_fee_account.mint != *quote_asset_mint {
-            return Err(errors::ErrorCode::ExerciseFeeTokenMustMatchQuoteAsset.into())
-        }
+    #[access_control(validate_price_band(lower_band_bps))]
+    /// Update the band widths, oracle or halt trading on an option's Serum market. Only the
+    /// fee owner can call this, until the Governance exists.
+    pub fn update_price_band(ctx: Context<UpdatePriceBand>, lower_band_bps: u16, upper_band_bps: u16, halted: bool) -> Result<()> {
+        // Validate the oracle is a valid price account
+        price_band::load_oracle_price(&ctx.accounts.oracle)?;
 
-        fee_accounts.exercise_fee_key = *exercise_fee_recipient.key;
+        let price_band = &mut ctx.accounts.price_band;
+        price_band.oracle = ctx.accounts.oracle.key();
+        price_band.lower_band_bps = lower_band_bps;
+        price_band.upper_band_bps = upper_band_bps;
+        price_band.halted = halted;
+        Ok(())
     }
-    Ok(fee_accounts)
-}
 
-fn validate_mint_fee_acct<'c, 'info>(
-    option_market: &OptionMarket,
-    remaining_accounts: &'c [AccountInfo<'info>]
-) -> Result<Option<&'c AccountInfo<'info>>> {
-    let account_info_iter = &mut remaining_accounts.iter();
-    let acct;
-    if fees::fee_amount(option_market.underlying_amount_per_contract) > 0 {
-        let mint_fee_recipient = next_account_info(account_info_iter)?;
-        if mint_fee_recipient.owner != &spl_token::ID {
-            return Err(errors::ErrorCode::ExpectedSPLTokenProgramId.into())
-        }
-        let mint_fee_account = SPLTokenAccount::unpack_from_slice(&mint_fee_recipient.try_borrow_data()?)?;
-        if mint_fee_account.owner != fees::fee_owner_key::ID {
-            return Err(errors::ErrorCode::MintFeeMustBeOwnedByFeeOwner.into()) 
-        }
-        // check that the mint fee recipient account's mint is also the underlying mint
-        if mint_fee_account.mint != option_market.underlying_asset_mint {
-            return Err(errors::ErrorCode::MintFeeTokenMustMatchUnderlyingAsset.into())
-        }
-        if *mint_fee_recipient.key != option_market.mint_fee_account {
-            return Err(errors::ErrorCode::MintFeeKeyDoesNotMatchOptionMarket.into())
-        }
-        acct = Some(mint_fee_recipient);
-    } else {
-        acct = None;
+    /// Create the ListingRegistry. Only the fee owner can call this.
+    pub fn init_listing_registry(ctx: Context<InitListingRegistry>, enforce: bool) -> Result<()> {
+        let listing_registry = &mut ctx.accounts.listing_registry;
+        listing_registry.enforce = enforce;
+        listing_registry.bump_seed = *ctx.bumps.get("listing_registry").unwrap();
+        Ok(())
     }
-    Ok(acct)
-}
 
-fn validate_exercise_fee_acct<'c, 'info>(
-    option_market: &OptionMarket,
-    remaining_accounts: &'c [AccountInfo<'info>]
-) -> Result<Option<&'c AccountInfo<'info>>> {
-    let account_info_iter = &mut remaining_accounts.iter();
-    let acct;
-    if fees::fee_amount(option_market.quote_amount_per_contract) > 0 {
-        let exercise_fee_recipient = next_account_info(account_info_iter)?;
-        if exercise_fee_recipient.owner != &spl_token::ID {
-            return Err(errors::ErrorCode::ExpectedSPLTokenProgramId.into())
-        }
-        let exercise_fee_account = SPLTokenAccount::unpack_from_slice(&exercise_fee_recipient.try_borrow_data()?)?;
-        if exercise_fee_account.owner != fees::fee_owner_key::ID {
-            return Err(errors::ErrorCode::ExerciseFeeMustBeOwnedByFeeOwner.into()) 
-        }
-        // check that the mint fee recipient account's mint is also the underlying mint
-        if exercise_fee_account.mint != option_market.quote_asset_mint {
-            return Err(errors::ErrorCode::ExerciseFeeTokenMustMatchQuoteAsset.into())
-        }
-        // Check the exercise fee account matches the one on the OptionMarket
-        if *exercise_fee_recipient.key != option_market.exercise_fee_account {
+    /// Turn on or off the requirement that new markets are official. Only the fee owner can
+    /// call this.
+    pub fn set_listing_enforcement(ctx: Context<SetListingEnforcement>, enforce: bool) -> Result<()> {
+        ctx.accounts.listing_registry.enforce = enforce;
+        Ok(())
+    }
+
+    #[access_control(ListPair::accounts(&ctx))]
+    /// Approve an underlying and quote asset pair for official markets. Only the fee owner can
+    /// call this.
+    pub fn list_pair(ctx: Context<ListPair>) -> Result<()> {
+        let listed_pair = &mut ctx.accounts.listed_pair;
+        listed_pair.underlying_asset_mint = ctx.accounts.underlying_asset_mint.key();
+        listed_pair.quote_asset_mint = ctx.accounts.quote_asset_mint.key();
+        listed_pair.bump_seed = *ctx.bumps.get("listed_pair").unwrap();
+        Ok(())
+    }
+
+    /// Remove a pair's approval. Existing markets keep their official flag. Only the fee owner
+    /// can call this.
+    pub fn delist_pair(_ctx: Context<DelistPair>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Authorize a lister to create official markets for any pair. Only the fee owner can
+    /// call this.
+    pub fn add_lister(ctx: Context<AddLister>) -> Result<()> {
+        let lister = &mut ctx.accounts.lister;
+        lister.authority = ctx.accounts.lister_authority.key();
+        lister.bump_seed = *ctx.bumps.get("lister").unwrap();
+        Ok(())
+    }
+
+    /// Revoke a lister. Only the fee owner can call this.
+    pub fn remove_lister(_ctx: Context<RemoveLister>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Approve an adapter program to hold markets' collateral in a YieldVault. Only the fee
+    /// owner can call this.
+    pub fn approve_yield_adapter(ctx: Context<ApproveYieldAdapter>) -> Result<()> {
+        let yield_adapter = &mut ctx.accounts.yield_adapter;
+        yield_adapter.adapter_program = ctx.accounts.adapter_program.key();
+        yield_adapter.bump_seed = *ctx.bumps.get("yield_adapter").unwrap();
+        Ok(())
+    }
+
+    /// Revoke an adapter's approval. Existing YieldVaults keep their adapter. Only the fee owner
+    /// can call this.
+    pub fn revoke_yield_adapter(_ctx: Context<RevokeYieldAdapter>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Flag an existing market as official or not, e.g. for markets created before the
+    /// ListingRegistry. Only the fee owner can call this.
+    pub fn set_market_official(ctx: Context<SetMarketOfficial>, official: bool) -> Result<()> {
+        ctx.accounts.option_market.official = official;
+        Ok(())
+    }
+
+    #[access_control(UpgradeMarket::accounts(&ctx))]
+    /// Record the creator and creation time of a market created before they were stored, taken
+    /// from its initialization transaction. Only the fee owner can call this.
+    pub fn upgrade_market(ctx: Context<UpgradeMarket>, creator: Pubkey, created_at: i64) -> Result<()> {
+        let option_market = &mut ctx.accounts.option_market;
+        option_market.creator = creator;
+        option_market.created_at = created_at;
+        Ok(())
+    }
+
+    #[access_control(MigrateMarket::accounts(&ctx))]
+    /// Grow a legacy OptionMarket to the current account size. Fields added since the legacy
+    /// layout already read their zero defaults from the reserved space, so only the version
+    /// changes. Anyone can call this and pays the additional rent.
+    pub fn migrate_market(ctx: Context<MigrateMarket>) -> Result<()> {
+        ctx.accounts.option_market.version = OptionMarket::CURRENT_VERSION;
+        Ok(())
+    }
+
+    /// Set the market's label. Only the market's creator can call this.
+    pub fn set_market_label(ctx: Context<SetMarketLabel>, label: [u8; 32]) -> Result<()> {
+        ctx.accounts.option_market.label = label;
+        Ok(())
+    }
+
+    #[access_control(series::validate_config(underlying_amount_per_contract, strike_increment, expiration_interval, expiration_offset))]
+    /// Standardize the markets of a pair. New markets must have the contract size, a strike
+    /// that is a multiple of `strike_increment` and expire at `expiration_offset` into an
+    /// `expiration_interval`, e.g. weekly on Fridays at 08:00 UTC. Only the fee owner can
+    /// call this.
+    pub fn init_series_config(
+        ctx: Context<InitSeriesConfig>,
+        underlying_amount_per_contract: u64,
+        strike_increment: u64,
+        expiration_interval: i64,
+        expiration_offset: i64
+    ) -> Result<()> {
+        let series_config = &mut ctx.accounts.series_config;
+        series_config.underlying_asset_mint = ctx.accounts.underlying_asset_mint.key();
+        series_config.quote_asset_mint = ctx.accounts.quote_asset_mint.key();
+        series_config.underlying_amount_per_contract = underlying_amount_per_contract;
+        series_config.strike_increment = strike_increment;
+        series_config.expiration_interval = expiration_interval;
+        series_config.expiration_offset = expiration_offset;
+        series_config.bump_seed = *ctx.bumps.get("series_config").unwrap();
+        Ok(())
+    }
+
+    #[access_control(series::validate_config(underlying_amount_per_contract, strike_increment, expiration_interval, expiration_offset))]
+    /// Change a pair's series grid. Existing markets are unaffected. Only the fee owner can
+    /// call this.
+    pub fn update_series_config(
+        ctx: Context<UpdateSeriesConfig>,
+        underlying_amount_per_contract: u64,
+        strike_increment: u64,
+        expiration_interval: i64,
+        expiration_offset: i64
+    ) -> Result<()> {
+        let series_config = &mut ctx.accounts.series_config;
+        series_config.underlying_amount_per_contract = underlying_amount_per_contract;
+        series_config.strike_increment = strike_increment;
+        series_config.expiration_interval = expiration_interval;
+        series_config.expiration_offset = expiration_offset;
+        Ok(())
+    }
+
+    /// Remove a pair's series grid so markets can be created with any expiration and strike.
+    /// Only the fee owner can call this.
+    pub fn close_series_config(_ctx: Context<CloseSeriesConfig>) -> Result<()> {
+        Ok(())
+    }
+
+    #[access_control(InitOptionChain::accounts(&ctx))]
+    /// Create the first page of a pair's OptionChain, the index of its markets. Anyone can
+    /// call this before the pair's first `initialize_market`.
+    pub fn init_option_chain(ctx: Context<InitOptionChain>) -> Result<()> {
+        let option_chain = &mut ctx.accounts.option_chain;
+        option_chain.underlying_asset_mint = ctx.accounts.underlying_asset_mint.key();
+        option_chain.quote_asset_mint = ctx.accounts.quote_asset_mint.key();
+        option_chain.page = 0;
+        option_chain.next_page = Pubkey::default();
+        option_chain.bump_seed = *ctx.bumps.get("option_chain").unwrap();
+        Ok(())
+    }
+
+    #[access_control(ExtendOptionChain::accounts(&ctx))]
+    /// Link a new page after the last page of an OptionChain once it is full. Anyone can call
+    /// this.
+    pub fn extend_option_chain(ctx: Context<ExtendOptionChain>) -> Result<()> {
+        let option_chain = &mut ctx.accounts.option_chain;
+        let next_page = &mut ctx.accounts.next_page;
+        next_page.underlying_asset_mint = option_chain.underlying_asset_mint;
+        next_page.quote_asset_mint = option_chain.quote_asset_mint;
+        next_page.page = option_chain.page + 1;
+        next_page.next_page = Pubkey::default();
+        next_page.bump_seed = *ctx.bumps.get("next_page").unwrap();
+        option_chain.next_page = next_page.key();
+        Ok(())
+    }
+
+    #[access_control(RemoveFromOptionChain::accounts(&ctx))]
+    /// Remove a closed market, one that is expired with no outstanding WriterTokens, from its
+    /// OptionChain page. Anyone can call this.
+    pub fn remove_from_option_chain(ctx: Context<RemoveFromOptionChain>) -> Result<()> {
+        ctx.accounts.option_chain.remove(&ctx.accounts.option_market.key())?;
+        Ok(())
+    }
+
+    /// Create the PauseConfig. Only the fee owner can call this.
+    pub fn init_pause_config(ctx: Context<InitPauseConfig>, flags: PauseFlags) -> Result<()> {
+        let pause_config = &mut ctx.accounts.pause_config;
+        pause_config.flags = flags;
+        pause_config.bump_seed = *ctx.bumps.get("pause_config").unwrap();
+        Ok(())
+    }
+
+    /// Pause or resume groups of instructions, or enter exit only mode. Exercising and closing
+    /// positions can not be paused. Only the fee owner can call this.
+    pub fn set_pause_flags(ctx: Context<SetPauseFlags>, flags: PauseFlags) -> Result<()> {
+        ctx.accounts.pause_config.flags = flags;
+        Ok(())
+    }
+
+    #[access_control(governance::validate_config(&members, threshold, timelock_seconds))]
+    /// Create the Governance. From then on changes to pause flags, listings, series configs,
+    /// market flags and referral fees need an approved Proposal rather than the fee owner. Only
+    /// the fee owner can call this.
+    pub fn init_governance(ctx: Context<InitGovernance>, members: Vec<Pubkey>, threshold: u8, timelock_seconds: i64) -> Result<()> {
+        let governance = &mut ctx.accounts.governance;
+        governance.members = members;
+        governance.threshold = threshold;
+        governance.timelock_seconds = timelock_seconds;
+        governance.proposal_count = 0;
+        governance.bump_seed = *ctx.bumps.get("governance").unwrap();
+        Ok(())
+    }
+
+    #[access_control(Propose::accounts(&ctx) governance::validate_action(&action))]
+    /// Propose a change, counting the proposer's approval. Only Governance members can call this.
+    pub fn propose(ctx: Context<Propose>, action: GovernanceAction) -> Result<()> {
+        let governance = &mut ctx.accounts.governance;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.index = governance.proposal_count;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.action = action.clone();
+        proposal.approvals = vec![ctx.accounts.proposer.key()];
+        proposal.eta = 0;
+        proposal.bump_seed = *ctx.bumps.get("proposal").unwrap();
+        governance.proposal_count = governance.proposal_count.checked_add(1).ok_or(errors::ErrorCode::NumberOverflow)?;
+        emit!(ProposalCreated {
+            proposal: proposal.key(),
+            index: proposal.index,
+            proposer: proposal.proposer,
+            action: action.clone(),
+        });
+        if let Some(eta) = governance.queue_if_approved(proposal, Clock::get()?.unix_timestamp)? {
+            emit!(ProposalQueued { proposal: proposal.key(), action, eta });
+        }
+        Ok(())
+    }
+
+    #[access_control(ApproveProposal::accounts(&ctx))]
+    /// Approve a proposal. Its timelock starts when it reaches the Governance's threshold. Only
+    /// Governance members can call this.
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        let governance = &ctx.accounts.governance;
+        let proposal = &mut ctx.accounts.proposal;
+        // Drop approvals of removed members so the approvals always fit the account
+        proposal.approvals.retain(|approver| governance.is_member(approver));
+        proposal.approvals.push(ctx.accounts.member.key());
+        emit!(ProposalApproved {
+            proposal: proposal.key(),
+            member: ctx.accounts.member.key(),
+            approvals: proposal.approvals.len() as u8,
+        });
+        if let Some(eta) = governance.queue_if_approved(proposal, Clock::get()?.unix_timestamp)? {
+            emit!(ProposalQueued { proposal: proposal.key(), action: proposal.action.clone(), eta });
+        }
+        Ok(())
+    }
+
+    #[access_control(ExecuteProposal::accounts(&ctx))]
+    /// Apply an approved proposal once its timelock has passed, or as soon as it is approved when
+    /// it only pauses more or halts a Serum market, then close it. Anyone can call this and pays
+    /// for any account the action creates. SetPriceBand takes the oracle as a remaining account,
+    /// followed by the OptionMarket, Serum market, underlying, quote and pc mints when the
+    /// PriceBandConfig is created.
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        let action = ctx.accounts.proposal.action.clone();
+        match action.clone() {
+            GovernanceAction::UpdateGovernance { members, threshold, timelock_seconds } => {
+                let governance = &mut ctx.accounts.governance;
+                governance.members = members;
+                governance.threshold = threshold;
+                governance.timelock_seconds = timelock_seconds;
+            }
+            _ => governance::apply(
+                &action,
+                &ctx.accounts.target,
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.proposer,
+                &ctx.accounts.system_program.to_account_info(),
+                ctx.remaining_accounts,
+            )?,
+        }
+        emit!(ProposalExecuted { proposal: ctx.accounts.proposal.key(), action });
+        Ok(())
+    }
+
+    /// Withdraw a proposal that has not been executed and close it. Only the proposer can call
+    /// this.
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        emit!(ProposalCancelled { proposal: ctx.accounts.proposal.key() });
+        Ok(())
+    }
+
+    #[access_control(VerifyMarket::accounts(&ctx))]
+    /// Check that the OptionMarket's pools cover its outstanding options and WriterTokens. The
+    /// MarketSolvencyReport is set as the return data before the check fails, so monitors that
+    /// simulate this see the shortfall, and composing programs can CPI into it before accepting
+    /// option tokens as collateral. Yield markets are checked against their YieldVault's
+    /// `collateral_size` and receipt pool.
+    pub fn verify_market(ctx: Context<VerifyMarket>) -> Result<()> {
+        let option_market = &ctx.accounts.option_market;
+        let unix_timestamp = Clock::get()?.unix_timestamp;
+        let report = if option_market.yield_vault == Pubkey::default() {
+            MarketSolvencyReport::new(
+                option_market.key(),
+                option_market,
+                ctx.accounts.option_mint.supply,
+                ctx.accounts.writer_token_mint.supply,
+                ctx.accounts.underlying_asset_pool.amount,
+                ctx.accounts.quote_asset_pool.amount,
+                unix_timestamp,
+            )
+        } else {
+            let yield_vault = Account::<YieldVault>::try_from(&ctx.accounts.yield_vault)?;
+            let receipt_pool = Account::<TokenAccount>::try_from(&ctx.accounts.receipt_pool)?;
+            MarketSolvencyReport::new_yield(
+                option_market,
+                &yield_vault,
+                ctx.accounts.option_mint.supply,
+                ctx.accounts.writer_token_mint.supply,
+                receipt_pool.amount,
+                ctx.accounts.quote_asset_pool.amount,
+                unix_timestamp,
+            )
+        };
+        set_return_data(&report.try_to_vec()?);
+        if !report.options_covered {
+            return Err(errors::ErrorCode::UnderlyingPoolDoesNotCoverOptions.into())
+        }
+        if !report.writer_tokens_covered {
+            return Err(errors::ErrorCode::PoolsDoNotCoverWriterTokens.into())
+        }
+        Ok(())
+    }
+
+    pub fn entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> Result<()> {
+        MarketProxy::new()
+            .middleware(&mut serum_proxy::Validation::new())
+            .middleware(&mut serum_proxy::PriceBand::new())
+            .middleware(&mut serum_proxy::Referral::new())
+            .middleware(&mut OpenOrdersPda::new())
+            .run(program_id, accounts, data)
+    }
+}
+
+struct FeeAccounts {
+    mint_fee_key: Pubkey,
+    exercise_fee_key: Pubkey
+}
+
+/// Validate that the size is greater than 0
+fn validate_size(size: u64) -> Result<()> {
+    if size <= 0 {
+        return Err(errors::ErrorCode::SizeCantBeLessThanEqZero.into())
+    }
+    Ok(())
+}
+
+/// Validate a whole contract of option tokens fits the contract math
+fn validate_option_decimals(option_decimals: u8) -> Result<()> {
+    if option_decimals > contracts::MAX_OPTION_DECIMALS {
+        return Err(errors::ErrorCode::OptionDecimalsTooLarge.into())
+    }
+    Ok(())
+}
+
+/// Validate the referrer share is a valid amount of basis points
+fn validate_referrer_share(frontend_share_bps: u16) -> Result<()> {
+    if frontend_share_bps > 10_000 {
+        return Err(errors::ErrorCode::ReferrerShareTooLarge.into())
+    }
+    Ok(())
+}
+
+/// Validate the lower band cannot go below a price of 0
+fn validate_price_band(lower_band_bps: u16) -> Result<()> {
+    if lower_band_bps > 10_000 {
+        return Err(errors::ErrorCode::LowerBandTooWide.into())
+    }
+    Ok(())
+}
+
+fn validate_fee_accounts<'info>(
+    remaining_accounts: &[AccountInfo],
+    underlying_asset_mint: &Pubkey,
+    quote_asset_mint: &Pubkey,
+    underlying_amount_per_contract: u64,
+    quote_amount_per_contract: u64
+) -> Result<FeeAccounts> {
+    let account_info_iter = &mut remaining_accounts.iter();
+    let mut fee_accounts = FeeAccounts {
+        mint_fee_key: fees::fee_owner_key::ID,
+        exercise_fee_key: fees::fee_owner_key::ID,
+    };
+
+    // if the mint fee account is required, check that it exists and has the proper owner
+    if fees::fee_amount(underlying_amount_per_contract)? > 0 {
+        let mint_fee_recipient = next_account_info(account_info_iter)?;
+        if mint_fee_recipient.owner != &spl_token::ID {
+            return Err(errors::ErrorCode::ExpectedSPLTokenProgramId.into())
+        }
+        let mint_fee_account = SPLTokenAccount::unpack_from_slice(&mint_fee_recipient.try_borrow_data()?)?;
+        if mint_fee_account.owner != fees::fee_owner_key::ID {
+            return Err(errors::ErrorCode::MintFeeMustBeOwnedByFeeOwner.into()) 
+        }
+        // check that the mint fee recipient account's mint is also the underlying mint
+        if mint_fee_account.mint != *underlying_asset_mint {
+            return Err(errors::ErrorCode::MintFeeTokenMustMatchUnderlyingAsset.into())
+        }
+
+        fee_accounts.mint_fee_key = *mint_fee_recipient.key;
+    }
+
+    // if the exercise fee account is required, check that it exists and has the proper owner
+    if fees::fee_amount(quote_amount_per_contract)? > 0 {
+        let exercise_fee_recipient = next_account_info(account_info_iter)?;
+        if exercise_fee_recipient.owner != &spl_token::ID {
+            return Err(errors::ErrorCode::ExpectedSPLTokenProgramId.into())
+        }
+        let exercise_fee_account = SPLTokenAccount::unpack_from_slice(&exercise_fee_recipient.try_borrow_data()?)?;
+        if exercise_fee_account.owner != fees::fee_owner_key::ID {
+            return Err(errors::ErrorCode::ExerciseFeeMustBeOwnedByFeeOwner.into()) 
+        }
+        // check that the exercise fee recipient account's mint is also the quote mint
+        if exercise_fee_account.mint != *quote_asset_mint {
+            return Err(errors::ErrorCode::ExerciseFeeTokenMustMatchQuoteAsset.into())
+        }
+
+        fee_accounts.exercise_fee_key = *exercise_fee_recipient.key;
+    }
+    Ok(fee_accounts)
+}
+
+fn validate_mint_fee_acct<'c, 'info>(
+    option_market: &OptionMarket,
+    remaining_accounts: &'c [AccountInfo<'info>]
+) -> Result<Option<&'c AccountInfo<'info>>> {
+    let account_info_iter = &mut remaining_accounts.iter();
+    let acct;
+    if fees::fee_amount(option_market.underlying_amount_per_contract)? > 0 {
+        let mint_fee_recipient = next_account_info(account_info_iter)?;
+        if mint_fee_recipient.owner != &spl_token::ID {
+            return Err(errors::ErrorCode::ExpectedSPLTokenProgramId.into())
+        }
+        let mint_fee_account = SPLTokenAccount::unpack_from_slice(&mint_fee_recipient.try_borrow_data()?)?;
+        if mint_fee_account.owner != fees::fee_owner_key::ID {
+            return Err(errors::ErrorCode::MintFeeMustBeOwnedByFeeOwner.into()) 
+        }
+        // check that the mint fee recipient account's mint is also the underlying mint
+        if mint_fee_account.mint != option_market.underlying_asset_mint {
+            return Err(errors::ErrorCode::MintFeeTokenMustMatchUnderlyingAsset.into())
+        }
+        if *mint_fee_recipient.key != option_market.mint_fee_account {
+            return Err(errors::ErrorCode::MintFeeKeyDoesNotMatchOptionMarket.into())
+        }
+        acct = Some(mint_fee_recipient);
+    } else {
+        acct = None;
+    }
+    Ok(acct)
+}
+
+fn validate_exercise_fee_acct<'c, 'info>(
+    option_market: &OptionMarket,
+    remaining_accounts: &'c [AccountInfo<'info>]
+) -> Result<Option<&'c AccountInfo<'info>>> {
+    let account_info_iter = &mut remaining_accounts.iter();
+    let acct;
+    if fees::fee_amount(option_market.quote_amount_per_contract)? > 0 {
+        let exercise_fee_recipient = next_account_info(account_info_iter)?;
+        if exercise_fee_recipient.owner != &spl_token::ID {
+            return Err(errors::ErrorCode::ExpectedSPLTokenProgramId.into())
+        }
+        let exercise_fee_account = SPLTokenAccount::unpack_from_slice(&exercise_fee_recipient.try_borrow_data()?)?;
+        if exercise_fee_account.owner != fees::fee_owner_key::ID {
+            return Err(errors::ErrorCode::ExerciseFeeMustBeOwnedByFeeOwner.into()) 
+        }
+        // check that the mint fee recipient account's mint is also the underlying mint
+        if exercise_fee_account.mint != option_market.quote_asset_mint {
+            return Err(errors::ErrorCode::ExerciseFeeTokenMustMatchQuoteAsset.into())
+        }
+        // Check the exercise fee account matches the one on the OptionMarket
+        if *exercise_fee_recipient.key != option_market.exercise_fee_account {
             return Err(errors::ErrorCode::ExerciseFeeKeyDoesNotMatchOptionMarket.into())
         }
         acct = Some(exercise_fee_recipient);
@@ -627,7 +2024,8 @@ fn validate_exercise_fee_acct<'c, 'info>(
     underlying_amount_per_contract: u64,
     quote_amount_per_contract: u64,
     expiration_unix_timestamp: i64,
-    bump_seed: u8
+    bump_seed: u8,
+    option_decimals: u8
 )]
 pub struct InitializeMarket<'info> {
     #[account(mut)]
@@ -638,7 +2036,7 @@ pub struct InitializeMarket<'info> {
         seeds = [&option_market.key().to_bytes()[..], b"optionToken"],
         bump,
         payer = authority,
-        mint::decimals = 0,
+        mint::decimals = option_decimals,
         mint::authority = option_market
     )]
     pub option_mint: Box<Account<'info, Mint>>,
@@ -646,7 +2044,7 @@ pub struct InitializeMarket<'info> {
         seeds = [&option_market.key().to_bytes()[..], b"writerToken"],
         bump,
         payer = authority,
-        mint::decimals = 0,
+        mint::decimals = option_decimals,
         mint::authority = option_market
     )]
     pub writer_token_mint: Box<Account<'info, Mint>>,
@@ -677,7 +2075,7 @@ pub struct InitializeMarket<'info> {
         ],
         bump,
         payer = authority,
-        space = 8 + std::mem::size_of::<OptionMarket>() + 300
+        space = migration::MARKET_SPACE
     )]
     pub option_market: Box<Account<'info, OptionMarket>>,
     /// The V1 fee owner
@@ -689,6 +2087,31 @@ pub struct InitializeMarket<'info> {
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
     pub clock: Sysvar<'info, Clock>,
+    /// CHECK: Validated by the seeds, may not exist
+    #[account(seeds = [listing::LISTING_REGISTRY_SEED], bump)]
+    pub listing_registry: AccountInfo<'info>,
+    /// CHECK: Validated by the seeds, exists when the pair is listed
+    #[account(
+        seeds = [listing::LISTED_PAIR_SEED, underlying_asset_mint.key().as_ref(), quote_asset_mint.key().as_ref()],
+        bump,
+    )]
+    pub listed_pair: AccountInfo<'info>,
+    /// CHECK: Validated by the seeds, exists when the authority is an authorized lister
+    #[account(seeds = [listing::LISTER_SEED, authority.key().as_ref()], bump)]
+    pub lister: AccountInfo<'info>,
+    /// CHECK: Validated by the seeds, exists when the pair has standardized series
+    #[account(
+        seeds = [series::SERIES_CONFIG_SEED, underlying_asset_mint.key().as_ref(), quote_asset_mint.key().as_ref()],
+        bump,
+    )]
+    pub series_config: AccountInfo<'info>,
+    /// CHECK: Validated by `option_chain::load_page`. A page of the pair's OptionChain with room
+    /// for the market, or the first page's address when the pair has no OptionChain
+    #[account(mut)]
+    pub option_chain: AccountInfo<'info>,
+    /// CHECK: Validated by the seeds, may not exist
+    #[account(seeds = [pause::PAUSE_CONFIG_SEED], bump)]
+    pub pause_config: AccountInfo<'info>,
 }
 impl<'info> InitializeMarket<'info> {
     fn accounts(ctx: &Context<InitializeMarket<'info>>) -> Result<()> {
@@ -710,6 +2133,25 @@ impl<'info> InitializeMarket<'info> {
         }
         Ok(())
     }
+
+    fn listing(ctx: &Context<InitializeMarket<'info>>) -> Result<()> {
+        // When the registry is enforced only official markets can be created
+        if listing::is_enforced(&ctx.accounts.listing_registry)? &&
+            !listing::is_official(&ctx.accounts.listed_pair, &ctx.accounts.lister) {
+            return Err(errors::ErrorCode::MarketNotListed.into())
+        }
+        Ok(())
+    }
+
+    fn option_chain(ctx: &Context<InitializeMarket<'info>>) -> Result<()> {
+        // Validate the OptionChain indexes this pair
+        option_chain::load_page(
+            &ctx.accounts.option_chain,
+            &ctx.accounts.underlying_asset_mint.key(),
+            &ctx.accounts.quote_asset_mint.key(),
+        )?;
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -743,13 +2185,14 @@ pub struct MintOption<'info> {
     pub clock: Sysvar<'info, Clock>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
+    /// CHECK: Validated by the seeds, may not exist
+    #[account(seeds = [pause::PAUSE_CONFIG_SEED], bump)]
+    pub pause_config: AccountInfo<'info>,
 }
 impl<'info> MintOption<'info> {
     fn accounts(ctx: &Context<MintOption<'info>>) -> Result<()> {
         // Validate the underlying asset pool is the same as on the OptionMarket
-        if *ctx.accounts.underlying_asset_pool.to_account_info().key != ctx.accounts.option_market.underlying_asset_pool {
-            return Err(errors::ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket.into())
-        }
+        ctx.accounts.option_market.validate_underlying_pool(*ctx.accounts.underlying_asset_pool.to_account_info().key)?;
 
         // Validate the option mint is the same as on the OptionMarket
         if *ctx.accounts.option_mint.to_account_info().key != ctx.accounts.option_market.option_mint {
@@ -803,13 +2246,14 @@ pub struct MintOptionV2<'info> {
     pub option_market: Box<Account<'info, OptionMarket>>,
 
     pub token_program: Program<'info, Token>,
+    /// CHECK: Validated by the seeds, may not exist
+    #[account(seeds = [pause::PAUSE_CONFIG_SEED], bump)]
+    pub pause_config: AccountInfo<'info>,
 }
 impl<'info> MintOptionV2<'info> {
     fn accounts(ctx: &Context<MintOptionV2<'info>>) -> Result<()> {
         // Validate the underlying asset pool is the same as on the OptionMarket
-        if *ctx.accounts.underlying_asset_pool.to_account_info().key != ctx.accounts.option_market.underlying_asset_pool {
-            return Err(errors::ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket.into())
-        }
+        ctx.accounts.option_market.validate_underlying_pool(*ctx.accounts.underlying_asset_pool.to_account_info().key)?;
 
         // Validate the option mint is the same as on the OptionMarket
         if *ctx.accounts.option_mint.to_account_info().key != ctx.accounts.option_market.option_mint {
@@ -833,23 +2277,388 @@ impl<'info> MintOptionV2<'info> {
 }
 
 #[derive(Accounts)]
-pub struct ExerciseOption<'info> {
-    /// The user_authority must be the authority that has ownership of the `quote_asset_src` account
+pub struct BatchMint<'info> {
+    /// The user authority must be the authority that has ownership of every `underlying_asset_src`
     pub user_authority: Signer<'info>,
-    /// The owner of the `exerciser_option_token_src` account
-    /// CHECK: Handled
-    #[account(mut, signer)]
-    pub option_authority: AccountInfo<'info>,
-    pub option_market: Box<Account<'info, OptionMarket>>,
-    #[account(mut)]
-    pub option_mint: Box<Account<'info, Mint>>,
-    #[account(mut)]
-    pub exerciser_option_token_src: Box<Account<'info, TokenAccount>>,
-    #[account(mut)]
-    pub underlying_asset_pool: Box<Account<'info, TokenAccount>>,
-    #[account(mut)]
-    pub underlying_asset_dest: Box<Account<'info, TokenAccount>>,
-    #[account(mut)]
+    pub token_program: Program<'info, Token>,
+    /// CHECK: Validated by the seeds, may not exist
+    #[account(seeds = [pause::PAUSE_CONFIG_SEED], bump)]
+    pub pause_config: AccountInfo<'info>,
+}
+impl<'info> BatchMint<'info> {
+    fn accounts(ctx: &Context<BatchMint>, sizes: &[u64]) -> Result<()> {
+        // Validate there is exactly one set of accounts per entry
+        if sizes.is_empty() || ctx.remaining_accounts.len() != sizes.len() * BATCH_MINT_ACCOUNTS {
+            return Err(errors::ErrorCode::BatchAccountsDoNotMatchEntries.into())
+        }
+        Ok(())
+    }
+}
+
+/// The remaining accounts for a single OptionMarket in a batch_mint, in order
+pub struct BatchMintEntry<'info> {
+    pub option_market: Account<'info, OptionMarket>,
+    pub option_mint: Account<'info, Mint>,
+    pub minted_option_dest: Account<'info, TokenAccount>,
+    pub writer_token_mint: Account<'info, Mint>,
+    pub minted_writer_token_dest: Account<'info, TokenAccount>,
+    pub underlying_asset_pool: Account<'info, TokenAccount>,
+    pub underlying_asset_src: Account<'info, TokenAccount>,
+}
+impl<'info> BatchMintEntry<'info> {
+    fn load(accounts: &[AccountInfo<'info>]) -> Result<Self> {
+        Ok(BatchMintEntry {
+            option_market: Account::try_from(&accounts[0])?,
+            option_mint: Account::try_from(&accounts[1])?,
+            minted_option_dest: Account::try_from(&accounts[2])?,
+            writer_token_mint: Account::try_from(&accounts[3])?,
+            minted_writer_token_dest: Account::try_from(&accounts[4])?,
+            underlying_asset_pool: Account::try_from(&accounts[5])?,
+            underlying_asset_src: Account::try_from(&accounts[6])?,
+        })
+    }
+
+    /// The same checks as MintOptionV2
+    fn validate(&self, unix_timestamp: i64) -> Result<()> {
+        // Validate the market is not expired
+        if self.option_market.expiration_unix_timestamp < unix_timestamp {
+            return Err(errors::ErrorCode::OptionMarketExpiredCantMint.into())
+        }
+
+        // Validate the underlying asset pool is the same as on the OptionMarket
+        self.option_market.validate_underlying_pool(self.underlying_asset_pool.key())?;
+
+        // Validate the option mint is the same as on the OptionMarket
+        if self.option_mint.key() != self.option_market.option_mint {
+            return Err(errors::ErrorCode::OptionTokenMintDoesNotMatchMarket.into())
+        }
+
+        // Validate the writer token mint is the same as on the OptionMarket
+        if self.writer_token_mint.key() != self.option_market.writer_token_mint {
+            return Err(errors::ErrorCode::WriterTokenMintDoesNotMatchMarket.into())
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct FlashMint<'info> {
+    pub user_authority: Signer<'info>,
+    /// Must stay at `flash::FLASH_OPTION_MARKET_INDEX`
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    #[account(mut)]
+    pub option_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub minted_option_dest: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub writer_token_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub minted_writer_token_dest: Box<Account<'info, TokenAccount>>,
+    /// CHECK: The instructions sysvar, checked by address
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    /// CHECK: Validated by the seeds, may not exist
+    #[account(seeds = [pause::PAUSE_CONFIG_SEED], bump)]
+    pub pause_config: AccountInfo<'info>,
+}
+impl<'info> FlashMint<'info> {
+    fn accounts(ctx: &Context<FlashMint>) -> Result<()> {
+        // Validate the option mint is the same as on the OptionMarket
+        if ctx.accounts.option_mint.key() != ctx.accounts.option_market.option_mint {
+            return Err(errors::ErrorCode::OptionTokenMintDoesNotMatchMarket.into())
+        }
+
+        // Validate the writer token mint is the same as on the OptionMarket
+        if ctx.accounts.writer_token_mint.key() != ctx.accounts.option_market.writer_token_mint {
+            return Err(errors::ErrorCode::WriterTokenMintDoesNotMatchMarket.into())
+        }
+
+        Ok(())
+    }
+    fn unexpired_market(ctx: &Context<FlashMint>) -> Result<()> {
+        // Validate the market is not expired
+        if ctx.accounts.option_market.expiration_unix_timestamp < Clock::get()?.unix_timestamp {
+            return Err(errors::ErrorCode::OptionMarketExpiredCantMint.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct FlashRepay<'info> {
+    /// The owner of the `option_token_src`, `writer_token_src` and `underlying_asset_src` accounts
+    pub user_authority: Signer<'info>,
+    /// Must stay at `flash::FLASH_OPTION_MARKET_INDEX`
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    #[account(mut)]
+    pub option_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub option_token_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub writer_token_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub writer_token_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub underlying_asset_pool: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub underlying_asset_src: Box<Account<'info, TokenAccount>>,
+    /// CHECK: The instructions sysvar, checked by address
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+impl<'info> FlashRepay<'info> {
+    fn accounts(ctx: &Context<FlashRepay>) -> Result<()> {
+        // Validate the option mint is the same as on the OptionMarket
+        if ctx.accounts.option_mint.key() != ctx.accounts.option_market.option_mint {
+            return Err(errors::ErrorCode::OptionTokenMintDoesNotMatchMarket.into())
+        }
+
+        // Validate the writer token mint is the same as on the OptionMarket
+        if ctx.accounts.writer_token_mint.key() != ctx.accounts.option_market.writer_token_mint {
+            return Err(errors::ErrorCode::WriterTokenMintDoesNotMatchMarket.into())
+        }
+
+        // Validate the underlying asset pool is the same as on the OptionMarket
+        ctx.accounts.option_market.validate_underlying_pool(ctx.accounts.underlying_asset_pool.key())?;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct MintAndSell<'info> {
+    /// The user authority must be the authority that has ownership of the `underlying_asset_src`
+    pub user_authority: Signer<'info>,
+    /// CHECK: Handled
+    pub underlying_asset_mint: AccountInfo<'info>,
+    #[account(mut)]
+    pub underlying_asset_pool: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub underlying_asset_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub option_mint: Box<Account<'info, Mint>>,
+    /// The user's OptionToken account the options are minted to and sold from
+    #[account(mut)]
+    pub minted_option_dest: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub writer_token_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub minted_writer_token_dest: Box<Account<'info, TokenAccount>>,
+    pub option_market: Box<Account<'info, OptionMarket>>,
+
+    // Serum accounts
+    pub dex_program: Program<'info, anchor_spl::dex::Dex>,
+    /// CHECK: Used for the Serum market seeds
+    pub pc_mint: AccountInfo<'info>,
+    /// CHECK: Validated by the seeds
+    #[account(mut,
+        seeds = [&option_market.key().to_bytes()[..], &pc_mint.key().to_bytes()[..], b"serumMarket"],
+        bump,
+    )]
+    pub serum_market: AccountInfo<'info>,
+    /// The user's OpenOrders PDA, initialized through the proxy
+    /// CHECK: Validated by the seeds
+    #[account(mut,
+        seeds = [b"open-orders", dex_program.key().as_ref(), serum_market.key().as_ref(), user_authority.key().as_ref()],
+        bump,
+    )]
+    pub open_orders: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub request_queue: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub event_queue: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub market_bids: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub market_asks: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub coin_vault: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub pc_vault: AccountInfo<'info>,
+    /// The PriceBandConfig PDA for the Serum market
+    /// CHECK: Validated by the PriceBand middleware
+    pub price_band: AccountInfo<'info>,
+    /// The oracle for the PriceBandConfig, or the PriceBandConfig key when the market has none
+    /// CHECK: Validated by the PriceBand middleware
+    pub price_oracle: AccountInfo<'info>,
+    /// This program, which the order is routed through as the Serum proxy
+    /// CHECK: Validated by the address
+    #[account(address = crate::ID)]
+    pub psy_american_program: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: Validated by the seeds, may not exist
+    #[account(seeds = [pause::PAUSE_CONFIG_SEED], bump)]
+    pub pause_config: AccountInfo<'info>,
+}
+impl<'info> MintAndSell<'info> {
+    fn accounts(ctx: &Context<MintAndSell<'info>>) -> Result<()> {
+        // Validate the underlying asset pool is the same as on the OptionMarket
+        ctx.accounts.option_market.validate_underlying_pool(ctx.accounts.underlying_asset_pool.key())?;
+
+        // Validate the option mint is the same as on the OptionMarket
+        if ctx.accounts.option_mint.key() != ctx.accounts.option_market.option_mint {
+            return Err(errors::ErrorCode::OptionTokenMintDoesNotMatchMarket.into())
+        }
+
+        // Validate the writer token mint is the same as on the OptionMarket
+        if ctx.accounts.writer_token_mint.key() != ctx.accounts.option_market.writer_token_mint {
+            return Err(errors::ErrorCode::WriterTokenMintDoesNotMatchMarket.into())
+        }
+
+        Ok(())
+    }
+    fn unexpired_market(ctx: &Context<MintAndSell<'info>>) -> Result<()> {
+        // Validate the market is not expired
+        if ctx.accounts.option_market.expiration_unix_timestamp < Clock::get()?.unix_timestamp {
+            return Err(errors::ErrorCode::OptionMarketExpiredCantMint.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct BuyAndExercise<'info> {
+    /// The user_authority must own the `pc_wallet`, `option_token_dest` and `quote_asset_src`
+    pub user_authority: Signer<'info>,
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    #[account(mut)]
+    pub option_mint: Box<Account<'info, Mint>>,
+    /// The user's OptionToken account the filled options are settled to and exercised from
+    #[account(mut)]
+    pub option_token_dest: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub underlying_asset_pool: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub underlying_asset_dest: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub quote_asset_pool: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub quote_asset_src: Box<Account<'info, TokenAccount>>,
+    /// The user's pc account that pays for the bid
+    #[account(mut)]
+    pub pc_wallet: Box<Account<'info, TokenAccount>>,
+
+    // Serum accounts
+    pub dex_program: Program<'info, anchor_spl::dex::Dex>,
+    /// CHECK: Used for the Serum market seeds
+    pub pc_mint: AccountInfo<'info>,
+    /// CHECK: Validated by the seeds
+    #[account(mut,
+        seeds = [&option_market.key().to_bytes()[..], &pc_mint.key().to_bytes()[..], b"serumMarket"],
+        bump,
+    )]
+    pub serum_market: AccountInfo<'info>,
+    /// The user's OpenOrders PDA, initialized through the proxy
+    /// CHECK: Validated by the seeds
+    #[account(mut,
+        seeds = [b"open-orders", dex_program.key().as_ref(), serum_market.key().as_ref(), user_authority.key().as_ref()],
+        bump,
+    )]
+    pub open_orders: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub request_queue: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub event_queue: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub market_bids: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub market_asks: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub coin_vault: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub pc_vault: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub vault_signer: AccountInfo<'info>,
+    /// The PriceBandConfig PDA for the Serum market
+    /// CHECK: Validated by the PriceBand middleware
+    pub price_band: AccountInfo<'info>,
+    /// The oracle for the PriceBandConfig, or the PriceBandConfig key when the market has none
+    /// CHECK: Validated by the PriceBand middleware
+    pub price_oracle: AccountInfo<'info>,
+    /// This program, which the order is routed through as the Serum proxy
+    /// CHECK: Validated by the address
+    #[account(address = crate::ID)]
+    pub psy_american_program: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: Validated by the seeds, may not exist
+    #[account(seeds = [pause::PAUSE_CONFIG_SEED], bump)]
+    pub pause_config: AccountInfo<'info>,
+}
+impl<'info> BuyAndExercise<'info> {
+    fn accounts(ctx: &Context<BuyAndExercise>) -> Result<()> {
+        // Validate the quote asset pool is the same as on the OptionMarket
+        if ctx.accounts.quote_asset_pool.key() != ctx.accounts.option_market.quote_asset_pool {
+            return Err(errors::ErrorCode::QuotePoolAccountDoesNotMatchMarket.into())
+        }
+
+        // Validate the underlying asset pool is the same as on the OptionMarket
+        ctx.accounts.option_market.validate_underlying_pool(ctx.accounts.underlying_asset_pool.key())?;
+
+        // Validate the option mint is the same as on the OptionMarket
+        if ctx.accounts.option_mint.key() != ctx.accounts.option_market.option_mint {
+            return Err(errors::ErrorCode::OptionTokenMintDoesNotMatchMarket.into())
+        }
+
+        // Validate the option destination holds the OptionMarket's options
+        if ctx.accounts.option_token_dest.mint != ctx.accounts.option_market.option_mint {
+            return Err(errors::ErrorCode::OptionTokenMintDoesNotMatchMarket.into())
+        }
+
+        // Validate the underlying destination has the same mint as the pool
+        if ctx.accounts.underlying_asset_dest.mint != ctx.accounts.option_market.underlying_asset_mint {
+            return Err(errors::ErrorCode::UnderlyingDestMintDoesNotMatchUnderlyingAsset.into())
+        }
+
+        Ok(())
+    }
+    fn unexpired_market(ctx: &Context<BuyAndExercise>) -> Result<()> {
+        // Validate the market is not expired
+        if ctx.accounts.option_market.expiration_unix_timestamp < Clock::get()?.unix_timestamp {
+            return Err(errors::ErrorCode::OptionMarketExpiredCantExercise.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ExerciseOption<'info> {
+    /// The user_authority must be the authority that has ownership of the `quote_asset_src` account
+    pub user_authority: Signer<'info>,
+    /// The owner of the `exerciser_option_token_src` account
+    /// CHECK: Handled
+    #[account(mut, signer)]
+    pub option_authority: AccountInfo<'info>,
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    #[account(mut)]
+    pub option_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub exerciser_option_token_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub underlying_asset_pool: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub underlying_asset_dest: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
     pub quote_asset_pool: Box<Account<'info, TokenAccount>>,
     #[account(mut)]
     pub quote_asset_src: Box<Account<'info, TokenAccount>>,
@@ -869,9 +2678,7 @@ impl<'info> ExerciseOption<'info> {
         }
 
         // Validate the underlying asset pool is the same as on the OptionMarket
-        if *ctx.accounts.underlying_asset_pool.to_account_info().key != ctx.accounts.option_market.underlying_asset_pool {
-            return Err(errors::ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket.into())
-        }
+        ctx.accounts.option_market.validate_underlying_pool(*ctx.accounts.underlying_asset_pool.to_account_info().key)?;
 
         // Validate the option mint is the same as on the OptionMarket
         if *ctx.accounts.option_mint.to_account_info().key != ctx.accounts.option_market.option_mint {
@@ -934,9 +2741,7 @@ impl<'info> ExerciseOptionV2<'info> {
         }
 
         // Validate the underlying asset pool is the same as on the OptionMarket
-        if ctx.accounts.underlying_asset_pool.key() != ctx.accounts.option_market.underlying_asset_pool {
-            return Err(errors::ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket.into())
-        }
+        ctx.accounts.option_market.validate_underlying_pool(ctx.accounts.underlying_asset_pool.key())?;
 
         // Validate the option mint is the same as on the OptionMarket
         if ctx.accounts.option_mint.key() != ctx.accounts.option_market.option_mint {
@@ -959,6 +2764,164 @@ impl<'info> ExerciseOptionV2<'info> {
     }
 }
 
+#[derive(Accounts)]
+pub struct NetExercise<'info> {
+    /// The owner of the `exerciser_option_token_src`, `underlying_asset_dest` and
+    /// `quote_asset_account` accounts, and the authority of the swap's source
+    pub user_authority: Signer<'info>,
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    #[account(mut)]
+    pub option_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub exerciser_option_token_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub underlying_asset_pool: Box<Account<'info, TokenAccount>>,
+    /// Receives the released underlying and is the source of the swap
+    #[account(mut)]
+    pub underlying_asset_dest: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub quote_asset_pool: Box<Account<'info, TokenAccount>>,
+    /// Receives the swap's quote and pays the strike into the pool
+    #[account(mut)]
+    pub quote_asset_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Any program implementing the `amm::swap` interface. It is not trusted, the
+    /// balances it moves are checked after the swap.
+    #[account(executable)]
+    pub amm_program: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+impl<'info> NetExercise<'info> {
+    fn accounts(ctx: &Context<NetExercise>) -> Result<()> {
+        // Validate the quote asset pool is the same as on the OptionMarket
+        if ctx.accounts.quote_asset_pool.key() != ctx.accounts.option_market.quote_asset_pool {
+            return Err(errors::ErrorCode::QuotePoolAccountDoesNotMatchMarket.into())
+        }
+
+        // Validate the underlying asset pool is the same as on the OptionMarket
+        ctx.accounts.option_market.validate_underlying_pool(ctx.accounts.underlying_asset_pool.key())?;
+
+        // Validate the option mint is the same as on the OptionMarket
+        if ctx.accounts.option_mint.key() != ctx.accounts.option_market.option_mint {
+            return Err(errors::ErrorCode::OptionTokenMintDoesNotMatchMarket.into())
+        }
+
+        // Validate the underlying destination has the same mint as the pool
+        if ctx.accounts.underlying_asset_dest.mint != ctx.accounts.option_market.underlying_asset_mint {
+            return Err(errors::ErrorCode::UnderlyingDestMintDoesNotMatchUnderlyingAsset.into())
+        }
+
+        // Validate the quote account has the same mint as the pool
+        if ctx.accounts.quote_asset_account.mint != ctx.accounts.option_market.quote_asset_mint {
+            return Err(errors::ErrorCode::QuoteMintDoesNotMatchMarket.into())
+        }
+
+        Ok(())
+    }
+    fn unexpired_market(ctx: &Context<NetExercise>) -> Result<()> {
+        // Validate the market is not expired
+        if ctx.accounts.option_market.expiration_unix_timestamp < Clock::get()?.unix_timestamp {
+            return Err(errors::ErrorCode::OptionMarketExpiredCantExercise.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct BatchExercise<'info> {
+    /// The user_authority must be the authority that has ownership of the `quote_asset_src` account
+    pub user_authority: Signer<'info>,
+    /// The owner of every `exerciser_option_token_src` account
+    pub option_authority: Signer<'info>,
+    #[account(mut)]
+    pub quote_asset_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub underlying_asset_dest: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+impl<'info> BatchExercise<'info> {
+    fn accounts(ctx: &Context<BatchExercise>, sizes: &[u64]) -> Result<()> {
+        // Validate there is exactly one set of accounts per entry
+        if sizes.is_empty() || ctx.remaining_accounts.len() != sizes.len() * BATCH_EXERCISE_ACCOUNTS {
+            return Err(errors::ErrorCode::BatchAccountsDoNotMatchEntries.into())
+        }
+        Ok(())
+    }
+}
+
+/// The remaining accounts for a single OptionMarket in a batch_exercise, in order
+pub struct BatchExerciseEntry<'info> {
+    pub option_market: Account<'info, OptionMarket>,
+    pub option_mint: Account<'info, Mint>,
+    pub exerciser_option_token_src: Account<'info, TokenAccount>,
+    pub underlying_asset_pool: Account<'info, TokenAccount>,
+    pub quote_asset_pool: Account<'info, TokenAccount>,
+}
+impl<'info> BatchExerciseEntry<'info> {
+    fn load(accounts: &[AccountInfo<'info>]) -> Result<Self> {
+        Ok(BatchExerciseEntry {
+            option_market: Account::try_from(&accounts[0])?,
+            option_mint: Account::try_from(&accounts[1])?,
+            exerciser_option_token_src: Account::try_from(&accounts[2])?,
+            underlying_asset_pool: Account::try_from(&accounts[3])?,
+            quote_asset_pool: Account::try_from(&accounts[4])?,
+        })
+    }
+
+    /// The same checks as ExerciseOptionV2, plus the checks that the entry can be paid for so
+    /// that it can be skipped rather than fail the whole batch
+    fn validate(
+        &self,
+        shared: &BatchExercise,
+        size: u64,
+        options_remaining: u64,
+        quote_remaining: u64,
+        unix_timestamp: i64
+    ) -> Result<()> {
+        validate_size(size)?;
+
+        // Validate the market is not expired
+        if self.option_market.expiration_unix_timestamp < unix_timestamp {
+            return Err(errors::ErrorCode::OptionMarketExpiredCantExercise.into())
+        }
+
+        // Validate the quote asset pool is the same as on the OptionMarket
+        if self.quote_asset_pool.key() != self.option_market.quote_asset_pool {
+            return Err(errors::ErrorCode::QuotePoolAccountDoesNotMatchMarket.into())
+        }
+
+        // Validate the underlying asset pool is the same as on the OptionMarket
+        self.option_market.validate_underlying_pool(self.underlying_asset_pool.key())?;
+
+        // Validate the option mint is the same as on the OptionMarket
+        if self.option_mint.key() != self.option_market.option_mint {
+            return Err(errors::ErrorCode::OptionTokenMintDoesNotMatchMarket.into())
+        }
+
+        // Validate the shared underlying destination has the same mint as the pool
+        if shared.underlying_asset_dest.mint != self.option_market.underlying_asset_mint {
+            return Err(errors::ErrorCode::UnderlyingDestMintDoesNotMatchUnderlyingAsset.into())
+        }
+
+        // Validate the shared quote source has the same mint as the pool
+        if shared.quote_asset_src.mint != self.option_market.quote_asset_mint {
+            return Err(errors::ErrorCode::QuoteMintDoesNotMatchMarket.into())
+        }
+
+        // Validate the entry can be paid for
+        if options_remaining < size {
+            return Err(errors::ErrorCode::NotEnoughOptionsToExercise.into())
+        }
+        let quote_transfer_amount = self.option_market.quote_amount_in(size)?;
+        if quote_remaining < quote_transfer_amount {
+            return Err(errors::ErrorCode::NotEnoughQuoteToExercise.into())
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Accounts)]
 pub struct ClosePostExp<'info> {
     pub user_authority: Signer<'info>,
@@ -978,9 +2941,7 @@ pub struct ClosePostExp<'info> {
 impl<'info> ClosePostExp<'info> {
     fn accounts(ctx: &Context<ClosePostExp>) -> Result<()> {
         // Validate the underlying asset pool is the same as on the OptionMarket
-        if *ctx.accounts.underlying_asset_pool.to_account_info().key != ctx.accounts.option_market.underlying_asset_pool {
-            return Err(errors::ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket.into())
-        }
+        ctx.accounts.option_market.validate_underlying_pool(*ctx.accounts.underlying_asset_pool.to_account_info().key)?;
 
         // Validate the writer mint is the same as on the OptionMarket
         if *ctx.accounts.writer_token_mint.to_account_info().key != ctx.accounts.option_market.writer_token_mint {
@@ -994,169 +2955,1550 @@ impl<'info> ClosePostExp<'info> {
 
         Ok(())
     }
-    fn expired_market(ctx: &Context<ClosePostExp>) -> Result<()> {
-        // Validate the market is expired
-        if ctx.accounts.option_market.expiration_unix_timestamp >= ctx.accounts.clock.unix_timestamp {
-            return Err(errors::ErrorCode::OptionMarketNotExpiredCantClose.into())
+    fn expired_market(ctx: &Context<ClosePostExp>) -> Result<()> {
+        // Validate the market is expired
+        if ctx.accounts.option_market.expiration_unix_timestamp >= ctx.accounts.clock.unix_timestamp {
+            return Err(errors::ErrorCode::OptionMarketNotExpiredCantClose.into())
+        }
+        Ok(())
+    }
+}
+
+
+#[derive(Accounts)]
+pub struct CloseOptionPosition<'info> {
+    pub user_authority: Signer<'info>,
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    #[account(mut)]
+    pub writer_token_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub writer_token_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub option_token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub option_token_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub underlying_asset_pool: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub underlying_asset_dest: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+impl<'info> CloseOptionPosition<'info> {
+    fn accounts(ctx: &Context<CloseOptionPosition>) -> Result<()> {
+        // Validate the WriterToken mint is the same as the OptionMarket
+        if *ctx.accounts.writer_token_mint.to_account_info().key != ctx.accounts.option_market.writer_token_mint {
+            return Err(errors::ErrorCode::WriterTokenMintDoesNotMatchMarket.into())
+        }
+
+        // Validate the OptionToken mint is the same as the OptionMarket
+        if *ctx.accounts.option_token_mint.to_account_info().key != ctx.accounts.option_market.option_mint {
+            return Err(errors::ErrorCode::OptionTokenMintDoesNotMatchMarket.into())
+        }
+
+        // Validate the underlying asset pool is the same as the OptionMarket
+        ctx.accounts.option_market.validate_underlying_pool(*ctx.accounts.underlying_asset_pool.to_account_info().key)?;
+
+        Ok(())
+    }
+}
+
+
+#[derive(Accounts)]
+pub struct InitYieldVault<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(mut,
+        has_one = creator @ errors::ErrorCode::NotMarketCreator,
+        has_one = writer_token_mint @ errors::ErrorCode::WriterTokenMintDoesNotMatchMarket
+    )]
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    pub writer_token_mint: Box<Account<'info, Mint>>,
+    /// CHECK: The adapter is trusted with the market's collateral, see `yield_vault` for its
+    /// interface
+    #[account(executable)]
+    pub adapter_program: AccountInfo<'info>,
+    /// CHECK: Validated by the seeds, exists when the adapter is approved
+    #[account(
+        seeds = [yield_vault::YIELD_ADAPTER_SEED, adapter_program.key().as_ref()],
+        bump,
+        constraint = yield_adapter.owner == &crate::ID @ errors::ErrorCode::YieldAdapterNotApproved
+    )]
+    pub yield_adapter: AccountInfo<'info>,
+    /// CHECK: The adapter's state for the receipt token, validated by the adapter it belongs to
+    #[account(owner = adapter_program.key() @ errors::ErrorCode::YieldAdapterAccountsDoNotMatchVault)]
+    pub adapter_reserve: AccountInfo<'info>,
+    /// The adapter's account holding the deposited underlying
+    #[account(
+        constraint = adapter_vault.mint == option_market.underlying_asset_mint
+            @ errors::ErrorCode::YieldAdapterAccountsDoNotMatchVault
+    )]
+    pub adapter_vault: Box<Account<'info, TokenAccount>>,
+    /// The adapter's receipt token
+    pub receipt_mint: Box<Account<'info, Mint>>,
+    #[account(init,
+        seeds = [yield_vault::YIELD_VAULT_SEED, option_market.key().as_ref()],
+        bump,
+        payer = creator,
+        space = 8 + std::mem::size_of::<YieldVault>()
+    )]
+    pub yield_vault: Box<Account<'info, YieldVault>>,
+    #[account(init,
+        seeds = [&yield_vault.key().to_bytes()[..], b"receiptPool"],
+        bump,
+        payer = creator,
+        token::mint = receipt_mint,
+        token::authority = yield_vault,
+    )]
+    pub receipt_pool: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+impl<'info> InitYieldVault<'info> {
+    fn accounts(ctx: &Context<InitYieldVault<'info>>) -> Result<()> {
+        // Validate no collateral is held in the underlying asset pool
+        if ctx.accounts.writer_token_mint.supply != 0 {
+            return Err(errors::ErrorCode::MarketHasWrittenOptions.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct MintOptionYield<'info> {
+    /// The authority of the adapter deposit's underlying source
+    pub user_authority: Signer<'info>,
+    #[account(mut)]
+    pub option_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub minted_option_dest: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub writer_token_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub minted_writer_token_dest: Box<Account<'info, TokenAccount>>,
+    #[account(
+        has_one = option_mint @ errors::ErrorCode::OptionTokenMintDoesNotMatchMarket,
+        has_one = writer_token_mint @ errors::ErrorCode::WriterTokenMintDoesNotMatchMarket
+    )]
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    #[account(mut,
+        seeds = [yield_vault::YIELD_VAULT_SEED, option_market.key().as_ref()],
+        bump = yield_vault.bump_seed,
+        has_one = receipt_pool @ errors::ErrorCode::ReceiptPoolDoesNotMatchVault,
+        has_one = adapter_program @ errors::ErrorCode::YieldAdapterDoesNotMatchVault
+    )]
+    pub yield_vault: Box<Account<'info, YieldVault>>,
+    #[account(mut)]
+    pub receipt_pool: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Validated by the YieldVault
+    #[account(executable)]
+    pub adapter_program: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    /// CHECK: Validated by the seeds, may not exist
+    #[account(seeds = [pause::PAUSE_CONFIG_SEED], bump)]
+    pub pause_config: AccountInfo<'info>,
+}
+impl<'info> MintOptionYield<'info> {
+    fn unexpired_market(ctx: &Context<MintOptionYield<'info>>) -> Result<()> {
+        // Validate the market is not expired
+        if ctx.accounts.option_market.expiration_unix_timestamp < Clock::get()?.unix_timestamp {
+            return Err(errors::ErrorCode::OptionMarketExpiredCantMint.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ExerciseOptionYield<'info> {
+    /// The owner of the `quote_asset_src` account
+    pub user_authority: Signer<'info>,
+    /// The owner of the `exerciser_option_token_src` account
+    pub option_authority: Signer<'info>,
+    #[account(
+        has_one = option_mint @ errors::ErrorCode::OptionTokenMintDoesNotMatchMarket,
+        has_one = quote_asset_pool @ errors::ErrorCode::QuotePoolAccountDoesNotMatchMarket
+    )]
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    #[account(mut)]
+    pub option_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub exerciser_option_token_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = underlying_asset_dest.mint == option_market.underlying_asset_mint
+            @ errors::ErrorCode::UnderlyingDestMintDoesNotMatchUnderlyingAsset
+    )]
+    pub underlying_asset_dest: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub quote_asset_pool: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub quote_asset_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        seeds = [yield_vault::YIELD_VAULT_SEED, option_market.key().as_ref()],
+        bump = yield_vault.bump_seed,
+        has_one = receipt_pool @ errors::ErrorCode::ReceiptPoolDoesNotMatchVault,
+        has_one = adapter_program @ errors::ErrorCode::YieldAdapterDoesNotMatchVault
+    )]
+    pub yield_vault: Box<Account<'info, YieldVault>>,
+    #[account(mut)]
+    pub receipt_pool: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Validated by the YieldVault
+    #[account(executable)]
+    pub adapter_program: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+impl<'info> ExerciseOptionYield<'info> {
+    fn unexpired_market(ctx: &Context<ExerciseOptionYield>) -> Result<()> {
+        // Validate the market is not expired
+        if ctx.accounts.option_market.expiration_unix_timestamp < Clock::get()?.unix_timestamp {
+            return Err(errors::ErrorCode::OptionMarketExpiredCantExercise.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CloseOptionPositionYield<'info> {
+    /// The owner of the `option_token_src` and `writer_token_src` accounts
+    pub user_authority: Signer<'info>,
+    #[account(
+        has_one = option_mint @ errors::ErrorCode::OptionTokenMintDoesNotMatchMarket,
+        has_one = writer_token_mint @ errors::ErrorCode::WriterTokenMintDoesNotMatchMarket
+    )]
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    #[account(mut)]
+    pub option_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub option_token_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub writer_token_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub writer_token_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = underlying_asset_dest.mint == option_market.underlying_asset_mint
+            @ errors::ErrorCode::UnderlyingDestMintDoesNotMatchUnderlyingAsset
+    )]
+    pub underlying_asset_dest: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        seeds = [yield_vault::YIELD_VAULT_SEED, option_market.key().as_ref()],
+        bump = yield_vault.bump_seed,
+        has_one = receipt_pool @ errors::ErrorCode::ReceiptPoolDoesNotMatchVault,
+        has_one = adapter_program @ errors::ErrorCode::YieldAdapterDoesNotMatchVault
+    )]
+    pub yield_vault: Box<Account<'info, YieldVault>>,
+    #[account(mut)]
+    pub receipt_pool: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Validated by the YieldVault
+    #[account(executable)]
+    pub adapter_program: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePostExpYield<'info> {
+    /// The owner of the `writer_token_src` account
+    pub user_authority: Signer<'info>,
+    #[account(has_one = writer_token_mint @ errors::ErrorCode::WriterTokenMintDoesNotMatchMarket)]
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    #[account(mut)]
+    pub writer_token_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub writer_token_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = underlying_asset_dest.mint == option_market.underlying_asset_mint
+            @ errors::ErrorCode::UnderlyingDestMintDoesNotMatchUnderlyingAsset
+    )]
+    pub underlying_asset_dest: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        seeds = [yield_vault::YIELD_VAULT_SEED, option_market.key().as_ref()],
+        bump = yield_vault.bump_seed,
+        has_one = receipt_pool @ errors::ErrorCode::ReceiptPoolDoesNotMatchVault,
+        has_one = adapter_program @ errors::ErrorCode::YieldAdapterDoesNotMatchVault
+    )]
+    pub yield_vault: Box<Account<'info, YieldVault>>,
+    #[account(mut)]
+    pub receipt_pool: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Validated by the YieldVault
+    #[account(executable)]
+    pub adapter_program: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+impl<'info> ClosePostExpYield<'info> {
+    fn expired_market(ctx: &Context<ClosePostExpYield>) -> Result<()> {
+        // Validate the market is expired
+        if ctx.accounts.option_market.expiration_unix_timestamp >= Clock::get()?.unix_timestamp {
+            return Err(errors::ErrorCode::OptionMarketNotExpiredCantClose.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RollPosition<'info> {
+    /// The owner of the `option_token_src`, `writer_token_src` and `underlying_asset_account` accounts
+    pub user_authority: Signer<'info>,
+    // The OptionMarket the position is closed in
+    pub from_option_market: Box<Account<'info, OptionMarket>>,
+    #[account(mut)]
+    pub from_option_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub from_writer_token_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub option_token_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub writer_token_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub from_underlying_asset_pool: Box<Account<'info, TokenAccount>>,
+    // The OptionMarket the position is opened in
+    pub to_option_market: Box<Account<'info, OptionMarket>>,
+    #[account(mut)]
+    pub to_option_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub to_writer_token_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub minted_option_dest: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub minted_writer_token_dest: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub to_underlying_asset_pool: Box<Account<'info, TokenAccount>>,
+    /// Receives the refund, or pays the difference, when the contract sizes differ
+    #[account(mut)]
+    pub underlying_asset_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    /// CHECK: Validated by the seeds, may not exist
+    #[account(seeds = [pause::PAUSE_CONFIG_SEED], bump)]
+    pub pause_config: AccountInfo<'info>,
+}
+impl<'info> RollPosition<'info> {
+    fn accounts(ctx: &Context<RollPosition>) -> Result<()> {
+        let from_market = &ctx.accounts.from_option_market;
+        let to_market = &ctx.accounts.to_option_market;
+
+        // Validate the closed position's accounts are the same as on its OptionMarket
+        if ctx.accounts.from_option_mint.key() != from_market.option_mint
+            || ctx.accounts.to_option_mint.key() != to_market.option_mint {
+            return Err(errors::ErrorCode::OptionTokenMintDoesNotMatchMarket.into())
+        }
+        if ctx.accounts.from_writer_token_mint.key() != from_market.writer_token_mint
+            || ctx.accounts.to_writer_token_mint.key() != to_market.writer_token_mint {
+            return Err(errors::ErrorCode::WriterTokenMintDoesNotMatchMarket.into())
+        }
+        from_market.validate_underlying_pool(ctx.accounts.from_underlying_asset_pool.key())?;
+        to_market.validate_underlying_pool(ctx.accounts.to_underlying_asset_pool.key())?;
+
+        // Validate the underlying can move from pool to pool
+        if from_market.underlying_asset_mint != to_market.underlying_asset_mint {
+            return Err(errors::ErrorCode::RollUnderlyingAssetsDiffer.into())
+        }
+
+        // Validate `size` is the same number of contracts in both markets
+        if from_market.option_decimals != to_market.option_decimals {
+            return Err(errors::ErrorCode::RollOptionDecimalsDiffer.into())
+        }
+
+        // Validate the user's underlying account has the same mint as the pools
+        if ctx.accounts.underlying_asset_account.mint != from_market.underlying_asset_mint {
+            return Err(errors::ErrorCode::UnderlyingDestMintDoesNotMatchUnderlyingAsset.into())
+        }
+
+        Ok(())
+    }
+    fn unexpired_market(ctx: &Context<RollPosition>) -> Result<()> {
+        // Validate the market being rolled into is not expired
+        if ctx.accounts.to_option_market.expiration_unix_timestamp < Clock::get()?.unix_timestamp {
+            return Err(errors::ErrorCode::OptionMarketExpiredCantMint.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct BurnWriterForQuote<'info> {
+    pub user_authority: Signer<'info>,
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    #[account(mut)]
+    pub writer_token_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub writer_token_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub quote_asset_pool: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub writer_quote_dest: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+impl<'info> BurnWriterForQuote<'info> {
+    fn accounts(ctx: &Context<BurnWriterForQuote>) -> Result<()>{
+        // Validate the Quote asset pool matches the OptionMarket
+        if ctx.accounts.quote_asset_pool.key() != ctx.accounts.option_market.quote_asset_pool {
+            return Err(errors::ErrorCode::QuotePoolAccountDoesNotMatchMarket.into())
+        }
+
+        // Validate WriteToken mint matches the OptionMarket
+        if ctx.accounts.writer_token_mint.key() != ctx.accounts.option_market.writer_token_mint {
+            return Err(errors::ErrorCode::WriterTokenMintDoesNotMatchMarket.into())
+        }
+
+        Ok(())
+    }
+
+    // Validate there is enough quote assets in the pool
+    fn quotes_in_pool(ctx: &Context<BurnWriterForQuote>, size: u64) -> Result<()> {
+        if ctx.accounts.quote_asset_pool.amount < ctx.accounts.option_market.quote_amount_out(size)? {
+            return Err(errors::ErrorCode::NotEnoughQuoteAssetsInPool.into())
+        }
+        Ok(())
+    }
+}
+
+
+#[derive(Accounts)]
+#[instruction(market_space: u64, vault_signer_nonce: u64, coin_lot_size: u64, pc_lot_size: u64, pc_dust_threshold: u64)]
+pub struct InitSerumMarket<'info> {
+    #[account(mut)]
+    pub user_authority: Signer<'info>,
+    // General market accounts
+    #[account(mut)]
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    /// CHECK: Handled
+    #[account(init,
+        seeds = [&option_market.key().to_bytes()[..], &pc_mint.key().to_bytes()[..], b"serumMarket"],
+        bump,
+        space = market_space as usize,
+        payer = user_authority,
+        owner = *dex_program.key
+    )]
+    pub serum_market: AccountInfo<'info>,
+    // system accounts
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub dex_program: Program<'info, anchor_spl::dex::Dex>,
+    pub rent: Sysvar<'info, Rent>,
+    pub pc_mint: Box<Account<'info, Mint>>,
+    pub option_mint: Box<Account<'info, Mint>>,
+    // INIT SERUM MARKET ACCOUNTS
+    /// CHECK: Handled by Serum
+    #[account(init,
+        seeds = [&option_market.key().to_bytes()[..], &pc_mint.key().to_bytes()[..], b"requestQueue"],
+        bump,
+        space = 5120 + 12,
+        payer = user_authority,
+        owner = *dex_program.key
+    )]
+    request_queue: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub event_queue: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub bids: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub asks: AccountInfo<'info>,
+    #[account(init,
+        seeds = [&option_market.key().to_bytes()[..], &pc_mint.key().to_bytes()[..], b"coinVault"],
+        bump,
+        payer = user_authority,    
+        token::mint = option_mint,
+        token::authority = vault_signer,
+    )]
+    pub coin_vault: Box<Account<'info, TokenAccount>>,
+    #[account(init,
+        seeds = [&option_market.key().to_bytes()[..], &pc_mint.key().to_bytes()[..], b"pcVault"],
+        bump,
+        payer = user_authority,
+        token::mint = pc_mint,
+        token::authority = vault_signer,
+    )]
+    pub pc_vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Handled by Serum
+    pub vault_signer: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub market_authority: AccountInfo<'info>,
+    /// CHECK: Validated by the seeds, may not exist
+    #[account(seeds = [pause::PAUSE_CONFIG_SEED], bump)]
+    pub pause_config: AccountInfo<'info>,
+}
+impl<'info> InitSerumMarket<'info> {
+    // Validate the coin_mint is the same as the OptionMarket.option_mint
+    pub fn accounts(ctx: &Context<InitSerumMarket>) -> Result<()> {
+        if ctx.accounts.option_mint.key() != ctx.accounts.option_market.option_mint {
+            return Err(errors::ErrorCode::CoinMintIsNotOptionMint.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(init,
+        seeds = [b"referrer", authority.key().as_ref()],
+        bump,
+        payer = authority,
+        space = 8 + std::mem::size_of::<Referrer>()
+    )]
+    pub referrer: Box<Account<'info, Referrer>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetReferrerShare<'info> {
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    #[account(mut)]
+    pub referrer: Box<Account<'info, Referrer>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralFees<'info> {
+    pub authority: Signer<'info>,
+    #[account(has_one = authority)]
+    pub referrer: Box<Account<'info, Referrer>>,
+    /// The token account, owned by the Referrer, that Serum pays referral fees to
+    #[account(mut)]
+    pub referral_fee_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub frontend_fee_dest: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub protocol_fee_dest: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+impl<'info> ClaimReferralFees<'info> {
+    fn accounts(ctx: &Context<ClaimReferralFees>) -> Result<()> {
+        // Validate the referral fees are held by the Referrer
+        if ctx.accounts.referral_fee_src.owner != ctx.accounts.referrer.key() {
+            return Err(errors::ErrorCode::InvalidReferrer.into())
+        }
+
+        // Validate the frontend's share goes to the Referrer authority
+        if ctx.accounts.frontend_fee_dest.owner != ctx.accounts.referrer.authority {
+            return Err(errors::ErrorCode::ReferralDestMustBeOwnedByAuthority.into())
+        }
+
+        // Validate the protocol's share goes to the fee owner
+        if ctx.accounts.protocol_fee_dest.owner != fees::fee_owner_key::ID {
+            return Err(errors::ErrorCode::FeeOwnerDoesNotMatchProgram.into())
+        }
+
+        // Validate all of the accounts are for the same token
+        if ctx.accounts.frontend_fee_dest.mint != ctx.accounts.referral_fee_src.mint ||
+            ctx.accounts.protocol_fee_dest.mint != ctx.accounts.referral_fee_src.mint {
+            return Err(errors::ErrorCode::ReferralFeeMintMismatch.into())
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitPriceBand<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    pub underlying_asset_mint: Box<Account<'info, Mint>>,
+    pub quote_asset_mint: Box<Account<'info, Mint>>,
+    pub pc_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Validated by the seeds
+    #[account(
+        seeds = [&option_market.key().to_bytes()[..], &pc_mint.key().to_bytes()[..], b"serumMarket"],
+        bump,
+    )]
+    pub serum_market: AccountInfo<'info>,
+    /// CHECK: Validated as a Pyth price account
+    pub oracle: AccountInfo<'info>,
+    #[account(init,
+        seeds = [serum_market.key().as_ref(), b"priceBand"],
+        bump,
+        payer = payer,
+        space = 8 + std::mem::size_of::<PriceBandConfig>()
+    )]
+    pub price_band: Box<Account<'info, PriceBandConfig>>,
+
+    pub system_program: Program<'info, System>,
+}
+impl<'info> InitPriceBand<'info> {
+    fn accounts(ctx: &Context<InitPriceBand>) -> Result<()> {
+        price_band::validate_mints(
+            &ctx.accounts.option_market,
+            &ctx.accounts.underlying_asset_mint.key(),
+            &ctx.accounts.quote_asset_mint.key(),
+            &ctx.accounts.pc_mint.key(),
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdatePriceBand<'info> {
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    #[account(mut)]
+    pub price_band: Box<Account<'info, PriceBandConfig>>,
+    /// CHECK: Validated as a Pyth price account
+    pub oracle: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitListingRegistry<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    #[account(init,
+        seeds = [listing::LISTING_REGISTRY_SEED],
+        bump,
+        payer = payer,
+        space = 8 + std::mem::size_of::<ListingRegistry>()
+    )]
+    pub listing_registry: Box<Account<'info, ListingRegistry>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetListingEnforcement<'info> {
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    #[account(mut, seeds = [listing::LISTING_REGISTRY_SEED], bump = listing_registry.bump_seed)]
+    pub listing_registry: Box<Account<'info, ListingRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct ListPair<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    pub underlying_asset_mint: Box<Account<'info, Mint>>,
+    pub quote_asset_mint: Box<Account<'info, Mint>>,
+    #[account(init,
+        seeds = [listing::LISTED_PAIR_SEED, underlying_asset_mint.key().as_ref(), quote_asset_mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + std::mem::size_of::<ListedPair>()
+    )]
+    pub listed_pair: Box<Account<'info, ListedPair>>,
+
+    pub system_program: Program<'info, System>,
+}
+impl<'info> ListPair<'info> {
+    fn accounts(ctx: &Context<ListPair>) -> Result<()> {
+        // check that underlying and quote are not the same asset
+        if ctx.accounts.underlying_asset_mint.key() == ctx.accounts.quote_asset_mint.key() {
+            return Err(errors::ErrorCode::QuoteAndUnderlyingAssetMustDiffer.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct DelistPair<'info> {
+    #[account(mut, address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    #[account(mut, close = fee_owner)]
+    pub listed_pair: Box<Account<'info, ListedPair>>,
+}
+
+#[derive(Accounts)]
+pub struct AddLister<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    /// CHECK: Any wallet or program can be a lister
+    pub lister_authority: AccountInfo<'info>,
+    #[account(init,
+        seeds = [listing::LISTER_SEED, lister_authority.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + std::mem::size_of::<Lister>()
+    )]
+    pub lister: Box<Account<'info, Lister>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveLister<'info> {
+    #[account(mut, address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    #[account(mut, close = fee_owner)]
+    pub lister: Box<Account<'info, Lister>>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveYieldAdapter<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    /// CHECK: The approved adapter, see `yield_vault` for its interface
+    #[account(executable)]
+    pub adapter_program: AccountInfo<'info>,
+    #[account(init,
+        seeds = [yield_vault::YIELD_ADAPTER_SEED, adapter_program.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + std::mem::size_of::<YieldAdapter>()
+    )]
+    pub yield_adapter: Box<Account<'info, YieldAdapter>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeYieldAdapter<'info> {
+    #[account(mut, address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    #[account(mut, close = fee_owner)]
+    pub yield_adapter: Box<Account<'info, YieldAdapter>>,
+}
+
+#[derive(Accounts)]
+pub struct SetMarketOfficial<'info> {
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    #[account(mut)]
+    pub option_market: Box<Account<'info, OptionMarket>>,
+}
+
+#[derive(Accounts)]
+pub struct UpgradeMarket<'info> {
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    #[account(mut)]
+    pub option_market: Box<Account<'info, OptionMarket>>,
+}
+impl<'info> UpgradeMarket<'info> {
+    fn accounts(ctx: &Context<UpgradeMarket>) -> Result<()> {
+        if ctx.accounts.option_market.creator != Pubkey::default() {
+            return Err(errors::ErrorCode::MarketAlreadyUpgraded.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct MigrateMarket<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut,
+        realloc = migration::MARKET_SPACE,
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub option_market: Box<Account<'info, OptionMarket>>,
+
+    pub system_program: Program<'info, System>,
+}
+impl<'info> MigrateMarket<'info> {
+    fn accounts(ctx: &Context<MigrateMarket>) -> Result<()> {
+        if ctx.accounts.option_market.version >= migration::SIZED_MARKET_VERSION {
+            return Err(errors::ErrorCode::MarketAlreadyMigrated.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetMarketLabel<'info> {
+    pub creator: Signer<'info>,
+    #[account(mut, has_one = creator @ errors::ErrorCode::NotMarketCreator)]
+    pub option_market: Box<Account<'info, OptionMarket>>,
+}
+
+#[derive(Accounts)]
+pub struct InitSeriesConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    pub underlying_asset_mint: Box<Account<'info, Mint>>,
+    pub quote_asset_mint: Box<Account<'info, Mint>>,
+    #[account(init,
+        seeds = [series::SERIES_CONFIG_SEED, underlying_asset_mint.key().as_ref(), quote_asset_mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + std::mem::size_of::<SeriesConfig>()
+    )]
+    pub series_config: Box<Account<'info, SeriesConfig>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSeriesConfig<'info> {
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    #[account(mut)]
+    pub series_config: Box<Account<'info, SeriesConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSeriesConfig<'info> {
+    #[account(mut, address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    #[account(mut, close = fee_owner)]
+    pub series_config: Box<Account<'info, SeriesConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct InitOptionChain<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub underlying_asset_mint: Box<Account<'info, Mint>>,
+    pub quote_asset_mint: Box<Account<'info, Mint>>,
+    #[account(init,
+        seeds = [
+            option_chain::OPTION_CHAIN_SEED,
+            underlying_asset_mint.key().as_ref(),
+            quote_asset_mint.key().as_ref(),
+            &0u32.to_le_bytes()
+        ],
+        bump,
+        payer = payer,
+        space = option_chain::OPTION_CHAIN_LEN
+    )]
+    pub option_chain: Box<Account<'info, OptionChain>>,
+
+    pub system_program: Program<'info, System>,
+}
+impl<'info> InitOptionChain<'info> {
+    fn accounts(ctx: &Context<InitOptionChain>) -> Result<()> {
+        // check that underlying and quote are not the same asset
+        if ctx.accounts.underlying_asset_mint.key() == ctx.accounts.quote_asset_mint.key() {
+            return Err(errors::ErrorCode::QuoteAndUnderlyingAssetMustDiffer.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ExtendOptionChain<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The last page of the OptionChain
+    #[account(mut)]
+    pub option_chain: Box<Account<'info, OptionChain>>,
+    #[account(init,
+        seeds = [
+            option_chain::OPTION_CHAIN_SEED,
+            option_chain.underlying_asset_mint.as_ref(),
+            option_chain.quote_asset_mint.as_ref(),
+            &(option_chain.page + 1).to_le_bytes()
+        ],
+        bump,
+        payer = payer,
+        space = option_chain::OPTION_CHAIN_LEN
+    )]
+    pub next_page: Box<Account<'info, OptionChain>>,
+
+    pub system_program: Program<'info, System>,
+}
+impl<'info> ExtendOptionChain<'info> {
+    fn accounts(ctx: &Context<ExtendOptionChain>) -> Result<()> {
+        // Pages are only added to the end of the chain
+        if !ctx.accounts.option_chain.is_last_page() {
+            return Err(errors::ErrorCode::OptionChainNotLastPage.into())
+        }
+        // Removed markets leave room that new markets should fill first
+        if !ctx.accounts.option_chain.is_full() {
+            return Err(errors::ErrorCode::OptionChainPageNotFull.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromOptionChain<'info> {
+    #[account(mut)]
+    pub option_chain: Box<Account<'info, OptionChain>>,
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    pub writer_token_mint: Box<Account<'info, Mint>>,
+    pub clock: Sysvar<'info, Clock>,
+}
+impl<'info> RemoveFromOptionChain<'info> {
+    fn accounts(ctx: &Context<RemoveFromOptionChain>) -> Result<()> {
+        // Validate the writer mint is the same as on the OptionMarket
+        if ctx.accounts.writer_token_mint.key() != ctx.accounts.option_market.writer_token_mint {
+            return Err(errors::ErrorCode::WriterTokenMintDoesNotMatchMarket.into())
+        }
+
+        // Validate the market is expired
+        if ctx.accounts.option_market.expiration_unix_timestamp >= ctx.accounts.clock.unix_timestamp {
+            return Err(errors::ErrorCode::OptionMarketNotExpiredCantClose.into())
+        }
+
+        // Validate every writer has closed their position
+        if ctx.accounts.writer_token_mint.supply > 0 {
+            return Err(errors::ErrorCode::OptionMarketHasOpenPositions.into())
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitPauseConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    #[account(init,
+        seeds = [pause::PAUSE_CONFIG_SEED],
+        bump,
+        payer = payer,
+        space = 8 + std::mem::size_of::<PauseConfig>()
+    )]
+    pub pause_config: Box<Account<'info, PauseConfig>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    /// CHECK: Validated by the seeds, the fee owner can only make changes until it exists
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump, constraint = governance.data_is_empty() @ errors::ErrorCode::GovernanceRequired)]
+    pub governance: AccountInfo<'info>,
+    #[account(mut, seeds = [pause::PAUSE_CONFIG_SEED], bump = pause_config.bump_seed)]
+    pub pause_config: Box<Account<'info, PauseConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct InitGovernance<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(address = fees::fee_owner_key::ID @ errors::ErrorCode::FeeOwnerDoesNotMatchProgram)]
+    pub fee_owner: Signer<'info>,
+    #[account(init,
+        seeds = [governance::GOVERNANCE_SEED],
+        bump,
+        payer = payer,
+        space = governance::GOVERNANCE_LEN
+    )]
+    pub governance: Box<Account<'info, Governance>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Propose<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    #[account(mut, seeds = [governance::GOVERNANCE_SEED], bump = governance.bump_seed)]
+    pub governance: Box<Account<'info, Governance>>,
+    #[account(init,
+        seeds = [governance::PROPOSAL_SEED, &governance.proposal_count.to_le_bytes()],
+        bump,
+        payer = proposer,
+        space = governance::PROPOSAL_LEN
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    pub system_program: Program<'info, System>,
+}
+impl<'info> Propose<'info> {
+    fn accounts(ctx: &Context<Propose<'info>>) -> Result<()> {
+        if !ctx.accounts.governance.is_member(&ctx.accounts.proposer.key()) {
+            return Err(errors::ErrorCode::NotGovernanceMember.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    pub member: Signer<'info>,
+    #[account(seeds = [governance::GOVERNANCE_SEED], bump = governance.bump_seed)]
+    pub governance: Box<Account<'info, Governance>>,
+    #[account(mut, seeds = [governance::PROPOSAL_SEED, &proposal.index.to_le_bytes()], bump = proposal.bump_seed)]
+    pub proposal: Box<Account<'info, Proposal>>,
+}
+impl<'info> ApproveProposal<'info> {
+    fn accounts(ctx: &Context<ApproveProposal<'info>>) -> Result<()> {
+        if !ctx.accounts.governance.is_member(&ctx.accounts.member.key()) {
+            return Err(errors::ErrorCode::NotGovernanceMember.into())
+        }
+        if ctx.accounts.proposal.approvals.contains(&ctx.accounts.member.key()) {
+            return Err(errors::ErrorCode::ProposalAlreadyApproved.into())
         }
         Ok(())
     }
 }
 
-
 #[derive(Accounts)]
-pub struct CloseOptionPosition<'info> {
-    pub user_authority: Signer<'info>,
-    pub option_market: Box<Account<'info, OptionMarket>>,
-    #[account(mut)]
-    pub writer_token_mint: Box<Account<'info, Mint>>,
-    #[account(mut)]
-    pub writer_token_src: Box<Account<'info, TokenAccount>>,
-    #[account(mut)]
-    pub option_token_mint: Account<'info, Mint>,
+pub struct ExecuteProposal<'info> {
+    /// Pays for any account the action creates
     #[account(mut)]
-    pub option_token_src: Box<Account<'info, TokenAccount>>,
+    pub payer: Signer<'info>,
+    #[account(mut, seeds = [governance::GOVERNANCE_SEED], bump = governance.bump_seed)]
+    pub governance: Box<Account<'info, Governance>>,
+    #[account(mut,
+        seeds = [governance::PROPOSAL_SEED, &proposal.index.to_le_bytes()],
+        bump = proposal.bump_seed,
+        has_one = proposer,
+        close = proposer
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+    /// Receives the rent of the proposal and of any account the action closes
+    /// CHECK: Validated by the proposal
     #[account(mut)]
-    pub underlying_asset_pool: Box<Account<'info, TokenAccount>>,
+    pub proposer: AccountInfo<'info>,
+    /// The account the action creates or changes
+    /// CHECK: Validated in ExecuteProposal::accounts
     #[account(mut)]
-    pub underlying_asset_dest: Box<Account<'info, TokenAccount>>,
+    pub target: AccountInfo<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
-impl<'info> CloseOptionPosition<'info> {
-    fn accounts(ctx: &Context<CloseOptionPosition>) -> Result<()> {
-        // Validate the WriterToken mint is the same as the OptionMarket
-        if *ctx.accounts.writer_token_mint.to_account_info().key != ctx.accounts.option_market.writer_token_mint {
-            return Err(errors::ErrorCode::WriterTokenMintDoesNotMatchMarket.into())
+impl<'info> ExecuteProposal<'info> {
+    fn accounts(ctx: &Context<ExecuteProposal<'info>>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        if ctx.accounts.target.key() != proposal.action.target() {
+            return Err(errors::ErrorCode::ProposalTargetDoesNotMatch.into())
         }
-
-        // Validate the OptionToken mint is the same as the OptionMarket
-        if *ctx.accounts.option_token_mint.to_account_info().key != ctx.accounts.option_market.option_mint {
-            return Err(errors::ErrorCode::OptionTokenMintDoesNotMatchMarket.into())
+        // Validate the proposal is still approved, members may have changed since it was queued
+        if !proposal.is_queued() || !ctx.accounts.governance.is_approved(proposal) {
+            return Err(errors::ErrorCode::ProposalNotApproved.into())
         }
-
-        // Validate the underlying asset pool is the same as the OptionMarket
-        if *ctx.accounts.underlying_asset_pool.to_account_info().key != ctx.accounts.option_market.underlying_asset_pool {
-            return Err(errors::ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket.into())
+        if Clock::get()?.unix_timestamp < proposal.eta && !governance::skips_timelock(&proposal.action, &ctx.accounts.target)? {
+            return Err(errors::ErrorCode::ProposalTimelocked.into())
         }
-
         Ok(())
     }
 }
 
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    #[account(mut,
+        seeds = [governance::PROPOSAL_SEED, &proposal.index.to_le_bytes()],
+        bump = proposal.bump_seed,
+        has_one = proposer @ errors::ErrorCode::NotProposer,
+        close = proposer
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+}
 
 #[derive(Accounts)]
-pub struct BurnWriterForQuote<'info> {
-    pub user_authority: Signer<'info>,
+pub struct VerifyMarket<'info> {
     pub option_market: Box<Account<'info, OptionMarket>>,
-    #[account(mut)]
+    pub option_mint: Box<Account<'info, Mint>>,
     pub writer_token_mint: Box<Account<'info, Mint>>,
-    #[account(mut)]
-    pub writer_token_src: Box<Account<'info, TokenAccount>>,
-    #[account(mut)]
+    pub underlying_asset_pool: Box<Account<'info, TokenAccount>>,
     pub quote_asset_pool: Box<Account<'info, TokenAccount>>,
-    #[account(mut)]
-    pub writer_quote_dest: Box<Account<'info, TokenAccount>>,
-
-    pub token_program: Program<'info, Token>,
+    /// CHECK: Validated by the seeds, it only exists on yield markets
+    #[account(
+        seeds = [yield_vault::YIELD_VAULT_SEED, option_market.key().as_ref()],
+        bump
+    )]
+    pub yield_vault: AccountInfo<'info>,
+    /// CHECK: Validated by the seeds, it only exists on yield markets
+    #[account(
+        seeds = [&yield_vault.key().to_bytes()[..], b"receiptPool"],
+        bump
+    )]
+    pub receipt_pool: AccountInfo<'info>,
 }
-impl<'info> BurnWriterForQuote<'info> {
-    fn accounts(ctx: &Context<BurnWriterForQuote>) -> Result<()>{
-        // Validate the Quote asset pool matches the OptionMarket
-        if ctx.accounts.quote_asset_pool.key() != ctx.accounts.option_market.quote_asset_pool {
-            return Err(errors::ErrorCode::QuotePoolAccountDoesNotMatchMarket.into())
+impl<'info> VerifyMarket<'info> {
+    fn accounts(ctx: &Context<VerifyMarket>) -> Result<()> {
+        // Validate the option mint is the same as on the OptionMarket
+        if ctx.accounts.option_mint.key() != ctx.accounts.option_market.option_mint {
+            return Err(errors::ErrorCode::OptionTokenMintDoesNotMatchMarket.into())
         }
 
-        // Validate WriteToken mint matches the OptionMarket
+        // Validate the WriterToken mint is the same as on the OptionMarket
         if ctx.accounts.writer_token_mint.key() != ctx.accounts.option_market.writer_token_mint {
             return Err(errors::ErrorCode::WriterTokenMintDoesNotMatchMarket.into())
         }
 
-        Ok(())
-    }
+        // Validate the underlying asset pool is the same as on the OptionMarket
+        if ctx.accounts.underlying_asset_pool.key() != ctx.accounts.option_market.underlying_asset_pool {
+            return Err(errors::ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket.into())
+        }
 
-    // Validate there is enough quote assets in the pool
-    fn quotes_in_pool(ctx: &Context<BurnWriterForQuote>, size: u64) -> Result<()> {
-        if ctx.accounts.quote_asset_pool.amount < size.checked_mul(ctx.accounts.option_market.quote_amount_per_contract).unwrap() {
-            return Err(errors::ErrorCode::NotEnoughQuoteAssetsInPool.into())
+        // Validate the quote asset pool is the same as on the OptionMarket
+        if ctx.accounts.quote_asset_pool.key() != ctx.accounts.option_market.quote_asset_pool {
+            return Err(errors::ErrorCode::QuotePoolAccountDoesNotMatchMarket.into())
         }
+
         Ok(())
     }
 }
 
-
-#[derive(Accounts)]
-#[instruction(market_space: u64, vault_signer_nonce: u64, coin_lot_size: u64, pc_lot_size: u64, pc_dust_threshold: u64)]
-pub struct InitSerumMarket<'info> {
-    #[account(mut)]
-    pub user_authority: Signer<'info>,
-    // General market accounts
-    #[account(mut)]
-    pub option_market: Box<Account<'info, OptionMarket>>,
-    /// CHECK: Handled
-    #[account(init,
-        seeds = [&option_market.key().to_bytes()[..], &pc_mint.key().to_bytes()[..], b"serumMarket"],
-        bump,
-        space = market_space as usize,
-        payer = user_authority,
-        owner = *dex_program.key
-    )]
-    pub serum_market: AccountInfo<'info>,
-    // system accounts
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub dex_program: Program<'info, anchor_spl::dex::Dex>,
-    pub rent: Sysvar<'info, Rent>,
-    pub pc_mint: Box<Account<'info, Mint>>,
-    pub option_mint: Box<Account<'info, Mint>>,
-    // INIT SERUM MARKET ACCOUNTS
-    /// CHECK: Handled by Serum
-    #[account(init,
-        seeds = [&option_market.key().to_bytes()[..], &pc_mint.key().to_bytes()[..], b"requestQueue"],
-        bump,
-        space = 5120 + 12,
-        payer = user_authority,
-        owner = *dex_program.key
-    )]
-    request_queue: AccountInfo<'info>,
-    /// CHECK: Handled by Serum
-    #[account(mut)]
-    pub event_queue: AccountInfo<'info>,
-    /// CHECK: Handled by Serum
-    #[account(mut)]
-    pub bids: AccountInfo<'info>,
-    /// CHECK: Handled by Serum
-    #[account(mut)]
-    pub asks: AccountInfo<'info>,
-    #[account(init,
-        seeds = [&option_market.key().to_bytes()[..], &pc_mint.key().to_bytes()[..], b"coinVault"],
-        bump,
-        payer = user_authority,    
-        token::mint = option_mint,
-        token::authority = vault_signer,
-    )]
-    pub coin_vault: Box<Account<'info, TokenAccount>>,
-    #[account(init,
-        seeds = [&option_market.key().to_bytes()[..], &pc_mint.key().to_bytes()[..], b"pcVault"],
-        bump,
-        payer = user_authority,
-        token::mint = pc_mint,
-        token::authority = vault_signer,
-    )]
-    pub pc_vault: Box<Account<'info, TokenAccount>>,
-    /// CHECK: Handled by Serum
-    pub vault_signer: AccountInfo<'info>,
-    /// CHECK: Handled by Serum
-    pub market_authority: AccountInfo<'info>,
+/// The result of `verify_market`, returned as Borsh serialized return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct MarketSolvencyReport {
+    /// The OptionMarket that was checked
+    pub option_market: Pubkey,
+    /// Supply of the option mint
+    pub option_supply: u64,
+    /// Supply of the WriterToken mint
+    pub writer_token_supply: u64,
+    /// Balance of the underlying asset pool
+    pub underlying_pool_amount: u64,
+    /// Balance of the quote asset pool
+    pub quote_pool_amount: u64,
+    /// Whether the market had expired when it was checked
+    pub expired: bool,
+    /// The underlying asset pool covers every outstanding option. Always true once expired,
+    /// as expired options can no longer be exercised.
+    pub options_covered: bool,
+    /// Every WriterToken can be redeemed for its underlying or its quote
+    pub writer_tokens_covered: bool,
+    /// The YieldVault's `collateral_size` on yield markets, whose `underlying_pool_amount` is
+    /// the balance of the receipt pool instead
+    pub collateral_size: Option<u64>,
 }
-impl<'info> InitSerumMarket<'info> {
-    // Validate the coin_mint is the same as the OptionMarket.option_mint
-    pub fn accounts(ctx: &Context<InitSerumMarket>) -> Result<()> {
-        if ctx.accounts.option_mint.key() != ctx.accounts.option_market.option_mint {
-            return Err(errors::ErrorCode::CoinMintIsNotOptionMint.into())
+impl MarketSolvencyReport {
+    pub fn new(
+        option_market_key: Pubkey,
+        option_market: &OptionMarket,
+        option_supply: u64,
+        writer_token_supply: u64,
+        underlying_pool_amount: u64,
+        quote_pool_amount: u64,
+        unix_timestamp: i64
+    ) -> Self {
+        let underlying_tokens = (underlying_pool_amount as u128 * Self::tokens_per_contract(option_market))
+            .checked_div(option_market.underlying_amount_per_contract as u128)
+            .unwrap_or(u128::MAX);
+        let (expired, options_covered, writer_tokens_covered) = Self::coverage(
+            option_market,
+            option_supply,
+            writer_token_supply,
+            underlying_tokens,
+            quote_pool_amount,
+            unix_timestamp,
+        );
+        MarketSolvencyReport {
+            option_market: option_market_key,
+            option_supply,
+            writer_token_supply,
+            underlying_pool_amount,
+            quote_pool_amount,
+            expired,
+            options_covered,
+            writer_tokens_covered,
+            collateral_size: None,
         }
-        Ok(())
     }
+
+    /// The report for a market with a YieldVault. The receipt tokens are only worth what the
+    /// adapter redeems them for, so the options are counted against the vault's
+    /// `collateral_size`, and an empty receipt pool covers none of them.
+    pub fn new_yield(
+        option_market: &OptionMarket,
+        yield_vault: &YieldVault,
+        option_supply: u64,
+        writer_token_supply: u64,
+        receipt_pool_amount: u64,
+        quote_pool_amount: u64,
+        unix_timestamp: i64
+    ) -> Self {
+        let underlying_tokens = if receipt_pool_amount == 0 { 0 } else { yield_vault.collateral_size as u128 };
+        let (expired, options_covered, writer_tokens_covered) = Self::coverage(
+            option_market,
+            option_supply,
+            writer_token_supply,
+            underlying_tokens,
+            quote_pool_amount,
+            unix_timestamp,
+        );
+        MarketSolvencyReport {
+            option_market: yield_vault.option_market,
+            option_supply,
+            writer_token_supply,
+            underlying_pool_amount: receipt_pool_amount,
+            quote_pool_amount,
+            expired,
+            options_covered,
+            writer_tokens_covered,
+            collateral_size: Some(yield_vault.collateral_size),
+        }
+    }
+
+    /// Option tokens per contract, so pools are counted in option tokens rounded down like
+    /// payouts are
+    fn tokens_per_contract(option_market: &OptionMarket) -> u128 {
+        10u128.pow(option_market.option_decimals.min(contracts::MAX_OPTION_DECIMALS) as u32)
+    }
+
+    /// Whether the market has expired, its options are covered by the option tokens the
+    /// underlying collateral can pay out, and its WriterTokens by those plus the quote pool
+    fn coverage(
+        option_market: &OptionMarket,
+        option_supply: u64,
+        writer_token_supply: u64,
+        underlying_tokens: u128,
+        quote_pool_amount: u64,
+        unix_timestamp: i64
+    ) -> (bool, bool, bool) {
+        let quote_tokens = (quote_pool_amount as u128 * Self::tokens_per_contract(option_market))
+            .checked_div(option_market.quote_amount_per_contract as u128)
+            .unwrap_or(u128::MAX);
+        let expired = option_market.expiration_unix_timestamp < unix_timestamp;
+        let options_covered = expired || underlying_tokens >= option_supply as u128;
+        let writer_tokens_covered = underlying_tokens.saturating_add(quote_tokens) >= writer_token_supply as u128;
+        (expired, options_covered, writer_tokens_covered)
+    }
+}
+
+#[account]
+#[derive(Default)]
+/// Price band and circuit breaker configuration for an option's Serum market. New orders
+/// must be priced between a discount to intrinsic value and a premium to the value of the
+/// underlying assets, as computed from the oracle.
+pub struct PriceBandConfig {
+    /// The OptionMarket the Serum market trades
+    pub option_market: Pubkey,
+    /// The Serum market the band applies to
+    pub serum_market: Pubkey,
+    /// Pyth price account for the asset that is not the Serum pc, denominated in the pc
+    pub oracle: Pubkey,
+    /// Copied from the OptionMarket so the middleware does not need to load it
+    pub underlying_amount_per_contract: u64,
+    /// Copied from the OptionMarket so the middleware does not need to load it
+    pub quote_amount_per_contract: u64,
+    /// Copied from the Serum market
+    pub coin_lot_size: u64,
+    /// Copied from the Serum market
+    pub pc_lot_size: u64,
+    /// The maximum discount to intrinsic value, in basis points
+    pub lower_band_bps: u16,
+    /// The maximum premium to the value of the underlying assets, in basis points
+    pub upper_band_bps: u16,
+    /// True when the oracle prices the underlying asset (calls), false when it prices the
+    /// quote asset (puts)
+    pub oracle_prices_underlying: bool,
+    /// Decimals of the asset the oracle prices
+    pub oracle_asset_decimals: u8,
+    /// Decimals of the Serum pc mint
+    pub pc_decimals: u8,
+    /// Circuit breaker, when set all new orders are rejected
+    pub halted: bool,
+    /// Bump seed for the PriceBandConfig PDA
+    pub bump_seed: u8,
+    /// Copied from the OptionMarket, a contract is `10^option_decimals` Serum coin units
+    pub option_decimals: u8,
+}
+
+#[account]
+#[derive(Default)]
+/// A frontend that is registered to earn referral fees on the PsyOptions Serum markets.
+/// Referral wallets passed to SettleFunds must be owned by this PDA.
+pub struct Referrer {
+    /// The frontend's authority that can claim the referral fees
+    pub authority: Pubkey,
+    /// The share of referral fees, in basis points, paid to the frontend. The rest is
+    /// paid to the protocol.
+    pub frontend_share_bps: u16,
+    /// Bump seed for the Referrer PDA
+    pub bump_seed: u8
+}
+
+#[account]
+#[derive(Default)]
+/// Program wide listing configuration, administered by the fee owner
+pub struct ListingRegistry {
+    /// When set `initialize_market` only creates official markets
+    pub enforce: bool,
+    /// Bump seed for the ListingRegistry PDA
+    pub bump_seed: u8,
+}
+
+#[account]
+#[derive(Default)]
+/// An underlying and quote asset pair approved for official markets
+pub struct ListedPair {
+    pub underlying_asset_mint: Pubkey,
+    pub quote_asset_mint: Pubkey,
+    /// Bump seed for the ListedPair PDA
+    pub bump_seed: u8,
+}
+
+#[account]
+#[derive(Default)]
+/// An authority allowed to create official markets for any pair
+pub struct Lister {
+    pub authority: Pubkey,
+    /// Bump seed for the Lister PDA
+    pub bump_seed: u8,
+}
+
+#[account]
+#[derive(Default)]
+/// The standard expirations and strikes for a pair's markets, so each economic option has a
+/// single canonical market
+pub struct SeriesConfig {
+    pub underlying_asset_mint: Pubkey,
+    pub quote_asset_mint: Pubkey,
+    /// The contract size every market of the pair must use
+    pub underlying_amount_per_contract: u64,
+    /// Strikes, the quote amount per contract, must be a multiple of this
+    pub strike_increment: u64,
+    /// Seconds between allowed expirations
+    pub expiration_interval: i64,
+    /// Allowed expirations are `expiration_offset` seconds into each interval since the
+    /// unix epoch
+    pub expiration_offset: i64,
+    /// Bump seed for the SeriesConfig PDA
+    pub bump_seed: u8,
+}
+
+#[account]
+#[derive(Default)]
+/// A page of the index of a pair's markets. Pages are linked from page 0, and markets are
+/// removed once they are closed.
+pub struct OptionChain {
+    pub underlying_asset_mint: Pubkey,
+    pub quote_asset_mint: Pubkey,
+    /// Index of this page, page 0 is the head of the chain
+    pub page: u32,
+    /// The following page, or the default Pubkey on the last page
+    pub next_page: Pubkey,
+    /// Bump seed for the OptionChain PDA
+    pub bump_seed: u8,
+    /// The pair's markets on this page, in no particular order
+    pub entries: Vec<OptionChainEntry>,
+}
+
+#[account]
+#[derive(Default)]
+/// Holds an OptionMarket's collateral as an adapter's receipt token. Options are collateralized
+/// by their share of the receipt pool, so the yield it accrues goes to the WriterTokens that
+/// redeem it.
+pub struct YieldVault {
+    pub option_market: Pubkey,
+    /// The program converting the underlying to receipt tokens and back
+    pub adapter_program: Pubkey,
+    /// The adapter's state, the first account of every adapter instruction
+    pub adapter_reserve: Pubkey,
+    /// The adapter's underlying account, which deposits and redemptions must move through
+    pub adapter_vault: Pubkey,
+    pub receipt_mint: Pubkey,
+    /// The receipt token account owned by the YieldVault
+    pub receipt_pool: Pubkey,
+    /// Outstanding options collateralized by the receipt pool, in option tokens
+    pub collateral_size: u64,
+    /// Bump seed for the YieldVault PDA
+    pub bump_seed: u8,
+}
+
+#[account]
+#[derive(Default)]
+/// An adapter program approved to hold markets' collateral in a YieldVault
+pub struct YieldAdapter {
+    pub adapter_program: Pubkey,
+    /// Bump seed for the YieldAdapter PDA
+    pub bump_seed: u8,
+}
+
+#[account]
+#[derive(Default)]
+/// Program wide pause flags, administered by the fee owner. See `pause::PauseGroup` for the
+/// instructions each flag pauses.
+pub struct PauseConfig {
+    pub flags: PauseFlags,
+    /// Bump seed for the PauseConfig PDA
+    pub bump_seed: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct PauseFlags {
+    /// Pause `initialize_market` and `init_serum_market`
+    pub initialize_market: bool,
+    /// Pause `mint_option` and `mint_option_v2`
+    pub mint_option: bool,
+    /// Pause `batch_mint`, `flash_mint`, `mint_and_sell` and `roll_position`
+    pub composite_mint: bool,
+    /// Pause `buy_and_exercise`
+    pub buy_and_exercise: bool,
+    /// Pause every group. Only exercising, closing positions and repaying flash mints remain,
+    /// so holders can always withdraw their collateral.
+    pub exit_only: bool,
+}
+
+#[account]
+#[derive(Default)]
+/// The multisig that approves changes to pause flags, listings, series configs, market flags
+/// and referral fees. Approved
+/// proposals wait out the timelock before they can be executed, except ones that only pause more.
+pub struct Governance {
+    pub members: Vec<Pubkey>,
+    /// Approvals needed to queue a proposal
+    pub threshold: u8,
+    /// Seconds between a proposal reaching the threshold and when it can be executed
+    pub timelock_seconds: i64,
+    /// Proposals created so far, the index of the next Proposal PDA
+    pub proposal_count: u64,
+    /// Bump seed for the Governance PDA
+    pub bump_seed: u8,
+}
+
+#[account]
+/// A change proposed to the Governance
+pub struct Proposal {
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: GovernanceAction,
+    /// Members that approved the proposal
+    pub approvals: Vec<Pubkey>,
+    /// When the proposal can be executed, 0 until it reaches the threshold
+    pub eta: i64,
+    /// Bump seed for the Proposal PDA
+    pub bump_seed: u8,
+}
+
+/// The changes the Governance can make, each to the account `GovernanceAction::target`.
+/// Protocol fee rates are constants in `fees`, so the referral share is the only fee setting.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum GovernanceAction {
+    /// Set the PauseConfig's flags, creating it if needed
+    SetPauseFlags { flags: PauseFlags },
+    /// Set the ListingRegistry's enforcement, creating it if needed
+    SetListingEnforcement { enforce: bool },
+    ListPair { underlying_asset_mint: Pubkey, quote_asset_mint: Pubkey },
+    DelistPair { underlying_asset_mint: Pubkey, quote_asset_mint: Pubkey },
+    AddLister { authority: Pubkey },
+    RemoveLister { authority: Pubkey },
+    SetReferrerShare { referrer: Pubkey, frontend_share_bps: u16 },
+    UpdateGovernance { members: Vec<Pubkey>, threshold: u8, timelock_seconds: i64 },
+    SetMarketOfficial { option_market: Pubkey, official: bool },
+    /// Record the creator and creation time of a market created before they were stored
+    UpgradeMarket { option_market: Pubkey, creator: Pubkey, created_at: i64 },
+    /// Set a pair's SeriesConfig, creating it if needed
+    SetSeriesConfig {
+        underlying_asset_mint: Pubkey,
+        quote_asset_mint: Pubkey,
+        underlying_amount_per_contract: u64,
+        strike_increment: u64,
+        expiration_interval: i64,
+        expiration_offset: i64,
+    },
+    CloseSeriesConfig { underlying_asset_mint: Pubkey, quote_asset_mint: Pubkey },
+    /// Set the PriceBandConfig of the option's Serum market for `pc_mint`, creating it if
+    /// needed. Proposals that only halt trading skip the timelock.
+    SetPriceBand {
+        option_market: Pubkey,
+        pc_mint: Pubkey,
+        oracle: Pubkey,
+        lower_band_bps: u16,
+        upper_band_bps: u16,
+        halted: bool,
+    },
+    ApproveYieldAdapter { adapter_program: Pubkey },
+    RevokeYieldAdapter { adapter_program: Pubkey },
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: GovernanceAction,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub proposal: Pubkey,
+    pub member: Pubkey,
+    pub approvals: u8,
+}
+
+#[event]
+/// A proposal reached the threshold. This is the notice users get before the change can take
+/// effect at `eta`.
+pub struct ProposalQueued {
+    pub proposal: Pubkey,
+    pub action: GovernanceAction,
+    pub eta: i64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal: Pubkey,
+    pub action: GovernanceAction,
+}
+
+#[event]
+pub struct ProposalCancelled {
+    pub proposal: Pubkey,
+}
+
+/// A market listed in an OptionChain
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct OptionChainEntry {
+    pub option_market: Pubkey,
+    pub underlying_amount_per_contract: u64,
+    /// The strike, in quote asset per contract
+    pub quote_amount_per_contract: u64,
+    pub expiration_unix_timestamp: i64,
 }
 
 #[account]
@@ -1193,5 +4535,60 @@ pub struct OptionMarket {
     /// This will be set when Serum markets are closed and expiration is validated
     pub expired: bool,
     /// Bump seed for the market PDA
-    pub bump_seed: u8
+    pub bump_seed: u8,
+    /// Decimals of the option and WriterToken mints. A whole contract is
+    /// `10^option_decimals` tokens. Markets created before fractional contracts read 0
+    /// from the reserved space.
+    pub option_decimals: u8,
+    /// Set when the market's pair was listed or it was created by an authorized lister
+    pub official: bool,
+    /// Schema version of the market. Markets created before versioning read 0 from the
+    /// reserved space, versions below 2 have the legacy account size until `migrate_market`
+    /// is called.
+    pub version: u8,
+    /// The authority that initialized the market
+    pub creator: Pubkey,
+    /// Unix timestamp of the market's initialization
+    pub created_at: i64,
+    /// Free-form label set by the creator, e.g. UTF-8 padded with zeros
+    pub label: [u8; 32],
+    /// The YieldVault holding the collateral as receipt tokens, or the default key when the
+    /// collateral is held in `underlying_asset_pool`
+    pub yield_vault: Pubkey,
+}
+impl OptionMarket {
+    /// The schema version of markets created or migrated by this program
+    pub const CURRENT_VERSION: u8 = 2;
+
+    /// The underlying assets paid into the pool for `size` option tokens, rounded up
+    pub fn underlying_amount_in(&self, size: u64) -> Result<u64> {
+        contracts::amount_for_size(self.underlying_amount_per_contract, size, self.option_decimals, Rounding::Up)
+    }
+    /// The underlying assets paid out of the pool for `size` option tokens, rounded down
+    pub fn underlying_amount_out(&self, size: u64) -> Result<u64> {
+        contracts::amount_for_size(self.underlying_amount_per_contract, size, self.option_decimals, Rounding::Down)
+    }
+    /// The quote assets paid into the pool for `size` option tokens, rounded up
+    pub fn quote_amount_in(&self, size: u64) -> Result<u64> {
+        contracts::amount_for_size(self.quote_amount_per_contract, size, self.option_decimals, Rounding::Up)
+    }
+    /// The quote assets paid out of the pool for `size` WriterTokens, rounded down
+    pub fn quote_amount_out(&self, size: u64) -> Result<u64> {
+        contracts::amount_for_size(self.quote_amount_per_contract, size, self.option_decimals, Rounding::Down)
+    }
+    /// The WriterTokens a quote pool holding `quote_pool_amount` pays out for, rounded down
+    pub fn quote_pool_size(&self, quote_pool_amount: u64) -> Result<u64> {
+        contracts::size_for_amount(self.quote_amount_per_contract, quote_pool_amount, self.option_decimals)
+    }
+    /// Validate `underlying_asset_pool` is the market's pool and holds its collateral. Markets
+    /// with a YieldVault hold it as receipt tokens and only support the yield instructions.
+    pub fn validate_underlying_pool(&self, underlying_asset_pool: Pubkey) -> Result<()> {
+        if underlying_asset_pool != self.underlying_asset_pool {
+            return Err(errors::ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket.into())
+        }
+        if self.yield_vault != Pubkey::default() {
+            return Err(errors::ErrorCode::YieldMarketUnsupported.into())
+        }
+        Ok(())
+    }
 }
\ No newline at end of file
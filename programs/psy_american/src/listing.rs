@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::{ListedPair, Lister, ListingRegistry};
+
+/// Seed of the ListingRegistry PDA. There is a single registry for the program.
+pub const LISTING_REGISTRY_SEED: &[u8] = b"listingRegistry";
+/// Seed of the ListedPair PDAs, followed by the underlying and quote asset mints
+pub const LISTED_PAIR_SEED: &[u8] = b"listedPair";
+/// Seed of the Lister PDAs, followed by the lister's authority
+pub const LISTER_SEED: &[u8] = b"lister";
+
+/// True when the ListingRegistry exists and only official markets can be initialized. Until
+/// the fee owner creates the registry anyone can initialize markets.
+pub fn is_enforced(listing_registry: &AccountInfo) -> Result<bool> {
+    if listing_registry.owner != &crate::ID {
+        return Ok(false)
+    }
+    let listing_registry = Account::<ListingRegistry>::try_from(listing_registry)?;
+    Ok(listing_registry.enforce)
+}
+
+/// A market is official when its mint pair is listed or it is created by an authorized
+/// lister. The PDAs are validated by the caller's seeds and may not exist.
+pub fn is_official(listed_pair: &AccountInfo, lister: &AccountInfo) -> bool {
+    Account::<ListedPair>::try_from(listed_pair).is_ok() || Account::<Lister>::try_from(lister).is_ok()
+}
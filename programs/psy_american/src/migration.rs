@@ -0,0 +1,59 @@
+/// Account size of OptionMarkets created before versioning, `8 + size_of::<OptionMarket>() + 300`
+/// with the original fields. Markets at version 0 or 1 have this layout.
+pub const LEGACY_MARKET_SPACE: usize = 596;
+
+/// Account size of OptionMarkets from version 2, created by `initialize_market` or grown by
+/// `migrate_market`. The size is fixed rather than derived from `size_of`, so adding a field
+/// never changes the layout of new markets.
+pub const MARKET_SPACE: usize = 1024;
+
+/// The first version with the `MARKET_SPACE` layout
+pub const SIZED_MARKET_VERSION: u8 = 2;
+
+/// The account size of an OptionMarket at `version`
+pub fn market_space(version: u8) -> usize {
+    if version >= SIZED_MARKET_VERSION {
+        MARKET_SPACE
+    } else {
+        LEGACY_MARKET_SPACE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OptionMarket;
+    use anchor_lang::prelude::*;
+
+    /// Until every market is migrated, OptionMarket fields must fit in the legacy layout so
+    /// both layouts deserialize. Fields added after the transition can use `MARKET_SPACE`.
+    #[test]
+    fn fields_fit_the_legacy_layout() {
+        let mut data = vec![];
+        OptionMarket::default().try_serialize(&mut data).unwrap();
+        assert!(data.len() <= LEGACY_MARKET_SPACE);
+    }
+
+    #[test]
+    fn legacy_reserved_space_reads_as_defaults() {
+        let market = OptionMarket {
+            underlying_amount_per_contract: 100,
+            quote_amount_per_contract: 500,
+            ..OptionMarket::default()
+        };
+        let mut data = vec![];
+        market.try_serialize(&mut data).unwrap();
+        data.resize(LEGACY_MARKET_SPACE, 0);
+        let loaded = OptionMarket::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(loaded.version, 0);
+        assert_eq!(loaded.option_decimals, 0);
+        assert_eq!(loaded.creator, Pubkey::default());
+        assert_eq!(market_space(loaded.version), LEGACY_MARKET_SPACE);
+    }
+
+    #[test]
+    fn current_markets_use_the_sized_layout() {
+        assert_eq!(market_space(OptionMarket::CURRENT_VERSION), MARKET_SPACE);
+        assert_eq!(market_space(1), LEGACY_MARKET_SPACE);
+    }
+}
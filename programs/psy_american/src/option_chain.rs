@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors, OptionChain, OptionChainEntry};
+
+/// Seed of the OptionChain PDAs, followed by the underlying and quote asset mints and the
+/// little endian u32 page index
+pub const OPTION_CHAIN_SEED: &[u8] = b"optionChain";
+/// Markets listed on each OptionChain page
+pub const OPTION_CHAIN_PAGE_CAPACITY: usize = 32;
+/// Serialized size of an OptionChainEntry
+pub const OPTION_CHAIN_ENTRY_LEN: usize = 32 + 8 + 8 + 8;
+/// Account size of an OptionChain page with room for every entry
+pub const OPTION_CHAIN_LEN: usize = 8 + 32 + 32 + 4 + 32 + 1 + 4 + OPTION_CHAIN_PAGE_CAPACITY * OPTION_CHAIN_ENTRY_LEN;
+
+/// The address of a page of a pair's OptionChain. Pages are numbered from 0, so clients can
+/// derive every page address and fetch the chain with a single `getMultipleAccounts`.
+pub fn page_address(underlying_asset_mint: &Pubkey, quote_asset_mint: &Pubkey, page: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[OPTION_CHAIN_SEED, underlying_asset_mint.as_ref(), quote_asset_mint.as_ref(), &page.to_le_bytes()],
+        &crate::ID,
+    )
+}
+
+/// Load the page of a pair's OptionChain a new market is added to. Indexing is opt in, so
+/// until someone creates the pair's chain `option_chain` is its first page's address and the
+/// market is not indexed. Once the chain exists the market must be added to one of its pages.
+pub fn load_page<'info>(
+    option_chain: &AccountInfo<'info>,
+    underlying_asset_mint: &Pubkey,
+    quote_asset_mint: &Pubkey,
+) -> Result<Option<Account<'info, OptionChain>>> {
+    if option_chain.owner != &crate::ID {
+        if *option_chain.key != page_address(underlying_asset_mint, quote_asset_mint, 0).0 {
+            return Err(errors::ErrorCode::OptionChainDoesNotMatchPair.into())
+        }
+        return Ok(None)
+    }
+    let page = Account::<OptionChain>::try_from(option_chain)?;
+    if *option_chain.key != page_address(underlying_asset_mint, quote_asset_mint, page.page).0 {
+        return Err(errors::ErrorCode::OptionChainDoesNotMatchPair.into())
+    }
+    if page.is_full() {
+        return Err(errors::ErrorCode::OptionChainPageFull.into())
+    }
+    Ok(Some(page))
+}
+
+impl OptionChain {
+    pub fn is_full(&self) -> bool {
+        self.entries.len() >= OPTION_CHAIN_PAGE_CAPACITY
+    }
+
+    /// True when no page follows this one
+    pub fn is_last_page(&self) -> bool {
+        self.next_page == Pubkey::default()
+    }
+
+    /// Add a market to the page
+    pub fn push(&mut self, entry: OptionChainEntry) -> Result<()> {
+        if self.is_full() {
+            return Err(errors::ErrorCode::OptionChainPageFull.into())
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Remove a market from the page. Entries are unordered, so the last entry takes its place.
+    pub fn remove(&mut self, option_market: &Pubkey) -> Result<OptionChainEntry> {
+        let index = self.entries
+            .iter()
+            .position(|entry| entry.option_market == *option_market)
+            .ok_or(errors::ErrorCode::OptionMarketNotInChain)?;
+        Ok(self.entries.swap_remove(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(expiration_unix_timestamp: i64) -> OptionChainEntry {
+        OptionChainEntry {
+            option_market: Pubkey::new_unique(),
+            underlying_amount_per_contract: 100,
+            quote_amount_per_contract: 500,
+            expiration_unix_timestamp,
+        }
+    }
+
+    #[test]
+    fn full_pages_reject_entries() {
+        let mut chain = OptionChain::default();
+        for i in 0..OPTION_CHAIN_PAGE_CAPACITY {
+            chain.push(entry(i as i64)).unwrap();
+        }
+        assert!(chain.is_full());
+        assert!(chain.push(entry(0)).is_err());
+    }
+
+    #[test]
+    fn removing_keeps_the_other_entries() {
+        let mut chain = OptionChain::default();
+        let entries: Vec<_> = (0..3).map(entry).collect();
+        for entry in &entries {
+            chain.push(*entry).unwrap();
+        }
+        assert_eq!(chain.remove(&entries[0].option_market).unwrap(), entries[0]);
+        assert_eq!(chain.entries, vec![entries[2], entries[1]]);
+        assert!(chain.remove(&entries[0].option_market).is_err());
+    }
+
+    #[test]
+    fn full_page_fits_the_account() {
+        let mut chain = OptionChain::default();
+        for i in 0..OPTION_CHAIN_PAGE_CAPACITY {
+            chain.push(entry(i as i64)).unwrap();
+        }
+        let mut data = vec![];
+        chain.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), OPTION_CHAIN_LEN);
+    }
+}
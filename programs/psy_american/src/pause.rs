@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors, PauseConfig, PauseFlags};
+
+/// Seed of the PauseConfig PDA. There is a single config for the program.
+pub const PAUSE_CONFIG_SEED: &[u8] = b"pauseConfig";
+
+/// The instructions that can be paused. Value returning paths are in no group and do not take
+/// the PauseConfig, so no combination of flags can stop holders from exiting:
+/// - `exercise_option`, `exercise_option_v2`, `batch_exercise`, `net_exercise` and
+///   `exercise_option_yield`
+/// - `close_option_position`, `close_post_expiration`, `burn_writer_for_quote` and their
+///   yield market variants
+/// - `flash_repay`, so a flash mint started before a pause can always be repaid
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PauseGroup {
+    /// `initialize_market` and `init_serum_market`
+    InitializeMarket,
+    /// `mint_option`, `mint_option_v2` and `mint_option_yield`
+    MintOption,
+    /// `batch_mint`, `flash_mint`, `mint_and_sell` and `roll_position`, which open positions
+    CompositeMint,
+    /// `buy_and_exercise`, which buys options on Serum before exercising them
+    BuyAndExercise,
+}
+
+impl PauseGroup {
+    pub const ALL: [PauseGroup; 4] = [
+        PauseGroup::InitializeMarket,
+        PauseGroup::MintOption,
+        PauseGroup::CompositeMint,
+        PauseGroup::BuyAndExercise,
+    ];
+}
+
+impl PauseFlags {
+    /// The flags until the fee owner creates the PauseConfig. Market creation and every path
+    /// that mints stay blocked as they were before the flags existed.
+    pub const UNCONFIGURED: PauseFlags = PauseFlags {
+        initialize_market: true,
+        mint_option: true,
+        composite_mint: true,
+        buy_and_exercise: false,
+        exit_only: false,
+    };
+
+    /// In exit only mode every group is paused, leaving only the value returning paths
+    pub fn is_paused(&self, group: PauseGroup) -> bool {
+        self.exit_only || match group {
+            PauseGroup::InitializeMarket => self.initialize_market,
+            PauseGroup::MintOption => self.mint_option,
+            PauseGroup::CompositeMint => self.composite_mint,
+            PauseGroup::BuyAndExercise => self.buy_and_exercise,
+        }
+    }
+
+    /// True when these flags pause every group `other` pauses, so switching from `other` to
+    /// these flags cannot reopen anything
+    pub fn pauses_at_least(&self, other: &PauseFlags) -> bool {
+        PauseGroup::ALL.iter().all(|group| self.is_paused(*group) || !other.is_paused(*group))
+    }
+}
+
+/// The flags in effect. The PauseConfig is validated by the caller's seeds and may not exist,
+/// in which case the UNCONFIGURED flags apply.
+pub fn current_flags(pause_config: &AccountInfo) -> Result<PauseFlags> {
+    if pause_config.owner == &crate::ID {
+        Ok(Account::<PauseConfig>::try_from(pause_config)?.flags)
+    } else {
+        Ok(PauseFlags::UNCONFIGURED)
+    }
+}
+
+/// Fail when the group is paused
+pub fn check(pause_config: &AccountInfo, group: PauseGroup) -> Result<()> {
+    let flags = current_flags(pause_config)?;
+    if flags.is_paused(group) {
+        return Err(errors::ErrorCode::InstructionPaused.into())
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unconfigured_flags_block_market_creation_and_minting() {
+        let flags = PauseFlags::UNCONFIGURED;
+        assert!(flags.is_paused(PauseGroup::InitializeMarket));
+        assert!(flags.is_paused(PauseGroup::MintOption));
+        assert!(flags.is_paused(PauseGroup::CompositeMint));
+        assert!(!flags.is_paused(PauseGroup::BuyAndExercise));
+    }
+
+    #[test]
+    fn exit_only_pauses_every_group() {
+        let flags = PauseFlags { exit_only: true, ..PauseFlags::default() };
+        for group in PauseGroup::ALL.iter() {
+            assert!(flags.is_paused(*group), "{:?}", group);
+            assert!(!PauseFlags::default().is_paused(*group), "{:?}", group);
+        }
+    }
+
+    #[test]
+    fn pauses_at_least_compares_paused_groups() {
+        let unpaused = PauseFlags::default();
+        let exit_only = PauseFlags { exit_only: true, ..PauseFlags::default() };
+        let minting = PauseFlags { mint_option: true, ..PauseFlags::default() };
+        assert!(PauseFlags::UNCONFIGURED.pauses_at_least(&unpaused));
+        assert!(exit_only.pauses_at_least(&PauseFlags::UNCONFIGURED));
+        assert!(PauseFlags::UNCONFIGURED.pauses_at_least(&minting));
+        assert!(!unpaused.pauses_at_least(&PauseFlags::UNCONFIGURED));
+        assert!(!minting.pauses_at_least(&exit_only));
+        assert!(!PauseFlags { buy_and_exercise: true, ..PauseFlags::default() }.pauses_at_least(&minting));
+    }
+}
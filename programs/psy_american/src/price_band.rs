@@ -0,0 +1,294 @@
+use anchor_lang::prelude::*;
+use std::convert::TryInto;
+
+use anchor_spl::token::Mint;
+
+use crate::{contracts, errors, OptionMarket, PriceBandConfig};
+
+/// Magic number at the start of every Pyth account
+const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+/// Offset of the price exponent in a Pyth price account
+const PYTH_EXPO_OFFSET: usize = 20;
+/// Offset of the aggregate price in a Pyth price account
+const PYTH_AGG_PRICE_OFFSET: usize = 208;
+/// Offset of the aggregate price status in a Pyth price account
+const PYTH_AGG_STATUS_OFFSET: usize = 224;
+/// Offset of the slot the aggregate price was published in a Pyth price account
+const PYTH_AGG_PUB_SLOT_OFFSET: usize = 232;
+/// Pyth status for a price that is currently trading
+const PYTH_STATUS_TRADING: u32 = 1;
+/// The most slots an aggregate price can trail the current slot, matching Pyth's own limit
+pub const MAX_ORACLE_STALENESS_SLOTS: u64 = 25;
+
+/// Offset of the coin lot size in a Serum MarketState account (including the 5 byte padding)
+const SERUM_COIN_LOT_SIZE_OFFSET: usize = 349;
+/// Offset of the pc lot size in a Serum MarketState account (including the 5 byte padding)
+const SERUM_PC_LOT_SIZE_OFFSET: usize = 357;
+/// Offset of the native coin free in a Serum OpenOrders account (including the 5 byte padding)
+const SERUM_NATIVE_COIN_FREE_OFFSET: usize = 77;
+
+/// A price read from an oracle. The price of one whole token is `price * 10^expo`.
+pub struct OraclePrice {
+    pub price: u64,
+    pub expo: i32,
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    let bytes = data.get(offset..offset + 4).ok_or(errors::ErrorCode::InvalidOracle)?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let bytes = data.get(offset..offset + 8).ok_or(errors::ErrorCode::InvalidOracle)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Load the aggregate price from a Pyth price account. Errors if the price is not
+/// currently trading or is stale, which trips the circuit breaker for the market.
+pub fn load_oracle_price(oracle: &AccountInfo) -> Result<OraclePrice> {
+    let data = oracle.try_borrow_data()?;
+    read_oracle_price(&data, Clock::get()?.slot)
+}
+
+/// Read the aggregate price from the data of a Pyth price account at `current_slot`
+pub fn read_oracle_price(data: &[u8], current_slot: u64) -> Result<OraclePrice> {
+    if read_u32(data, 0)? != PYTH_MAGIC {
+        return Err(errors::ErrorCode::InvalidOracle.into())
+    }
+    if read_u32(data, PYTH_AGG_STATUS_OFFSET)? != PYTH_STATUS_TRADING {
+        return Err(errors::ErrorCode::OracleNotTrading.into())
+    }
+    let pub_slot = read_u64(data, PYTH_AGG_PUB_SLOT_OFFSET)?;
+    if current_slot.saturating_sub(pub_slot) > MAX_ORACLE_STALENESS_SLOTS {
+        return Err(errors::ErrorCode::OracleStale.into())
+    }
+    let expo = read_u32(data, PYTH_EXPO_OFFSET)? as i32;
+    let price = read_u64(data, PYTH_AGG_PRICE_OFFSET)? as i64;
+    if price <= 0 {
+        return Err(errors::ErrorCode::InvalidOracle.into())
+    }
+    Ok(OraclePrice { price: price as u64, expo })
+}
+
+/// Read the (coin_lot_size, pc_lot_size) from a Serum market account
+pub fn load_lot_sizes(serum_market: &AccountInfo) -> Result<(u64, u64)> {
+    let data = serum_market.try_borrow_data()?;
+    let coin_lot_size = read_u64(&data, SERUM_COIN_LOT_SIZE_OFFSET)?;
+    let pc_lot_size = read_u64(&data, SERUM_PC_LOT_SIZE_OFFSET)?;
+    Ok((coin_lot_size, pc_lot_size))
+}
+
+/// Read the native coin that is free to settle from a Serum OpenOrders account
+pub fn load_native_coin_free(open_orders: &AccountInfo) -> Result<u64> {
+    let data = open_orders.try_borrow_data()?;
+    read_u64(&data, SERUM_NATIVE_COIN_FREE_OFFSET)
+}
+
+/// The address of the PsyOptions Serum market for an option and pc mint
+pub fn serum_market_address(option_market: &Pubkey, pc_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[option_market.as_ref(), pc_mint.as_ref(), b"serumMarket"], &crate::ID).0
+}
+
+/// The address of the PriceBandConfig for a Serum market
+pub fn price_band_address(serum_market: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[serum_market.as_ref(), b"priceBand"], &crate::ID)
+}
+
+/// Validate the mints match the OptionMarket and the pc is one of its assets
+pub fn validate_mints(
+    option_market: &OptionMarket,
+    underlying_asset_mint: &Pubkey,
+    quote_asset_mint: &Pubkey,
+    pc_mint: &Pubkey
+) -> Result<()> {
+    if *underlying_asset_mint != option_market.underlying_asset_mint {
+        return Err(errors::ErrorCode::UnderlyingMintDoesNotMatchMarket.into())
+    }
+    if *quote_asset_mint != option_market.quote_asset_mint {
+        return Err(errors::ErrorCode::QuoteMintDoesNotMatchMarket.into())
+    }
+    // The oracle prices the asset that is not the pc, so one side must be the pc
+    if *pc_mint != option_market.quote_asset_mint && *pc_mint != option_market.underlying_asset_mint {
+        return Err(errors::ErrorCode::PcMintMustBeUnderlyingOrQuote.into())
+    }
+    Ok(())
+}
+
+/// Build the PriceBandConfig for an option's Serum market, copying what the middleware needs
+/// from the OptionMarket, mints and Serum market. The mints are validated by the caller.
+#[allow(clippy::too_many_arguments)]
+pub fn new_config(
+    option_market: &Account<OptionMarket>,
+    serum_market: &AccountInfo,
+    oracle: &AccountInfo,
+    underlying_asset_mint: &Mint,
+    quote_asset_mint: &Mint,
+    pc_mint: &Account<Mint>,
+    lower_band_bps: u16,
+    upper_band_bps: u16,
+    bump_seed: u8
+) -> Result<PriceBandConfig> {
+    // Validate the oracle is a valid price account
+    load_oracle_price(oracle)?;
+    let (coin_lot_size, pc_lot_size) = load_lot_sizes(serum_market)?;
+    // Validate the Serum market is initialized, the band divides by its lot sizes
+    if coin_lot_size == 0 || pc_lot_size == 0 {
+        return Err(errors::ErrorCode::SerumMarketNotInitialized.into())
+    }
+    let oracle_prices_underlying = pc_mint.key() == option_market.quote_asset_mint;
+    Ok(PriceBandConfig {
+        option_market: option_market.key(),
+        serum_market: serum_market.key(),
+        oracle: oracle.key(),
+        underlying_amount_per_contract: option_market.underlying_amount_per_contract,
+        quote_amount_per_contract: option_market.quote_amount_per_contract,
+        coin_lot_size,
+        pc_lot_size,
+        lower_band_bps,
+        upper_band_bps,
+        oracle_prices_underlying,
+        oracle_asset_decimals: if oracle_prices_underlying {
+            underlying_asset_mint.decimals
+        } else {
+            quote_asset_mint.decimals
+        },
+        pc_decimals: pc_mint.decimals,
+        halted: false,
+        bump_seed,
+        option_decimals: option_market.option_decimals,
+    })
+}
+
+/// True when a governance change to the PriceBandConfig only halts trading, so it can take
+/// effect as soon as it is approved. `price_band` may not exist.
+pub fn only_halts(
+    price_band: &AccountInfo,
+    oracle: &Pubkey,
+    lower_band_bps: u16,
+    upper_band_bps: u16,
+    halted: bool
+) -> Result<bool> {
+    if !halted || price_band.owner != &crate::ID {
+        return Ok(false)
+    }
+    let config = Account::<PriceBandConfig>::try_from(price_band)?;
+    Ok(config.oracle == *oracle && config.lower_band_bps == lower_band_bps && config.upper_band_bps == upper_band_bps)
+}
+
+/// Value `amount` native units of the oracle priced asset in native units of the pc asset
+fn value_in_pc(amount: u64, oracle_price: &OraclePrice, asset_decimals: u8, pc_decimals: u8) -> Result<u128> {
+    let value = (amount as u128).checked_mul(oracle_price.price as u128).ok_or(errors::ErrorCode::NumberOverflow)?;
+    let scale = oracle_price.expo + pc_decimals as i32 - asset_decimals as i32;
+    let factor = 10u128.checked_pow(scale.unsigned_abs()).ok_or(errors::ErrorCode::NumberOverflow)?;
+    if scale >= 0 {
+        Ok(value.checked_mul(factor).ok_or(errors::ErrorCode::NumberOverflow)?)
+    } else {
+        Ok(value / factor)
+    }
+}
+
+/// Validate an order's limit price against the PriceBandConfig for the Serum market. Markets
+/// without a PriceBandConfig are not restricted.
+pub fn validate_order_price(
+    price_band_info: &AccountInfo,
+    oracle_info: &AccountInfo,
+    serum_market: &Pubkey,
+    limit_price: u64
+) -> Result<()> {
+    // Validate the PriceBandConfig is the PDA for the Serum market
+    let (price_band_key, _bump) = price_band_address(serum_market);
+    if *price_band_info.key != price_band_key {
+        return Err(errors::ErrorCode::PriceBandDoesNotMatchMarket.into())
+    }
+    if price_band_info.data_is_empty() {
+        return Ok(())
+    }
+
+    let config = Account::<PriceBandConfig>::try_from(price_band_info)?;
+    if config.halted {
+        return Err(errors::ErrorCode::MarketHalted.into())
+    }
+    if *oracle_info.key != config.oracle {
+        return Err(errors::ErrorCode::OracleDoesNotMatchPriceBand.into())
+    }
+    let oracle_price = load_oracle_price(oracle_info)?;
+    let (lower, upper) = config.band(&oracle_price)?;
+    let price = config.price_per_contract(limit_price);
+    if price < lower || price > upper {
+        msg!("Order price {} outside of band [{}, {}]", price, lower, upper);
+        return Err(errors::ErrorCode::OrderPriceOutsideBand.into())
+    }
+    Ok(())
+}
+
+impl PriceBandConfig {
+    /// The (lower, upper) bound, in native pc per option contract, that orders must be
+    /// priced within. The lower bound is a discount to intrinsic value and the upper
+    /// bound is a premium to the value of the underlying assets in a contract.
+    pub fn band(&self, oracle_price: &OraclePrice) -> Result<(u128, u128)> {
+        let (underlying_value, quote_value) = if self.oracle_prices_underlying {
+            (
+                value_in_pc(self.underlying_amount_per_contract, oracle_price, self.oracle_asset_decimals, self.pc_decimals)?,
+                self.quote_amount_per_contract as u128,
+            )
+        } else {
+            (
+                self.underlying_amount_per_contract as u128,
+                value_in_pc(self.quote_amount_per_contract, oracle_price, self.oracle_asset_decimals, self.pc_decimals)?,
+            )
+        };
+        let intrinsic_value = underlying_value.saturating_sub(quote_value);
+        let lower = intrinsic_value * (10_000 - self.lower_band_bps as u128) / 10_000;
+        let upper = underlying_value
+            .checked_mul(10_000 + self.upper_band_bps as u128)
+            .ok_or(errors::ErrorCode::NumberOverflow)? / 10_000;
+        Ok((lower, upper))
+    }
+
+    /// Convert a Serum limit price (pc lots per coin lot) to native pc per option contract.
+    /// A coin is one option token, which is a fraction of a contract when the option mint
+    /// has decimals.
+    pub fn price_per_contract(&self, limit_price: u64) -> u128 {
+        let tokens_per_contract = 10u128.pow(self.option_decimals.min(contracts::MAX_OPTION_DECIMALS) as u32);
+        limit_price as u128 * self.pc_lot_size as u128 * tokens_per_contract / self.coin_lot_size as u128
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pyth_price(price: i64, status: u32, pub_slot: u64) -> Vec<u8> {
+        let mut data = vec![0u8; PYTH_AGG_PUB_SLOT_OFFSET + 8];
+        data[0..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+        data[PYTH_EXPO_OFFSET..PYTH_EXPO_OFFSET + 4].copy_from_slice(&(-6i32).to_le_bytes());
+        data[PYTH_AGG_PRICE_OFFSET..PYTH_AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+        data[PYTH_AGG_STATUS_OFFSET..PYTH_AGG_STATUS_OFFSET + 4].copy_from_slice(&status.to_le_bytes());
+        data[PYTH_AGG_PUB_SLOT_OFFSET..PYTH_AGG_PUB_SLOT_OFFSET + 8].copy_from_slice(&pub_slot.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn reads_a_recent_trading_price() {
+        let data = pyth_price(25_000_000, PYTH_STATUS_TRADING, 1_000);
+        let price = read_oracle_price(&data, 1_000 + MAX_ORACLE_STALENESS_SLOTS).unwrap();
+        assert_eq!(price.price, 25_000_000);
+        assert_eq!(price.expo, -6);
+    }
+
+    #[test]
+    fn rejects_stale_prices() {
+        let data = pyth_price(25_000_000, PYTH_STATUS_TRADING, 1_000);
+        let err = read_oracle_price(&data, 1_001 + MAX_ORACLE_STALENESS_SLOTS).err().unwrap();
+        assert_eq!(err, errors::ErrorCode::OracleStale.into());
+    }
+
+    #[test]
+    fn rejects_prices_that_are_not_trading() {
+        let data = pyth_price(25_000_000, 0, 1_000);
+        let err = read_oracle_price(&data, 1_000).err().unwrap();
+        assert_eq!(err, errors::ErrorCode::OracleNotTrading.into());
+        let err = read_oracle_price(&pyth_price(0, PYTH_STATUS_TRADING, 1_000), 1_000).err().unwrap();
+        assert_eq!(err, errors::ErrorCode::InvalidOracle.into());
+    }
+}
@@ -0,0 +1,378 @@
+//! Helpers for programs that CPI into the PsyOptions Serum proxy. Each function builds the
+//! Serum DEX instruction, adds the discriminators and accounts the proxy middleware expect,
+//! and invokes the proxy with the `CpiContext`'s signer seeds. The `CpiContext` program must be
+//! the PsyOptions American program.
+use anchor_lang::prelude::*;
+use anchor_spl::dex::serum_dex;
+use anchor_spl::dex::serum_dex::instruction::SelfTradeBehavior;
+use anchor_spl::dex::serum_dex::matching::{OrderType, Side};
+use solana_program::instruction::Instruction;
+use std::num::NonZeroU64;
+
+use crate::errors;
+
+/// Validation and OpenOrdersPda discriminators for each proxied instruction
+const INIT_OPEN_ORDERS_DISC: u8 = 0;
+const NEW_ORDER_V3_DISC: u8 = 1;
+const CANCEL_ORDER_V2_DISC: u8 = 2;
+const CANCEL_ORDER_BY_CLIENT_ID_V2_DISC: u8 = 3;
+const SETTLE_FUNDS_DISC: u8 = 4;
+const CLOSE_OPEN_ORDERS_DISC: u8 = 5;
+const PRUNE_DISC: u8 = 6;
+
+#[derive(Accounts)]
+pub struct InitOpenOrders<'info> {
+    /// CHECK: Serum DEX program
+    pub dex_program: AccountInfo<'info>,
+    /// CHECK: System program
+    pub system_program: AccountInfo<'info>,
+    /// The authority's OpenOrders PDA, initialized by the proxy
+    /// CHECK: Validated by the proxy
+    pub open_orders: AccountInfo<'info>,
+    /// The authority that pays for and owns the OpenOrders PDA
+    /// CHECK: Must sign
+    pub authority: AccountInfo<'info>,
+    /// CHECK: Validated by the proxy
+    pub market: AccountInfo<'info>,
+    /// CHECK: Rent sysvar
+    pub rent: AccountInfo<'info>,
+    /// The PsyOptions market authority PDA for the Serum market
+    /// CHECK: Validated by the proxy
+    pub market_authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct NewOrderV3<'info> {
+    /// CHECK: Serum DEX program
+    pub dex_program: AccountInfo<'info>,
+    /// The PriceBandConfig PDA for the Serum market
+    /// CHECK: Validated by the PriceBand middleware
+    pub price_band: AccountInfo<'info>,
+    /// The PriceBandConfig's oracle, or the PriceBandConfig key when the market has none
+    /// CHECK: Validated by the PriceBand middleware
+    pub price_oracle: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub market: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub open_orders: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub request_queue: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub event_queue: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub market_bids: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub market_asks: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub order_payer_token_account: AccountInfo<'info>,
+    /// The owner of the OpenOrders PDA and the `order_payer_token_account`
+    /// CHECK: Must sign
+    pub authority: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub coin_vault: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub pc_vault: AccountInfo<'info>,
+    /// CHECK: Token program
+    pub token_program: AccountInfo<'info>,
+    /// CHECK: Rent sysvar
+    pub rent: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrderV2<'info> {
+    /// CHECK: Serum DEX program
+    pub dex_program: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub market: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub market_bids: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub market_asks: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub open_orders: AccountInfo<'info>,
+    /// The owner of the OpenOrders PDA
+    /// CHECK: Must sign
+    pub authority: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub event_queue: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleFunds<'info> {
+    /// CHECK: Serum DEX program
+    pub dex_program: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub market: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub open_orders: AccountInfo<'info>,
+    /// The owner of the OpenOrders PDA
+    /// CHECK: Must sign
+    pub authority: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub coin_vault: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub pc_vault: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub coin_wallet: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub pc_wallet: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub vault_signer: AccountInfo<'info>,
+    /// CHECK: Token program
+    pub token_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseOpenOrders<'info> {
+    /// CHECK: Serum DEX program
+    pub dex_program: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub open_orders: AccountInfo<'info>,
+    /// The owner of the OpenOrders PDA
+    /// CHECK: Must sign
+    pub authority: AccountInfo<'info>,
+    /// The account that receives the OpenOrders rent
+    /// CHECK: Handled by Serum
+    pub destination: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub market: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Prune<'info> {
+    /// CHECK: Serum DEX program
+    pub dex_program: AccountInfo<'info>,
+    /// The expired OptionMarket for the Serum market
+    /// CHECK: Validated by the Validation middleware
+    pub option_market: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub market: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub market_bids: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub market_asks: AccountInfo<'info>,
+    /// The PsyOptions market authority PDA, signed for by the proxy
+    /// CHECK: Validated by the proxy
+    pub market_authority: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub open_orders: AccountInfo<'info>,
+    /// The owner of the OpenOrders PDA, which does not need to sign
+    /// CHECK: Handled by the proxy
+    pub open_orders_owner: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub event_queue: AccountInfo<'info>,
+}
+
+/// Route a Serum DEX instruction through the proxy. The proxy expects the DEX program as the
+/// first account and the Validation and OpenOrdersPda discriminators before the DEX data.
+fn proxy_ix(mut ix: Instruction, program_id: &Pubkey, dex_program: &Pubkey, discriminator: u8) -> Instruction {
+    ix.program_id = *program_id;
+    // OpenOrdersPda discriminator
+    ix.data.insert(0, discriminator);
+    // Validation discriminator
+    ix.data.insert(0, discriminator);
+    ix.accounts.insert(0, AccountMeta::new_readonly(*dex_program, false));
+    ix
+}
+
+fn invoke_proxy<'a, 'b, 'c, 'info, T: ToAccountMetas + ToAccountInfos<'info>>(
+    ix: &Instruction,
+    ctx: CpiContext<'a, 'b, 'c, 'info, T>
+) -> Result<()> {
+    // The CpiContext's account infos include the remaining accounts and the program
+    solana_program::program::invoke_signed(ix, &ctx.to_account_infos(), ctx.signer_seeds)?;
+    Ok(())
+}
+
+/// Initialize the authority's OpenOrders PDA. `open_orders_bump` and `market_authority_bump` are
+/// the bumps of the OpenOrders and market authority PDAs.
+pub fn init_open_orders<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, InitOpenOrders<'info>>,
+    open_orders_bump: u8,
+    market_authority_bump: u8
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let mut ix = serum_dex::instruction::init_open_orders(
+        accounts.dex_program.key,
+        accounts.open_orders.key,
+        accounts.authority.key,
+        accounts.market.key,
+        Some(accounts.market_authority.key),
+    ).map_err(|_x| errors::ErrorCode::DexIxError)?;
+    // The market authority is signed for by the OpenOrdersPda middleware
+    ix.accounts[4].is_signer = false;
+    // Writable because it must pay for the PDA initialization.
+    ix.accounts[1].is_writable = true;
+    // Prepend to the account list extra accounts needed for PDA initialization.
+    ix.accounts.insert(0, AccountMeta::new_readonly(*accounts.system_program.key, false));
+    ix.accounts.insert(0, AccountMeta::new_readonly(*accounts.dex_program.key, false));
+    // The OpenOrdersPda middleware reads the bumps after its discriminator
+    ix.data.insert(0, market_authority_bump);
+    ix.data.insert(0, open_orders_bump);
+    let ix = proxy_ix(ix, ctx.program.key, accounts.dex_program.key, INIT_OPEN_ORDERS_DISC);
+    invoke_proxy(&ix, ctx)
+}
+
+/// Place an order from the authority's OpenOrders PDA
+pub fn new_order_v3<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, NewOrderV3<'info>>,
+    side: Side,
+    limit_price: NonZeroU64,
+    max_coin_qty: NonZeroU64,
+    max_native_pc_qty_including_fees: NonZeroU64,
+    self_trade_behavior: SelfTradeBehavior,
+    order_type: OrderType,
+    client_order_id: u64,
+    limit: u16
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let mut ix = serum_dex::instruction::new_order(
+        accounts.market.key,
+        accounts.open_orders.key,
+        accounts.request_queue.key,
+        accounts.event_queue.key,
+        accounts.market_bids.key,
+        accounts.market_asks.key,
+        accounts.order_payer_token_account.key,
+        accounts.authority.key,
+        accounts.coin_vault.key,
+        accounts.pc_vault.key,
+        accounts.token_program.key,
+        accounts.rent.key,
+        None,
+        accounts.dex_program.key,
+        side,
+        limit_price,
+        max_coin_qty,
+        order_type,
+        client_order_id,
+        self_trade_behavior,
+        limit,
+        max_native_pc_qty_including_fees,
+    ).map_err(|_x| errors::ErrorCode::DexIxError)?;
+    // Prepend the accounts for the PriceBand middleware
+    ix.accounts.insert(0, AccountMeta::new_readonly(*accounts.price_oracle.key, false));
+    ix.accounts.insert(0, AccountMeta::new_readonly(*accounts.price_band.key, false));
+    let ix = proxy_ix(ix, ctx.program.key, accounts.dex_program.key, NEW_ORDER_V3_DISC);
+    invoke_proxy(&ix, ctx)
+}
+
+/// Cancel an order by its Serum order id
+pub fn cancel_order_v2<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, CancelOrderV2<'info>>,
+    side: Side,
+    order_id: u128
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let ix = serum_dex::instruction::cancel_order(
+        accounts.dex_program.key,
+        accounts.market.key,
+        accounts.market_bids.key,
+        accounts.market_asks.key,
+        accounts.open_orders.key,
+        accounts.authority.key,
+        accounts.event_queue.key,
+        side,
+        order_id,
+    ).map_err(|_x| errors::ErrorCode::DexIxError)?;
+    let ix = proxy_ix(ix, ctx.program.key, accounts.dex_program.key, CANCEL_ORDER_V2_DISC);
+    invoke_proxy(&ix, ctx)
+}
+
+/// Cancel an order by the client order id it was placed with
+pub fn cancel_order_by_client_id_v2<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, CancelOrderV2<'info>>,
+    client_order_id: u64
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let ix = serum_dex::instruction::cancel_order_by_client_order_id(
+        accounts.dex_program.key,
+        accounts.market.key,
+        accounts.market_bids.key,
+        accounts.market_asks.key,
+        accounts.open_orders.key,
+        accounts.authority.key,
+        accounts.event_queue.key,
+        client_order_id,
+    ).map_err(|_x| errors::ErrorCode::DexIxError)?;
+    let ix = proxy_ix(ix, ctx.program.key, accounts.dex_program.key, CANCEL_ORDER_BY_CLIENT_ID_V2_DISC);
+    invoke_proxy(&ix, ctx)
+}
+
+/// Settle the free funds in the authority's OpenOrders PDA. To pay referral fees, pass the
+/// referrer pc wallet, and the `Referrer` that owns it if it is a registered referrer, as the
+/// `CpiContext`'s remaining accounts.
+pub fn settle_funds<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, SettleFunds<'info>>
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let referrer_pc_wallet = ctx.remaining_accounts.get(0).map(|account| account.key);
+    let mut ix = serum_dex::instruction::settle_funds(
+        accounts.dex_program.key,
+        accounts.market.key,
+        accounts.token_program.key,
+        accounts.open_orders.key,
+        accounts.authority.key,
+        accounts.coin_vault.key,
+        accounts.coin_wallet.key,
+        accounts.pc_vault.key,
+        accounts.pc_wallet.key,
+        referrer_pc_wallet,
+        accounts.vault_signer.key,
+    ).map_err(|_x| errors::ErrorCode::DexIxError)?;
+    // The Referrer is read by the Referral middleware and stripped before reaching Serum
+    if let Some(referrer) = ctx.remaining_accounts.get(1) {
+        ix.accounts.push(AccountMeta::new_readonly(*referrer.key, false));
+    }
+    let ix = proxy_ix(ix, ctx.program.key, accounts.dex_program.key, SETTLE_FUNDS_DISC);
+    invoke_proxy(&ix, ctx)
+}
+
+/// Close the authority's OpenOrders PDA, returning the rent to the destination
+pub fn close_open_orders<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, CloseOpenOrders<'info>>
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let ix = serum_dex::instruction::close_open_orders(
+        accounts.dex_program.key,
+        accounts.open_orders.key,
+        accounts.authority.key,
+        accounts.destination.key,
+        accounts.market.key,
+    ).map_err(|_x| errors::ErrorCode::DexIxError)?;
+    let ix = proxy_ix(ix, ctx.program.key, accounts.dex_program.key, CLOSE_OPEN_ORDERS_DISC);
+    invoke_proxy(&ix, ctx)
+}
+
+/// Prune the orders of an OpenOrders account from the Serum market of an expired OptionMarket.
+/// `market_authority_bump` is the bump of the market authority PDA.
+pub fn prune<'a, 'b, 'c, 'info>(
+    ctx: CpiContext<'a, 'b, 'c, 'info, Prune<'info>>,
+    limit: u16,
+    market_authority_bump: u8
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let mut ix = serum_dex::instruction::prune(
+        accounts.dex_program.key,
+        accounts.market.key,
+        accounts.market_bids.key,
+        accounts.market_asks.key,
+        accounts.market_authority.key,
+        accounts.open_orders.key,
+        accounts.open_orders_owner.key,
+        accounts.event_queue.key,
+        limit,
+    ).map_err(|_x| errors::ErrorCode::DexIxError)?;
+    // The market authority is signed for by the Validation middleware
+    ix.accounts[3].is_signer = false;
+    // OpenOrdersPda discriminator
+    ix.data.insert(0, PRUNE_DISC);
+    // The Validation middleware reads the market authority bump after its discriminator and
+    // strips the OptionMarket from the accounts
+    ix.data.insert(0, market_authority_bump);
+    ix.data.insert(0, PRUNE_DISC);
+    ix.accounts.insert(0, AccountMeta::new_readonly(*accounts.option_market.key, false));
+    ix.accounts.insert(0, AccountMeta::new_readonly(*accounts.dex_program.key, false));
+    ix.program_id = *ctx.program.key;
+    invoke_proxy(&ix, ctx)
+}
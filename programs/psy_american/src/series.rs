@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors, SeriesConfig};
+
+/// Seed of the SeriesConfig PDAs, followed by the underlying and quote asset mints
+pub const SERIES_CONFIG_SEED: &[u8] = b"seriesConfig";
+
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+pub const SECONDS_PER_WEEK: i64 = 7 * SECONDS_PER_DAY;
+/// The unix epoch is a Thursday, so weekly expirations on Fridays at 08:00 UTC are offset by
+/// a day and 8 hours
+pub const FRIDAY_0800_UTC_OFFSET: i64 = SECONDS_PER_DAY + 8 * 60 * 60;
+
+/// The OptionMarket address for a series. With a SeriesConfig there is exactly one market per
+/// (pair, expiry, strike), where the strike is the quote amount per contract.
+pub fn market_address(
+    underlying_asset_mint: &Pubkey,
+    quote_asset_mint: &Pubkey,
+    underlying_amount_per_contract: u64,
+    quote_amount_per_contract: u64,
+    expiration_unix_timestamp: i64
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            underlying_asset_mint.as_ref(),
+            quote_asset_mint.as_ref(),
+            &underlying_amount_per_contract.to_le_bytes(),
+            &quote_amount_per_contract.to_le_bytes(),
+            &expiration_unix_timestamp.to_le_bytes(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// Validate the grid of a SeriesConfig can be satisfied
+pub fn validate_config(
+    underlying_amount_per_contract: u64,
+    strike_increment: u64,
+    expiration_interval: i64,
+    expiration_offset: i64
+) -> Result<()> {
+    if underlying_amount_per_contract == 0 || strike_increment == 0 || expiration_interval <= 0 {
+        return Err(errors::ErrorCode::InvalidSeriesConfig.into())
+    }
+    if expiration_offset < 0 || expiration_offset >= expiration_interval {
+        return Err(errors::ErrorCode::InvalidSeriesConfig.into())
+    }
+    Ok(())
+}
+
+impl SeriesConfig {
+    /// True when the expiration falls on the grid, e.g. every Friday at 08:00 UTC
+    pub fn is_allowed_expiration(&self, expiration_unix_timestamp: i64) -> bool {
+        expiration_unix_timestamp.rem_euclid(self.expiration_interval) == self.expiration_offset
+    }
+
+    /// True when the strike, the quote amount per contract, is a multiple of the increment
+    pub fn is_allowed_strike(&self, quote_amount_per_contract: u64) -> bool {
+        quote_amount_per_contract % self.strike_increment == 0
+    }
+
+    /// Validate a new market is on the series grid
+    pub fn validate_market(
+        &self,
+        underlying_amount_per_contract: u64,
+        quote_amount_per_contract: u64,
+        expiration_unix_timestamp: i64
+    ) -> Result<()> {
+        if underlying_amount_per_contract != self.underlying_amount_per_contract {
+            return Err(errors::ErrorCode::SeriesContractSizeNotAllowed.into())
+        }
+        if !self.is_allowed_strike(quote_amount_per_contract) {
+            return Err(errors::ErrorCode::SeriesStrikeNotAllowed.into())
+        }
+        if !self.is_allowed_expiration(expiration_unix_timestamp) {
+            return Err(errors::ErrorCode::SeriesExpirationNotAllowed.into())
+        }
+        Ok(())
+    }
+
+    /// The canonical OptionMarket address for a strike and expiration in this series
+    pub fn market_address(&self, quote_amount_per_contract: u64, expiration_unix_timestamp: i64) -> (Pubkey, u8) {
+        market_address(
+            &self.underlying_asset_mint,
+            &self.quote_asset_mint,
+            self.underlying_amount_per_contract,
+            quote_amount_per_contract,
+            expiration_unix_timestamp,
+        )
+    }
+}
+
+/// Validate a new market against the pair's SeriesConfig. Pairs without one accept any
+/// expiration and strike.
+pub fn validate_market(
+    series_config: &AccountInfo,
+    underlying_amount_per_contract: u64,
+    quote_amount_per_contract: u64,
+    expiration_unix_timestamp: i64
+) -> Result<()> {
+    if series_config.owner != &crate::ID {
+        return Ok(())
+    }
+    let series_config = Account::<SeriesConfig>::try_from(series_config)?;
+    series_config.validate_market(underlying_amount_per_contract, quote_amount_per_contract, expiration_unix_timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2100-01-01 00:00 UTC, a Friday
+    const FRIDAY: i64 = 4_102_444_800;
+
+    fn weekly() -> SeriesConfig {
+        SeriesConfig {
+            underlying_asset_mint: Pubkey::new_unique(),
+            quote_asset_mint: Pubkey::new_unique(),
+            underlying_amount_per_contract: 100,
+            strike_increment: 5,
+            expiration_interval: SECONDS_PER_WEEK,
+            expiration_offset: FRIDAY_0800_UTC_OFFSET,
+            bump_seed: 255,
+        }
+    }
+
+    #[test]
+    fn weekly_expirations_are_fridays_at_0800_utc() {
+        let config = weekly();
+        assert!(config.is_allowed_expiration(FRIDAY + 8 * 60 * 60));
+        assert!(config.is_allowed_expiration(FRIDAY + 8 * 60 * 60 + SECONDS_PER_WEEK));
+        assert!(config.is_allowed_expiration(FRIDAY + 8 * 60 * 60 - 100 * SECONDS_PER_WEEK));
+        assert!(!config.is_allowed_expiration(FRIDAY));
+        assert!(!config.is_allowed_expiration(FRIDAY + 8 * 60 * 60 + SECONDS_PER_DAY));
+    }
+
+    #[test]
+    fn strikes_are_multiples_of_the_increment() {
+        let config = weekly();
+        assert!(config.is_allowed_strike(5));
+        assert!(config.is_allowed_strike(250));
+        assert!(!config.is_allowed_strike(251));
+    }
+
+    #[test]
+    fn markets_off_the_grid_are_rejected() {
+        let config = weekly();
+        let expiration = FRIDAY + 8 * 60 * 60;
+        assert!(config.validate_market(100, 250, expiration).is_ok());
+        assert_eq!(
+            config.validate_market(101, 250, expiration).unwrap_err(),
+            errors::ErrorCode::SeriesContractSizeNotAllowed.into()
+        );
+        assert_eq!(
+            config.validate_market(100, 251, expiration).unwrap_err(),
+            errors::ErrorCode::SeriesStrikeNotAllowed.into()
+        );
+        assert_eq!(
+            config.validate_market(100, 250, expiration + 1).unwrap_err(),
+            errors::ErrorCode::SeriesExpirationNotAllowed.into()
+        );
+    }
+
+    #[test]
+    fn canonical_address_is_the_market_pda() {
+        let config = weekly();
+        let expiration = FRIDAY + 8 * 60 * 60;
+        assert_eq!(
+            config.market_address(250, expiration),
+            market_address(&config.underlying_asset_mint, &config.quote_asset_mint, 100, 250, expiration)
+        );
+        assert_ne!(config.market_address(250, expiration), config.market_address(255, expiration));
+    }
+
+    #[test]
+    fn config_offset_must_be_inside_the_interval() {
+        assert!(validate_config(100, 5, SECONDS_PER_WEEK, FRIDAY_0800_UTC_OFFSET).is_ok());
+        assert!(validate_config(100, 5, SECONDS_PER_WEEK, SECONDS_PER_WEEK).is_err());
+        assert!(validate_config(100, 5, SECONDS_PER_WEEK, -1).is_err());
+        assert!(validate_config(100, 0, SECONDS_PER_WEEK, 0).is_err());
+        assert!(validate_config(0, 5, SECONDS_PER_WEEK, 0).is_err());
+        assert!(validate_config(100, 5, 0, 0).is_err());
+    }
+}
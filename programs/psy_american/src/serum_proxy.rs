@@ -1,8 +1,13 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token;
 use psyfi_serum_dex_permissioned::{Context, MarketMiddleware};
+use psyfi_serum_dex_permissioned::serum_dex::instruction::NewOrderInstructionV3;
 use solana_program::clock::Clock;
 
-use crate::{OptionMarket, errors};
+use crate::{OptionMarket, Referrer, errors, price_band};
+
+/// Index of the optional referrer pc wallet in the Serum SettleFunds accounts
+const REFERRER_PC_WALLET_INDEX: usize = 9;
 
 pub struct Validation {
     pub market_auth_bump: u8,
@@ -55,6 +60,72 @@ impl MarketMiddleware for Validation {
     }
 }
 
+/// Rejects new orders priced outside of the band configured for the Serum market. Only
+/// accounts in the instruction can be read, so every NewOrderV3 is prefixed with the Serum
+/// market's PriceBandConfig PDA and its oracle, and orders without them are rejected. Markets
+/// without a PriceBandConfig are not restricted, and their oracle can be any account.
+pub struct PriceBand;
+impl PriceBand {
+    pub fn new() -> Self {
+        Self
+    }
+}
+impl MarketMiddleware for PriceBand {
+    fn new_order_v3(&self, ctx: &mut Context, ix: &mut NewOrderInstructionV3) -> Result<()> {
+        // With the band accounts prepended the Serum market is the third account. Without
+        // them the first account is not the PDA of the third, which validate_order_price rejects.
+        let (price_band_info, oracle_info, serum_market) = match ctx.accounts.get(0..3) {
+            Some([price_band_info, oracle_info, serum_market]) => (price_band_info.clone(), oracle_info.clone(), *serum_market.key),
+            _ => return Err(errors::ErrorCode::PriceBandDoesNotMatchMarket.into()),
+        };
+        ctx.accounts = (&ctx.accounts[2..]).to_vec();
+        price_band::validate_order_price(
+            &price_band_info,
+            &oracle_info,
+            &serum_market,
+            ix.limit_price.get()
+        )
+    }
+}
+
+/// Replaces the single-key `ReferralFees` middleware. The referrer pc wallet on SettleFunds
+/// must either be owned by the protocol's referral authority, or by a registered `Referrer`
+/// PDA. In the latter case the client appends the `Referrer` account after the wallet,
+/// which is stripped before the instruction is forwarded to the DEX.
+pub struct Referral;
+impl Referral {
+    pub fn new() -> Self {
+        Self
+    }
+}
+impl MarketMiddleware for Referral {
+    fn settle_funds(&self, ctx: &mut Context) -> Result<()> {
+        // The referral wallet is optional on SettleFunds
+        let referral_wallet = match ctx.accounts.get(REFERRER_PC_WALLET_INDEX) {
+            Some(account) => account.clone(),
+            None => return Ok(()),
+        };
+        let referral_authority = token::accessor::authority(&referral_wallet)?;
+        if referral_authority == referral::ID {
+            ctx.accounts.truncate(REFERRER_PC_WALLET_INDEX + 1);
+            return Ok(())
+        }
+
+        // Validate the wallet is owned by the registered Referrer that was passed in
+        let referrer_info = match ctx.accounts.get(REFERRER_PC_WALLET_INDEX + 1) {
+            Some(account) => account.clone(),
+            None => return Err(errors::ErrorCode::InvalidReferrer.into()),
+        };
+        ctx.accounts.truncate(REFERRER_PC_WALLET_INDEX + 1);
+        // Account::try_from validates the Referrer is owned by this program
+        Account::<Referrer>::try_from(&referrer_info)?;
+        if referral_authority != *referrer_info.key {
+            return Err(errors::ErrorCode::InvalidReferrer.into())
+        }
+        Ok(())
+    }
+}
+
 pub mod referral {
     solana_program::declare_id!("6c33US7ErPmLXZog9SyChQUYUrrJY51k4GmzdhrbhNnD");
 }
@@ -0,0 +1,183 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use solana_program::{instruction::{AccountMeta, Instruction}, program::{invoke, invoke_signed}};
+use std::convert::TryInto;
+
+use crate::{errors, YieldVault};
+
+/// Seed of a market's YieldVault PDA, followed by the OptionMarket's key
+pub const YIELD_VAULT_SEED: &[u8] = b"yieldVault";
+/// Seed of the YieldAdapter PDAs, followed by the adapter program's key
+pub const YIELD_ADAPTER_SEED: &[u8] = b"yieldAdapter";
+
+/// Adapter instructions are a tag followed by a little endian u64 amount. The adapter's
+/// accounts are passed through in order, like `amm::swap`. The first must be the YieldVault's
+/// adapter reserve and its adapter vault must be among them, so the collateral can only move
+/// through the accounts bound at `init_yield_vault`.
+///
+/// Deposit `amount` of the underlying from the user's source, minting receipt tokens to the
+/// receipt pool. The user signs.
+pub const DEPOSIT_TAG: u8 = 1;
+/// Redeem receipt tokens from the receipt pool for exactly `amount` of the underlying to a
+/// destination. The YieldVault signs.
+pub const WITHDRAW_TAG: u8 = 2;
+/// Redeem `amount` receipt tokens from the receipt pool for the underlying they are worth to a
+/// destination. The YieldVault signs.
+pub const REDEEM_TAG: u8 = 3;
+
+/// Build an adapter instruction. `signer` is marked as a signer wherever it is passed, so the
+/// YieldVault PDA can sign withdrawals.
+pub fn adapter_instruction(
+    adapter_program: &Pubkey,
+    accounts: &[AccountInfo],
+    tag: u8,
+    amount: u64,
+    signer: Option<&Pubkey>
+) -> Instruction {
+    let mut data = vec![tag];
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: *adapter_program,
+        accounts: accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: *account.key,
+                is_signer: account.is_signer || Some(account.key) == signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data,
+    }
+}
+
+/// Validate the adapter's accounts are the ones bound to the YieldVault
+pub fn validate_adapter_accounts(yield_vault: &YieldVault, accounts: &[AccountInfo]) -> Result<()> {
+    let reserve_matches = accounts.first().map_or(false, |reserve| reserve.key == &yield_vault.adapter_reserve);
+    if !reserve_matches || !accounts.iter().any(|account| account.key == &yield_vault.adapter_vault) {
+        return Err(errors::ErrorCode::YieldAdapterAccountsDoNotMatchVault.into())
+    }
+    Ok(())
+}
+
+/// Deposit `amount` of the underlying through the adapter. Callers must check the receipt
+/// tokens it minted.
+pub fn deposit<'info>(
+    adapter_program: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    amount: u64,
+    yield_vault: &YieldVault
+) -> Result<()> {
+    validate_adapter_accounts(yield_vault, accounts)?;
+    let ix = adapter_instruction(adapter_program.key, accounts, DEPOSIT_TAG, amount, None);
+    let mut account_infos = accounts.to_vec();
+    account_infos.push(adapter_program.clone());
+    invoke(&ix, &account_infos)?;
+    Ok(())
+}
+
+/// Withdraw or redeem from the receipt pool through the adapter, signed by the YieldVault.
+/// Callers must check the balances it moved.
+pub fn redeem<'info>(
+    adapter_program: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    tag: u8,
+    amount: u64,
+    yield_vault: &Account<'info, YieldVault>
+) -> Result<()> {
+    validate_adapter_accounts(yield_vault, accounts)?;
+    let yield_vault_key = yield_vault.key();
+    let ix = adapter_instruction(adapter_program.key, accounts, tag, amount, Some(&yield_vault_key));
+    let mut account_infos = accounts.to_vec();
+    account_infos.push(adapter_program.clone());
+    let seeds: &[&[u8]] = &[YIELD_VAULT_SEED, yield_vault.option_market.as_ref(), &[yield_vault.bump_seed]];
+    invoke_signed(&ix, &account_infos, &[seeds])?;
+    Ok(())
+}
+
+/// Redeem the receipt share of `size` options to the destination in the adapter's accounts
+/// and remove them from the vault's collateral
+pub fn redeem_share<'info>(
+    yield_vault: &mut Account<'info, YieldVault>,
+    receipt_pool: &mut Account<'info, TokenAccount>,
+    adapter_program: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    size: u64
+) -> Result<()> {
+    let receipts_before = receipt_pool.amount;
+    let receipts = yield_vault.receipt_share(receipts_before, size)?;
+    if receipts > 0 {
+        redeem(adapter_program, accounts, REDEEM_TAG, receipts, yield_vault)?;
+    }
+    // Validate the adapter redeemed no more than the position's share
+    receipt_pool.reload()?;
+    if receipts_before.saturating_sub(receipt_pool.amount) > receipts {
+        return Err(errors::ErrorCode::YieldAdapterOverdrew.into())
+    }
+    yield_vault.collateral_size = yield_vault.collateral_size.checked_sub(size).ok_or(errors::ErrorCode::NumberOverflow)?;
+    Ok(())
+}
+
+impl YieldVault {
+    /// The receipt tokens backing `size` of the collateralized options, rounded down. A
+    /// closed position receives this share of the principal and the accrued yield.
+    pub fn receipt_share(&self, receipt_pool_amount: u64, size: u64) -> Result<u64> {
+        if size > self.collateral_size {
+            return Err(errors::ErrorCode::UnderlyingPoolDoesNotCoverOptions.into())
+        }
+        let share = receipt_pool_amount as u128 * size as u128 / self.collateral_size as u128;
+        share.try_into().map_err(|_| errors::ErrorCode::NumberOverflow.into())
+    }
+
+    /// The receipt tokens a deposit for `size` must mint so the outstanding options keep their
+    /// share of the accrued yield, rounded up
+    pub fn receipts_required(&self, receipt_pool_amount: u64, size: u64) -> Result<u64> {
+        if self.collateral_size == 0 {
+            return Ok(0)
+        }
+        let collateral_size = self.collateral_size as u128;
+        let required = (receipt_pool_amount as u128 * size as u128 + collateral_size - 1) / collateral_size;
+        required.try_into().map_err(|_| errors::ErrorCode::NumberOverflow.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault(collateral_size: u64) -> YieldVault {
+        YieldVault { collateral_size, ..YieldVault::default() }
+    }
+
+    #[test]
+    fn closing_every_option_takes_the_whole_pool() {
+        let vault = vault(3);
+        assert_eq!(vault.receipt_share(1_000, 1).unwrap(), 333);
+        assert_eq!(vault.receipt_share(1_000, 3).unwrap(), 1_000);
+        assert!(vault.receipt_share(1_000, 4).is_err());
+    }
+
+    #[test]
+    fn deposits_buy_in_at_the_accrued_share() {
+        assert_eq!(vault(0).receipts_required(50, 10).unwrap(), 0);
+        let vault = vault(3);
+        assert_eq!(vault.receipts_required(1_000, 1).unwrap(), 334);
+        assert_eq!(vault.receipts_required(900, 3).unwrap(), 900);
+    }
+
+    #[test]
+    fn the_vault_signs_only_its_own_account() {
+        let adapter = Pubkey::new_unique();
+        let vault_key = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let (mut vault_lamports, mut other_lamports) = (0, 0);
+        let (mut vault_data, mut other_data) = (vec![], vec![]);
+        let accounts = [
+            AccountInfo::new(&vault_key, false, false, &mut vault_lamports, &mut vault_data, &adapter, false, 0),
+            AccountInfo::new(&other, false, true, &mut other_lamports, &mut other_data, &adapter, false, 0),
+        ];
+        let ix = adapter_instruction(&adapter, &accounts, WITHDRAW_TAG, 7, Some(&vault_key));
+        assert!(ix.accounts[0].is_signer);
+        assert!(!ix.accounts[1].is_signer && ix.accounts[1].is_writable);
+        assert_eq!(ix.data, [vec![WITHDRAW_TAG], 7u64.to_le_bytes().to_vec()].concat());
+    }
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use common::*;
+use psy_american::errors::ErrorCode;
+use solana_program::instruction::AccountMeta;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signer};
+
+const WRITTEN: u64 = 10;
+const OPTIONS: u64 = 4;
+/// The near expiration, the far one is EXPIRATION
+const NEAR_EXPIRATION: i64 = EXPIRATION - 86_400;
+
+struct Setup {
+    context: ProgramTestContext,
+    /// The near expiration market and the user's options on it, then the far one
+    markets: Vec<(MarketFixture, Pubkey)>,
+    user: UserFixture,
+}
+
+async fn setup(quote: u64) -> Setup {
+    let mut program_test = program_test();
+    let far = MarketFixture::default();
+    let near = MarketFixture::with_mints(
+        far.market.underlying_asset_mint,
+        far.market.quote_asset_mint,
+        UNDERLYING_AMOUNT_PER_CONTRACT,
+        2 * QUOTE_AMOUNT_PER_CONTRACT,
+        NEAR_EXPIRATION,
+    );
+    far.add_to(&mut program_test, WRITTEN, 0);
+    near.add_to(&mut program_test, WRITTEN, 0);
+    let user = UserFixture::add_to(&mut program_test, &far, OPTIONS, 0, 0, quote);
+    let near_options = Pubkey::new_unique();
+    add_token_account(&mut program_test, &near_options, &near.market.option_mint, &user.authority.pubkey(), OPTIONS);
+    let far_options = user.option_account;
+    Setup {
+        context: program_test.start_with_context().await,
+        markets: vec![(near, near_options), (far, far_options)],
+        user,
+    }
+}
+
+fn batch_exercise_ix(setup: &Setup, sizes: Vec<u64>, all_or_nothing: bool) -> Instruction {
+    let mut ix = instruction(
+        psy_american::accounts::BatchExercise {
+            user_authority: setup.user.authority.pubkey(),
+            option_authority: setup.user.authority.pubkey(),
+            quote_asset_src: setup.user.quote_account,
+            underlying_asset_dest: setup.user.underlying_account,
+            token_program: spl_token::ID,
+        },
+        psy_american::instruction::BatchExercise { sizes, all_or_nothing },
+    );
+    for (fixture, option_account) in &setup.markets {
+        ix.accounts.extend(vec![
+            AccountMeta::new_readonly(fixture.key, false),
+            AccountMeta::new(fixture.market.option_mint, false),
+            AccountMeta::new(*option_account, false),
+            AccountMeta::new(fixture.market.underlying_asset_pool, false),
+            AccountMeta::new(fixture.market.quote_asset_pool, false),
+        ]);
+    }
+    ix
+}
+
+/// Quote for `near` options on the near market and `far` on the far one
+fn quote_for(near: u64, far: u64) -> u64 {
+    near * 2 * QUOTE_AMOUNT_PER_CONTRACT + far * QUOTE_AMOUNT_PER_CONTRACT
+}
+
+#[tokio::test]
+async fn batch_exercise_exercises_every_market() {
+    let mut setup = setup(quote_for(OPTIONS, 3)).await;
+    let ix = batch_exercise_ix(&setup, vec![OPTIONS, 3], true);
+    process(&mut setup.context, ix, &[&setup.user.authority]).await.unwrap();
+
+    let context = &mut setup.context;
+    let (near, near_options) = &setup.markets[0];
+    let (far, far_options) = &setup.markets[1];
+    assert_eq!(token_balance(context, near_options).await, 0);
+    assert_eq!(token_balance(context, far_options).await, OPTIONS - 3);
+    assert_eq!(token_balance(context, &near.market.quote_asset_pool).await, quote_for(OPTIONS, 0));
+    assert_eq!(token_balance(context, &far.market.quote_asset_pool).await, quote_for(0, 3));
+    assert_eq!(token_balance(context, &setup.user.quote_account).await, 0);
+    assert_eq!(
+        token_balance(context, &setup.user.underlying_account).await,
+        (OPTIONS + 3) * UNDERLYING_AMOUNT_PER_CONTRACT
+    );
+}
+
+#[tokio::test]
+async fn batch_exercise_all_or_nothing_fails_on_expired_market() {
+    let mut setup = setup(quote_for(OPTIONS, OPTIONS)).await;
+    warp_past(&mut setup.context, NEAR_EXPIRATION).await;
+    let ix = batch_exercise_ix(&setup, vec![OPTIONS, OPTIONS], true);
+    assert_error(
+        process(&mut setup.context, ix, &[&setup.user.authority]).await,
+        ErrorCode::OptionMarketExpiredCantExercise,
+    );
+}
+
+#[tokio::test]
+async fn batch_exercise_skips_expired_market() {
+    let mut setup = setup(quote_for(OPTIONS, OPTIONS)).await;
+    warp_past(&mut setup.context, NEAR_EXPIRATION).await;
+    let ix = batch_exercise_ix(&setup, vec![OPTIONS, OPTIONS], false);
+    process(&mut setup.context, ix, &[&setup.user.authority]).await.unwrap();
+
+    let near_options = setup.markets[0].1;
+    let far_options = setup.markets[1].1;
+    assert_eq!(token_balance(&mut setup.context, &near_options).await, OPTIONS);
+    assert_eq!(token_balance(&mut setup.context, &far_options).await, 0);
+    assert_eq!(token_balance(&mut setup.context, &setup.user.quote_account).await, quote_for(OPTIONS, 0));
+}
+
+#[tokio::test]
+async fn batch_exercise_skips_entries_it_cannot_pay_for() {
+    // Enough quote for the near market only
+    let mut setup = setup(quote_for(OPTIONS, 0)).await;
+    let ix = batch_exercise_ix(&setup, vec![OPTIONS, 1], true);
+    assert_error(process(&mut setup.context, ix, &[&setup.user.authority]).await, ErrorCode::NotEnoughQuoteToExercise);
+
+    let ix = batch_exercise_ix(&setup, vec![OPTIONS, 1], false);
+    process(&mut setup.context, ix, &[&setup.user.authority]).await.unwrap();
+    let far_options = setup.markets[1].1;
+    assert_eq!(token_balance(&mut setup.context, &far_options).await, OPTIONS);
+    assert_eq!(token_balance(&mut setup.context, &setup.user.underlying_account).await, OPTIONS * UNDERLYING_AMOUNT_PER_CONTRACT);
+}
+
+#[tokio::test]
+async fn batch_exercise_validates_each_entry() {
+    let mut setup = setup(quote_for(OPTIONS, OPTIONS)).await;
+    // Offsets into the second entry, past the five BatchExercise accounts
+    let second_entry = 5 + psy_american::BATCH_EXERCISE_ACCOUNTS;
+    let near = &setup.markets[0].0.market;
+    let cases = vec![
+        (1, near.option_mint, ErrorCode::OptionTokenMintDoesNotMatchMarket),
+        (3, near.underlying_asset_pool, ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket),
+        (4, near.quote_asset_pool, ErrorCode::QuotePoolAccountDoesNotMatchMarket),
+    ];
+    for (offset, substitute, error) in cases {
+        let mut ix = batch_exercise_ix(&setup, vec![1, 1], true);
+        ix.accounts[second_entry + offset].pubkey = substitute;
+        assert_error(process(&mut setup.context, ix, &[&setup.user.authority]).await, error);
+    }
+
+    let ix = batch_exercise_ix(&setup, vec![1, OPTIONS + 1], true);
+    assert_error(process(&mut setup.context, ix, &[&setup.user.authority]).await, ErrorCode::NotEnoughOptionsToExercise);
+
+    let ix = batch_exercise_ix(&setup, vec![1, 0], true);
+    assert_error(process(&mut setup.context, ix, &[&setup.user.authority]).await, ErrorCode::SizeCantBeLessThanEqZero);
+
+    let ix = batch_exercise_ix(&setup, vec![1], true);
+    assert_error(process(&mut setup.context, ix, &[&setup.user.authority]).await, ErrorCode::BatchAccountsDoNotMatchEntries);
+
+    // The shared destination must hold the underlying asset
+    let mut ix = batch_exercise_ix(&setup, vec![1, 1], true);
+    ix.accounts[3].pubkey = setup.user.quote_account;
+    assert_error(
+        process(&mut setup.context, ix, &[&setup.user.authority]).await,
+        ErrorCode::UnderlyingDestMintDoesNotMatchUnderlyingAsset,
+    );
+}
+
+/// An exercise of `size` far options twice, from the same option account
+fn repeated_far_ix(setup: &Setup, size: u64, all_or_nothing: bool) -> Instruction {
+    let mut ix = batch_exercise_ix(setup, vec![size, size], all_or_nothing);
+    // Replace the near entry, past the five BatchExercise accounts, with the far one
+    let entries = ix.accounts.split_off(5);
+    let far = &entries[psy_american::BATCH_EXERCISE_ACCOUNTS..];
+    ix.accounts.extend(far.iter().chain(far.iter()).cloned());
+    ix
+}
+
+#[tokio::test]
+async fn batch_exercise_counts_repeated_option_sources() {
+    let mut setup = setup(quote_for(0, 2 * 3)).await;
+    let ix = repeated_far_ix(&setup, 3, true);
+    assert_error(process(&mut setup.context, ix, &[&setup.user.authority]).await, ErrorCode::NotEnoughOptionsToExercise);
+
+    let ix = repeated_far_ix(&setup, 3, false);
+    process(&mut setup.context, ix, &[&setup.user.authority]).await.unwrap();
+    let far_options = setup.markets[1].1;
+    assert_eq!(token_balance(&mut setup.context, &far_options).await, OPTIONS - 3);
+    assert_eq!(token_balance(&mut setup.context, &setup.user.quote_account).await, quote_for(0, 3));
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use common::*;
+use psy_american::{errors::ErrorCode, PauseFlags};
+use solana_program::instruction::AccountMeta;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signer};
+
+/// The user's destination accounts for a market
+struct Destinations {
+    option_account: Pubkey,
+    writer_token_account: Pubkey,
+}
+
+struct Setup {
+    context: ProgramTestContext,
+    markets: Vec<(MarketFixture, Destinations)>,
+    user: UserFixture,
+}
+
+/// Two strikes on the same underlying and quote
+async fn setup(underlying: u64) -> Setup {
+    let mut program_test = program_test();
+    add_pause_config(&mut program_test, PauseFlags::default());
+    let low_strike = MarketFixture::default();
+    let high_strike = MarketFixture::with_mints(
+        low_strike.market.underlying_asset_mint,
+        low_strike.market.quote_asset_mint,
+        UNDERLYING_AMOUNT_PER_CONTRACT,
+        2 * QUOTE_AMOUNT_PER_CONTRACT,
+        EXPIRATION,
+    );
+    low_strike.add_to(&mut program_test, 0, 0);
+    high_strike.add_to(&mut program_test, 0, 0);
+    let user = UserFixture::add_to(&mut program_test, &low_strike, 0, 0, underlying, 0);
+    let high_strike_destinations = Destinations {
+        option_account: Pubkey::new_unique(),
+        writer_token_account: Pubkey::new_unique(),
+    };
+    let owner = user.authority.pubkey();
+    add_token_account(&mut program_test, &high_strike_destinations.option_account, &high_strike.market.option_mint, &owner, 0);
+    add_token_account(&mut program_test, &high_strike_destinations.writer_token_account, &high_strike.market.writer_token_mint, &owner, 0);
+    let low_strike_destinations = Destinations {
+        option_account: user.option_account,
+        writer_token_account: user.writer_token_account,
+    };
+    Setup {
+        context: program_test.start_with_context().await,
+        markets: vec![(low_strike, low_strike_destinations), (high_strike, high_strike_destinations)],
+        user,
+    }
+}
+
+fn entry_accounts(fixture: &MarketFixture, destinations: &Destinations, underlying_asset_src: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(fixture.key, false),
+        AccountMeta::new(fixture.market.option_mint, false),
+        AccountMeta::new(destinations.option_account, false),
+        AccountMeta::new(fixture.market.writer_token_mint, false),
+        AccountMeta::new(destinations.writer_token_account, false),
+        AccountMeta::new(fixture.market.underlying_asset_pool, false),
+        AccountMeta::new(*underlying_asset_src, false),
+    ]
+}
+
+fn batch_mint_ix(setup: &Setup, sizes: Vec<u64>) -> Instruction {
+    let mut ix = instruction(
+        psy_american::accounts::BatchMint {
+            user_authority: setup.user.authority.pubkey(),
+            token_program: spl_token::ID,
+            pause_config: pause_config_key(),
+        },
+        psy_american::instruction::BatchMint { sizes },
+    );
+    for (fixture, destinations) in &setup.markets {
+        ix.accounts.extend(entry_accounts(fixture, destinations, &setup.user.underlying_account));
+    }
+    ix
+}
+
+#[tokio::test]
+async fn batch_mint_mints_every_market() {
+    let mut setup = setup(5 * UNDERLYING_AMOUNT_PER_CONTRACT).await;
+    let ix = batch_mint_ix(&setup, vec![2, 3]);
+    process(&mut setup.context, ix, &[&setup.user.authority]).await.unwrap();
+
+    let context = &mut setup.context;
+    for ((fixture, destinations), size) in setup.markets.iter().zip([2, 3]) {
+        assert_eq!(token_balance(context, &destinations.option_account).await, size);
+        assert_eq!(token_balance(context, &destinations.writer_token_account).await, size);
+        assert_eq!(mint_supply(context, &fixture.market.option_mint).await, size);
+        assert_eq!(
+            token_balance(context, &fixture.market.underlying_asset_pool).await,
+            size * UNDERLYING_AMOUNT_PER_CONTRACT
+        );
+    }
+    assert_eq!(token_balance(context, &setup.user.underlying_account).await, 0);
+}
+
+#[tokio::test]
+async fn batch_mint_merges_repeated_markets() {
+    let mut setup = setup(5 * UNDERLYING_AMOUNT_PER_CONTRACT).await;
+    let (fixture, destinations) = &setup.markets[0];
+    let mut ix = instruction(
+        psy_american::accounts::BatchMint {
+            user_authority: setup.user.authority.pubkey(),
+            token_program: spl_token::ID,
+            pause_config: pause_config_key(),
+        },
+        psy_american::instruction::BatchMint { sizes: vec![1, 4] },
+    );
+    ix.accounts.extend(entry_accounts(fixture, destinations, &setup.user.underlying_account));
+    ix.accounts.extend(entry_accounts(fixture, destinations, &setup.user.underlying_account));
+    let option_account = destinations.option_account;
+    let pool = fixture.market.underlying_asset_pool;
+    process(&mut setup.context, ix, &[&setup.user.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut setup.context, &option_account).await, 5);
+    assert_eq!(token_balance(&mut setup.context, &pool).await, 5 * UNDERLYING_AMOUNT_PER_CONTRACT);
+}
+
+#[tokio::test]
+async fn batch_mint_is_atomic() {
+    // Only enough underlying for the first entry
+    let mut setup = setup(2 * UNDERLYING_AMOUNT_PER_CONTRACT).await;
+    let ix = batch_mint_ix(&setup, vec![2, 3]);
+    assert!(process(&mut setup.context, ix, &[&setup.user.authority]).await.is_err());
+
+    let option_account = setup.markets[0].1.option_account;
+    assert_eq!(token_balance(&mut setup.context, &option_account).await, 0);
+    assert_eq!(token_balance(&mut setup.context, &setup.user.underlying_account).await, 2 * UNDERLYING_AMOUNT_PER_CONTRACT);
+}
+
+#[tokio::test]
+async fn batch_mint_requires_accounts_for_every_entry() {
+    let mut setup = setup(5 * UNDERLYING_AMOUNT_PER_CONTRACT).await;
+
+    let ix = batch_mint_ix(&setup, vec![2]);
+    assert_error(process(&mut setup.context, ix, &[&setup.user.authority]).await, ErrorCode::BatchAccountsDoNotMatchEntries);
+
+    let ix = batch_mint_ix(&setup, vec![]);
+    assert_error(process(&mut setup.context, ix, &[&setup.user.authority]).await, ErrorCode::BatchAccountsDoNotMatchEntries);
+}
+
+#[tokio::test]
+async fn batch_mint_validates_each_entry() {
+    let mut setup = setup(5 * UNDERLYING_AMOUNT_PER_CONTRACT).await;
+    let second_entry = psy_american::BATCH_MINT_ACCOUNTS;
+    let high_strike = &setup.markets[1].0.market;
+    let low_strike = &setup.markets[0].0.market;
+    let cases = vec![
+        (5, low_strike.underlying_asset_pool, ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket),
+        (1, high_strike.writer_token_mint, ErrorCode::OptionTokenMintDoesNotMatchMarket),
+        (3, high_strike.option_mint, ErrorCode::WriterTokenMintDoesNotMatchMarket),
+    ];
+    for (offset, substitute, error) in cases {
+        let mut ix = batch_mint_ix(&setup, vec![2, 3]);
+        // Offset past the BatchMint accounts into the second entry
+        ix.accounts[2 + second_entry + offset].pubkey = substitute;
+        assert_error(process(&mut setup.context, ix, &[&setup.user.authority]).await, error);
+    }
+
+    let ix = batch_mint_ix(&setup, vec![2, 0]);
+    assert_error(process(&mut setup.context, ix, &[&setup.user.authority]).await, ErrorCode::SizeCantBeLessThanEqZero);
+
+    warp_past(&mut setup.context, EXPIRATION).await;
+    let ix = batch_mint_ix(&setup, vec![2, 3]);
+    assert_error(process(&mut setup.context, ix, &[&setup.user.authority]).await, ErrorCode::OptionMarketExpiredCantMint);
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::*;
+use psy_american::errors::ErrorCode;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::Signer;
+
+const WRITTEN: u64 = 10;
+const EXERCISED: u64 = 3;
+const WRITER_TOKENS: u64 = 5;
+
+async fn setup() -> (ProgramTestContext, MarketFixture, UserFixture) {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, WRITTEN, EXERCISED);
+    let user = UserFixture::add_to(&mut program_test, &fixture, 0, WRITER_TOKENS, 0, 0);
+    (program_test.start_with_context().await, fixture, user)
+}
+
+fn burn_accounts(fixture: &MarketFixture, user: &UserFixture) -> psy_american::accounts::BurnWriterForQuote {
+    psy_american::accounts::BurnWriterForQuote {
+        user_authority: user.authority.pubkey(),
+        option_market: fixture.key,
+        writer_token_mint: fixture.market.writer_token_mint,
+        writer_token_src: user.writer_token_account,
+        quote_asset_pool: fixture.market.quote_asset_pool,
+        writer_quote_dest: user.quote_account,
+        token_program: spl_token::ID,
+    }
+}
+
+#[tokio::test]
+async fn burn_writer_for_quote_pays_out_exercised_quote() {
+    let (mut context, fixture, user) = setup().await;
+    let ix = instruction(burn_accounts(&fixture, &user), psy_american::instruction::BurnWriterForQuote { size: EXERCISED });
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &user.writer_token_account).await, WRITER_TOKENS - EXERCISED);
+    assert_eq!(mint_supply(&mut context, &fixture.market.writer_token_mint).await, WRITTEN - EXERCISED);
+    assert_eq!(token_balance(&mut context, &user.quote_account).await, EXERCISED * QUOTE_AMOUNT_PER_CONTRACT);
+    assert_eq!(token_balance(&mut context, &fixture.market.quote_asset_pool).await, 0);
+}
+
+#[tokio::test]
+async fn burn_writer_for_quote_requires_quote_in_pool() {
+    let (mut context, fixture, user) = setup().await;
+    let ix = instruction(burn_accounts(&fixture, &user), psy_american::instruction::BurnWriterForQuote { size: EXERCISED + 1 });
+    assert_error(process(&mut context, ix, &[&user.authority]).await, ErrorCode::NotEnoughQuoteAssetsInPool);
+}
+
+#[tokio::test]
+async fn burn_writer_for_quote_validates_market_accounts() {
+    let (mut context, fixture, user) = setup().await;
+    let cases: Vec<(Box<dyn Fn(&mut psy_american::accounts::BurnWriterForQuote)>, ErrorCode)> = vec![
+        (Box::new(|a| a.quote_asset_pool = user.quote_account), ErrorCode::QuotePoolAccountDoesNotMatchMarket),
+        (Box::new(|a| a.writer_token_mint = fixture.market.option_mint), ErrorCode::WriterTokenMintDoesNotMatchMarket),
+    ];
+    for (modify, error) in cases {
+        let mut accounts = burn_accounts(&fixture, &user);
+        modify(&mut accounts);
+        let ix = instruction(accounts, psy_american::instruction::BurnWriterForQuote { size: 1 });
+        assert_error(process(&mut context, ix, &[&user.authority]).await, error);
+    }
+}
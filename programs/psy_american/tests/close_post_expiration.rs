@@ -0,0 +1,75 @@
+mod common;
+
+use common::*;
+use psy_american::errors::ErrorCode;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::Signer;
+
+const WRITTEN: u64 = 10;
+const WRITER_TOKENS: u64 = 6;
+
+async fn setup() -> (ProgramTestContext, MarketFixture, UserFixture) {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, WRITTEN, 0);
+    let user = UserFixture::add_to(&mut program_test, &fixture, 0, WRITER_TOKENS, 0, 0);
+    (program_test.start_with_context().await, fixture, user)
+}
+
+fn close_accounts(fixture: &MarketFixture, user: &UserFixture) -> psy_american::accounts::ClosePostExp {
+    psy_american::accounts::ClosePostExp {
+        user_authority: user.authority.pubkey(),
+        option_market: fixture.key,
+        writer_token_mint: fixture.market.writer_token_mint,
+        writer_token_src: user.writer_token_account,
+        underlying_asset_pool: fixture.market.underlying_asset_pool,
+        underlying_asset_dest: user.underlying_account,
+        token_program: spl_token::ID,
+        clock: clock_sysvar(),
+    }
+}
+
+#[tokio::test]
+async fn close_post_expiration_returns_underlying() {
+    let (mut context, fixture, user) = setup().await;
+    warp_past(&mut context, EXPIRATION).await;
+    let ix = instruction(close_accounts(&fixture, &user), psy_american::instruction::ClosePostExpiration { size: WRITER_TOKENS });
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &user.writer_token_account).await, 0);
+    assert_eq!(mint_supply(&mut context, &fixture.market.writer_token_mint).await, WRITTEN - WRITER_TOKENS);
+    assert_eq!(token_balance(&mut context, &user.underlying_account).await, WRITER_TOKENS * UNDERLYING_AMOUNT_PER_CONTRACT);
+    assert_eq!(
+        token_balance(&mut context, &fixture.market.underlying_asset_pool).await,
+        (WRITTEN - WRITER_TOKENS) * UNDERLYING_AMOUNT_PER_CONTRACT
+    );
+}
+
+#[tokio::test]
+async fn close_post_expiration_fails_before_expiration() {
+    let (mut context, fixture, user) = setup().await;
+    let ix = instruction(close_accounts(&fixture, &user), psy_american::instruction::ClosePostExpiration { size: 1 });
+    assert_error(process(&mut context, ix, &[&user.authority]).await, ErrorCode::OptionMarketNotExpiredCantClose);
+
+    // The market is still open at exactly the expiration timestamp
+    warp_before(&mut context, EXPIRATION + 1).await;
+    let ix = instruction(close_accounts(&fixture, &user), psy_american::instruction::ClosePostExpiration { size: 2 });
+    assert_error(process(&mut context, ix, &[&user.authority]).await, ErrorCode::OptionMarketNotExpiredCantClose);
+}
+
+#[tokio::test]
+async fn close_post_expiration_validates_market_accounts() {
+    let (mut context, fixture, user) = setup().await;
+    warp_past(&mut context, EXPIRATION).await;
+    let cases: Vec<(Box<dyn Fn(&mut psy_american::accounts::ClosePostExp)>, ErrorCode)> = vec![
+        (Box::new(|a| a.underlying_asset_pool = user.underlying_account), ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket),
+        (Box::new(|a| a.writer_token_mint = fixture.market.option_mint), ErrorCode::WriterTokenMintDoesNotMatchMarket),
+        (Box::new(|a| a.underlying_asset_dest = user.quote_account), ErrorCode::UnderlyingDestMintDoesNotMatchUnderlyingAsset),
+    ];
+    for (modify, error) in cases {
+        let mut accounts = close_accounts(&fixture, &user);
+        modify(&mut accounts);
+        let ix = instruction(accounts, psy_american::instruction::ClosePostExpiration { size: 1 });
+        assert_error(process(&mut context, ix, &[&user.authority]).await, error);
+    }
+}
@@ -0,0 +1,428 @@
+//! Shared setup for the psy_american integration tests. `initialize_market` and minting are
+//! paused until the fee owner creates a PauseConfig, so markets and their outstanding positions
+//! are written directly into the test bank instead of being created through the program.
+#![allow(dead_code)]
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::dex;
+use psy_american::{
+    errors::ErrorCode, fees, governance, migration, option_chain, pause, yield_vault, Governance, OptionChain,
+    OptionChainEntry, OptionMarket, PauseConfig, PauseFlags,
+};
+use solana_program::{program_option::COption, program_pack::Pack, sysvar};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    bpf_loader,
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::{Transaction, TransactionError},
+    transport::TransportError,
+};
+use std::path::{Path, PathBuf};
+
+pub const UNDERLYING_AMOUNT_PER_CONTRACT: u64 = 10_000_000;
+pub const QUOTE_AMOUNT_PER_CONTRACT: u64 = 50_000_000;
+/// 2100-01-01, so fixture markets start unexpired until the clock is warped
+pub const EXPIRATION: i64 = 4_102_444_800;
+
+/// The Serum DEX `.so` the TypeScript tests use. It is found from the crate upwards so the
+/// fuzz crate, which includes these fixtures, loads the same file.
+fn dex_program_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .ancestors()
+        .map(|dir| dir.join("tests/deps/serum_dex.so"))
+        .find(|path| path.exists())
+        .expect("tests/deps/serum_dex.so is missing")
+}
+
+/// Load psy_american natively and the Serum DEX
+pub fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "psy_american",
+        psy_american::ID,
+        processor!(psy_american::entry),
+    );
+    let dex_program = std::fs::read(dex_program_path()).expect("tests/deps/serum_dex.so is unreadable");
+    program_test.add_account(dex::ID, Account {
+        lamports: Rent::default().minimum_balance(dex_program.len()),
+        data: dex_program,
+        owner: bpf_loader::id(),
+        executable: true,
+        rent_epoch: 0,
+    });
+    program_test
+}
+
+pub fn add_mint(program_test: &mut ProgramTest, mint: &Pubkey, authority: &Pubkey, supply: u64) {
+    add_mint_with_decimals(program_test, mint, authority, supply, 0);
+}
+
+pub fn add_mint_with_decimals(program_test: &mut ProgramTest, mint: &Pubkey, authority: &Pubkey, supply: u64, decimals: u8) {
+    program_test.add_packable_account(
+        *mint,
+        Rent::default().minimum_balance(spl_token::state::Mint::LEN),
+        &spl_token::state::Mint {
+            mint_authority: COption::Some(*authority),
+            supply,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        },
+        &spl_token::ID,
+    );
+}
+
+pub fn add_token_account(program_test: &mut ProgramTest, account: &Pubkey, mint: &Pubkey, owner: &Pubkey, amount: u64) {
+    program_test.add_packable_account(
+        *account,
+        Rent::default().minimum_balance(spl_token::state::Account::LEN),
+        &spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            delegate: COption::None,
+            state: spl_token::state::AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        },
+        &spl_token::ID,
+    );
+}
+
+/// An OptionMarket written directly into the bank, along with its mints and pools
+pub struct MarketFixture {
+    pub key: Pubkey,
+    pub market: OptionMarket,
+}
+
+impl MarketFixture {
+    pub fn new(underlying_amount_per_contract: u64, quote_amount_per_contract: u64, expiration_unix_timestamp: i64) -> Self {
+        Self::with_mints(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            underlying_amount_per_contract,
+            quote_amount_per_contract,
+            expiration_unix_timestamp,
+        )
+    }
+
+    /// A market on existing assets, for chains of markets that share their underlying and quote
+    pub fn with_mints(
+        underlying_asset_mint: Pubkey,
+        quote_asset_mint: Pubkey,
+        underlying_amount_per_contract: u64,
+        quote_amount_per_contract: u64,
+        expiration_unix_timestamp: i64
+    ) -> Self {
+        let (key, bump_seed) = Pubkey::find_program_address(
+            &[
+                underlying_asset_mint.as_ref(),
+                quote_asset_mint.as_ref(),
+                &underlying_amount_per_contract.to_le_bytes(),
+                &quote_amount_per_contract.to_le_bytes(),
+                &expiration_unix_timestamp.to_le_bytes(),
+            ],
+            &psy_american::ID,
+        );
+        let market = OptionMarket {
+            option_mint: Pubkey::new_unique(),
+            writer_token_mint: Pubkey::new_unique(),
+            underlying_asset_mint,
+            quote_asset_mint,
+            underlying_amount_per_contract,
+            quote_amount_per_contract,
+            expiration_unix_timestamp,
+            underlying_asset_pool: Pubkey::new_unique(),
+            quote_asset_pool: Pubkey::new_unique(),
+            mint_fee_account: Pubkey::new_unique(),
+            exercise_fee_account: Pubkey::new_unique(),
+            expired: false,
+            bump_seed,
+            option_decimals: 0,
+            official: false,
+            version: OptionMarket::CURRENT_VERSION,
+            creator: Pubkey::new_unique(),
+            created_at: 0,
+            label: [0; 32],
+            yield_vault: Pubkey::default(),
+        };
+        Self { key, market }
+    }
+
+    /// The same market with fractional contracts of `10^option_decimals` option tokens
+    pub fn with_option_decimals(mut self, option_decimals: u8) -> Self {
+        self.market.option_decimals = option_decimals;
+        self
+    }
+
+    /// The same market created by `creator`
+    pub fn with_creator(mut self, creator: Pubkey) -> Self {
+        self.market.creator = creator;
+        self
+    }
+
+    /// The same market as created before versioning, with zeroed metadata
+    pub fn legacy(mut self) -> Self {
+        self.market.version = 0;
+        self.market.creator = Pubkey::default();
+        self
+    }
+
+    pub fn default() -> Self {
+        Self::new(UNDERLYING_AMOUNT_PER_CONTRACT, QUOTE_AMOUNT_PER_CONTRACT, EXPIRATION)
+    }
+
+    /// Add the market with `written` contracts, of which `exercised` have been exercised
+    pub fn add_to(&self, program_test: &mut ProgramTest, written: u64, exercised: u64) {
+        self.add_with_pools(
+            program_test,
+            written - exercised,
+            written,
+            self.market.underlying_amount_in(written - exercised).unwrap(),
+            self.market.quote_amount_in(exercised).unwrap(),
+        );
+    }
+
+    /// Add the market with arbitrary supplies and pool balances, which need not be solvent
+    pub fn add_with_pools(
+        &self,
+        program_test: &mut ProgramTest,
+        option_supply: u64,
+        writer_token_supply: u64,
+        underlying_pool_amount: u64,
+        quote_pool_amount: u64
+    ) {
+        let market = &self.market;
+        let mut data = vec![];
+        market.try_serialize(&mut data).unwrap();
+        data.resize(migration::market_space(market.version), 0);
+        program_test.add_account(self.key, Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: psy_american::ID,
+            executable: false,
+            rent_epoch: 0,
+        });
+
+        add_mint(program_test, &market.underlying_asset_mint, &Pubkey::new_unique(), 0);
+        add_mint(program_test, &market.quote_asset_mint, &Pubkey::new_unique(), 0);
+        add_mint_with_decimals(program_test, &market.option_mint, &self.key, option_supply, market.option_decimals);
+        add_mint_with_decimals(program_test, &market.writer_token_mint, &self.key, writer_token_supply, market.option_decimals);
+        add_token_account(
+            program_test,
+            &market.underlying_asset_pool,
+            &market.underlying_asset_mint,
+            &self.key,
+            underlying_pool_amount,
+        );
+        add_token_account(
+            program_test,
+            &market.quote_asset_pool,
+            &market.quote_asset_mint,
+            &self.key,
+            quote_pool_amount,
+        );
+        add_token_account(program_test, &market.mint_fee_account, &market.underlying_asset_mint, &fees::fee_owner_key::ID, 0);
+        add_token_account(program_test, &market.exercise_fee_account, &market.quote_asset_mint, &fees::fee_owner_key::ID, 0);
+    }
+}
+
+/// Add a psy_american account, e.g. a PDA the fee owner would have created
+pub fn add_program_account(program_test: &mut ProgramTest, key: &Pubkey, account: &impl AccountSerialize) {
+    add_program_account_with_space(program_test, key, account, 0);
+}
+
+/// Add a psy_american account with at least `space` bytes of data, for accounts that grow
+pub fn add_program_account_with_space(program_test: &mut ProgramTest, key: &Pubkey, account: &impl AccountSerialize, space: usize) {
+    let mut data = vec![];
+    account.try_serialize(&mut data).unwrap();
+    if data.len() < space {
+        data.resize(space, 0);
+    }
+    program_test.add_account(*key, Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: psy_american::ID,
+        executable: false,
+        rent_epoch: 0,
+    });
+}
+
+/// Add a page of a pair's OptionChain and return its address
+pub fn add_option_chain(
+    program_test: &mut ProgramTest,
+    underlying_asset_mint: &Pubkey,
+    quote_asset_mint: &Pubkey,
+    page: u32,
+    entries: Vec<OptionChainEntry>
+) -> Pubkey {
+    let (key, bump_seed) = option_chain::page_address(underlying_asset_mint, quote_asset_mint, page);
+    let chain = OptionChain {
+        underlying_asset_mint: *underlying_asset_mint,
+        quote_asset_mint: *quote_asset_mint,
+        page,
+        next_page: Pubkey::default(),
+        bump_seed,
+        entries,
+    };
+    add_program_account_with_space(program_test, &key, &chain, option_chain::OPTION_CHAIN_LEN);
+    key
+}
+
+/// The PauseConfig address, passed to pausable instructions whether or not it exists
+pub fn pause_config_key() -> Pubkey {
+    Pubkey::find_program_address(&[pause::PAUSE_CONFIG_SEED], &psy_american::ID).0
+}
+
+/// Add the PauseConfig the fee owner would have created
+pub fn add_pause_config(program_test: &mut ProgramTest, flags: PauseFlags) {
+    let (key, bump_seed) = Pubkey::find_program_address(&[pause::PAUSE_CONFIG_SEED], &psy_american::ID);
+    add_program_account(program_test, &key, &PauseConfig { flags, bump_seed });
+}
+
+/// The VerifyMarket accounts, passing the YieldVault and receipt pool addresses whether or not
+/// they exist
+pub fn verify_market_accounts(fixture: &MarketFixture) -> psy_american::accounts::VerifyMarket {
+    let yield_vault = Pubkey::find_program_address(
+        &[yield_vault::YIELD_VAULT_SEED, fixture.key.as_ref()],
+        &psy_american::ID,
+    ).0;
+    let receipt_pool = Pubkey::find_program_address(&[&yield_vault.to_bytes()[..], b"receiptPool"], &psy_american::ID).0;
+    psy_american::accounts::VerifyMarket {
+        option_market: fixture.key,
+        option_mint: fixture.market.option_mint,
+        writer_token_mint: fixture.market.writer_token_mint,
+        underlying_asset_pool: fixture.market.underlying_asset_pool,
+        quote_asset_pool: fixture.market.quote_asset_pool,
+        yield_vault,
+        receipt_pool,
+    }
+}
+
+pub fn governance_key() -> Pubkey {
+    Pubkey::find_program_address(&[governance::GOVERNANCE_SEED], &psy_american::ID).0
+}
+
+/// Add the Governance the fee owner would have created, sized for its largest member list
+pub fn add_governance(program_test: &mut ProgramTest, members: Vec<Pubkey>, threshold: u8, timelock_seconds: i64) {
+    let (key, bump_seed) = Pubkey::find_program_address(&[governance::GOVERNANCE_SEED], &psy_american::ID);
+    let account = Governance { members, threshold, timelock_seconds, proposal_count: 0, bump_seed };
+    add_program_account_with_space(program_test, &key, &account, governance::GOVERNANCE_LEN);
+}
+
+/// A user's token accounts for a market
+pub struct UserFixture {
+    pub authority: Keypair,
+    pub option_account: Pubkey,
+    pub writer_token_account: Pubkey,
+    pub underlying_account: Pubkey,
+    pub quote_account: Pubkey,
+}
+
+impl UserFixture {
+    pub fn add_to(
+        program_test: &mut ProgramTest,
+        fixture: &MarketFixture,
+        options: u64,
+        writer_tokens: u64,
+        underlying: u64,
+        quote: u64
+    ) -> Self {
+        let authority = Keypair::new();
+        program_test.add_account(authority.pubkey(), Account {
+            lamports: 100_000_000_000,
+            data: vec![],
+            owner: system_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        });
+        let user = Self {
+            authority,
+            option_account: Pubkey::new_unique(),
+            writer_token_account: Pubkey::new_unique(),
+            underlying_account: Pubkey::new_unique(),
+            quote_account: Pubkey::new_unique(),
+        };
+        let owner = user.authority.pubkey();
+        let market = &fixture.market;
+        add_token_account(program_test, &user.option_account, &market.option_mint, &owner, options);
+        add_token_account(program_test, &user.writer_token_account, &market.writer_token_mint, &owner, writer_tokens);
+        add_token_account(program_test, &user.underlying_account, &market.underlying_asset_mint, &owner, underlying);
+        add_token_account(program_test, &user.quote_account, &market.quote_asset_mint, &owner, quote);
+        user
+    }
+}
+
+pub fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: psy_american::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+pub async fn process(context: &mut ProgramTestContext, ix: Instruction, signers: &[&Keypair]) -> Result<(), TransportError> {
+    process_all(context, &[ix], signers).await
+}
+
+/// Process `ixs` in a single transaction
+pub async fn process_all(context: &mut ProgramTestContext, ixs: &[Instruction], signers: &[&Keypair]) -> Result<(), TransportError> {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&context.payer.pubkey()), &all_signers, blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+/// Move the clock so that markets with `expiration_unix_timestamp` are expired
+pub async fn warp_past(context: &mut ProgramTestContext, expiration_unix_timestamp: i64) {
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = expiration_unix_timestamp + 1;
+    context.set_sysvar(&clock);
+}
+
+/// Move the clock back before `expiration_unix_timestamp`
+pub async fn warp_before(context: &mut ProgramTestContext, expiration_unix_timestamp: i64) {
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = expiration_unix_timestamp - 1;
+    context.set_sysvar(&clock);
+}
+
+pub async fn token_balance(context: &mut ProgramTestContext, account: &Pubkey) -> u64 {
+    let account = context.banks_client.get_account(*account).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+/// Load a psy_american account
+pub async fn program_account<T: AccountDeserialize>(context: &mut ProgramTestContext, key: &Pubkey) -> T {
+    let account = context.banks_client.get_account(*key).await.unwrap().unwrap();
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn mint_supply(context: &mut ProgramTestContext, mint: &Pubkey) -> u64 {
+    let account = context.banks_client.get_account(*mint).await.unwrap().unwrap();
+    spl_token::state::Mint::unpack(&account.data).unwrap().supply
+}
+
+pub fn assert_error(result: Result<(), TransportError>, error: ErrorCode) {
+    match result {
+        Err(TransportError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code)))) => {
+            assert_eq!(code, u32::from(error), "expected {:?}", error)
+        }
+        other => panic!("expected {:?}, got {:?}", error, other),
+    }
+}
+
+pub fn clock_sysvar() -> Pubkey {
+    sysvar::clock::ID
+}
+
+pub fn rent_sysvar() -> Pubkey {
+    sysvar::rent::ID
+}
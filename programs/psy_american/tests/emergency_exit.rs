@@ -0,0 +1,279 @@
+mod common;
+
+use common::*;
+use psy_american::{errors::ErrorCode, fees, PauseFlags};
+use solana_program::{
+    instruction::AccountMeta,
+    system_program,
+    sysvar,
+};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{account::Account, pubkey::Pubkey, rent::Rent, signature::Signer};
+
+const WRITTEN: u64 = 10;
+const EXERCISED: u64 = 2;
+const OPTIONS: u64 = 4;
+const WRITER_TOKENS: u64 = 6;
+
+/// Exit only mode with every other flag cleared, so only `exit_only` pauses anything
+fn exit_only() -> PauseFlags {
+    PauseFlags { exit_only: true, ..PauseFlags::default() }
+}
+
+fn exit_only_program_test() -> ProgramTest {
+    let mut program_test = program_test();
+    add_pause_config(&mut program_test, exit_only());
+    program_test
+}
+
+/// A user with options, WriterTokens and quote for three exercises, one paying the fee
+async fn setup() -> (ProgramTestContext, MarketFixture, UserFixture) {
+    let mut program_test = exit_only_program_test();
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, WRITTEN, EXERCISED);
+    let quote = 3 * QUOTE_AMOUNT_PER_CONTRACT + fees::fee_amount(QUOTE_AMOUNT_PER_CONTRACT).unwrap();
+    let user = UserFixture::add_to(
+        &mut program_test,
+        &fixture,
+        OPTIONS,
+        WRITER_TOKENS,
+        10 * UNDERLYING_AMOUNT_PER_CONTRACT,
+        quote,
+    );
+    (program_test.start_with_context().await, fixture, user)
+}
+
+#[tokio::test]
+async fn exit_only_pauses_minting() {
+    let (mut context, fixture, user) = setup().await;
+    let ix = instruction(
+        psy_american::accounts::MintOptionV2 {
+            user_authority: user.authority.pubkey(),
+            underlying_asset_mint: fixture.market.underlying_asset_mint,
+            underlying_asset_pool: fixture.market.underlying_asset_pool,
+            underlying_asset_src: user.underlying_account,
+            option_mint: fixture.market.option_mint,
+            minted_option_dest: user.option_account,
+            writer_token_mint: fixture.market.writer_token_mint,
+            minted_writer_token_dest: user.writer_token_account,
+            option_market: fixture.key,
+            token_program: spl_token::ID,
+            pause_config: pause_config_key(),
+        },
+        psy_american::instruction::MintOptionV2 { size: 1 },
+    );
+    assert_error(process(&mut context, ix, &[&user.authority]).await, ErrorCode::InstructionPaused);
+
+    let ix = instruction(
+        psy_american::accounts::FlashMint {
+            user_authority: user.authority.pubkey(),
+            option_market: fixture.key,
+            option_mint: fixture.market.option_mint,
+            minted_option_dest: user.option_account,
+            writer_token_mint: fixture.market.writer_token_mint,
+            minted_writer_token_dest: user.writer_token_account,
+            instructions: sysvar::instructions::ID,
+            token_program: spl_token::ID,
+            pause_config: pause_config_key(),
+        },
+        psy_american::instruction::FlashMint { size: 1 },
+    );
+    assert_error(process(&mut context, ix, &[&user.authority]).await, ErrorCode::InstructionPaused);
+}
+
+#[tokio::test]
+async fn exit_only_leaves_exercising_open() {
+    let (mut context, fixture, user) = setup().await;
+
+    let mut ix = instruction(
+        psy_american::accounts::ExerciseOption {
+            user_authority: user.authority.pubkey(),
+            option_authority: user.authority.pubkey(),
+            option_market: fixture.key,
+            option_mint: fixture.market.option_mint,
+            exerciser_option_token_src: user.option_account,
+            underlying_asset_pool: fixture.market.underlying_asset_pool,
+            underlying_asset_dest: user.underlying_account,
+            quote_asset_pool: fixture.market.quote_asset_pool,
+            quote_asset_src: user.quote_account,
+            fee_owner: fees::fee_owner_key::ID,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            clock: clock_sysvar(),
+        },
+        psy_american::instruction::ExerciseOption { size: 1 },
+    );
+    ix.accounts.push(AccountMeta::new(fixture.market.exercise_fee_account, false));
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    let ix = instruction(
+        psy_american::accounts::ExerciseOptionV2 {
+            user_authority: user.authority.pubkey(),
+            option_authority: user.authority.pubkey(),
+            option_market: fixture.key,
+            option_mint: fixture.market.option_mint,
+            exerciser_option_token_src: user.option_account,
+            underlying_asset_pool: fixture.market.underlying_asset_pool,
+            underlying_asset_dest: user.underlying_account,
+            quote_asset_pool: fixture.market.quote_asset_pool,
+            quote_asset_src: user.quote_account,
+            token_program: spl_token::ID,
+        },
+        psy_american::instruction::ExerciseOptionV2 { size: 1 },
+    );
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    let mut ix = instruction(
+        psy_american::accounts::BatchExercise {
+            user_authority: user.authority.pubkey(),
+            option_authority: user.authority.pubkey(),
+            quote_asset_src: user.quote_account,
+            underlying_asset_dest: user.underlying_account,
+            token_program: spl_token::ID,
+        },
+        psy_american::instruction::BatchExercise { sizes: vec![1], all_or_nothing: true },
+    );
+    ix.accounts.extend(vec![
+        AccountMeta::new_readonly(fixture.key, false),
+        AccountMeta::new(fixture.market.option_mint, false),
+        AccountMeta::new(user.option_account, false),
+        AccountMeta::new(fixture.market.underlying_asset_pool, false),
+        AccountMeta::new(fixture.market.quote_asset_pool, false),
+    ]);
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &user.option_account).await, OPTIONS - 3);
+    assert_eq!(token_balance(&mut context, &user.quote_account).await, 0);
+    assert_eq!(
+        token_balance(&mut context, &user.underlying_account).await,
+        13 * UNDERLYING_AMOUNT_PER_CONTRACT
+    );
+}
+
+#[tokio::test]
+async fn exit_only_leaves_closing_open() {
+    let (mut context, fixture, user) = setup().await;
+
+    let ix = instruction(
+        psy_american::accounts::CloseOptionPosition {
+            user_authority: user.authority.pubkey(),
+            option_market: fixture.key,
+            writer_token_mint: fixture.market.writer_token_mint,
+            writer_token_src: user.writer_token_account,
+            option_token_mint: fixture.market.option_mint,
+            option_token_src: user.option_account,
+            underlying_asset_pool: fixture.market.underlying_asset_pool,
+            underlying_asset_dest: user.underlying_account,
+            token_program: spl_token::ID,
+        },
+        psy_american::instruction::CloseOptionPosition { size: 1 },
+    );
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    let ix = instruction(
+        psy_american::accounts::BurnWriterForQuote {
+            user_authority: user.authority.pubkey(),
+            option_market: fixture.key,
+            writer_token_mint: fixture.market.writer_token_mint,
+            writer_token_src: user.writer_token_account,
+            quote_asset_pool: fixture.market.quote_asset_pool,
+            writer_quote_dest: user.quote_account,
+            token_program: spl_token::ID,
+        },
+        psy_american::instruction::BurnWriterForQuote { size: EXERCISED },
+    );
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    warp_past(&mut context, EXPIRATION).await;
+    let remaining = WRITER_TOKENS - 1 - EXERCISED;
+    let ix = instruction(
+        psy_american::accounts::ClosePostExp {
+            user_authority: user.authority.pubkey(),
+            option_market: fixture.key,
+            writer_token_mint: fixture.market.writer_token_mint,
+            writer_token_src: user.writer_token_account,
+            underlying_asset_pool: fixture.market.underlying_asset_pool,
+            underlying_asset_dest: user.underlying_account,
+            token_program: spl_token::ID,
+            clock: clock_sysvar(),
+        },
+        psy_american::instruction::ClosePostExpiration { size: remaining },
+    );
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &user.writer_token_account).await, 0);
+    assert_eq!(
+        token_balance(&mut context, &user.underlying_account).await,
+        (10 + 1 + remaining) * UNDERLYING_AMOUNT_PER_CONTRACT
+    );
+}
+
+#[tokio::test]
+async fn exit_only_leaves_net_exercise_open() {
+    let mut program_test = exit_only_program_test();
+    program_test.add_program("mock_amm", mock_amm::ID, processor!(mock_amm::process_instruction));
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, WRITTEN, 0);
+    let user = UserFixture::add_to(&mut program_test, &fixture, OPTIONS, 0, 0, 0);
+
+    // A pool paying 10 quote per underlying, twice the strike
+    let pool = Pubkey::new_unique();
+    let (authority, authority_bump) = mock_amm::pool_authority(&pool);
+    let data = mock_amm::Pool { price_numerator: 10, price_denominator: 1, authority_bump }.pack();
+    program_test.add_account(pool, Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: mock_amm::ID,
+        executable: false,
+        rent_epoch: 0,
+    });
+    let underlying_vault = Pubkey::new_unique();
+    let quote_vault = Pubkey::new_unique();
+    add_token_account(&mut program_test, &underlying_vault, &fixture.market.underlying_asset_mint, &authority, 0);
+    add_token_account(&mut program_test, &quote_vault, &fixture.market.quote_asset_mint, &authority, 10 * QUOTE_AMOUNT_PER_CONTRACT);
+    let mut context = program_test.start_with_context().await;
+
+    let mut ix = instruction(
+        psy_american::accounts::NetExercise {
+            user_authority: user.authority.pubkey(),
+            option_market: fixture.key,
+            option_mint: fixture.market.option_mint,
+            exerciser_option_token_src: user.option_account,
+            underlying_asset_pool: fixture.market.underlying_asset_pool,
+            underlying_asset_dest: user.underlying_account,
+            quote_asset_pool: fixture.market.quote_asset_pool,
+            quote_asset_account: user.quote_account,
+            amm_program: mock_amm::ID,
+            token_program: spl_token::ID,
+        },
+        psy_american::instruction::NetExercise { size: 1, underlying_in: UNDERLYING_AMOUNT_PER_CONTRACT / 2 },
+    );
+    ix.accounts.extend(vec![
+        AccountMeta::new_readonly(pool, false),
+        AccountMeta::new_readonly(authority, false),
+        AccountMeta::new_readonly(user.authority.pubkey(), true),
+        AccountMeta::new(user.underlying_account, false),
+        AccountMeta::new(underlying_vault, false),
+        AccountMeta::new(quote_vault, false),
+        AccountMeta::new(user.quote_account, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+    ]);
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &user.option_account).await, OPTIONS - 1);
+    assert_eq!(token_balance(&mut context, &user.underlying_account).await, UNDERLYING_AMOUNT_PER_CONTRACT / 2);
+}
+
+#[tokio::test]
+async fn only_the_fee_owner_sets_pause_flags() {
+    let (mut context, _, user) = setup().await;
+    let ix = instruction(
+        psy_american::accounts::SetPauseFlags {
+            fee_owner: user.authority.pubkey(),
+            governance: governance_key(),
+            pause_config: pause_config_key(),
+        },
+        psy_american::instruction::SetPauseFlags { flags: PauseFlags::default() },
+    );
+    assert_error(process(&mut context, ix, &[&user.authority]).await, ErrorCode::FeeOwnerDoesNotMatchProgram);
+}
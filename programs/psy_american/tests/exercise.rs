@@ -0,0 +1,227 @@
+mod common;
+
+use common::*;
+use psy_american::{errors::ErrorCode, fees};
+use solana_program::{instruction::AccountMeta, system_program};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+const WRITTEN: u64 = 10;
+const OPTIONS: u64 = 5;
+
+async fn setup() -> (ProgramTestContext, MarketFixture, UserFixture) {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, WRITTEN, 0);
+    let quote = OPTIONS * (QUOTE_AMOUNT_PER_CONTRACT + fees::fee_amount(QUOTE_AMOUNT_PER_CONTRACT).unwrap());
+    let user = UserFixture::add_to(&mut program_test, &fixture, OPTIONS, 0, 0, quote);
+    (program_test.start_with_context().await, fixture, user)
+}
+
+fn exercise_accounts(fixture: &MarketFixture, user: &UserFixture) -> psy_american::accounts::ExerciseOption {
+    psy_american::accounts::ExerciseOption {
+        user_authority: user.authority.pubkey(),
+        option_authority: user.authority.pubkey(),
+        option_market: fixture.key,
+        option_mint: fixture.market.option_mint,
+        exerciser_option_token_src: user.option_account,
+        underlying_asset_pool: fixture.market.underlying_asset_pool,
+        underlying_asset_dest: user.underlying_account,
+        quote_asset_pool: fixture.market.quote_asset_pool,
+        quote_asset_src: user.quote_account,
+        fee_owner: fees::fee_owner_key::ID,
+        token_program: spl_token::ID,
+        system_program: system_program::ID,
+        clock: clock_sysvar(),
+    }
+}
+
+fn exercise_v2_accounts(fixture: &MarketFixture, user: &UserFixture) -> psy_american::accounts::ExerciseOptionV2 {
+    psy_american::accounts::ExerciseOptionV2 {
+        user_authority: user.authority.pubkey(),
+        option_authority: user.authority.pubkey(),
+        option_market: fixture.key,
+        option_mint: fixture.market.option_mint,
+        exerciser_option_token_src: user.option_account,
+        underlying_asset_pool: fixture.market.underlying_asset_pool,
+        underlying_asset_dest: user.underlying_account,
+        quote_asset_pool: fixture.market.quote_asset_pool,
+        quote_asset_src: user.quote_account,
+        token_program: spl_token::ID,
+    }
+}
+
+async fn exercise(
+    context: &mut ProgramTestContext,
+    user: &UserFixture,
+    accounts: psy_american::accounts::ExerciseOption,
+    exercise_fee_account: Pubkey,
+    size: u64
+) -> Result<(), solana_sdk::transport::TransportError> {
+    let mut ix = instruction(accounts, psy_american::instruction::ExerciseOption { size });
+    ix.accounts.push(AccountMeta::new(exercise_fee_account, false));
+    process(context, ix, &[&user.authority]).await
+}
+
+#[tokio::test]
+async fn exercise_option_moves_assets_and_pays_fee() {
+    let (mut context, fixture, user) = setup().await;
+    let accounts = exercise_accounts(&fixture, &user);
+    exercise(&mut context, &user, accounts, fixture.market.exercise_fee_account, OPTIONS).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &user.option_account).await, 0);
+    assert_eq!(mint_supply(&mut context, &fixture.market.option_mint).await, WRITTEN - OPTIONS);
+    assert_eq!(token_balance(&mut context, &user.underlying_account).await, OPTIONS * UNDERLYING_AMOUNT_PER_CONTRACT);
+    assert_eq!(token_balance(&mut context, &user.quote_account).await, 0);
+    assert_eq!(
+        token_balance(&mut context, &fixture.market.underlying_asset_pool).await,
+        (WRITTEN - OPTIONS) * UNDERLYING_AMOUNT_PER_CONTRACT
+    );
+    assert_eq!(token_balance(&mut context, &fixture.market.quote_asset_pool).await, OPTIONS * QUOTE_AMOUNT_PER_CONTRACT);
+    assert_eq!(
+        token_balance(&mut context, &fixture.market.exercise_fee_account).await,
+        OPTIONS * fees::fee_amount(QUOTE_AMOUNT_PER_CONTRACT).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn exercise_option_validates_market_accounts() {
+    let (mut context, fixture, user) = setup().await;
+
+    let mut accounts = exercise_accounts(&fixture, &user);
+    accounts.quote_asset_pool = user.quote_account;
+    assert_error(
+        exercise(&mut context, &user, accounts, fixture.market.exercise_fee_account, 1).await,
+        ErrorCode::QuotePoolAccountDoesNotMatchMarket,
+    );
+
+    let mut accounts = exercise_accounts(&fixture, &user);
+    accounts.underlying_asset_pool = user.underlying_account;
+    assert_error(
+        exercise(&mut context, &user, accounts, fixture.market.exercise_fee_account, 1).await,
+        ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket,
+    );
+
+    let mut accounts = exercise_accounts(&fixture, &user);
+    accounts.option_mint = fixture.market.writer_token_mint;
+    assert_error(
+        exercise(&mut context, &user, accounts, fixture.market.exercise_fee_account, 1).await,
+        ErrorCode::OptionTokenMintDoesNotMatchMarket,
+    );
+
+    let mut accounts = exercise_accounts(&fixture, &user);
+    accounts.underlying_asset_dest = user.quote_account;
+    assert_error(
+        exercise(&mut context, &user, accounts, fixture.market.exercise_fee_account, 1).await,
+        ErrorCode::UnderlyingDestMintDoesNotMatchUnderlyingAsset,
+    );
+
+    let mut accounts = exercise_accounts(&fixture, &user);
+    accounts.fee_owner = user.authority.pubkey();
+    assert_error(
+        exercise(&mut context, &user, accounts, fixture.market.exercise_fee_account, 1).await,
+        ErrorCode::FeeOwnerDoesNotMatchProgram,
+    );
+}
+
+#[tokio::test]
+async fn exercise_option_validates_exercise_fee_account() {
+    let (mut context, fixture, user) = setup().await;
+
+    // Not a token account
+    let accounts = exercise_accounts(&fixture, &user);
+    assert_error(
+        exercise(&mut context, &user, accounts, user.authority.pubkey(), 1).await,
+        ErrorCode::ExpectedSPLTokenProgramId,
+    );
+
+    // Not owned by the fee owner
+    let accounts = exercise_accounts(&fixture, &user);
+    assert_error(
+        exercise(&mut context, &user, accounts, user.quote_account, 1).await,
+        ErrorCode::ExerciseFeeMustBeOwnedByFeeOwner,
+    );
+
+    // Owned by the fee owner, but for the underlying asset
+    let accounts = exercise_accounts(&fixture, &user);
+    assert_error(
+        exercise(&mut context, &user, accounts, fixture.market.mint_fee_account, 1).await,
+        ErrorCode::ExerciseFeeTokenMustMatchQuoteAsset,
+    );
+}
+
+#[tokio::test]
+async fn exercise_option_rejects_other_fee_owner_quote_account() {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, WRITTEN, 0);
+    let quote = OPTIONS * (QUOTE_AMOUNT_PER_CONTRACT + fees::fee_amount(QUOTE_AMOUNT_PER_CONTRACT).unwrap());
+    let user = UserFixture::add_to(&mut program_test, &fixture, OPTIONS, 0, 0, quote);
+    let other_fee_account = Pubkey::new_unique();
+    add_token_account(&mut program_test, &other_fee_account, &fixture.market.quote_asset_mint, &fees::fee_owner_key::ID, 0);
+    let mut context = program_test.start_with_context().await;
+
+    let accounts = exercise_accounts(&fixture, &user);
+    assert_error(
+        exercise(&mut context, &user, accounts, other_fee_account, 1).await,
+        ErrorCode::ExerciseFeeKeyDoesNotMatchOptionMarket,
+    );
+}
+
+#[tokio::test]
+async fn exercise_option_fails_after_expiration() {
+    let (mut context, fixture, user) = setup().await;
+    warp_past(&mut context, EXPIRATION).await;
+    let accounts = exercise_accounts(&fixture, &user);
+    assert_error(
+        exercise(&mut context, &user, accounts, fixture.market.exercise_fee_account, 1).await,
+        ErrorCode::OptionMarketExpiredCantExercise,
+    );
+}
+
+#[tokio::test]
+async fn exercise_option_v2_moves_assets_without_fee() {
+    let (mut context, fixture, user) = setup().await;
+    let ix = instruction(
+        exercise_v2_accounts(&fixture, &user),
+        psy_american::instruction::ExerciseOptionV2 { size: OPTIONS },
+    );
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &user.option_account).await, 0);
+    assert_eq!(token_balance(&mut context, &user.underlying_account).await, OPTIONS * UNDERLYING_AMOUNT_PER_CONTRACT);
+    assert_eq!(
+        token_balance(&mut context, &user.quote_account).await,
+        OPTIONS * fees::fee_amount(QUOTE_AMOUNT_PER_CONTRACT).unwrap()
+    );
+    assert_eq!(token_balance(&mut context, &fixture.market.quote_asset_pool).await, OPTIONS * QUOTE_AMOUNT_PER_CONTRACT);
+    assert_eq!(token_balance(&mut context, &fixture.market.exercise_fee_account).await, 0);
+}
+
+#[tokio::test]
+async fn exercise_option_v2_validates_market_accounts() {
+    let (mut context, fixture, user) = setup().await;
+    let cases: Vec<(Box<dyn Fn(&mut psy_american::accounts::ExerciseOptionV2)>, ErrorCode)> = vec![
+        (Box::new(|a| a.quote_asset_pool = user.quote_account), ErrorCode::QuotePoolAccountDoesNotMatchMarket),
+        (Box::new(|a| a.underlying_asset_pool = user.underlying_account), ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket),
+        (Box::new(|a| a.option_mint = fixture.market.writer_token_mint), ErrorCode::OptionTokenMintDoesNotMatchMarket),
+        (Box::new(|a| a.underlying_asset_dest = user.quote_account), ErrorCode::UnderlyingDestMintDoesNotMatchUnderlyingAsset),
+    ];
+    for (modify, error) in cases {
+        let mut accounts = exercise_v2_accounts(&fixture, &user);
+        modify(&mut accounts);
+        let ix = instruction(accounts, psy_american::instruction::ExerciseOptionV2 { size: 1 });
+        assert_error(process(&mut context, ix, &[&user.authority]).await, error);
+    }
+}
+
+#[tokio::test]
+async fn exercise_option_v2_fails_after_expiration() {
+    let (mut context, fixture, user) = setup().await;
+    warp_past(&mut context, EXPIRATION).await;
+    let ix = instruction(
+        exercise_v2_accounts(&fixture, &user),
+        psy_american::instruction::ExerciseOptionV2 { size: 1 },
+    );
+    assert_error(process(&mut context, ix, &[&user.authority]).await, ErrorCode::OptionMarketExpiredCantExercise);
+}
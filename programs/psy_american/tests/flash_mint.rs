@@ -0,0 +1,141 @@
+mod common;
+
+use common::*;
+use psy_american::{errors::ErrorCode, PauseFlags};
+use solana_program::sysvar;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{instruction::Instruction, signature::Signer};
+
+const WRITTEN: u64 = 10;
+const SIZE: u64 = 3;
+
+async fn setup(underlying: u64) -> (ProgramTestContext, MarketFixture, UserFixture) {
+    let mut program_test = program_test();
+    add_pause_config(&mut program_test, PauseFlags::default());
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, WRITTEN, 0);
+    let user = UserFixture::add_to(&mut program_test, &fixture, 0, 0, underlying, 0);
+    (program_test.start_with_context().await, fixture, user)
+}
+
+fn flash_mint_ix(fixture: &MarketFixture, user: &UserFixture, size: u64) -> Instruction {
+    instruction(
+        psy_american::accounts::FlashMint {
+            user_authority: user.authority.pubkey(),
+            option_market: fixture.key,
+            option_mint: fixture.market.option_mint,
+            minted_option_dest: user.option_account,
+            writer_token_mint: fixture.market.writer_token_mint,
+            minted_writer_token_dest: user.writer_token_account,
+            instructions: sysvar::instructions::ID,
+            token_program: spl_token::ID,
+            pause_config: pause_config_key(),
+        },
+        psy_american::instruction::FlashMint { size },
+    )
+}
+
+fn flash_repay_ix(fixture: &MarketFixture, user: &UserFixture, burn_size: u64, deposit_size: u64) -> Instruction {
+    instruction(
+        psy_american::accounts::FlashRepay {
+            user_authority: user.authority.pubkey(),
+            option_market: fixture.key,
+            option_mint: fixture.market.option_mint,
+            option_token_src: user.option_account,
+            writer_token_mint: fixture.market.writer_token_mint,
+            writer_token_src: user.writer_token_account,
+            underlying_asset_pool: fixture.market.underlying_asset_pool,
+            underlying_asset_src: user.underlying_account,
+            instructions: sysvar::instructions::ID,
+            token_program: spl_token::ID,
+        },
+        psy_american::instruction::FlashRepay { burn_size, deposit_size },
+    )
+}
+
+#[tokio::test]
+async fn flash_mint_burned_in_the_same_transaction() {
+    let (mut context, fixture, user) = setup(0).await;
+    let ixs = [flash_mint_ix(&fixture, &user, SIZE), flash_repay_ix(&fixture, &user, SIZE, 0)];
+    process_all(&mut context, &ixs, &[&user.authority]).await.unwrap();
+
+    assert_eq!(mint_supply(&mut context, &fixture.market.option_mint).await, WRITTEN);
+    assert_eq!(mint_supply(&mut context, &fixture.market.writer_token_mint).await, WRITTEN);
+    assert_eq!(token_balance(&mut context, &user.option_account).await, 0);
+}
+
+#[tokio::test]
+async fn flash_mint_collateralized_in_the_same_transaction() {
+    let (mut context, fixture, user) = setup(2 * UNDERLYING_AMOUNT_PER_CONTRACT).await;
+    let ixs = [flash_mint_ix(&fixture, &user, SIZE), flash_repay_ix(&fixture, &user, 1, 2)];
+    process_all(&mut context, &ixs, &[&user.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &user.option_account).await, 2);
+    assert_eq!(token_balance(&mut context, &user.writer_token_account).await, 2);
+    assert_eq!(token_balance(&mut context, &user.underlying_account).await, 0);
+    assert_eq!(
+        token_balance(&mut context, &fixture.market.underlying_asset_pool).await,
+        (WRITTEN + 2) * UNDERLYING_AMOUNT_PER_CONTRACT
+    );
+}
+
+#[tokio::test]
+async fn flash_mint_fails_when_repay_fails() {
+    // Not enough underlying to collateralize the flash mint
+    let (mut context, fixture, user) = setup(UNDERLYING_AMOUNT_PER_CONTRACT).await;
+    let ixs = [flash_mint_ix(&fixture, &user, SIZE), flash_repay_ix(&fixture, &user, 0, SIZE)];
+    assert!(process_all(&mut context, &ixs, &[&user.authority]).await.is_err());
+
+    assert_eq!(mint_supply(&mut context, &fixture.market.option_mint).await, WRITTEN);
+    assert_eq!(token_balance(&mut context, &user.option_account).await, 0);
+}
+
+#[tokio::test]
+async fn flash_mint_requires_a_matching_repay() {
+    let (mut context, fixture, user) = setup(0).await;
+
+    let ixs = [flash_mint_ix(&fixture, &user, SIZE)];
+    assert_error(process_all(&mut context, &ixs, &[&user.authority]).await, ErrorCode::FlashMintNotRepaid);
+
+    let ixs = [flash_mint_ix(&fixture, &user, SIZE), flash_repay_ix(&fixture, &user, SIZE - 1, 0)];
+    assert_error(process_all(&mut context, &ixs, &[&user.authority]).await, ErrorCode::FlashRepayDoesNotMatchMint);
+
+    // A repay before the mint does not count
+    let ixs = [flash_repay_ix(&fixture, &user, SIZE, 0), flash_mint_ix(&fixture, &user, SIZE)];
+    assert_error(process_all(&mut context, &ixs, &[&user.authority]).await, ErrorCode::FlashRepayWithoutMint);
+}
+
+#[tokio::test]
+async fn flash_mints_cannot_share_a_repay() {
+    let (mut context, fixture, user) = setup(0).await;
+    let ixs = [
+        flash_mint_ix(&fixture, &user, SIZE),
+        flash_mint_ix(&fixture, &user, 1),
+        flash_repay_ix(&fixture, &user, SIZE, 0),
+    ];
+    assert_error(process_all(&mut context, &ixs, &[&user.authority]).await, ErrorCode::FlashMintNotRepaid);
+}
+
+#[tokio::test]
+async fn flash_repay_requires_a_flash_mint() {
+    let (mut context, fixture, user) = setup(UNDERLYING_AMOUNT_PER_CONTRACT).await;
+    let ixs = [flash_repay_ix(&fixture, &user, 0, 1)];
+    assert_error(process_all(&mut context, &ixs, &[&user.authority]).await, ErrorCode::FlashRepayWithoutMint);
+}
+
+#[tokio::test]
+async fn flash_mint_validates_market() {
+    let (mut context, fixture, user) = setup(0).await;
+
+    let mut ix = flash_mint_ix(&fixture, &user, SIZE);
+    ix.accounts[2].pubkey = fixture.market.writer_token_mint;
+    let ixs = [ix, flash_repay_ix(&fixture, &user, SIZE, 0)];
+    assert_error(process_all(&mut context, &ixs, &[&user.authority]).await, ErrorCode::OptionTokenMintDoesNotMatchMarket);
+
+    let ixs = [flash_mint_ix(&fixture, &user, 0), flash_repay_ix(&fixture, &user, 0, 0)];
+    assert_error(process_all(&mut context, &ixs, &[&user.authority]).await, ErrorCode::SizeCantBeLessThanEqZero);
+
+    warp_past(&mut context, EXPIRATION).await;
+    let ixs = [flash_mint_ix(&fixture, &user, SIZE), flash_repay_ix(&fixture, &user, SIZE, 0)];
+    assert_error(process_all(&mut context, &ixs, &[&user.authority]).await, ErrorCode::OptionMarketExpiredCantMint);
+}
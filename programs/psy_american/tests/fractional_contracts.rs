@@ -0,0 +1,188 @@
+mod common;
+
+use common::*;
+use psy_american::{errors::ErrorCode, MarketSolvencyReport, PauseFlags};
+use solana_program::instruction::AccountMeta;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::Signer;
+
+/// A 100 unit contract split into 1,000 option tokens
+const UNDERLYING_PER_CONTRACT: u64 = 100;
+/// An odd strike, so fractional exercises round
+const QUOTE_PER_CONTRACT: u64 = 5_001;
+const OPTION_DECIMALS: u8 = 3;
+/// 10 contracts
+const WRITTEN: u64 = 10_000;
+/// 0.333 of a contract
+const POSITION: u64 = 333;
+
+async fn setup(exercised: u64, underlying: u64, quote: u64) -> (ProgramTestContext, MarketFixture, UserFixture) {
+    let mut program_test = program_test();
+    add_pause_config(&mut program_test, PauseFlags::default());
+    let fixture = MarketFixture::new(UNDERLYING_PER_CONTRACT, QUOTE_PER_CONTRACT, EXPIRATION)
+        .with_option_decimals(OPTION_DECIMALS);
+    fixture.add_to(&mut program_test, WRITTEN, exercised);
+    let user = UserFixture::add_to(&mut program_test, &fixture, POSITION, POSITION, underlying, quote);
+    (program_test.start_with_context().await, fixture, user)
+}
+
+#[tokio::test]
+async fn exercise_option_v2_rounds_in_favor_of_the_pool() {
+    // 333 * 5,001 / 1,000 = 1,665.333 quote is rounded up
+    let (mut context, fixture, user) = setup(0, 0, 1_666).await;
+    let ix = instruction(
+        psy_american::accounts::ExerciseOptionV2 {
+            user_authority: user.authority.pubkey(),
+            option_authority: user.authority.pubkey(),
+            option_market: fixture.key,
+            option_mint: fixture.market.option_mint,
+            exerciser_option_token_src: user.option_account,
+            underlying_asset_pool: fixture.market.underlying_asset_pool,
+            underlying_asset_dest: user.underlying_account,
+            quote_asset_pool: fixture.market.quote_asset_pool,
+            quote_asset_src: user.quote_account,
+            token_program: spl_token::ID,
+        },
+        psy_american::instruction::ExerciseOptionV2 { size: POSITION },
+    );
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &user.option_account).await, 0);
+    assert_eq!(token_balance(&mut context, &user.quote_account).await, 0);
+    assert_eq!(token_balance(&mut context, &fixture.market.quote_asset_pool).await, 1_666);
+    // 333 * 100 / 1,000 = 33.3 underlying is rounded down
+    assert_eq!(token_balance(&mut context, &user.underlying_account).await, 33);
+    assert_eq!(token_balance(&mut context, &fixture.market.underlying_asset_pool).await, 1_000 - 33);
+}
+
+#[tokio::test]
+async fn close_option_position_rounds_in_favor_of_the_pool() {
+    let (mut context, fixture, user) = setup(0, 0, 0).await;
+    let ix = instruction(
+        psy_american::accounts::CloseOptionPosition {
+            user_authority: user.authority.pubkey(),
+            option_market: fixture.key,
+            writer_token_mint: fixture.market.writer_token_mint,
+            writer_token_src: user.writer_token_account,
+            option_token_mint: fixture.market.option_mint,
+            option_token_src: user.option_account,
+            underlying_asset_pool: fixture.market.underlying_asset_pool,
+            underlying_asset_dest: user.underlying_account,
+            token_program: spl_token::ID,
+        },
+        psy_american::instruction::CloseOptionPosition { size: POSITION },
+    );
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    assert_eq!(mint_supply(&mut context, &fixture.market.option_mint).await, WRITTEN - POSITION);
+    assert_eq!(token_balance(&mut context, &user.underlying_account).await, 33);
+    assert_eq!(token_balance(&mut context, &fixture.market.underlying_asset_pool).await, 1_000 - 33);
+}
+
+#[tokio::test]
+async fn close_post_expiration_rounds_in_favor_of_the_pool() {
+    let (mut context, fixture, user) = setup(0, 0, 0).await;
+    warp_past(&mut context, EXPIRATION).await;
+    let ix = instruction(
+        psy_american::accounts::ClosePostExp {
+            user_authority: user.authority.pubkey(),
+            option_market: fixture.key,
+            writer_token_mint: fixture.market.writer_token_mint,
+            writer_token_src: user.writer_token_account,
+            underlying_asset_pool: fixture.market.underlying_asset_pool,
+            underlying_asset_dest: user.underlying_account,
+            token_program: spl_token::ID,
+            clock: clock_sysvar(),
+        },
+        psy_american::instruction::ClosePostExpiration { size: POSITION },
+    );
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &user.writer_token_account).await, 0);
+    assert_eq!(token_balance(&mut context, &user.underlying_account).await, 33);
+}
+
+fn burn_ix(fixture: &MarketFixture, user: &UserFixture, size: u64) -> solana_sdk::instruction::Instruction {
+    instruction(
+        psy_american::accounts::BurnWriterForQuote {
+            user_authority: user.authority.pubkey(),
+            option_market: fixture.key,
+            writer_token_mint: fixture.market.writer_token_mint,
+            writer_token_src: user.writer_token_account,
+            quote_asset_pool: fixture.market.quote_asset_pool,
+            writer_quote_dest: user.quote_account,
+            token_program: spl_token::ID,
+        },
+        psy_american::instruction::BurnWriterForQuote { size },
+    )
+}
+
+#[tokio::test]
+async fn burn_writer_for_quote_rounds_in_favor_of_the_pool() {
+    // One whole contract was exercised, leaving 5,001 quote in the pool
+    let (mut context, fixture, user) = setup(1_000, 0, 0).await;
+    process(&mut context, burn_ix(&fixture, &user, POSITION), &[&user.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &user.writer_token_account).await, 0);
+    assert_eq!(token_balance(&mut context, &user.quote_account).await, 1_665);
+    assert_eq!(token_balance(&mut context, &fixture.market.quote_asset_pool).await, 5_001 - 1_665);
+}
+
+#[tokio::test]
+async fn burn_writer_for_quote_checks_fractional_quote_in_pool() {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::new(UNDERLYING_PER_CONTRACT, QUOTE_PER_CONTRACT, EXPIRATION)
+        .with_option_decimals(OPTION_DECIMALS);
+    fixture.add_to(&mut program_test, WRITTEN, 1_000);
+    let user = UserFixture::add_to(&mut program_test, &fixture, 0, 1_001, 0, 0);
+    let mut context = program_test.start_with_context().await;
+
+    // 1,001 WriterTokens are owed 5,006.001 quote, more than the 5,001 in the pool
+    assert_error(
+        process(&mut context, burn_ix(&fixture, &user, 1_001), &[&user.authority]).await,
+        ErrorCode::NotEnoughQuoteAssetsInPool,
+    );
+    process(&mut context, burn_ix(&fixture, &user, 1_000), &[&user.authority]).await.unwrap();
+    assert_eq!(token_balance(&mut context, &user.quote_account).await, 5_001);
+}
+
+#[tokio::test]
+async fn batch_mint_rounds_in_favor_of_the_pool() {
+    // 33.3 underlying is rounded up
+    let (mut context, fixture, user) = setup(0, 34, 0).await;
+    let mut ix = instruction(
+        psy_american::accounts::BatchMint {
+            user_authority: user.authority.pubkey(),
+            token_program: spl_token::ID,
+            pause_config: pause_config_key(),
+        },
+        psy_american::instruction::BatchMint { sizes: vec![POSITION] },
+    );
+    ix.accounts.extend(vec![
+        AccountMeta::new_readonly(fixture.key, false),
+        AccountMeta::new(fixture.market.option_mint, false),
+        AccountMeta::new(user.option_account, false),
+        AccountMeta::new(fixture.market.writer_token_mint, false),
+        AccountMeta::new(user.writer_token_account, false),
+        AccountMeta::new(fixture.market.underlying_asset_pool, false),
+        AccountMeta::new(user.underlying_account, false),
+    ]);
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &user.option_account).await, 2 * POSITION);
+    assert_eq!(token_balance(&mut context, &user.underlying_account).await, 0);
+    assert_eq!(token_balance(&mut context, &fixture.market.underlying_asset_pool).await, 1_000 + 34);
+}
+
+#[test]
+fn solvency_report_counts_option_tokens() {
+    let fixture = MarketFixture::new(UNDERLYING_PER_CONTRACT, QUOTE_PER_CONTRACT, EXPIRATION)
+        .with_option_decimals(OPTION_DECIMALS);
+    let report = MarketSolvencyReport::new(fixture.key, &fixture.market, POSITION, POSITION, 33, 0, EXPIRATION);
+    assert!(!report.options_covered);
+    assert!(!report.writer_tokens_covered);
+
+    let report = MarketSolvencyReport::new(fixture.key, &fixture.market, POSITION, POSITION, 34, 0, EXPIRATION);
+    assert!(report.options_covered);
+    assert!(report.writer_tokens_covered);
+}
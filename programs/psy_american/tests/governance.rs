@@ -0,0 +1,418 @@
+mod common;
+
+use common::*;
+use psy_american::{
+    errors::ErrorCode,
+    governance,
+    listing,
+    price_band,
+    series,
+    yield_vault,
+    Governance,
+    GovernanceAction,
+    ListedPair,
+    OptionMarket,
+    PauseConfig,
+    PauseFlags,
+    PriceBandConfig,
+    Proposal,
+    SeriesConfig,
+    YieldAdapter,
+};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    instruction::AccountMeta,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction,
+    system_program,
+    transport::TransportError,
+};
+
+const TIMELOCK: i64 = governance::MIN_TIMELOCK_SECONDS;
+
+/// A three member Governance needing two approvals. The keypairs are funded and the last is
+/// not a member.
+async fn setup() -> (ProgramTestContext, Vec<Keypair>) {
+    setup_with(|_| {}).await
+}
+
+async fn setup_with(add_accounts: impl FnOnce(&mut ProgramTest)) -> (ProgramTestContext, Vec<Keypair>) {
+    let mut program_test = program_test();
+    add_accounts(&mut program_test);
+    let keypairs: Vec<Keypair> = (0..4).map(|_| Keypair::new()).collect();
+    for member in keypairs.iter() {
+        program_test.add_account(member.pubkey(), Account {
+            lamports: 100_000_000_000,
+            data: vec![],
+            owner: system_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        });
+    }
+    add_governance(&mut program_test, keypairs[..3].iter().map(|member| member.pubkey()).collect(), 2, TIMELOCK);
+    (program_test.start_with_context().await, keypairs)
+}
+
+async fn propose(context: &mut ProgramTestContext, proposer: &Keypair, action: GovernanceAction) -> Result<Pubkey, TransportError> {
+    let governance: Governance = program_account(context, &governance_key()).await;
+    let proposal = governance::proposal_address(governance.proposal_count).0;
+    let ix = instruction(
+        psy_american::accounts::Propose {
+            proposer: proposer.pubkey(),
+            governance: governance_key(),
+            proposal,
+            system_program: system_program::ID,
+        },
+        psy_american::instruction::Propose { action },
+    );
+    process(context, ix, &[proposer]).await?;
+    Ok(proposal)
+}
+
+async fn approve(context: &mut ProgramTestContext, member: &Keypair, proposal: Pubkey) -> Result<(), TransportError> {
+    let ix = instruction(
+        psy_american::accounts::ApproveProposal { member: member.pubkey(), governance: governance_key(), proposal },
+        psy_american::instruction::ApproveProposal {},
+    );
+    process(context, ix, &[member]).await
+}
+
+async fn execute(context: &mut ProgramTestContext, payer: &Keypair, proposal: Pubkey) -> Result<(), TransportError> {
+    execute_with(context, payer, proposal, &[]).await
+}
+
+/// Execute with `remaining_accounts` for the action to read
+async fn execute_with(
+    context: &mut ProgramTestContext,
+    payer: &Keypair,
+    proposal: Pubkey,
+    remaining_accounts: &[Pubkey]
+) -> Result<(), TransportError> {
+    let state: Proposal = program_account(context, &proposal).await;
+    let mut ix = instruction(
+        psy_american::accounts::ExecuteProposal {
+            payer: payer.pubkey(),
+            governance: governance_key(),
+            proposal,
+            proposer: state.proposer,
+            target: state.action.target(),
+            system_program: system_program::ID,
+        },
+        psy_american::instruction::ExecuteProposal {},
+    );
+    ix.accounts.extend(remaining_accounts.iter().map(|key| AccountMeta::new_readonly(*key, false)));
+    process(context, ix, &[payer]).await
+}
+
+/// Propose with the first member and approve with the second, queueing the proposal
+async fn queue(context: &mut ProgramTestContext, members: &[Keypair], action: GovernanceAction) -> Pubkey {
+    let proposal = propose(context, &members[0], action).await.unwrap();
+    approve(context, &members[1], proposal).await.unwrap();
+    proposal
+}
+
+/// Queue the action and execute it once the timelock has passed
+async fn enact(context: &mut ProgramTestContext, members: &[Keypair], action: GovernanceAction) -> Result<(), TransportError> {
+    let proposal = queue(context, members, action).await;
+    let state: Proposal = program_account(context, &proposal).await;
+    warp_past(context, state.eta).await;
+    execute(context, &members[2], proposal).await
+}
+
+#[tokio::test]
+async fn proposals_queue_at_the_threshold() {
+    let (mut context, members) = setup().await;
+    let proposal = propose(&mut context, &members[0], GovernanceAction::SetListingEnforcement { enforce: true })
+        .await
+        .unwrap();
+    let state: Proposal = program_account(&mut context, &proposal).await;
+    assert_eq!(state.approvals, vec![members[0].pubkey()]);
+    assert!(!state.is_queued());
+
+    assert_error(approve(&mut context, &members[0], proposal).await, ErrorCode::ProposalAlreadyApproved);
+    approve(&mut context, &members[1], proposal).await.unwrap();
+    let state: Proposal = program_account(&mut context, &proposal).await;
+    assert!(state.is_queued());
+    let governance: Governance = program_account(&mut context, &governance_key()).await;
+    assert_eq!(governance.proposal_count, 1);
+}
+
+#[tokio::test]
+async fn only_members_propose_and_approve() {
+    let (mut context, members) = setup().await;
+    let outsider = &members[3];
+    let action = GovernanceAction::SetPauseFlags { flags: PauseFlags::default() };
+    assert_error(propose(&mut context, outsider, action.clone()).await.map(|_| ()), ErrorCode::NotGovernanceMember);
+
+    let proposal = propose(&mut context, &members[0], action).await.unwrap();
+    let ix = instruction(
+        psy_american::accounts::ApproveProposal { member: outsider.pubkey(), governance: governance_key(), proposal },
+        psy_american::instruction::ApproveProposal {},
+    );
+    assert_error(process(&mut context, ix, &[outsider]).await, ErrorCode::NotGovernanceMember);
+}
+
+#[tokio::test]
+async fn execution_waits_for_the_timelock() {
+    let (mut context, members) = setup().await;
+    // Unpausing the UNCONFIGURED flags
+    let flags = PauseFlags::default();
+    let proposal = queue(&mut context, &members, GovernanceAction::SetPauseFlags { flags }).await;
+    let state: Proposal = program_account(&mut context, &proposal).await;
+    assert_error(execute(&mut context, &members[2], proposal).await, ErrorCode::ProposalTimelocked);
+
+    warp_past(&mut context, state.eta).await;
+    execute(&mut context, &members[3], proposal).await.unwrap();
+    let pause_config: PauseConfig = program_account(&mut context, &pause_config_key()).await;
+    assert_eq!(pause_config.flags, flags);
+    assert!(context.banks_client.get_account(proposal).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn pausing_skips_the_timelock() {
+    let (mut context, members) = setup().await;
+    let exit_only = PauseFlags { exit_only: true, ..PauseFlags::default() };
+    let proposal = queue(&mut context, &members, GovernanceAction::SetPauseFlags { flags: exit_only }).await;
+    execute(&mut context, &members[3], proposal).await.unwrap();
+    let pause_config: PauseConfig = program_account(&mut context, &pause_config_key()).await;
+    assert_eq!(pause_config.flags, exit_only);
+
+    // Lifting exit only, even partially, waits out the timelock
+    let flags = PauseFlags { mint_option: true, ..PauseFlags::default() };
+    let proposal = queue(&mut context, &members, GovernanceAction::SetPauseFlags { flags }).await;
+    assert_error(execute(&mut context, &members[3], proposal).await, ErrorCode::ProposalTimelocked);
+}
+
+#[tokio::test]
+async fn halting_a_serum_market_skips_the_timelock() {
+    let fixture = MarketFixture::default();
+    let pc_mint = fixture.market.quote_asset_mint;
+    let serum_market = price_band::serum_market_address(&fixture.key, &pc_mint);
+    let (price_band_key, bump_seed) = price_band::price_band_address(&serum_market);
+    let oracle = Pubkey::new_unique();
+    let config = PriceBandConfig {
+        option_market: fixture.key,
+        serum_market,
+        oracle,
+        lower_band_bps: 5_000,
+        upper_band_bps: 5_000,
+        bump_seed,
+        ..PriceBandConfig::default()
+    };
+    let (mut context, members) = setup_with(|program_test| add_program_account(program_test, &price_band_key, &config)).await;
+    let set_price_band = |lower_band_bps, halted| GovernanceAction::SetPriceBand {
+        option_market: fixture.key,
+        pc_mint,
+        oracle,
+        lower_band_bps,
+        upper_band_bps: 5_000,
+        halted,
+    };
+    // The halt does not read the oracle, which may be why trading is halted
+    let proposal = queue(&mut context, &members, set_price_band(5_000, true)).await;
+    execute_with(&mut context, &members[3], proposal, &[oracle]).await.unwrap();
+    let band: PriceBandConfig = program_account(&mut context, &price_band_key).await;
+    assert!(band.halted);
+
+    // Changing the band while halting, and resuming trading, wait out the timelock
+    let proposal = queue(&mut context, &members, set_price_band(10_000, true)).await;
+    assert_error(execute_with(&mut context, &members[3], proposal, &[oracle]).await, ErrorCode::ProposalTimelocked);
+    let proposal = queue(&mut context, &members, set_price_band(5_000, false)).await;
+    assert_error(execute_with(&mut context, &members[3], proposal, &[oracle]).await, ErrorCode::ProposalTimelocked);
+}
+
+#[tokio::test]
+async fn unapproved_proposals_do_not_execute() {
+    let (mut context, members) = setup().await;
+    let proposal = propose(&mut context, &members[0], GovernanceAction::SetListingEnforcement { enforce: true })
+        .await
+        .unwrap();
+    assert_error(execute(&mut context, &members[0], proposal).await, ErrorCode::ProposalNotApproved);
+}
+
+#[tokio::test]
+async fn execution_requires_the_action_target() {
+    let (mut context, members) = setup().await;
+    let proposal = queue(&mut context, &members, GovernanceAction::SetPauseFlags { flags: PauseFlags::default() }).await;
+    let state: Proposal = program_account(&mut context, &proposal).await;
+    warp_past(&mut context, state.eta).await;
+    let ix = instruction(
+        psy_american::accounts::ExecuteProposal {
+            payer: members[0].pubkey(),
+            governance: governance_key(),
+            proposal,
+            proposer: state.proposer,
+            target: governance::proposal_address(7).0,
+            system_program: system_program::ID,
+        },
+        psy_american::instruction::ExecuteProposal {},
+    );
+    assert_error(process(&mut context, ix, &[&members[0]]).await, ErrorCode::ProposalTargetDoesNotMatch);
+}
+
+#[tokio::test]
+async fn governance_lists_and_delists_pairs() {
+    let (mut context, members) = setup().await;
+    let underlying_asset_mint = Pubkey::new_unique();
+    let quote_asset_mint = Pubkey::new_unique();
+    let listed_pair = Pubkey::find_program_address(
+        &[listing::LISTED_PAIR_SEED, underlying_asset_mint.as_ref(), quote_asset_mint.as_ref()],
+        &psy_american::ID,
+    ).0;
+
+    let proposal = queue(&mut context, &members, GovernanceAction::ListPair { underlying_asset_mint, quote_asset_mint }).await;
+    let state: Proposal = program_account(&mut context, &proposal).await;
+    warp_past(&mut context, state.eta).await;
+    execute(&mut context, &members[2], proposal).await.unwrap();
+    let pair: ListedPair = program_account(&mut context, &listed_pair).await;
+    assert_eq!(pair.underlying_asset_mint, underlying_asset_mint);
+    assert_eq!(pair.quote_asset_mint, quote_asset_mint);
+
+    let proposal = queue(&mut context, &members, GovernanceAction::DelistPair { underlying_asset_mint, quote_asset_mint }).await;
+    let state: Proposal = program_account(&mut context, &proposal).await;
+    warp_past(&mut context, state.eta).await;
+    execute(&mut context, &members[2], proposal).await.unwrap();
+    assert!(context.banks_client.get_account(listed_pair).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn governance_approves_and_revokes_yield_adapters() {
+    let (mut context, members) = setup().await;
+    let adapter_program = Pubkey::new_unique();
+    let yield_adapter = Pubkey::find_program_address(
+        &[yield_vault::YIELD_ADAPTER_SEED, adapter_program.as_ref()],
+        &psy_american::ID,
+    ).0;
+
+    enact(&mut context, &members, GovernanceAction::ApproveYieldAdapter { adapter_program }).await.unwrap();
+    let adapter: YieldAdapter = program_account(&mut context, &yield_adapter).await;
+    assert_eq!(adapter.adapter_program, adapter_program);
+
+    enact(&mut context, &members, GovernanceAction::RevokeYieldAdapter { adapter_program }).await.unwrap();
+    assert!(context.banks_client.get_account(yield_adapter).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn governance_creates_prefunded_targets() {
+    let (mut context, members) = setup().await;
+    let underlying_asset_mint = Pubkey::new_unique();
+    let quote_asset_mint = Pubkey::new_unique();
+    let action = GovernanceAction::ListPair { underlying_asset_mint, quote_asset_mint };
+    // Anyone can send lamports to the PDA before it is created
+    let lamports = Rent::default().minimum_balance(0);
+    let ix = system_instruction::transfer(&members[3].pubkey(), &action.target(), lamports);
+    process(&mut context, ix, &[&members[3]]).await.unwrap();
+
+    let proposal = queue(&mut context, &members, action.clone()).await;
+    let state: Proposal = program_account(&mut context, &proposal).await;
+    warp_past(&mut context, state.eta).await;
+    execute(&mut context, &members[2], proposal).await.unwrap();
+    let pair: ListedPair = program_account(&mut context, &action.target()).await;
+    assert_eq!(pair.underlying_asset_mint, underlying_asset_mint);
+    let account = context.banks_client.get_account(action.target()).await.unwrap().unwrap();
+    assert_eq!(account.owner, psy_american::ID);
+    assert_eq!(account.lamports, Rent::default().minimum_balance(account.data.len()));
+}
+
+#[tokio::test]
+async fn governance_sets_and_closes_series_configs() {
+    let (mut context, members) = setup().await;
+    let underlying_asset_mint = Pubkey::new_unique();
+    let quote_asset_mint = Pubkey::new_unique();
+    let set_series_config = |strike_increment| GovernanceAction::SetSeriesConfig {
+        underlying_asset_mint,
+        quote_asset_mint,
+        underlying_amount_per_contract: UNDERLYING_AMOUNT_PER_CONTRACT,
+        strike_increment,
+        expiration_interval: series::SECONDS_PER_WEEK,
+        expiration_offset: series::FRIDAY_0800_UTC_OFFSET,
+    };
+    let series_config_key = set_series_config(1).target();
+
+    enact(&mut context, &members, set_series_config(1)).await.unwrap();
+    let series_config: SeriesConfig = program_account(&mut context, &series_config_key).await;
+    assert_eq!(series_config.underlying_asset_mint, underlying_asset_mint);
+    assert_eq!(series_config.strike_increment, 1);
+
+    enact(&mut context, &members, set_series_config(10)).await.unwrap();
+    let series_config: SeriesConfig = program_account(&mut context, &series_config_key).await;
+    assert_eq!(series_config.strike_increment, 10);
+
+    enact(&mut context, &members, GovernanceAction::CloseSeriesConfig { underlying_asset_mint, quote_asset_mint })
+        .await
+        .unwrap();
+    assert!(context.banks_client.get_account(series_config_key).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn governance_flags_and_upgrades_markets() {
+    let fixture = MarketFixture::default().legacy();
+    let (mut context, members) = setup_with(|program_test| fixture.add_to(program_test, 0, 0)).await;
+    let option_market = fixture.key;
+
+    enact(&mut context, &members, GovernanceAction::SetMarketOfficial { option_market, official: true })
+        .await
+        .unwrap();
+    let creator = Pubkey::new_unique();
+    enact(&mut context, &members, GovernanceAction::UpgradeMarket { option_market, creator, created_at: 1 })
+        .await
+        .unwrap();
+    let market: OptionMarket = program_account(&mut context, &option_market).await;
+    assert!(market.official);
+    assert_eq!(market.creator, creator);
+    assert_eq!(market.created_at, 1);
+
+    let action = GovernanceAction::UpgradeMarket { option_market, creator: Pubkey::new_unique(), created_at: 2 };
+    assert_error(enact(&mut context, &members, action).await, ErrorCode::MarketAlreadyUpgraded);
+}
+
+#[tokio::test]
+async fn governance_updates_its_members() {
+    let (mut context, members) = setup().await;
+    let new_members = vec![members[0].pubkey(), members[2].pubkey()];
+    let action = GovernanceAction::UpdateGovernance {
+        members: new_members.clone(),
+        threshold: 1,
+        timelock_seconds: 2 * TIMELOCK,
+    };
+    let proposal = queue(&mut context, &members, action).await;
+    let state: Proposal = program_account(&mut context, &proposal).await;
+    warp_past(&mut context, state.eta).await;
+    execute(&mut context, &members[2], proposal).await.unwrap();
+
+    let governance: Governance = program_account(&mut context, &governance_key()).await;
+    assert_eq!(governance.members, new_members);
+    assert_eq!(governance.threshold, 1);
+    assert_eq!(governance.timelock_seconds, 2 * TIMELOCK);
+    assert_error(
+        propose(&mut context, &members[1], GovernanceAction::SetListingEnforcement { enforce: false }).await.map(|_| ()),
+        ErrorCode::NotGovernanceMember,
+    );
+}
+
+#[tokio::test]
+async fn invalid_actions_are_rejected_when_proposed() {
+    let (mut context, members) = setup().await;
+    let action = GovernanceAction::UpdateGovernance {
+        members: vec![members[0].pubkey()],
+        threshold: 2,
+        timelock_seconds: TIMELOCK,
+    };
+    assert_error(propose(&mut context, &members[0], action).await.map(|_| ()), ErrorCode::InvalidGovernanceConfig);
+}
+
+#[tokio::test]
+async fn only_the_proposer_cancels() {
+    let (mut context, members) = setup().await;
+    let proposal = queue(&mut context, &members, GovernanceAction::SetPauseFlags { flags: PauseFlags::default() }).await;
+    let cancel = |proposer: &Keypair| instruction(
+        psy_american::accounts::CancelProposal { proposer: proposer.pubkey(), proposal },
+        psy_american::instruction::CancelProposal {},
+    );
+    assert_error(process(&mut context, cancel(&members[1]), &[&members[1]]).await, ErrorCode::NotProposer);
+    process(&mut context, cancel(&members[0]), &[&members[0]]).await.unwrap();
+    assert!(context.banks_client.get_account(proposal).await.unwrap().is_none());
+}
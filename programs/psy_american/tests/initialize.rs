@@ -0,0 +1,327 @@
+mod common;
+
+use common::*;
+use psy_american::{errors::ErrorCode, fees, listing, option_chain, series, ListedPair, Lister, ListingRegistry, OptionChainEntry, SeriesConfig};
+use solana_program::system_program;
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::{Keypair, Signer}};
+
+fn program_pda(seeds: &[&[u8]]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, &psy_american::ID)
+}
+
+fn initialize_accounts(
+    payer: &Pubkey,
+    underlying_asset_mint: Pubkey,
+    quote_asset_mint: Pubkey,
+    expiration_unix_timestamp: i64
+) -> (psy_american::accounts::InitializeMarket, psy_american::instruction::InitializeMarket) {
+    let (option_market, bump_seed) = series::market_address(
+        &underlying_asset_mint,
+        &quote_asset_mint,
+        UNDERLYING_AMOUNT_PER_CONTRACT,
+        QUOTE_AMOUNT_PER_CONTRACT,
+        expiration_unix_timestamp,
+    );
+    let market_pda = |seed: &[u8]| Pubkey::find_program_address(&[option_market.as_ref(), seed], &psy_american::ID).0;
+    let accounts = psy_american::accounts::InitializeMarket {
+        authority: *payer,
+        underlying_asset_mint,
+        quote_asset_mint,
+        option_mint: market_pda(b"optionToken"),
+        writer_token_mint: market_pda(b"writerToken"),
+        quote_asset_pool: market_pda(b"quoteAssetPool"),
+        underlying_asset_pool: market_pda(b"underlyingAssetPool"),
+        option_market,
+        fee_owner: fees::fee_owner_key::ID,
+        token_program: spl_token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+        rent: rent_sysvar(),
+        system_program: system_program::ID,
+        clock: clock_sysvar(),
+        listing_registry: program_pda(&[listing::LISTING_REGISTRY_SEED]).0,
+        listed_pair: program_pda(&[listing::LISTED_PAIR_SEED, underlying_asset_mint.as_ref(), quote_asset_mint.as_ref()]).0,
+        lister: program_pda(&[listing::LISTER_SEED, payer.as_ref()]).0,
+        series_config: program_pda(&[series::SERIES_CONFIG_SEED, underlying_asset_mint.as_ref(), quote_asset_mint.as_ref()]).0,
+        option_chain: option_chain::page_address(&underlying_asset_mint, &quote_asset_mint, 0).0,
+        pause_config: pause_config_key(),
+    };
+    let args = psy_american::instruction::InitializeMarket {
+        underlying_amount_per_contract: UNDERLYING_AMOUNT_PER_CONTRACT,
+        quote_amount_per_contract: QUOTE_AMOUNT_PER_CONTRACT,
+        expiration_unix_timestamp,
+        bump_seed,
+        option_decimals: 0,
+    };
+    (accounts, args)
+}
+
+async fn setup() -> (ProgramTestContext, Pubkey, Pubkey) {
+    setup_with(|_, _, _| {}).await
+}
+
+/// Setup with `configure` adding accounts for the underlying and quote asset mints. Both
+/// orderings of the pair have an empty OptionChain.
+async fn setup_with(configure: impl FnOnce(&mut ProgramTest, &Pubkey, &Pubkey)) -> (ProgramTestContext, Pubkey, Pubkey) {
+    let mut program_test = program_test();
+    let underlying_asset_mint = Pubkey::new_unique();
+    let quote_asset_mint = Pubkey::new_unique();
+    add_mint(&mut program_test, &underlying_asset_mint, &Pubkey::new_unique(), 0);
+    add_mint(&mut program_test, &quote_asset_mint, &Pubkey::new_unique(), 0);
+    add_option_chain(&mut program_test, &underlying_asset_mint, &quote_asset_mint, 0, vec![]);
+    add_option_chain(&mut program_test, &quote_asset_mint, &underlying_asset_mint, 0, vec![]);
+    configure(&mut program_test, &underlying_asset_mint, &quote_asset_mint);
+    (program_test.start_with_context().await, underlying_asset_mint, quote_asset_mint)
+}
+
+fn add_listing_registry(program_test: &mut ProgramTest, enforce: bool) {
+    let (key, bump_seed) = program_pda(&[listing::LISTING_REGISTRY_SEED]);
+    add_program_account(program_test, &key, &ListingRegistry { enforce, bump_seed });
+}
+
+fn add_listed_pair(program_test: &mut ProgramTest, underlying_asset_mint: &Pubkey, quote_asset_mint: &Pubkey) {
+    let (key, bump_seed) = program_pda(&[listing::LISTED_PAIR_SEED, underlying_asset_mint.as_ref(), quote_asset_mint.as_ref()]);
+    add_program_account(program_test, &key, &ListedPair {
+        underlying_asset_mint: *underlying_asset_mint,
+        quote_asset_mint: *quote_asset_mint,
+        bump_seed,
+    });
+}
+
+/// Weekly series on Fridays at 08:00 UTC
+fn add_series_config(program_test: &mut ProgramTest, underlying_asset_mint: &Pubkey, quote_asset_mint: &Pubkey, strike_increment: u64) {
+    let (key, bump_seed) = program_pda(&[series::SERIES_CONFIG_SEED, underlying_asset_mint.as_ref(), quote_asset_mint.as_ref()]);
+    add_program_account(program_test, &key, &SeriesConfig {
+        underlying_asset_mint: *underlying_asset_mint,
+        quote_asset_mint: *quote_asset_mint,
+        underlying_amount_per_contract: UNDERLYING_AMOUNT_PER_CONTRACT,
+        strike_increment,
+        expiration_interval: series::SECONDS_PER_WEEK,
+        expiration_offset: series::FRIDAY_0800_UTC_OFFSET,
+        bump_seed,
+    });
+}
+
+/// Add a funded wallet that is an authorized lister
+fn add_lister(program_test: &mut ProgramTest, authority: &Keypair) {
+    let (key, bump_seed) = program_pda(&[listing::LISTER_SEED, authority.pubkey().as_ref()]);
+    add_program_account(program_test, &key, &Lister { authority: authority.pubkey(), bump_seed });
+    program_test.add_account(authority.pubkey(), Account {
+        lamports: 100_000_000_000,
+        data: vec![],
+        owner: system_program::ID,
+        executable: false,
+        rent_epoch: 0,
+    });
+}
+
+#[tokio::test]
+async fn initialize_market_is_paused_by_default() {
+    let (mut context, underlying_asset_mint, quote_asset_mint) = setup().await;
+    let (accounts, args) = initialize_accounts(&context.payer.pubkey(), underlying_asset_mint, quote_asset_mint, EXPIRATION);
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::InstructionPaused);
+
+    // Nothing was created
+    let option_market = series::market_address(
+        &underlying_asset_mint,
+        &quote_asset_mint,
+        UNDERLYING_AMOUNT_PER_CONTRACT,
+        QUOTE_AMOUNT_PER_CONTRACT,
+        EXPIRATION,
+    ).0;
+    assert!(context.banks_client.get_account(option_market).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn initialize_market_rejects_matching_assets_before_pausing() {
+    let (mut context, underlying_asset_mint, _) = setup_with(|program_test, underlying, _| {
+        add_option_chain(program_test, underlying, underlying, 0, vec![]);
+    }).await;
+    let (accounts, args) = initialize_accounts(&context.payer.pubkey(), underlying_asset_mint, underlying_asset_mint, EXPIRATION);
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::QuoteAndUnderlyingAssetMustDiffer);
+}
+
+#[tokio::test]
+async fn initialize_market_rejects_too_many_option_decimals_before_pausing() {
+    let (mut context, underlying_asset_mint, quote_asset_mint) = setup().await;
+    let (accounts, mut args) = initialize_accounts(&context.payer.pubkey(), underlying_asset_mint, quote_asset_mint, EXPIRATION);
+    args.option_decimals = psy_american::contracts::MAX_OPTION_DECIMALS + 1;
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::OptionDecimalsTooLarge);
+}
+
+#[tokio::test]
+async fn initialize_market_rejects_unlisted_pairs_when_enforced() {
+    let (mut context, underlying_asset_mint, quote_asset_mint) = setup_with(|program_test, _, _| {
+        add_listing_registry(program_test, true);
+    }).await;
+    let (accounts, args) = initialize_accounts(&context.payer.pubkey(), underlying_asset_mint, quote_asset_mint, EXPIRATION);
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::MarketNotListed);
+}
+
+#[tokio::test]
+async fn initialize_market_allows_listed_pairs_when_enforced() {
+    let (mut context, underlying_asset_mint, quote_asset_mint) = setup_with(|program_test, underlying, quote| {
+        add_listing_registry(program_test, true);
+        add_listed_pair(program_test, underlying, quote);
+    }).await;
+    let (accounts, args) = initialize_accounts(&context.payer.pubkey(), underlying_asset_mint, quote_asset_mint, EXPIRATION);
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::InstructionPaused);
+
+    // The listing is for the ordered pair
+    let (accounts, args) = initialize_accounts(&context.payer.pubkey(), quote_asset_mint, underlying_asset_mint, EXPIRATION);
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::MarketNotListed);
+}
+
+#[tokio::test]
+async fn initialize_market_allows_authorized_listers_when_enforced() {
+    let lister = Keypair::new();
+    let (mut context, underlying_asset_mint, quote_asset_mint) = setup_with(|program_test, _, _| {
+        add_listing_registry(program_test, true);
+        add_lister(program_test, &lister);
+    }).await;
+    let (accounts, args) = initialize_accounts(&lister.pubkey(), underlying_asset_mint, quote_asset_mint, EXPIRATION);
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[&lister]).await, ErrorCode::InstructionPaused);
+}
+
+#[tokio::test]
+async fn initialize_market_allows_unlisted_pairs_when_not_enforced() {
+    let (mut context, underlying_asset_mint, quote_asset_mint) = setup_with(|program_test, _, _| {
+        add_listing_registry(program_test, false);
+    }).await;
+    let (accounts, args) = initialize_accounts(&context.payer.pubkey(), underlying_asset_mint, quote_asset_mint, EXPIRATION);
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::InstructionPaused);
+}
+
+#[tokio::test]
+async fn listing_requires_the_fee_owner() {
+    let (mut context, underlying_asset_mint, quote_asset_mint) = setup().await;
+    let not_fee_owner = Keypair::new();
+    let ix = instruction(
+        psy_american::accounts::ListPair {
+            payer: context.payer.pubkey(),
+            fee_owner: not_fee_owner.pubkey(),
+            governance: governance_key(),
+            underlying_asset_mint,
+            quote_asset_mint,
+            listed_pair: program_pda(&[listing::LISTED_PAIR_SEED, underlying_asset_mint.as_ref(), quote_asset_mint.as_ref()]).0,
+            system_program: system_program::ID,
+        },
+        psy_american::instruction::ListPair {},
+    );
+    assert_error(process(&mut context, ix, &[&not_fee_owner]).await, ErrorCode::FeeOwnerDoesNotMatchProgram);
+
+    let ix = instruction(
+        psy_american::accounts::InitListingRegistry {
+            payer: context.payer.pubkey(),
+            fee_owner: not_fee_owner.pubkey(),
+            governance: governance_key(),
+            listing_registry: program_pda(&[listing::LISTING_REGISTRY_SEED]).0,
+            system_program: system_program::ID,
+        },
+        psy_american::instruction::InitListingRegistry { enforce: true },
+    );
+    assert_error(process(&mut context, ix, &[&not_fee_owner]).await, ErrorCode::FeeOwnerDoesNotMatchProgram);
+}
+
+#[tokio::test]
+async fn set_market_official_requires_the_fee_owner() {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, 0, 0);
+    let mut context = program_test.start_with_context().await;
+    let not_fee_owner = Keypair::new();
+    let ix = instruction(
+        psy_american::accounts::SetMarketOfficial {
+            fee_owner: not_fee_owner.pubkey(),
+            governance: governance_key(),
+            option_market: fixture.key,
+        },
+        psy_american::instruction::SetMarketOfficial { official: true },
+    );
+    assert_error(process(&mut context, ix, &[&not_fee_owner]).await, ErrorCode::FeeOwnerDoesNotMatchProgram);
+}
+
+#[tokio::test]
+async fn initialize_market_follows_the_series_grid() {
+    let (mut context, underlying_asset_mint, quote_asset_mint) = setup_with(|program_test, underlying, quote| {
+        add_series_config(program_test, underlying, quote, QUOTE_AMOUNT_PER_CONTRACT / 10);
+    }).await;
+
+    // EXPIRATION is a Friday at 00:00 UTC
+    let (accounts, args) = initialize_accounts(&context.payer.pubkey(), underlying_asset_mint, quote_asset_mint, EXPIRATION);
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::SeriesExpirationNotAllowed);
+
+    let friday_0800 = EXPIRATION + 8 * 60 * 60;
+    let (accounts, args) = initialize_accounts(&context.payer.pubkey(), underlying_asset_mint, quote_asset_mint, friday_0800);
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::InstructionPaused);
+}
+
+#[tokio::test]
+async fn initialize_market_rejects_strikes_off_the_series_grid() {
+    let (mut context, underlying_asset_mint, quote_asset_mint) = setup_with(|program_test, underlying, quote| {
+        add_series_config(program_test, underlying, quote, QUOTE_AMOUNT_PER_CONTRACT / 10 + 1);
+    }).await;
+    let friday_0800 = EXPIRATION + 8 * 60 * 60;
+    let (accounts, args) = initialize_accounts(&context.payer.pubkey(), underlying_asset_mint, quote_asset_mint, friday_0800);
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::SeriesStrikeNotAllowed);
+}
+
+#[tokio::test]
+async fn initialize_market_requires_the_pairs_option_chain() {
+    let (mut context, underlying_asset_mint, quote_asset_mint) = setup().await;
+    let (mut accounts, args) = initialize_accounts(&context.payer.pubkey(), underlying_asset_mint, quote_asset_mint, EXPIRATION);
+    accounts.option_chain = option_chain::page_address(&quote_asset_mint, &underlying_asset_mint, 0).0;
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::OptionChainDoesNotMatchPair);
+}
+
+#[tokio::test]
+async fn initialize_market_does_not_require_an_option_chain() {
+    let mut program_test = program_test();
+    let underlying_asset_mint = Pubkey::new_unique();
+    let quote_asset_mint = Pubkey::new_unique();
+    add_mint(&mut program_test, &underlying_asset_mint, &Pubkey::new_unique(), 0);
+    add_mint(&mut program_test, &quote_asset_mint, &Pubkey::new_unique(), 0);
+    let mut context = program_test.start_with_context().await;
+    let (accounts, args) = initialize_accounts(&context.payer.pubkey(), underlying_asset_mint, quote_asset_mint, EXPIRATION);
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::InstructionPaused);
+
+    // Only the first page's address stands in for a missing OptionChain
+    let (mut accounts, args) = initialize_accounts(&context.payer.pubkey(), underlying_asset_mint, quote_asset_mint, EXPIRATION);
+    accounts.option_chain = option_chain::page_address(&underlying_asset_mint, &quote_asset_mint, 1).0;
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::OptionChainDoesNotMatchPair);
+}
+
+#[tokio::test]
+async fn initialize_market_cannot_skip_an_existing_option_chain() {
+    let (mut context, underlying_asset_mint, quote_asset_mint) = setup().await;
+    let (mut accounts, args) = initialize_accounts(&context.payer.pubkey(), underlying_asset_mint, quote_asset_mint, EXPIRATION);
+    accounts.option_chain = option_chain::page_address(&underlying_asset_mint, &quote_asset_mint, 1).0;
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::OptionChainDoesNotMatchPair);
+}
+
+#[tokio::test]
+async fn initialize_market_requires_room_in_the_option_chain() {
+    let (mut context, underlying_asset_mint, quote_asset_mint) = setup_with(|program_test, underlying, quote| {
+        let entries = vec![OptionChainEntry::default(); option_chain::OPTION_CHAIN_PAGE_CAPACITY];
+        add_option_chain(program_test, underlying, quote, 1, entries);
+    }).await;
+    let (mut accounts, args) = initialize_accounts(&context.payer.pubkey(), underlying_asset_mint, quote_asset_mint, EXPIRATION);
+    accounts.option_chain = option_chain::page_address(&underlying_asset_mint, &quote_asset_mint, 1).0;
+    let ix = instruction(accounts, args);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::OptionChainPageFull);
+}
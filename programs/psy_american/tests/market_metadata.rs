@@ -0,0 +1,84 @@
+mod common;
+
+use common::*;
+use psy_american::{errors::ErrorCode, OptionMarket};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::{Keypair, Signer}};
+
+fn label(text: &str) -> [u8; 32] {
+    let mut label = [0; 32];
+    label[..text.len()].copy_from_slice(text.as_bytes());
+    label
+}
+
+fn set_label_ix(fixture: &MarketFixture, creator: Pubkey, label: [u8; 32]) -> Instruction {
+    instruction(
+        psy_american::accounts::SetMarketLabel { creator, option_market: fixture.key },
+        psy_american::instruction::SetMarketLabel { label },
+    )
+}
+
+#[tokio::test]
+async fn creator_sets_the_market_label() {
+    let creator = Keypair::new();
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default().with_creator(creator.pubkey());
+    fixture.add_to(&mut program_test, 0, 0);
+    let mut context = program_test.start_with_context().await;
+
+    process(&mut context, set_label_ix(&fixture, creator.pubkey(), label("SOL weekly")), &[&creator]).await.unwrap();
+
+    let market: OptionMarket = program_account(&mut context, &fixture.key).await;
+    assert_eq!(market.label, label("SOL weekly"));
+    assert_eq!(market.creator, creator.pubkey());
+    assert_eq!(market.version, OptionMarket::CURRENT_VERSION);
+}
+
+#[tokio::test]
+async fn only_the_creator_sets_the_market_label() {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, 0, 0);
+    let mut context = program_test.start_with_context().await;
+
+    let not_creator = Keypair::new();
+    assert_error(
+        process(&mut context, set_label_ix(&fixture, not_creator.pubkey(), label("spam")), &[&not_creator]).await,
+        ErrorCode::NotMarketCreator,
+    );
+}
+
+#[tokio::test]
+async fn upgrade_market_requires_the_fee_owner() {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default().legacy();
+    fixture.add_to(&mut program_test, 0, 0);
+    let mut context = program_test.start_with_context().await;
+
+    let not_fee_owner = Keypair::new();
+    let ix = instruction(
+        psy_american::accounts::UpgradeMarket {
+            fee_owner: not_fee_owner.pubkey(),
+            governance: governance_key(),
+            option_market: fixture.key,
+        },
+        psy_american::instruction::UpgradeMarket { creator: not_fee_owner.pubkey(), created_at: 0 },
+    );
+    assert_error(process(&mut context, ix, &[&not_fee_owner]).await, ErrorCode::FeeOwnerDoesNotMatchProgram);
+}
+
+#[tokio::test]
+async fn legacy_markets_read_zeroed_metadata() {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default().legacy();
+    fixture.add_to(&mut program_test, 4, 1);
+    let mut context = program_test.start_with_context().await;
+
+    let market: OptionMarket = program_account(&mut context, &fixture.key).await;
+    assert_eq!(market.version, 0);
+    assert_eq!(market.creator, Pubkey::default());
+    assert_eq!(market.created_at, 0);
+
+    // Legacy markets keep working
+    let ix = instruction(verify_market_accounts(&fixture), psy_american::instruction::VerifyMarket {});
+    process(&mut context, ix, &[]).await.unwrap();
+}
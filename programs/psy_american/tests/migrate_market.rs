@@ -0,0 +1,74 @@
+mod common;
+
+use common::*;
+use psy_american::{errors::ErrorCode, migration, OptionMarket};
+use solana_program::system_program;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{instruction::Instruction, rent::Rent, signature::Signer};
+
+fn migrate_ix(context: &ProgramTestContext, fixture: &MarketFixture) -> Instruction {
+    instruction(
+        psy_american::accounts::MigrateMarket {
+            payer: context.payer.pubkey(),
+            option_market: fixture.key,
+            system_program: system_program::ID,
+        },
+        psy_american::instruction::MigrateMarket {},
+    )
+}
+
+fn verify_ix(fixture: &MarketFixture) -> Instruction {
+    instruction(verify_market_accounts(fixture), psy_american::instruction::VerifyMarket {})
+}
+
+#[tokio::test]
+async fn migrate_market_grows_legacy_markets() {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default().with_option_decimals(2).legacy();
+    fixture.add_to(&mut program_test, 10, 3);
+    let mut context = program_test.start_with_context().await;
+
+    let account = context.banks_client.get_account(fixture.key).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), migration::LEGACY_MARKET_SPACE);
+
+    let ix = migrate_ix(&context, &fixture);
+    process(&mut context, ix, &[]).await.unwrap();
+
+    let account = context.banks_client.get_account(fixture.key).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), migration::MARKET_SPACE);
+    assert!(account.lamports >= Rent::default().minimum_balance(migration::MARKET_SPACE));
+
+    let market: OptionMarket = program_account(&mut context, &fixture.key).await;
+    assert_eq!(market.version, OptionMarket::CURRENT_VERSION);
+    assert_eq!(market.option_mint, fixture.market.option_mint);
+    assert_eq!(market.underlying_asset_pool, fixture.market.underlying_asset_pool);
+    assert_eq!(market.expiration_unix_timestamp, fixture.market.expiration_unix_timestamp);
+    assert_eq!(market.option_decimals, 2);
+    assert_eq!(market.bump_seed, fixture.market.bump_seed);
+
+    // The migrated market keeps working
+    process(&mut context, verify_ix(&fixture), &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn migrate_market_only_migrates_once() {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, 0, 0);
+    let mut context = program_test.start_with_context().await;
+
+    let ix = migrate_ix(&context, &fixture);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::MarketAlreadyMigrated);
+}
+
+#[tokio::test]
+async fn legacy_and_migrated_markets_work_side_by_side() {
+    let mut program_test = program_test();
+    let legacy = MarketFixture::default().legacy();
+    legacy.add_to(&mut program_test, 5, 2);
+    let current = MarketFixture::new(UNDERLYING_AMOUNT_PER_CONTRACT, QUOTE_AMOUNT_PER_CONTRACT, EXPIRATION + 1);
+    current.add_to(&mut program_test, 5, 2);
+    let mut context = program_test.start_with_context().await;
+
+    process_all(&mut context, &[verify_ix(&legacy), verify_ix(&current)], &[]).await.unwrap();
+}
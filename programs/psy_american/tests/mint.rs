@@ -0,0 +1,242 @@
+mod common;
+
+use common::*;
+use psy_american::{errors::ErrorCode, fees, PauseFlags};
+use anchor_spl::dex;
+use solana_program::{instruction::AccountMeta, system_program, sysvar};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+async fn setup() -> (ProgramTestContext, MarketFixture, UserFixture) {
+    setup_with_pause_flags(None).await
+}
+
+async fn setup_with_pause_flags(flags: Option<PauseFlags>) -> (ProgramTestContext, MarketFixture, UserFixture) {
+    let mut program_test = program_test();
+    if let Some(flags) = flags {
+        add_pause_config(&mut program_test, flags);
+    }
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, 0, 0);
+    let user = UserFixture::add_to(&mut program_test, &fixture, 0, 0, 10 * UNDERLYING_AMOUNT_PER_CONTRACT, 0);
+    (program_test.start_with_context().await, fixture, user)
+}
+
+fn mint_accounts(fixture: &MarketFixture, user: &UserFixture) -> psy_american::accounts::MintOption {
+    psy_american::accounts::MintOption {
+        user_authority: user.authority.pubkey(),
+        underlying_asset_mint: fixture.market.underlying_asset_mint,
+        underlying_asset_pool: fixture.market.underlying_asset_pool,
+        underlying_asset_src: user.underlying_account,
+        option_mint: fixture.market.option_mint,
+        minted_option_dest: user.option_account,
+        writer_token_mint: fixture.market.writer_token_mint,
+        minted_writer_token_dest: user.writer_token_account,
+        option_market: fixture.key,
+        fee_owner: fees::fee_owner_key::ID,
+        token_program: spl_token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+        clock: clock_sysvar(),
+        rent: rent_sysvar(),
+        system_program: system_program::ID,
+        pause_config: pause_config_key(),
+    }
+}
+
+fn mint_v2_accounts(fixture: &MarketFixture, user: &UserFixture) -> psy_american::accounts::MintOptionV2 {
+    psy_american::accounts::MintOptionV2 {
+        user_authority: user.authority.pubkey(),
+        underlying_asset_mint: fixture.market.underlying_asset_mint,
+        underlying_asset_pool: fixture.market.underlying_asset_pool,
+        underlying_asset_src: user.underlying_account,
+        option_mint: fixture.market.option_mint,
+        minted_option_dest: user.option_account,
+        writer_token_mint: fixture.market.writer_token_mint,
+        minted_writer_token_dest: user.writer_token_account,
+        option_market: fixture.key,
+        token_program: spl_token::ID,
+        pause_config: pause_config_key(),
+    }
+}
+
+#[tokio::test]
+async fn mint_option_is_paused_by_default() {
+    let (mut context, fixture, user) = setup().await;
+    let ix = instruction(mint_accounts(&fixture, &user), psy_american::instruction::MintOption { size: 1 });
+    assert_error(process(&mut context, ix, &[&user.authority]).await, ErrorCode::InstructionPaused);
+}
+
+#[tokio::test]
+async fn mint_option_validates_before_pausing() {
+    let (mut context, fixture, user) = setup().await;
+    let cases: Vec<(Box<dyn Fn(&mut psy_american::accounts::MintOption)>, ErrorCode)> = vec![
+        (Box::new(|a| a.underlying_asset_pool = user.underlying_account), ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket),
+        (Box::new(|a| a.option_mint = fixture.market.writer_token_mint), ErrorCode::OptionTokenMintDoesNotMatchMarket),
+        (Box::new(|a| a.writer_token_mint = fixture.market.option_mint), ErrorCode::WriterTokenMintDoesNotMatchMarket),
+        (Box::new(|a| a.fee_owner = user.authority.pubkey()), ErrorCode::FeeOwnerDoesNotMatchProgram),
+    ];
+    for (modify, error) in cases {
+        let mut accounts = mint_accounts(&fixture, &user);
+        modify(&mut accounts);
+        let ix = instruction(accounts, psy_american::instruction::MintOption { size: 1 });
+        assert_error(process(&mut context, ix, &[&user.authority]).await, error);
+    }
+
+    let ix = instruction(mint_accounts(&fixture, &user), psy_american::instruction::MintOption { size: 0 });
+    assert_error(process(&mut context, ix, &[&user.authority]).await, ErrorCode::SizeCantBeLessThanEqZero);
+
+    warp_past(&mut context, EXPIRATION).await;
+    let ix = instruction(mint_accounts(&fixture, &user), psy_american::instruction::MintOption { size: 1 });
+    assert_error(process(&mut context, ix, &[&user.authority]).await, ErrorCode::OptionMarketExpiredCantMint);
+}
+
+#[tokio::test]
+async fn mint_option_v2_is_paused_by_default() {
+    let (mut context, fixture, user) = setup().await;
+    let ix = instruction(mint_v2_accounts(&fixture, &user), psy_american::instruction::MintOptionV2 { size: 1 });
+    assert_error(process(&mut context, ix, &[&user.authority]).await, ErrorCode::InstructionPaused);
+}
+
+#[tokio::test]
+async fn mint_option_v2_mints_when_unpaused() {
+    let (mut context, fixture, user) = setup_with_pause_flags(Some(PauseFlags::default())).await;
+    let ix = instruction(mint_v2_accounts(&fixture, &user), psy_american::instruction::MintOptionV2 { size: 2 });
+    process(&mut context, ix, &[&user.authority]).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &user.option_account).await, 2);
+    assert_eq!(token_balance(&mut context, &user.writer_token_account).await, 2);
+    assert_eq!(token_balance(&mut context, &user.underlying_account).await, 8 * UNDERLYING_AMOUNT_PER_CONTRACT);
+    assert_eq!(
+        token_balance(&mut context, &fixture.market.underlying_asset_pool).await,
+        2 * UNDERLYING_AMOUNT_PER_CONTRACT
+    );
+}
+
+#[tokio::test]
+async fn mint_option_v2_validates_before_pausing() {
+    let (mut context, fixture, user) = setup().await;
+    let cases: Vec<(Box<dyn Fn(&mut psy_american::accounts::MintOptionV2)>, ErrorCode)> = vec![
+        (Box::new(|a| a.underlying_asset_pool = user.underlying_account), ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket),
+        (Box::new(|a| a.option_mint = fixture.market.writer_token_mint), ErrorCode::OptionTokenMintDoesNotMatchMarket),
+        (Box::new(|a| a.writer_token_mint = fixture.market.option_mint), ErrorCode::WriterTokenMintDoesNotMatchMarket),
+    ];
+    for (modify, error) in cases {
+        let mut accounts = mint_v2_accounts(&fixture, &user);
+        modify(&mut accounts);
+        let ix = instruction(accounts, psy_american::instruction::MintOptionV2 { size: 1 });
+        assert_error(process(&mut context, ix, &[&user.authority]).await, error);
+    }
+
+    let ix = instruction(mint_v2_accounts(&fixture, &user), psy_american::instruction::MintOptionV2 { size: 0 });
+    assert_error(process(&mut context, ix, &[&user.authority]).await, ErrorCode::SizeCantBeLessThanEqZero);
+
+    warp_past(&mut context, EXPIRATION).await;
+    let ix = instruction(mint_v2_accounts(&fixture, &user), psy_american::instruction::MintOptionV2 { size: 1 });
+    assert_error(process(&mut context, ix, &[&user.authority]).await, ErrorCode::OptionMarketExpiredCantMint);
+}
+
+#[tokio::test]
+async fn composite_mints_are_paused_by_default() {
+    let (mut context, fixture, user) = setup().await;
+    let market = &fixture.market;
+    let mut batch_mint = instruction(
+        psy_american::accounts::BatchMint {
+            user_authority: user.authority.pubkey(),
+            token_program: spl_token::ID,
+            pause_config: pause_config_key(),
+        },
+        psy_american::instruction::BatchMint { sizes: vec![1] },
+    );
+    batch_mint.accounts.extend(vec![
+        AccountMeta::new_readonly(fixture.key, false),
+        AccountMeta::new(market.option_mint, false),
+        AccountMeta::new(user.option_account, false),
+        AccountMeta::new(market.writer_token_mint, false),
+        AccountMeta::new(user.writer_token_account, false),
+        AccountMeta::new(market.underlying_asset_pool, false),
+        AccountMeta::new(user.underlying_account, false),
+    ]);
+    let flash_mint = instruction(
+        psy_american::accounts::FlashMint {
+            user_authority: user.authority.pubkey(),
+            option_market: fixture.key,
+            option_mint: market.option_mint,
+            minted_option_dest: user.option_account,
+            writer_token_mint: market.writer_token_mint,
+            minted_writer_token_dest: user.writer_token_account,
+            instructions: sysvar::instructions::ID,
+            token_program: spl_token::ID,
+            pause_config: pause_config_key(),
+        },
+        psy_american::instruction::FlashMint { size: 1 },
+    );
+    // Rolling within the market passes every check before the pause
+    let roll_position = instruction(
+        psy_american::accounts::RollPosition {
+            user_authority: user.authority.pubkey(),
+            from_option_market: fixture.key,
+            from_option_mint: market.option_mint,
+            from_writer_token_mint: market.writer_token_mint,
+            option_token_src: user.option_account,
+            writer_token_src: user.writer_token_account,
+            from_underlying_asset_pool: market.underlying_asset_pool,
+            to_option_market: fixture.key,
+            to_option_mint: market.option_mint,
+            to_writer_token_mint: market.writer_token_mint,
+            minted_option_dest: user.option_account,
+            minted_writer_token_dest: user.writer_token_account,
+            to_underlying_asset_pool: market.underlying_asset_pool,
+            underlying_asset_account: user.underlying_account,
+            token_program: spl_token::ID,
+            pause_config: pause_config_key(),
+        },
+        psy_american::instruction::RollPosition { size: 1 },
+    );
+    // The Serum accounts are only used after the pause, so they do not need to exist
+    let serum_market = Pubkey::find_program_address(
+        &[&fixture.key.to_bytes()[..], &market.quote_asset_mint.to_bytes()[..], b"serumMarket"],
+        &psy_american::ID,
+    ).0;
+    let open_orders = Pubkey::find_program_address(
+        &[b"open-orders", dex::ID.as_ref(), serum_market.as_ref(), user.authority.pubkey().as_ref()],
+        &psy_american::ID,
+    ).0;
+    let mint_and_sell = instruction(
+        psy_american::accounts::MintAndSell {
+            user_authority: user.authority.pubkey(),
+            underlying_asset_mint: market.underlying_asset_mint,
+            underlying_asset_pool: market.underlying_asset_pool,
+            underlying_asset_src: user.underlying_account,
+            option_mint: market.option_mint,
+            minted_option_dest: user.option_account,
+            writer_token_mint: market.writer_token_mint,
+            minted_writer_token_dest: user.writer_token_account,
+            option_market: fixture.key,
+            dex_program: dex::ID,
+            pc_mint: market.quote_asset_mint,
+            serum_market,
+            open_orders,
+            request_queue: Pubkey::new_unique(),
+            event_queue: Pubkey::new_unique(),
+            market_bids: Pubkey::new_unique(),
+            market_asks: Pubkey::new_unique(),
+            coin_vault: Pubkey::new_unique(),
+            pc_vault: Pubkey::new_unique(),
+            price_band: Pubkey::new_unique(),
+            price_oracle: Pubkey::new_unique(),
+            psy_american_program: psy_american::ID,
+            token_program: spl_token::ID,
+            rent: rent_sysvar(),
+            pause_config: pause_config_key(),
+        },
+        psy_american::instruction::MintAndSell {
+            size: 1,
+            limit_price: 1,
+            order_type: psy_american::OrderType::Limit,
+            client_order_id: 0,
+        },
+    );
+    for ix in vec![batch_mint, flash_mint, roll_position, mint_and_sell] {
+        assert_error(process(&mut context, ix, &[&user.authority]).await, ErrorCode::InstructionPaused);
+    }
+}
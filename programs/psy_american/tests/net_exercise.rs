@@ -0,0 +1,209 @@
+mod common;
+
+use common::*;
+use psy_american::errors::ErrorCode;
+use solana_program::instruction::AccountMeta;
+use solana_program_test::{processor, ProgramTestContext};
+use solana_sdk::{account::Account, instruction::Instruction, pubkey::Pubkey, rent::Rent, signature::Signer};
+
+const WRITTEN: u64 = 10;
+const OPTIONS: u64 = 2;
+/// The quote vault of the mock AMM
+const AMM_QUOTE_LIQUIDITY: u64 = 1_000 * QUOTE_AMOUNT_PER_CONTRACT;
+
+/// A constant price pool on the mock AMM that buys the underlying for quote
+struct AmmFixture {
+    pool: Pubkey,
+    authority: Pubkey,
+    underlying_vault: Pubkey,
+    quote_vault: Pubkey,
+}
+
+struct Setup {
+    context: ProgramTestContext,
+    fixture: MarketFixture,
+    user: UserFixture,
+    amm: AmmFixture,
+}
+
+/// The AMM pays `quote_per_underlying` quote for each underlying, the strike is 5
+async fn setup(quote_per_underlying: u64) -> Setup {
+    let mut program_test = program_test();
+    program_test.add_program("mock_amm", mock_amm::ID, processor!(mock_amm::process_instruction));
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, WRITTEN, 0);
+    let user = UserFixture::add_to(&mut program_test, &fixture, OPTIONS, 0, 0, 0);
+
+    let pool = Pubkey::new_unique();
+    let (authority, authority_bump) = mock_amm::pool_authority(&pool);
+    let data = mock_amm::Pool { price_numerator: quote_per_underlying, price_denominator: 1, authority_bump }.pack();
+    program_test.add_account(pool, Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: mock_amm::ID,
+        executable: false,
+        rent_epoch: 0,
+    });
+    let amm = AmmFixture {
+        pool,
+        authority,
+        underlying_vault: Pubkey::new_unique(),
+        quote_vault: Pubkey::new_unique(),
+    };
+    add_token_account(&mut program_test, &amm.underlying_vault, &fixture.market.underlying_asset_mint, &authority, 0);
+    add_token_account(&mut program_test, &amm.quote_vault, &fixture.market.quote_asset_mint, &authority, AMM_QUOTE_LIQUIDITY);
+
+    Setup {
+        context: program_test.start_with_context().await,
+        fixture,
+        user,
+        amm,
+    }
+}
+
+fn net_exercise_accounts(setup: &Setup) -> psy_american::accounts::NetExercise {
+    psy_american::accounts::NetExercise {
+        user_authority: setup.user.authority.pubkey(),
+        option_market: setup.fixture.key,
+        option_mint: setup.fixture.market.option_mint,
+        exerciser_option_token_src: setup.user.option_account,
+        underlying_asset_pool: setup.fixture.market.underlying_asset_pool,
+        underlying_asset_dest: setup.user.underlying_account,
+        quote_asset_pool: setup.fixture.market.quote_asset_pool,
+        quote_asset_account: setup.user.quote_account,
+        amm_program: mock_amm::ID,
+        token_program: spl_token::ID,
+    }
+}
+
+/// The mock AMM's swap accounts, selling the user's underlying for quote into `quote_dest`
+fn swap_accounts(setup: &Setup, quote_dest: Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(setup.amm.pool, false),
+        AccountMeta::new_readonly(setup.amm.authority, false),
+        AccountMeta::new_readonly(setup.user.authority.pubkey(), true),
+        AccountMeta::new(setup.user.underlying_account, false),
+        AccountMeta::new(setup.amm.underlying_vault, false),
+        AccountMeta::new(setup.amm.quote_vault, false),
+        AccountMeta::new(quote_dest, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+    ]
+}
+
+fn net_exercise_ix(
+    accounts: psy_american::accounts::NetExercise,
+    swap_accounts: Vec<AccountMeta>,
+    size: u64,
+    underlying_in: u64
+) -> Instruction {
+    let mut ix = instruction(accounts, psy_american::instruction::NetExercise { size, underlying_in });
+    ix.accounts.extend(swap_accounts);
+    ix
+}
+
+async fn net_exercise(setup: &mut Setup, size: u64, underlying_in: u64) -> Result<(), solana_sdk::transport::TransportError> {
+    let ix = net_exercise_ix(net_exercise_accounts(setup), swap_accounts(setup, setup.user.quote_account), size, underlying_in);
+    process(&mut setup.context, ix, &[&setup.user.authority]).await
+}
+
+#[tokio::test]
+async fn net_exercise_pays_the_strike_from_the_swap() {
+    // At 8 quote per underlying, 12.5M of the 20M underlying pays the 100M strike
+    let mut setup = setup(8).await;
+    net_exercise(&mut setup, OPTIONS, 12_500_000).await.unwrap();
+
+    let context = &mut setup.context;
+    assert_eq!(token_balance(context, &setup.user.option_account).await, 0);
+    assert_eq!(mint_supply(context, &setup.fixture.market.option_mint).await, WRITTEN - OPTIONS);
+    assert_eq!(token_balance(context, &setup.user.underlying_account).await, 7_500_000);
+    assert_eq!(token_balance(context, &setup.user.quote_account).await, 0);
+    assert_eq!(
+        token_balance(context, &setup.fixture.market.underlying_asset_pool).await,
+        (WRITTEN - OPTIONS) * UNDERLYING_AMOUNT_PER_CONTRACT
+    );
+    assert_eq!(
+        token_balance(context, &setup.fixture.market.quote_asset_pool).await,
+        OPTIONS * QUOTE_AMOUNT_PER_CONTRACT
+    );
+}
+
+#[tokio::test]
+async fn net_exercise_keeps_quote_beyond_the_strike() {
+    let mut setup = setup(8).await;
+    net_exercise(&mut setup, OPTIONS, 15_000_000).await.unwrap();
+
+    let context = &mut setup.context;
+    assert_eq!(token_balance(context, &setup.user.underlying_account).await, 5_000_000);
+    assert_eq!(token_balance(context, &setup.user.quote_account).await, 20_000_000);
+    assert_eq!(
+        token_balance(context, &setup.fixture.market.quote_asset_pool).await,
+        OPTIONS * QUOTE_AMOUNT_PER_CONTRACT
+    );
+}
+
+#[tokio::test]
+async fn net_exercise_fails_out_of_the_money() {
+    // At 4 quote per underlying, all 20M underlying only buys 80M quote
+    let mut setup = setup(4).await;
+    assert!(net_exercise(&mut setup, OPTIONS, 2 * UNDERLYING_AMOUNT_PER_CONTRACT).await.is_err());
+
+    let context = &mut setup.context;
+    assert_eq!(token_balance(context, &setup.user.option_account).await, OPTIONS);
+    assert_eq!(token_balance(context, &setup.user.underlying_account).await, 0);
+    assert_eq!(token_balance(context, &setup.fixture.market.quote_asset_pool).await, 0);
+}
+
+#[tokio::test]
+async fn net_exercise_only_swaps_released_underlying() {
+    let mut setup = setup(8).await;
+    assert_error(
+        net_exercise(&mut setup, OPTIONS, 2 * UNDERLYING_AMOUNT_PER_CONTRACT + 1).await,
+        ErrorCode::NetExerciseSwapsTooMuchUnderlying,
+    );
+}
+
+#[tokio::test]
+async fn net_exercise_requires_the_swap_to_pay_the_quote_account() {
+    let mut setup = setup(8).await;
+    // The swap proceeds go to another quote account, so the strike is not covered
+    let other_quote_account = setup.amm.quote_vault;
+    let ix = net_exercise_ix(
+        net_exercise_accounts(&setup),
+        swap_accounts(&setup, other_quote_account),
+        OPTIONS,
+        12_500_000,
+    );
+    assert_error(process(&mut setup.context, ix, &[&setup.user.authority]).await, ErrorCode::SwapDidNotCoverStrike);
+}
+
+#[tokio::test]
+async fn net_exercise_fails_after_expiration() {
+    let mut setup = setup(8).await;
+    warp_past(&mut setup.context, EXPIRATION).await;
+    assert_error(net_exercise(&mut setup, OPTIONS, 12_500_000).await, ErrorCode::OptionMarketExpiredCantExercise);
+}
+
+#[tokio::test]
+async fn net_exercise_validates_market_accounts() {
+    let mut setup = setup(8).await;
+    let market = &setup.fixture.market;
+    let cases: Vec<(Box<dyn Fn(&mut psy_american::accounts::NetExercise)>, ErrorCode)> = vec![
+        (Box::new(|a| a.quote_asset_pool = market.underlying_asset_pool), ErrorCode::QuotePoolAccountDoesNotMatchMarket),
+        (Box::new(|a| a.underlying_asset_pool = market.quote_asset_pool), ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket),
+        (Box::new(|a| a.option_mint = market.writer_token_mint), ErrorCode::OptionTokenMintDoesNotMatchMarket),
+        (Box::new(|a| a.underlying_asset_dest = setup.user.quote_account), ErrorCode::UnderlyingDestMintDoesNotMatchUnderlyingAsset),
+        (Box::new(|a| a.quote_asset_account = setup.user.underlying_account), ErrorCode::QuoteMintDoesNotMatchMarket),
+    ];
+    let mut ixs = vec![];
+    for (modify, error) in cases {
+        let mut accounts = net_exercise_accounts(&setup);
+        modify(&mut accounts);
+        let swap_accounts = swap_accounts(&setup, setup.user.quote_account);
+        ixs.push((net_exercise_ix(accounts, swap_accounts, OPTIONS, 12_500_000), error));
+    }
+    for (ix, error) in ixs {
+        assert_error(process(&mut setup.context, ix, &[&setup.user.authority]).await, error);
+    }
+
+    assert_error(net_exercise(&mut setup, 0, 0).await, ErrorCode::SizeCantBeLessThanEqZero);
+}
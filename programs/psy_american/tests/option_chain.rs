@@ -0,0 +1,151 @@
+mod common;
+
+use common::*;
+use psy_american::{errors::ErrorCode, option_chain, OptionChain, OptionChainEntry};
+use solana_program::system_program;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signer};
+
+fn entry(fixture: &MarketFixture) -> OptionChainEntry {
+    OptionChainEntry {
+        option_market: fixture.key,
+        underlying_amount_per_contract: fixture.market.underlying_amount_per_contract,
+        quote_amount_per_contract: fixture.market.quote_amount_per_contract,
+        expiration_unix_timestamp: fixture.market.expiration_unix_timestamp,
+    }
+}
+
+/// A full page of other markets
+fn full_page() -> Vec<OptionChainEntry> {
+    (0..option_chain::OPTION_CHAIN_PAGE_CAPACITY)
+        .map(|_| OptionChainEntry { option_market: Pubkey::new_unique(), ..OptionChainEntry::default() })
+        .collect()
+}
+
+fn extend_ix(payer: Pubkey, underlying_asset_mint: &Pubkey, quote_asset_mint: &Pubkey, page: u32) -> Instruction {
+    instruction(
+        psy_american::accounts::ExtendOptionChain {
+            payer,
+            option_chain: option_chain::page_address(underlying_asset_mint, quote_asset_mint, page).0,
+            next_page: option_chain::page_address(underlying_asset_mint, quote_asset_mint, page + 1).0,
+            system_program: system_program::ID,
+        },
+        psy_american::instruction::ExtendOptionChain {},
+    )
+}
+
+fn remove_ix(fixture: &MarketFixture, option_chain: Pubkey) -> Instruction {
+    instruction(
+        psy_american::accounts::RemoveFromOptionChain {
+            option_chain,
+            option_market: fixture.key,
+            writer_token_mint: fixture.market.writer_token_mint,
+            clock: clock_sysvar(),
+        },
+        psy_american::instruction::RemoveFromOptionChain {},
+    )
+}
+
+#[tokio::test]
+async fn init_option_chain_creates_the_first_page() {
+    let mut program_test = program_test();
+    let underlying_asset_mint = Pubkey::new_unique();
+    let quote_asset_mint = Pubkey::new_unique();
+    add_mint(&mut program_test, &underlying_asset_mint, &Pubkey::new_unique(), 0);
+    add_mint(&mut program_test, &quote_asset_mint, &Pubkey::new_unique(), 0);
+    let mut context = program_test.start_with_context().await;
+
+    let key = option_chain::page_address(&underlying_asset_mint, &quote_asset_mint, 0).0;
+    let ix = instruction(
+        psy_american::accounts::InitOptionChain {
+            payer: context.payer.pubkey(),
+            underlying_asset_mint,
+            quote_asset_mint,
+            option_chain: key,
+            system_program: system_program::ID,
+        },
+        psy_american::instruction::InitOptionChain {},
+    );
+    process(&mut context, ix, &[]).await.unwrap();
+
+    let chain: OptionChain = program_account(&mut context, &key).await;
+    assert_eq!(chain.underlying_asset_mint, underlying_asset_mint);
+    assert_eq!(chain.quote_asset_mint, quote_asset_mint);
+    assert_eq!(chain.page, 0);
+    assert_eq!(chain.next_page, Pubkey::default());
+    assert!(chain.entries.is_empty());
+}
+
+#[tokio::test]
+async fn extend_option_chain_links_a_page_after_a_full_page() {
+    let mut program_test = program_test();
+    let underlying_asset_mint = Pubkey::new_unique();
+    let quote_asset_mint = Pubkey::new_unique();
+    add_option_chain(&mut program_test, &underlying_asset_mint, &quote_asset_mint, 0, full_page());
+    let mut context = program_test.start_with_context().await;
+
+    let payer = context.payer.pubkey();
+    process(&mut context, extend_ix(payer, &underlying_asset_mint, &quote_asset_mint, 0), &[]).await.unwrap();
+
+    let page_1 = option_chain::page_address(&underlying_asset_mint, &quote_asset_mint, 1).0;
+    let head: OptionChain = program_account(&mut context, &option_chain::page_address(&underlying_asset_mint, &quote_asset_mint, 0).0).await;
+    assert_eq!(head.next_page, page_1);
+    let next: OptionChain = program_account(&mut context, &page_1).await;
+    assert_eq!(next.page, 1);
+    assert_eq!(next.underlying_asset_mint, underlying_asset_mint);
+    assert!(next.entries.is_empty());
+
+    // Page 0 is no longer the last page and page 1 has room
+    assert_error(
+        process(&mut context, extend_ix(payer, &underlying_asset_mint, &quote_asset_mint, 0), &[]).await,
+        ErrorCode::OptionChainNotLastPage,
+    );
+    assert_error(
+        process(&mut context, extend_ix(payer, &underlying_asset_mint, &quote_asset_mint, 1), &[]).await,
+        ErrorCode::OptionChainPageNotFull,
+    );
+}
+
+/// A chain page listing `fixture`, written with `written` contracts
+async fn setup_remove(written: u64) -> (ProgramTestContext, MarketFixture, Pubkey) {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, written, 0);
+    let other = OptionChainEntry { option_market: Pubkey::new_unique(), ..entry(&fixture) };
+    let key = add_option_chain(
+        &mut program_test,
+        &fixture.market.underlying_asset_mint,
+        &fixture.market.quote_asset_mint,
+        0,
+        vec![entry(&fixture), other],
+    );
+    (program_test.start_with_context().await, fixture, key)
+}
+
+#[tokio::test]
+async fn remove_from_option_chain_removes_closed_markets() {
+    let (mut context, fixture, key) = setup_remove(0).await;
+    warp_past(&mut context, EXPIRATION).await;
+    process(&mut context, remove_ix(&fixture, key), &[]).await.unwrap();
+
+    let chain: OptionChain = program_account(&mut context, &key).await;
+    assert_eq!(chain.entries.len(), 1);
+    assert_ne!(chain.entries[0].option_market, fixture.key);
+
+    // Already removed
+    warp_past(&mut context, EXPIRATION + 1).await;
+    assert_error(process(&mut context, remove_ix(&fixture, key), &[]).await, ErrorCode::OptionMarketNotInChain);
+}
+
+#[tokio::test]
+async fn remove_from_option_chain_keeps_open_markets() {
+    let (mut context, fixture, key) = setup_remove(0).await;
+    assert_error(process(&mut context, remove_ix(&fixture, key), &[]).await, ErrorCode::OptionMarketNotExpiredCantClose);
+
+    let (mut context, fixture, key) = setup_remove(1).await;
+    warp_past(&mut context, EXPIRATION).await;
+    assert_error(process(&mut context, remove_ix(&fixture, key), &[]).await, ErrorCode::OptionMarketHasOpenPositions);
+
+    let chain: OptionChain = program_account(&mut context, &key).await;
+    assert_eq!(chain.entries.len(), 2);
+}
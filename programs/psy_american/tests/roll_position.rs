@@ -0,0 +1,194 @@
+mod common;
+
+use common::*;
+use psy_american::{errors::ErrorCode, PauseFlags};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+const WRITTEN: u64 = 10;
+const POSITION: u64 = 4;
+const NEAR_EXPIRATION: i64 = EXPIRATION - 86_400;
+
+struct Setup {
+    context: ProgramTestContext,
+    from: MarketFixture,
+    to: MarketFixture,
+    user: UserFixture,
+    to_option_account: Pubkey,
+    to_writer_token_account: Pubkey,
+}
+
+/// A position in a near expiration market, rolling into a far one with `to_underlying_amount_per_contract`
+async fn setup(to_underlying_amount_per_contract: u64, underlying: u64) -> Setup {
+    let mut program_test = program_test();
+    add_pause_config(&mut program_test, PauseFlags::default());
+    let from = MarketFixture::new(UNDERLYING_AMOUNT_PER_CONTRACT, QUOTE_AMOUNT_PER_CONTRACT, NEAR_EXPIRATION);
+    let to = MarketFixture::with_mints(
+        from.market.underlying_asset_mint,
+        from.market.quote_asset_mint,
+        to_underlying_amount_per_contract,
+        QUOTE_AMOUNT_PER_CONTRACT,
+        EXPIRATION,
+    );
+    from.add_to(&mut program_test, WRITTEN, 0);
+    to.add_to(&mut program_test, 0, 0);
+    let user = UserFixture::add_to(&mut program_test, &from, POSITION, POSITION, underlying, 0);
+    let to_option_account = Pubkey::new_unique();
+    let to_writer_token_account = Pubkey::new_unique();
+    add_token_account(&mut program_test, &to_option_account, &to.market.option_mint, &user.authority.pubkey(), 0);
+    add_token_account(&mut program_test, &to_writer_token_account, &to.market.writer_token_mint, &user.authority.pubkey(), 0);
+    Setup {
+        context: program_test.start_with_context().await,
+        from,
+        to,
+        user,
+        to_option_account,
+        to_writer_token_account,
+    }
+}
+
+fn roll_accounts(setup: &Setup) -> psy_american::accounts::RollPosition {
+    psy_american::accounts::RollPosition {
+        user_authority: setup.user.authority.pubkey(),
+        from_option_market: setup.from.key,
+        from_option_mint: setup.from.market.option_mint,
+        from_writer_token_mint: setup.from.market.writer_token_mint,
+        option_token_src: setup.user.option_account,
+        writer_token_src: setup.user.writer_token_account,
+        from_underlying_asset_pool: setup.from.market.underlying_asset_pool,
+        to_option_market: setup.to.key,
+        to_option_mint: setup.to.market.option_mint,
+        to_writer_token_mint: setup.to.market.writer_token_mint,
+        minted_option_dest: setup.to_option_account,
+        minted_writer_token_dest: setup.to_writer_token_account,
+        to_underlying_asset_pool: setup.to.market.underlying_asset_pool,
+        underlying_asset_account: setup.user.underlying_account,
+        token_program: spl_token::ID,
+        pause_config: pause_config_key(),
+    }
+}
+
+async fn roll(setup: &mut Setup, accounts: psy_american::accounts::RollPosition, size: u64) -> Result<(), solana_sdk::transport::TransportError> {
+    let ix = instruction(accounts, psy_american::instruction::RollPosition { size });
+    process(&mut setup.context, ix, &[&setup.user.authority]).await
+}
+
+async fn assert_rolled(setup: &mut Setup, to_underlying_amount_per_contract: u64) {
+    let context = &mut setup.context;
+    assert_eq!(token_balance(context, &setup.user.option_account).await, 0);
+    assert_eq!(token_balance(context, &setup.user.writer_token_account).await, 0);
+    assert_eq!(token_balance(context, &setup.to_option_account).await, POSITION);
+    assert_eq!(token_balance(context, &setup.to_writer_token_account).await, POSITION);
+    assert_eq!(mint_supply(context, &setup.from.market.option_mint).await, WRITTEN - POSITION);
+    assert_eq!(mint_supply(context, &setup.to.market.writer_token_mint).await, POSITION);
+    assert_eq!(
+        token_balance(context, &setup.from.market.underlying_asset_pool).await,
+        (WRITTEN - POSITION) * UNDERLYING_AMOUNT_PER_CONTRACT
+    );
+    assert_eq!(
+        token_balance(context, &setup.to.market.underlying_asset_pool).await,
+        POSITION * to_underlying_amount_per_contract
+    );
+}
+
+#[tokio::test]
+async fn roll_position_moves_underlying_pool_to_pool() {
+    let mut setup = setup(UNDERLYING_AMOUNT_PER_CONTRACT, 0).await;
+    roll(&mut setup, roll_accounts(&setup), POSITION).await.unwrap();
+    assert_rolled(&mut setup, UNDERLYING_AMOUNT_PER_CONTRACT).await;
+    assert_eq!(token_balance(&mut setup.context, &setup.user.underlying_account).await, 0);
+}
+
+#[tokio::test]
+async fn roll_position_refunds_smaller_contracts() {
+    let to_underlying_amount_per_contract = UNDERLYING_AMOUNT_PER_CONTRACT / 2;
+    let mut setup = setup(to_underlying_amount_per_contract, 0).await;
+    roll(&mut setup, roll_accounts(&setup), POSITION).await.unwrap();
+    assert_rolled(&mut setup, to_underlying_amount_per_contract).await;
+    assert_eq!(
+        token_balance(&mut setup.context, &setup.user.underlying_account).await,
+        POSITION * (UNDERLYING_AMOUNT_PER_CONTRACT - to_underlying_amount_per_contract)
+    );
+}
+
+#[tokio::test]
+async fn roll_position_pulls_larger_contracts() {
+    let to_underlying_amount_per_contract = UNDERLYING_AMOUNT_PER_CONTRACT * 2;
+    let mut setup = setup(to_underlying_amount_per_contract, POSITION * UNDERLYING_AMOUNT_PER_CONTRACT).await;
+    roll(&mut setup, roll_accounts(&setup), POSITION).await.unwrap();
+    assert_rolled(&mut setup, to_underlying_amount_per_contract).await;
+    assert_eq!(token_balance(&mut setup.context, &setup.user.underlying_account).await, 0);
+}
+
+#[tokio::test]
+async fn roll_position_works_after_the_position_expires() {
+    let mut setup = setup(UNDERLYING_AMOUNT_PER_CONTRACT, 0).await;
+    warp_past(&mut setup.context, NEAR_EXPIRATION).await;
+    roll(&mut setup, roll_accounts(&setup), POSITION).await.unwrap();
+    assert_rolled(&mut setup, UNDERLYING_AMOUNT_PER_CONTRACT).await;
+}
+
+#[tokio::test]
+async fn roll_position_fails_into_expired_market() {
+    let mut setup = setup(UNDERLYING_AMOUNT_PER_CONTRACT, 0).await;
+    warp_past(&mut setup.context, EXPIRATION).await;
+    let accounts = roll_accounts(&setup);
+    assert_error(roll(&mut setup, accounts, POSITION).await, ErrorCode::OptionMarketExpiredCantMint);
+}
+
+#[tokio::test]
+async fn roll_position_requires_the_same_underlying() {
+    let mut program_test = program_test();
+    add_pause_config(&mut program_test, PauseFlags::default());
+    let from = MarketFixture::default();
+    let to = MarketFixture::default();
+    from.add_to(&mut program_test, WRITTEN, 0);
+    to.add_to(&mut program_test, 0, 0);
+    let user = UserFixture::add_to(&mut program_test, &from, POSITION, POSITION, 0, 0);
+    let to_option_account = Pubkey::new_unique();
+    let to_writer_token_account = Pubkey::new_unique();
+    add_token_account(&mut program_test, &to_option_account, &to.market.option_mint, &user.authority.pubkey(), 0);
+    add_token_account(&mut program_test, &to_writer_token_account, &to.market.writer_token_mint, &user.authority.pubkey(), 0);
+    let mut setup = Setup {
+        context: program_test.start_with_context().await,
+        from,
+        to,
+        user,
+        to_option_account,
+        to_writer_token_account,
+    };
+    let accounts = roll_accounts(&setup);
+    assert_error(roll(&mut setup, accounts, POSITION).await, ErrorCode::RollUnderlyingAssetsDiffer);
+}
+
+#[tokio::test]
+async fn roll_position_validates_market_accounts() {
+    let mut setup = setup(UNDERLYING_AMOUNT_PER_CONTRACT, 0).await;
+    let from = &setup.from.market;
+    let to = &setup.to.market;
+    let cases: Vec<(Box<dyn Fn(&mut psy_american::accounts::RollPosition)>, ErrorCode)> = vec![
+        (Box::new(|a| a.from_option_mint = to.option_mint), ErrorCode::OptionTokenMintDoesNotMatchMarket),
+        (Box::new(|a| a.to_option_mint = from.option_mint), ErrorCode::OptionTokenMintDoesNotMatchMarket),
+        (Box::new(|a| a.from_writer_token_mint = to.writer_token_mint), ErrorCode::WriterTokenMintDoesNotMatchMarket),
+        (Box::new(|a| a.to_writer_token_mint = from.writer_token_mint), ErrorCode::WriterTokenMintDoesNotMatchMarket),
+        (Box::new(|a| a.from_underlying_asset_pool = to.underlying_asset_pool), ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket),
+        (Box::new(|a| a.to_underlying_asset_pool = from.underlying_asset_pool), ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket),
+    ];
+    let mut results = vec![];
+    for (modify, error) in cases {
+        let mut accounts = roll_accounts(&setup);
+        modify(&mut accounts);
+        let ix = instruction(accounts, psy_american::instruction::RollPosition { size: 1 });
+        results.push((ix, error));
+    }
+    for (ix, error) in results {
+        assert_error(process(&mut setup.context, ix, &[&setup.user.authority]).await, error);
+    }
+
+    let mut accounts = roll_accounts(&setup);
+    accounts.underlying_asset_account = setup.user.quote_account;
+    assert_error(roll(&mut setup, accounts, 1).await, ErrorCode::UnderlyingDestMintDoesNotMatchUnderlyingAsset);
+
+    let accounts = roll_accounts(&setup);
+    assert_error(roll(&mut setup, accounts, 0).await, ErrorCode::SizeCantBeLessThanEqZero);
+}
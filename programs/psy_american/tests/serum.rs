@@ -0,0 +1,198 @@
+mod common;
+
+use anchor_spl::dex::{self, serum_dex};
+use common::*;
+use psy_american::{errors::ErrorCode, PriceBandConfig};
+use serum_dex::instruction::SelfTradeBehavior;
+use serum_dex::matching::{OrderType, Side};
+use solana_program::{instruction::AccountMeta, system_program};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::TransactionError,
+    transport::TransportError,
+};
+use std::num::NonZeroU64;
+
+/// Validation and OpenOrdersPda discriminator for NewOrderV3
+const NEW_ORDER_V3_DISC: u8 = 1;
+/// Validation and OpenOrdersPda discriminator for Prune
+const PRUNE_DISC: u8 = 6;
+
+async fn setup() -> (ProgramTestContext, MarketFixture) {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, 0, 0);
+    (program_test.start_with_context().await, fixture)
+}
+
+fn init_serum_market_ix(payer: &Pubkey, fixture: &MarketFixture, option_mint: Pubkey) -> Instruction {
+    let pc_mint = fixture.market.quote_asset_mint;
+    let market_pda = |seed: &[u8]| {
+        Pubkey::find_program_address(&[fixture.key.as_ref(), pc_mint.as_ref(), seed], &psy_american::ID).0
+    };
+    let serum_market = market_pda(b"serumMarket");
+    let (vault_signer, vault_signer_nonce) = (0..u64::MAX)
+        .find_map(|nonce| {
+            Pubkey::create_program_address(&[serum_market.as_ref(), &nonce.to_le_bytes()], &dex::ID)
+                .ok()
+                .map(|key| (key, nonce))
+        })
+        .unwrap();
+    let market_authority = Pubkey::find_program_address(
+        &[b"open-orders-init", dex::ID.as_ref(), serum_market.as_ref()],
+        &psy_american::ID,
+    ).0;
+    instruction(
+        psy_american::accounts::InitSerumMarket {
+            user_authority: *payer,
+            option_market: fixture.key,
+            serum_market,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            dex_program: dex::ID,
+            rent: rent_sysvar(),
+            pc_mint,
+            option_mint,
+            request_queue: market_pda(b"requestQueue"),
+            event_queue: Pubkey::new_unique(),
+            bids: Pubkey::new_unique(),
+            asks: Pubkey::new_unique(),
+            coin_vault: market_pda(b"coinVault"),
+            pc_vault: market_pda(b"pcVault"),
+            vault_signer,
+            market_authority,
+            pause_config: pause_config_key(),
+        },
+        psy_american::instruction::InitSerumMarket {
+            _market_space: 388,
+            vault_signer_nonce,
+            coin_lot_size: 1,
+            pc_lot_size: 1,
+            pc_dust_threshold: 1,
+        },
+    )
+}
+
+/// A Prune routed through the MarketProxy, in the layout `proxy_cpi::prune` produces
+fn prune_ix(fixture: &MarketFixture, prune_authority: &Pubkey) -> Instruction {
+    let serum_market = Pubkey::new_unique();
+    let (market_authority, market_authority_bump) = Pubkey::find_program_address(
+        &[b"open-orders-init", dex::ID.as_ref(), serum_market.as_ref()],
+        &psy_american::ID,
+    );
+    let mut ix = serum_dex::instruction::prune(
+        &dex::ID,
+        &serum_market,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &market_authority,
+        &Pubkey::new_unique(),
+        prune_authority,
+        &Pubkey::new_unique(),
+        u16::MAX,
+    ).unwrap();
+    ix.accounts[3].is_signer = false;
+    ix.data.insert(0, PRUNE_DISC);
+    ix.data.insert(0, market_authority_bump);
+    ix.data.insert(0, PRUNE_DISC);
+    ix.accounts.insert(0, AccountMeta::new_readonly(fixture.key, false));
+    ix.accounts.insert(0, AccountMeta::new_readonly(dex::ID, false));
+    ix.program_id = psy_american::ID;
+    ix
+}
+
+/// A NewOrderV3 routed through the MarketProxy, in the layout `proxy_cpi::new_order_v3`
+/// produces when `band_accounts` are given
+fn new_order_ix(serum_market: &Pubkey, owner: &Pubkey, band_accounts: Option<(Pubkey, Pubkey)>) -> Instruction {
+    let mut ix = serum_dex::instruction::new_order(
+        serum_market,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        owner,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &spl_token::ID,
+        &rent_sysvar(),
+        None,
+        &dex::ID,
+        Side::Ask,
+        NonZeroU64::new(1).unwrap(),
+        NonZeroU64::new(1).unwrap(),
+        OrderType::Limit,
+        0,
+        SelfTradeBehavior::AbortTransaction,
+        u16::MAX,
+        NonZeroU64::new(u64::MAX).unwrap(),
+    ).unwrap();
+    if let Some((price_band, oracle)) = band_accounts {
+        ix.accounts.insert(0, AccountMeta::new_readonly(oracle, false));
+        ix.accounts.insert(0, AccountMeta::new_readonly(price_band, false));
+    }
+    ix.data.insert(0, NEW_ORDER_V3_DISC);
+    ix.data.insert(0, NEW_ORDER_V3_DISC);
+    ix.accounts.insert(0, AccountMeta::new_readonly(dex::ID, false));
+    ix.program_id = psy_american::ID;
+    ix
+}
+
+#[tokio::test]
+async fn init_serum_market_is_paused_by_default() {
+    let (mut context, fixture) = setup().await;
+    let ix = init_serum_market_ix(&context.payer.pubkey(), &fixture, fixture.market.option_mint);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::InstructionPaused);
+}
+
+#[tokio::test]
+async fn init_serum_market_requires_option_mint() {
+    let (mut context, fixture) = setup().await;
+    let ix = init_serum_market_ix(&context.payer.pubkey(), &fixture, fixture.market.writer_token_mint);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::CoinMintIsNotOptionMint);
+}
+
+#[tokio::test]
+async fn prune_rejects_active_market() {
+    let (mut context, fixture) = setup().await;
+    let ix = prune_ix(&fixture, &context.payer.pubkey());
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::CannotPruneActiveMarket);
+}
+
+#[tokio::test]
+async fn prune_passes_validation_after_expiration() {
+    let (mut context, fixture) = setup().await;
+    warp_past(&mut context, EXPIRATION).await;
+    let ix = prune_ix(&fixture, &context.payer.pubkey());
+    // The Serum accounts are placeholders, so the DEX itself rejects the prune
+    match process(&mut context, ix, &[]).await {
+        Err(TransportError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code)))) => {
+            assert_ne!(code, u32::from(ErrorCode::CannotPruneActiveMarket))
+        }
+        Err(_) => {}
+        Ok(()) => panic!("expected the DEX to reject the placeholder market"),
+    }
+}
+
+#[tokio::test]
+async fn new_orders_must_pass_the_price_band_accounts() {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default();
+    fixture.add_to(&mut program_test, 0, 0);
+    let serum_market = Pubkey::new_unique();
+    let (price_band, bump_seed) = Pubkey::find_program_address(&[serum_market.as_ref(), b"priceBand"], &psy_american::ID);
+    let config = PriceBandConfig { option_market: fixture.key, serum_market, halted: true, bump_seed, ..PriceBandConfig::default() };
+    add_program_account(&mut program_test, &price_band, &config);
+    let mut context = program_test.start_with_context().await;
+    let owner = context.payer.pubkey();
+
+    // Leaving out the band accounts does not skip the circuit breaker
+    let ix = new_order_ix(&serum_market, &owner, None);
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::PriceBandDoesNotMatchMarket);
+    let ix = new_order_ix(&serum_market, &owner, Some((price_band, price_band)));
+    assert_error(process(&mut context, ix, &[]).await, ErrorCode::MarketHalted);
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::*;
+use psy_american::{errors::ErrorCode, MarketSolvencyReport, YieldVault};
+use solana_program_test::ProgramTestContext;
+
+async fn setup(
+    option_supply: u64,
+    writer_token_supply: u64,
+    underlying_pool_amount: u64,
+    quote_pool_amount: u64
+) -> (ProgramTestContext, MarketFixture) {
+    let mut program_test = program_test();
+    let fixture = MarketFixture::default();
+    fixture.add_with_pools(&mut program_test, option_supply, writer_token_supply, underlying_pool_amount, quote_pool_amount);
+    (program_test.start_with_context().await, fixture)
+}
+
+async fn verify(context: &mut ProgramTestContext, accounts: psy_american::accounts::VerifyMarket) -> Result<(), solana_sdk::transport::TransportError> {
+    let ix = instruction(accounts, psy_american::instruction::VerifyMarket {});
+    process(context, ix, &[]).await
+}
+
+#[tokio::test]
+async fn verify_market_passes_for_solvent_market() {
+    // 10 written, 4 exercised
+    let (mut context, fixture) = setup(6, 10, 6 * UNDERLYING_AMOUNT_PER_CONTRACT, 4 * QUOTE_AMOUNT_PER_CONTRACT).await;
+    verify(&mut context, verify_market_accounts(&fixture)).await.unwrap();
+}
+
+#[tokio::test]
+async fn verify_market_rejects_uncovered_options() {
+    let (mut context, fixture) = setup(6, 10, 6 * UNDERLYING_AMOUNT_PER_CONTRACT - 1, 4 * QUOTE_AMOUNT_PER_CONTRACT).await;
+    assert_error(verify(&mut context, verify_market_accounts(&fixture)).await, ErrorCode::UnderlyingPoolDoesNotCoverOptions);
+}
+
+#[tokio::test]
+async fn verify_market_rejects_uncovered_writer_tokens() {
+    let (mut context, fixture) = setup(6, 10, 6 * UNDERLYING_AMOUNT_PER_CONTRACT, 4 * QUOTE_AMOUNT_PER_CONTRACT - 1).await;
+    assert_error(verify(&mut context, verify_market_accounts(&fixture)).await, ErrorCode::PoolsDoNotCoverWriterTokens);
+}
+
+#[tokio::test]
+async fn verify_market_ignores_options_after_expiration() {
+    // Writers have closed 4 positions after expiration, leaving 6 worthless options
+    let (mut context, fixture) = setup(6, 2, 2 * UNDERLYING_AMOUNT_PER_CONTRACT, 0).await;
+    assert_error(verify(&mut context, verify_market_accounts(&fixture)).await, ErrorCode::UnderlyingPoolDoesNotCoverOptions);
+    warp_past(&mut context, EXPIRATION).await;
+    // An unused remaining account, so this is not rejected as a duplicate of the first transaction
+    let mut ix = instruction(verify_market_accounts(&fixture), psy_american::instruction::VerifyMarket {});
+    ix.accounts.push(solana_program::instruction::AccountMeta::new_readonly(clock_sysvar(), false));
+    process(&mut context, ix, &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn verify_market_validates_market_accounts() {
+    let (mut context, fixture) = setup(0, 0, 0, 0).await;
+    let cases: Vec<(Box<dyn Fn(&mut psy_american::accounts::VerifyMarket)>, ErrorCode)> = vec![
+        (Box::new(|a| a.option_mint = fixture.market.writer_token_mint), ErrorCode::OptionTokenMintDoesNotMatchMarket),
+        (Box::new(|a| a.writer_token_mint = fixture.market.option_mint), ErrorCode::WriterTokenMintDoesNotMatchMarket),
+        (Box::new(|a| a.underlying_asset_pool = fixture.market.quote_asset_pool), ErrorCode::UnderlyingPoolAccountDoesNotMatchMarket),
+        (Box::new(|a| a.quote_asset_pool = fixture.market.underlying_asset_pool), ErrorCode::QuotePoolAccountDoesNotMatchMarket),
+    ];
+    for (modify, error) in cases {
+        let mut accounts = verify_market_accounts(&fixture);
+        modify(&mut accounts);
+        assert_error(verify(&mut context, accounts).await, error);
+    }
+}
+
+#[test]
+fn solvency_report_counts_whole_contracts() {
+    let fixture = MarketFixture::default();
+    let report = MarketSolvencyReport::new(
+        fixture.key,
+        &fixture.market,
+        6,
+        10,
+        6 * UNDERLYING_AMOUNT_PER_CONTRACT,
+        4 * QUOTE_AMOUNT_PER_CONTRACT,
+        EXPIRATION,
+    );
+    assert!(!report.expired);
+    assert!(report.options_covered);
+    assert!(report.writer_tokens_covered);
+
+    // Dust in both pools does not add up to another contract
+    let report = MarketSolvencyReport::new(
+        fixture.key,
+        &fixture.market,
+        6,
+        11,
+        7 * UNDERLYING_AMOUNT_PER_CONTRACT - 1,
+        5 * QUOTE_AMOUNT_PER_CONTRACT - 1,
+        EXPIRATION,
+    );
+    assert!(report.options_covered);
+    assert!(!report.writer_tokens_covered);
+
+    let report = MarketSolvencyReport::new(fixture.key, &fixture.market, 1, 0, 0, 0, EXPIRATION + 1);
+    assert!(report.expired);
+    assert!(report.options_covered);
+}
+
+#[test]
+fn solvency_report_counts_the_yield_vaults_collateral() {
+    let fixture = MarketFixture::default();
+    let yield_vault = YieldVault { option_market: fixture.key, collateral_size: 6, ..YieldVault::default() };
+    // The receipt pool's balance is not priced, only the options it collateralizes are counted
+    let report = MarketSolvencyReport::new_yield(&fixture.market, &yield_vault, 6, 10, 1, 4 * QUOTE_AMOUNT_PER_CONTRACT, EXPIRATION);
+    assert_eq!(report.option_market, fixture.key);
+    assert_eq!(report.underlying_pool_amount, 1);
+    assert_eq!(report.collateral_size, Some(6));
+    assert!(report.options_covered);
+    assert!(report.writer_tokens_covered);
+
+    let report = MarketSolvencyReport::new_yield(&fixture.market, &yield_vault, 7, 10, 1, 4 * QUOTE_AMOUNT_PER_CONTRACT, EXPIRATION);
+    assert!(!report.options_covered);
+
+    // An emptied receipt pool covers nothing
+    let report = MarketSolvencyReport::new_yield(&fixture.market, &yield_vault, 6, 10, 0, 4 * QUOTE_AMOUNT_PER_CONTRACT, EXPIRATION);
+    assert!(!report.options_covered);
+    assert!(!report.writer_tokens_covered);
+
+    let report = MarketSolvencyReport::new(fixture.key, &fixture.market, 6, 10, 6 * UNDERLYING_AMOUNT_PER_CONTRACT, 0, EXPIRATION);
+    assert_eq!(report.collateral_size, None);
+}
@@ -0,0 +1,400 @@
+mod common;
+
+use common::*;
+use mock_yield_adapter::Reserve;
+use psy_american::{errors::ErrorCode, yield_vault, PauseFlags, YieldAdapter, YieldVault};
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program_test::{processor, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_program,
+    sysvar,
+    transport::TransportError,
+};
+
+const U: u64 = UNDERLYING_AMOUNT_PER_CONTRACT;
+
+/// A fresh market created by `creator`, an approved mock adapter Reserve whose vault holds
+/// `surplus` underlying to pay yield, and a user with `underlying` and quote for four exercises
+struct YieldFixture {
+    market: MarketFixture,
+    creator: Keypair,
+    user: UserFixture,
+    reserve: Pubkey,
+    reserve_authority: Pubkey,
+    reserve_vault: Pubkey,
+    receipt_mint: Pubkey,
+    yield_vault: Pubkey,
+    receipt_pool: Pubkey,
+}
+
+fn yield_adapter_address(adapter_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[yield_vault::YIELD_ADAPTER_SEED, adapter_program.as_ref()], &psy_american::ID)
+}
+
+impl YieldFixture {
+    async fn start(written: u64, surplus: u64, underlying: u64) -> (ProgramTestContext, Self) {
+        let mut program_test = program_test();
+        program_test.add_program(
+            "mock_yield_adapter",
+            mock_yield_adapter::ID,
+            processor!(mock_yield_adapter::process_instruction),
+        );
+        add_pause_config(&mut program_test, PauseFlags::default());
+        let (yield_adapter, bump_seed) = yield_adapter_address(&mock_yield_adapter::ID);
+        add_program_account(&mut program_test, &yield_adapter, &YieldAdapter { adapter_program: mock_yield_adapter::ID, bump_seed });
+        let creator = Keypair::new();
+        program_test.add_account(creator.pubkey(), Account {
+            lamports: 100_000_000_000,
+            data: vec![],
+            owner: system_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        });
+        let market = MarketFixture::default().with_creator(creator.pubkey());
+        market.add_to(&mut program_test, written, 0);
+        let user = UserFixture::add_to(&mut program_test, &market, 0, 0, underlying, 4 * QUOTE_AMOUNT_PER_CONTRACT);
+
+        let reserve = Pubkey::new_unique();
+        let (reserve_authority, authority_bump) = mock_yield_adapter::reserve_authority(&reserve);
+        let data = Reserve { rate_numerator: 1, rate_denominator: 1, authority_bump }.pack();
+        program_test.add_account(reserve, Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: mock_yield_adapter::ID,
+            executable: false,
+            rent_epoch: 0,
+        });
+        let reserve_vault = Pubkey::new_unique();
+        add_token_account(&mut program_test, &reserve_vault, &market.market.underlying_asset_mint, &reserve_authority, surplus);
+        let receipt_mint = Pubkey::new_unique();
+        add_mint(&mut program_test, &receipt_mint, &reserve_authority, 0);
+
+        let yield_vault = Pubkey::find_program_address(
+            &[yield_vault::YIELD_VAULT_SEED, market.key.as_ref()],
+            &psy_american::ID,
+        ).0;
+        let receipt_pool = Pubkey::find_program_address(&[&yield_vault.to_bytes()[..], b"receiptPool"], &psy_american::ID).0;
+        let fixture = Self {
+            market,
+            creator,
+            user,
+            reserve,
+            reserve_authority,
+            reserve_vault,
+            receipt_mint,
+            yield_vault,
+            receipt_pool,
+        };
+        (program_test.start_with_context().await, fixture)
+    }
+
+    fn init_yield_vault(&self, creator: &Keypair) -> Instruction {
+        instruction(
+            psy_american::accounts::InitYieldVault {
+                creator: creator.pubkey(),
+                option_market: self.market.key,
+                writer_token_mint: self.market.market.writer_token_mint,
+                adapter_program: mock_yield_adapter::ID,
+                yield_adapter: yield_adapter_address(&mock_yield_adapter::ID).0,
+                adapter_reserve: self.reserve,
+                adapter_vault: self.reserve_vault,
+                receipt_mint: self.receipt_mint,
+                yield_vault: self.yield_vault,
+                receipt_pool: self.receipt_pool,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            },
+            psy_american::instruction::InitYieldVault {},
+        )
+    }
+
+    fn mint(&self, size: u64, underlying_in: u64) -> Instruction {
+        let mut ix = instruction(
+            psy_american::accounts::MintOptionYield {
+                user_authority: self.user.authority.pubkey(),
+                option_mint: self.market.market.option_mint,
+                minted_option_dest: self.user.option_account,
+                writer_token_mint: self.market.market.writer_token_mint,
+                minted_writer_token_dest: self.user.writer_token_account,
+                option_market: self.market.key,
+                yield_vault: self.yield_vault,
+                receipt_pool: self.receipt_pool,
+                adapter_program: mock_yield_adapter::ID,
+                token_program: spl_token::ID,
+                pause_config: pause_config_key(),
+            },
+            psy_american::instruction::MintOptionYield { size, underlying_in },
+        );
+        ix.accounts.extend(vec![
+            AccountMeta::new_readonly(self.reserve, false),
+            AccountMeta::new_readonly(self.reserve_authority, false),
+            AccountMeta::new_readonly(self.user.authority.pubkey(), true),
+            AccountMeta::new(self.user.underlying_account, false),
+            AccountMeta::new(self.reserve_vault, false),
+            AccountMeta::new(self.receipt_mint, false),
+            AccountMeta::new(self.receipt_pool, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ]);
+        ix
+    }
+
+    /// The adapter's withdraw and redeem accounts, paying the user's underlying account
+    fn redeem_accounts(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.reserve, false),
+            AccountMeta::new_readonly(self.reserve_authority, false),
+            AccountMeta::new_readonly(self.yield_vault, false),
+            AccountMeta::new(self.receipt_pool, false),
+            AccountMeta::new(self.receipt_mint, false),
+            AccountMeta::new(self.reserve_vault, false),
+            AccountMeta::new(self.user.underlying_account, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ]
+    }
+
+    fn exercise(&self, size: u64) -> Instruction {
+        let mut ix = instruction(
+            psy_american::accounts::ExerciseOptionYield {
+                user_authority: self.user.authority.pubkey(),
+                option_authority: self.user.authority.pubkey(),
+                option_market: self.market.key,
+                option_mint: self.market.market.option_mint,
+                exerciser_option_token_src: self.user.option_account,
+                underlying_asset_dest: self.user.underlying_account,
+                quote_asset_pool: self.market.market.quote_asset_pool,
+                quote_asset_src: self.user.quote_account,
+                yield_vault: self.yield_vault,
+                receipt_pool: self.receipt_pool,
+                adapter_program: mock_yield_adapter::ID,
+                token_program: spl_token::ID,
+            },
+            psy_american::instruction::ExerciseOptionYield { size },
+        );
+        ix.accounts.extend(self.redeem_accounts());
+        ix
+    }
+
+    fn close_position(&self, size: u64) -> Instruction {
+        let mut ix = instruction(
+            psy_american::accounts::CloseOptionPositionYield {
+                user_authority: self.user.authority.pubkey(),
+                option_market: self.market.key,
+                option_mint: self.market.market.option_mint,
+                option_token_src: self.user.option_account,
+                writer_token_mint: self.market.market.writer_token_mint,
+                writer_token_src: self.user.writer_token_account,
+                underlying_asset_dest: self.user.underlying_account,
+                yield_vault: self.yield_vault,
+                receipt_pool: self.receipt_pool,
+                adapter_program: mock_yield_adapter::ID,
+                token_program: spl_token::ID,
+            },
+            psy_american::instruction::CloseOptionPositionYield { size },
+        );
+        ix.accounts.extend(self.redeem_accounts());
+        ix
+    }
+
+    fn close_post_expiration(&self, size: u64) -> Instruction {
+        let mut ix = instruction(
+            psy_american::accounts::ClosePostExpYield {
+                user_authority: self.user.authority.pubkey(),
+                option_market: self.market.key,
+                writer_token_mint: self.market.market.writer_token_mint,
+                writer_token_src: self.user.writer_token_account,
+                underlying_asset_dest: self.user.underlying_account,
+                yield_vault: self.yield_vault,
+                receipt_pool: self.receipt_pool,
+                adapter_program: mock_yield_adapter::ID,
+                token_program: spl_token::ID,
+            },
+            psy_american::instruction::ClosePostExpirationYield { size },
+        );
+        ix.accounts.extend(self.redeem_accounts());
+        ix
+    }
+
+    /// Set the Reserve's rate to `rate_numerator` underlying per receipt token
+    fn accrue(&self, rate_numerator: u64) -> Instruction {
+        let mut data = vec![mock_yield_adapter::ACCRUE_TAG];
+        data.extend_from_slice(&rate_numerator.to_le_bytes());
+        Instruction {
+            program_id: mock_yield_adapter::ID,
+            accounts: vec![AccountMeta::new(self.reserve, false)],
+            data,
+        }
+    }
+
+    async fn init(&self, context: &mut ProgramTestContext) {
+        process(context, self.init_yield_vault(&self.creator), &[&self.creator]).await.unwrap();
+    }
+
+    async fn process_as_user(&self, context: &mut ProgramTestContext, ix: Instruction) -> Result<(), TransportError> {
+        process(context, ix, &[&self.user.authority]).await
+    }
+}
+
+#[tokio::test]
+async fn exercising_withdraws_the_underlying_and_leaves_the_yield() {
+    let (mut context, fixture) = YieldFixture::start(0, 10 * U, 4 * U).await;
+    fixture.init(&mut context).await;
+    fixture.process_as_user(&mut context, fixture.mint(4, 4 * U)).await.unwrap();
+    assert_eq!(token_balance(&mut context, &fixture.receipt_pool).await, 4 * U);
+    assert_eq!(token_balance(&mut context, &fixture.user.underlying_account).await, 0);
+
+    // The receipt tokens are now worth twice the underlying
+    process(&mut context, fixture.accrue(2), &[]).await.unwrap();
+    fixture.process_as_user(&mut context, fixture.exercise(1)).await.unwrap();
+    assert_eq!(token_balance(&mut context, &fixture.user.underlying_account).await, U);
+    assert_eq!(token_balance(&mut context, &fixture.receipt_pool).await, 4 * U - U / 2);
+    assert_eq!(token_balance(&mut context, &fixture.user.option_account).await, 3);
+    let vault: YieldVault = program_account(&mut context, &fixture.yield_vault).await;
+    assert_eq!(vault.collateral_size, 3);
+}
+
+#[tokio::test]
+async fn writers_redeem_the_accrued_yield_after_expiration() {
+    let (mut context, fixture) = YieldFixture::start(0, 10 * U, 2 * U).await;
+    fixture.init(&mut context).await;
+    fixture.process_as_user(&mut context, fixture.mint(2, 2 * U)).await.unwrap();
+    process(&mut context, fixture.accrue(2), &[]).await.unwrap();
+
+    assert_error(
+        fixture.process_as_user(&mut context, fixture.close_post_expiration(1)).await,
+        ErrorCode::OptionMarketNotExpiredCantClose,
+    );
+    warp_past(&mut context, EXPIRATION).await;
+    fixture.process_as_user(&mut context, fixture.close_post_expiration(2)).await.unwrap();
+    assert_eq!(token_balance(&mut context, &fixture.user.underlying_account).await, 4 * U);
+    assert_eq!(token_balance(&mut context, &fixture.user.writer_token_account).await, 0);
+    assert_eq!(token_balance(&mut context, &fixture.receipt_pool).await, 0);
+}
+
+#[tokio::test]
+async fn closing_a_position_redeems_its_share_of_the_yield() {
+    let (mut context, fixture) = YieldFixture::start(0, 10 * U, 2 * U).await;
+    fixture.init(&mut context).await;
+    fixture.process_as_user(&mut context, fixture.mint(2, 2 * U)).await.unwrap();
+    process(&mut context, fixture.accrue(3), &[]).await.unwrap();
+
+    fixture.process_as_user(&mut context, fixture.close_position(1)).await.unwrap();
+    assert_eq!(token_balance(&mut context, &fixture.user.underlying_account).await, 3 * U);
+    assert_eq!(token_balance(&mut context, &fixture.receipt_pool).await, U);
+    let vault: YieldVault = program_account(&mut context, &fixture.yield_vault).await;
+    assert_eq!(vault.collateral_size, 1);
+}
+
+#[tokio::test]
+async fn later_writers_buy_in_at_the_accrued_yield() {
+    let (mut context, fixture) = YieldFixture::start(0, 10 * U, 4 * U).await;
+    fixture.init(&mut context).await;
+    fixture.process_as_user(&mut context, fixture.mint(2, 2 * U)).await.unwrap();
+    process(&mut context, fixture.accrue(2), &[]).await.unwrap();
+
+    // The collateral alone mints half the receipt tokens each outstanding option holds
+    assert_error(
+        fixture.process_as_user(&mut context, fixture.mint(1, U)).await,
+        ErrorCode::YieldDepositTooSmall,
+    );
+    fixture.process_as_user(&mut context, fixture.mint(1, 2 * U)).await.unwrap();
+    assert_eq!(token_balance(&mut context, &fixture.receipt_pool).await, 3 * U);
+    assert_eq!(token_balance(&mut context, &fixture.user.option_account).await, 3);
+}
+
+#[tokio::test]
+async fn verify_market_counts_the_vaults_collateral() {
+    let (mut context, fixture) = YieldFixture::start(0, 10 * U, 4 * U).await;
+    fixture.init(&mut context).await;
+    fixture.process_as_user(&mut context, fixture.mint(4, 4 * U)).await.unwrap();
+    fixture.process_as_user(&mut context, fixture.exercise(1)).await.unwrap();
+
+    // The underlying asset pool is empty, the 3 outstanding options are collateralized by the
+    // receipt pool and the exercised position by the quote pool
+    assert_eq!(token_balance(&mut context, &fixture.market.market.underlying_asset_pool).await, 0);
+    let ix = instruction(verify_market_accounts(&fixture.market), psy_american::instruction::VerifyMarket {});
+    process(&mut context, ix, &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn yield_markets_reject_the_plain_instructions() {
+    let (mut context, fixture) = YieldFixture::start(0, 0, U).await;
+    fixture.init(&mut context).await;
+    let ix = instruction(
+        psy_american::accounts::MintOptionV2 {
+            user_authority: fixture.user.authority.pubkey(),
+            underlying_asset_mint: fixture.market.market.underlying_asset_mint,
+            underlying_asset_pool: fixture.market.market.underlying_asset_pool,
+            underlying_asset_src: fixture.user.underlying_account,
+            option_mint: fixture.market.market.option_mint,
+            minted_option_dest: fixture.user.option_account,
+            writer_token_mint: fixture.market.market.writer_token_mint,
+            minted_writer_token_dest: fixture.user.writer_token_account,
+            option_market: fixture.market.key,
+            token_program: spl_token::ID,
+            pause_config: pause_config_key(),
+        },
+        psy_american::instruction::MintOptionV2 { size: 1 },
+    );
+    assert_error(fixture.process_as_user(&mut context, ix).await, ErrorCode::YieldMarketUnsupported);
+}
+
+#[tokio::test]
+async fn only_the_creator_adds_a_yield_vault_before_writing() {
+    let (mut context, fixture) = YieldFixture::start(0, 0, 0).await;
+    let ix = fixture.init_yield_vault(&fixture.user.authority);
+    assert_error(fixture.process_as_user(&mut context, ix).await, ErrorCode::NotMarketCreator);
+
+    let (mut context, fixture) = YieldFixture::start(1, 0, 0).await;
+    assert_error(
+        process(&mut context, fixture.init_yield_vault(&fixture.creator), &[&fixture.creator]).await,
+        ErrorCode::MarketHasWrittenOptions,
+    );
+}
+
+#[tokio::test]
+async fn only_approved_adapters_hold_collateral() {
+    let (mut context, fixture) = YieldFixture::start(0, 0, 0).await;
+    // Any executable program could take the collateral, the token program stands in for one
+    let mut ix = fixture.init_yield_vault(&fixture.creator);
+    ix.accounts[3].pubkey = spl_token::ID;
+    ix.accounts[4].pubkey = yield_adapter_address(&spl_token::ID).0;
+    assert_error(
+        process(&mut context, ix, &[&fixture.creator]).await,
+        ErrorCode::YieldAdapterNotApproved,
+    );
+}
+
+#[tokio::test]
+async fn the_adapter_accounts_are_bound_at_init() {
+    let (mut context, fixture) = YieldFixture::start(0, 0, 2 * U).await;
+    let mut ix = fixture.init_yield_vault(&fixture.creator);
+    // The reserve must belong to the adapter
+    let reserve_index = ix.accounts.iter().position(|meta| meta.pubkey == fixture.reserve).unwrap();
+    ix.accounts[reserve_index].pubkey = fixture.user.underlying_account;
+    assert_error(
+        process(&mut context, ix, &[&fixture.creator]).await,
+        ErrorCode::YieldAdapterAccountsDoNotMatchVault,
+    );
+    fixture.init(&mut context).await;
+    let vault: YieldVault = program_account(&mut context, &fixture.yield_vault).await;
+    assert_eq!(vault.adapter_reserve, fixture.reserve);
+    assert_eq!(vault.adapter_vault, fixture.reserve_vault);
+
+    // Deposit the underlying somewhere other than the bound vault
+    let other_vault = fixture.market.market.underlying_asset_pool;
+    let mut ix = fixture.mint(1, U);
+    let vault_index = ix.accounts.iter().position(|meta| meta.pubkey == fixture.reserve_vault).unwrap();
+    ix.accounts[vault_index].pubkey = other_vault;
+    assert_error(fixture.process_as_user(&mut context, ix).await, ErrorCode::YieldAdapterAccountsDoNotMatchVault);
+
+    fixture.process_as_user(&mut context, fixture.mint(1, U)).await.unwrap();
+    let mut ix = fixture.exercise(1);
+    let vault_index = ix.accounts.iter().position(|meta| meta.pubkey == fixture.reserve_vault).unwrap();
+    ix.accounts[vault_index].pubkey = other_vault;
+    assert_error(fixture.process_as_user(&mut context, ix).await, ErrorCode::YieldAdapterAccountsDoNotMatchVault);
+}
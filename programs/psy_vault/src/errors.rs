@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum ErrorCode {
+  #[msg("The vault has an active epoch")]
+  EpochInProgress,
+  #[msg("The vault does not have an active epoch")]
+  NoActiveEpoch,
+  #[msg("The auction for the epoch must be settled first")]
+  AuctionNotSettled,
+  #[msg("The auction for the epoch is already settled")]
+  AuctionAlreadySettled,
+  #[msg("OptionMarket underlying asset does not match the vault")]
+  UnderlyingAssetDoesNotMatchVault,
+  #[msg("OptionMarket quote asset does not match the vault")]
+  QuoteAssetDoesNotMatchVault,
+  #[msg("OptionMarket does not match the vault's active epoch")]
+  OptionMarketDoesNotMatchVault,
+  #[msg("Serum market pc mint does not match the vault's strategy")]
+  PcMintDoesNotMatchStrategy,
+  #[msg("Serum market does not match the vault's active epoch")]
+  SerumMarketDoesNotMatchVault,
+  #[msg("The amount must be > 0")]
+  AmountCantBeZero,
+  #[msg("The vault has shares outstanding but no underlying assets")]
+  VaultHasNoUnderlying,
+  #[msg("The deposit is too small to receive any shares")]
+  DepositTooSmall,
+  #[msg("Numerical overflow")]
+  NumberOverflow,
+  #[msg("OpenOrders account could not be read")]
+  InvalidOpenOrders,
+  #[msg("Only the admin can settle the auction before the options expire")]
+  AdminRequiredBeforeExpiration,
+}
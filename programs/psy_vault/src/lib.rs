@@ -0,0 +1,866 @@
+use anchor_lang::prelude::*;
+use anchor_spl::dex::Dex;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+use psy_american::cpi::accounts::{BurnWriterForQuote, CloseOptionPosition, ClosePostExp, MintAndSell};
+use psy_american::{proxy_cpi, OptionMarket, OrderType};
+use std::convert::TryInto;
+
+pub mod errors;
+
+declare_id!("6pCvCScAycnf4NgbyDY9doY7NvT27jDzp41uehjZzbXM");
+
+/// Size of a Serum OpenOrders account, which the vault authority pays for when it is initialized
+const OPEN_ORDERS_SIZE: usize = 3228;
+/// Offset of the free slot bitmask in a Serum OpenOrders account (including the 5 byte padding)
+const OPEN_ORDERS_FREE_SLOT_BITS_OFFSET: usize = 109;
+
+/// Option writing vaults. Depositors receive shares for the vault's collateral, which is the
+/// underlying asset of the options it writes. Each epoch the admin mints options against the
+/// collateral and auctions them on the PsyOptions Serum market. After expiration anyone can
+/// settle the auction and the epoch, returning the vault's share of the unexercised underlying
+/// and exercised quote assets to the vault.
+///
+/// A covered call vault's collateral is the asset (e.g. SOL) and premiums are paid in the option's
+/// quote asset. A cash-secured put vault writes put-style markets where the collateral is the
+/// quote currency (e.g. USDC), so premiums are paid in the collateral and assigned assets are
+/// the option's quote asset.
+#[program]
+pub mod psy_vault {
+    use super::*;
+
+    pub fn initialize_vault(ctx: Context<InitializeVault>, strategy: VaultStrategy) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.admin = ctx.accounts.admin.key();
+        vault.strategy = strategy;
+        vault.underlying_asset_mint = ctx.accounts.underlying_asset_mint.key();
+        vault.quote_asset_mint = ctx.accounts.quote_asset_mint.key();
+        vault.vault_underlying = ctx.accounts.vault_underlying.key();
+        vault.vault_quote = ctx.accounts.vault_quote.key();
+        vault.share_mint = ctx.accounts.share_mint.key();
+        vault.option_market = Pubkey::default();
+        vault.serum_market = Pubkey::default();
+        vault.epoch = 0;
+        vault.auction_settled = false;
+        vault.bump_seed = *ctx.bumps.get("vault").unwrap();
+        vault.authority_bump_seed = *ctx.bumps.get("vault_authority").unwrap();
+        Ok(())
+    }
+
+    #[access_control(no_active_epoch(&ctx.accounts.vault))]
+    /// Deposit collateral for shares of the vault. When the vault holds quote assets from
+    /// a prior epoch the depositor must also deposit the same proportion of quote assets.
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Err(errors::ErrorCode::AmountCantBeZero.into())
+        }
+        let share_supply = ctx.accounts.share_mint.supply;
+        let underlying_balance = ctx.accounts.vault_underlying.amount;
+        let quote_balance = ctx.accounts.vault_quote.amount;
+
+        let (shares, quote_amount) = if share_supply == 0 {
+            (amount, 0)
+        } else {
+            if underlying_balance == 0 {
+                return Err(errors::ErrorCode::VaultHasNoUnderlying.into())
+            }
+            // Round shares down and the required quote up so existing shareholders are not diluted
+            let shares = (amount as u128)
+                .checked_mul(share_supply as u128)
+                .ok_or(errors::ErrorCode::NumberOverflow)? / underlying_balance as u128;
+            let quote_amount = (amount as u128)
+                .checked_mul(quote_balance as u128)
+                .ok_or(errors::ErrorCode::NumberOverflow)?
+                .checked_add(underlying_balance as u128 - 1)
+                .ok_or(errors::ErrorCode::NumberOverflow)? / underlying_balance as u128;
+            (shares as u64, quote_amount as u64)
+        };
+        if shares == 0 {
+            return Err(errors::ErrorCode::DepositTooSmall.into())
+        }
+
+        // Transfer the underlying assets to the vault
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.underlying_asset_src.to_account_info(),
+            to: ctx.accounts.vault_underlying.to_account_info(),
+            authority: ctx.accounts.depositor.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        // Transfer the proportional quote assets to the vault
+        if quote_amount > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.quote_asset_src.to_account_info(),
+                to: ctx.accounts.vault_quote.to_account_info(),
+                authority: ctx.accounts.depositor.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            token::transfer(cpi_ctx, quote_amount)?;
+        }
+
+        // Mint the shares to the depositor
+        let vault_key = ctx.accounts.vault.key();
+        let seeds = &[
+            vault_key.as_ref(),
+            b"vaultAuthority",
+            &[ctx.accounts.vault.authority_bump_seed]
+        ];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.share_mint.to_account_info(),
+            to: ctx.accounts.share_dest.to_account_info(),
+            authority: ctx.accounts.vault_authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+        token::mint_to(cpi_ctx, shares)
+    }
+
+    #[access_control(no_active_epoch(&ctx.accounts.vault))]
+    /// Burn shares for the pro rata amount of the vault's underlying and quote assets
+    pub fn withdraw(ctx: Context<Withdraw>, shares: u64) -> Result<()> {
+        if shares == 0 {
+            return Err(errors::ErrorCode::AmountCantBeZero.into())
+        }
+        let share_supply = ctx.accounts.share_mint.supply as u128;
+        let underlying_amount = (shares as u128)
+            .checked_mul(ctx.accounts.vault_underlying.amount as u128)
+            .ok_or(errors::ErrorCode::NumberOverflow)? / share_supply;
+        let quote_amount = (shares as u128)
+            .checked_mul(ctx.accounts.vault_quote.amount as u128)
+            .ok_or(errors::ErrorCode::NumberOverflow)? / share_supply;
+
+        // Burn the shares
+        let cpi_accounts = Burn {
+            mint: ctx.accounts.share_mint.to_account_info(),
+            from: ctx.accounts.share_src.to_account_info(),
+            authority: ctx.accounts.withdrawer.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::burn(cpi_ctx, shares)?;
+
+        let vault_key = ctx.accounts.vault.key();
+        let seeds = &[
+            vault_key.as_ref(),
+            b"vaultAuthority",
+            &[ctx.accounts.vault.authority_bump_seed]
+        ];
+        let signer = &[&seeds[..]];
+
+        // Transfer the underlying assets to the withdrawer
+        if underlying_amount > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.vault_underlying.to_account_info(),
+                to: ctx.accounts.underlying_asset_dest.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token::transfer(cpi_ctx, underlying_amount as u64)?;
+        }
+
+        // Transfer the quote assets to the withdrawer
+        if quote_amount > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.vault_quote.to_account_info(),
+                to: ctx.accounts.quote_asset_dest.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token::transfer(cpi_ctx, quote_amount as u64)?;
+        }
+        Ok(())
+    }
+
+    /// Initialize the vault authority's OpenOrders account for a PsyOptions Serum market
+    pub fn init_open_orders(ctx: Context<InitOpenOrders>) -> Result<()> {
+        // The vault authority pays for the OpenOrders PDA, so fund it with the rent
+        let lamports = Rent::get()?.minimum_balance(OPEN_ORDERS_SIZE);
+        solana_program::program::invoke(
+            &solana_program::system_instruction::transfer(
+                ctx.accounts.admin.key,
+                ctx.accounts.vault_authority.key,
+                lamports
+            ),
+            &[
+                ctx.accounts.admin.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        let vault_key = ctx.accounts.vault.key();
+        let seeds = &[
+            vault_key.as_ref(),
+            b"vaultAuthority",
+            &[ctx.accounts.vault.authority_bump_seed]
+        ];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = proxy_cpi::InitOpenOrders {
+            dex_program: ctx.accounts.dex_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            open_orders: ctx.accounts.open_orders.to_account_info(),
+            authority: ctx.accounts.vault_authority.to_account_info(),
+            market: ctx.accounts.serum_market.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+            market_authority: ctx.accounts.market_authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.psy_american_program.clone(), cpi_accounts, signer);
+        proxy_cpi::init_open_orders(
+            cpi_ctx,
+            *ctx.bumps.get("open_orders").unwrap(),
+            *ctx.bumps.get("market_authority").unwrap()
+        )
+    }
+
+    #[access_control(no_active_epoch(&ctx.accounts.vault) StartEpoch::accounts(&ctx))]
+    /// Start a new epoch by minting `size` options against the vault's collateral and
+    /// posting them as an ask on the OptionMarket's Serum market
+    pub fn start_epoch<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, StartEpoch<'info>>,
+        size: u64,
+        limit_price: u64,
+        order_type: OrderType
+    ) -> Result<()> {
+        let epoch = ctx.accounts.vault.epoch.checked_add(1).ok_or(errors::ErrorCode::NumberOverflow)?;
+        let vault_key = ctx.accounts.vault.key();
+        let seeds = &[
+            vault_key.as_ref(),
+            b"vaultAuthority",
+            &[ctx.accounts.vault.authority_bump_seed]
+        ];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = MintAndSell {
+            user_authority: ctx.accounts.vault_authority.to_account_info(),
+            underlying_asset_mint: ctx.accounts.underlying_asset_mint.to_account_info(),
+            underlying_asset_pool: ctx.accounts.underlying_asset_pool.to_account_info(),
+            underlying_asset_src: ctx.accounts.vault_underlying.to_account_info(),
+            option_mint: ctx.accounts.option_mint.to_account_info(),
+            minted_option_dest: ctx.accounts.vault_options.to_account_info(),
+            writer_token_mint: ctx.accounts.writer_token_mint.to_account_info(),
+            minted_writer_token_dest: ctx.accounts.vault_writer_tokens.to_account_info(),
+            option_market: ctx.accounts.option_market.to_account_info(),
+            dex_program: ctx.accounts.dex_program.to_account_info(),
+            pc_mint: ctx.accounts.pc_mint.to_account_info(),
+            serum_market: ctx.accounts.serum_market.to_account_info(),
+            open_orders: ctx.accounts.open_orders.to_account_info(),
+            request_queue: ctx.accounts.request_queue.to_account_info(),
+            event_queue: ctx.accounts.event_queue.to_account_info(),
+            market_bids: ctx.accounts.market_bids.to_account_info(),
+            market_asks: ctx.accounts.market_asks.to_account_info(),
+            coin_vault: ctx.accounts.coin_vault.to_account_info(),
+            pc_vault: ctx.accounts.pc_vault.to_account_info(),
+            price_band: ctx.accounts.price_band.to_account_info(),
+            price_oracle: ctx.accounts.price_oracle.to_account_info(),
+            psy_american_program: ctx.accounts.psy_american_program.clone(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+            pause_config: ctx.accounts.pause_config.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.psy_american_program.clone(), cpi_accounts, signer);
+        psy_american::cpi::mint_and_sell(cpi_ctx, size, limit_price, order_type, epoch)?;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.option_market = ctx.accounts.option_market.key();
+        vault.serum_market = ctx.accounts.serum_market.key();
+        vault.epoch = epoch;
+        vault.auction_settled = false;
+        Ok(())
+    }
+
+    #[access_control(SettleAuction::accounts(&ctx))]
+    /// End the epoch's auction. Cancels the vault's remaining ask, settles the premium and any
+    /// unsold options from the OpenOrders account, and closes the unsold option positions to
+    /// return their underlying assets to the vault. Only the admin can end the auction before
+    /// the options expire, after that anyone can.
+    pub fn settle_auction(ctx: Context<SettleAuction>) -> Result<()> {
+        let vault_key = ctx.accounts.vault.key();
+        let seeds = &[
+            vault_key.as_ref(),
+            b"vaultAuthority",
+            &[ctx.accounts.vault.authority_bump_seed]
+        ];
+        let signer = &[&seeds[..]];
+
+        // Cancel the rest of the ask if it has not been completely filled
+        if has_open_orders(&ctx.accounts.open_orders)? {
+            let cpi_accounts = proxy_cpi::CancelOrderV2 {
+                dex_program: ctx.accounts.dex_program.to_account_info(),
+                market: ctx.accounts.serum_market.to_account_info(),
+                market_bids: ctx.accounts.market_bids.to_account_info(),
+                market_asks: ctx.accounts.market_asks.to_account_info(),
+                open_orders: ctx.accounts.open_orders.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+                event_queue: ctx.accounts.event_queue.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.psy_american_program.clone(), cpi_accounts, signer);
+            proxy_cpi::cancel_order_by_client_id_v2(cpi_ctx, ctx.accounts.vault.epoch)?;
+        }
+
+        // Settle the premium and the unsold options back to the vault
+        let pc_wallet = match ctx.accounts.vault.strategy {
+            VaultStrategy::CoveredCall => ctx.accounts.vault_quote.to_account_info(),
+            VaultStrategy::CashSecuredPut => ctx.accounts.vault_underlying.to_account_info(),
+        };
+        let cpi_accounts = proxy_cpi::SettleFunds {
+            dex_program: ctx.accounts.dex_program.to_account_info(),
+            market: ctx.accounts.serum_market.to_account_info(),
+            open_orders: ctx.accounts.open_orders.to_account_info(),
+            authority: ctx.accounts.vault_authority.to_account_info(),
+            coin_vault: ctx.accounts.coin_vault.to_account_info(),
+            pc_vault: ctx.accounts.pc_vault.to_account_info(),
+            coin_wallet: ctx.accounts.vault_options.to_account_info(),
+            pc_wallet,
+            vault_signer: ctx.accounts.vault_signer.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.psy_american_program.clone(), cpi_accounts, signer);
+        proxy_cpi::settle_funds(cpi_ctx)?;
+
+        // Close the unsold options to return the underlying assets to the vault
+        ctx.accounts.vault_options.reload()?;
+        let unsold = ctx.accounts.vault_options.amount;
+        if unsold > 0 {
+            let cpi_accounts = CloseOptionPosition {
+                user_authority: ctx.accounts.vault_authority.to_account_info(),
+                option_market: ctx.accounts.option_market.to_account_info(),
+                writer_token_mint: ctx.accounts.writer_token_mint.to_account_info(),
+                writer_token_src: ctx.accounts.vault_writer_tokens.to_account_info(),
+                option_token_mint: ctx.accounts.option_mint.to_account_info(),
+                option_token_src: ctx.accounts.vault_options.to_account_info(),
+                underlying_asset_pool: ctx.accounts.underlying_asset_pool.to_account_info(),
+                underlying_asset_dest: ctx.accounts.vault_underlying.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.psy_american_program.clone(), cpi_accounts, signer);
+            psy_american::cpi::close_option_position(cpi_ctx, unsold)?;
+        }
+
+        ctx.accounts.vault.auction_settled = true;
+        Ok(())
+    }
+
+    #[access_control(SettleEpoch::accounts(&ctx))]
+    /// Permissionless settlement of an expired epoch. The vault's WriterTokens are redeemed for
+    /// their pro rata share of the exercised quote assets and the remaining unexercised
+    /// underlying assets, ending the epoch.
+    pub fn settle_epoch(ctx: Context<SettleEpoch>) -> Result<()> {
+        let (burn_for_quote, close_for_underlying) = settlement_sizes(
+            &ctx.accounts.option_market,
+            ctx.accounts.vault_writer_tokens.amount,
+            ctx.accounts.writer_token_mint.supply,
+            ctx.accounts.quote_asset_pool.amount,
+        )?;
+
+        let vault_key = ctx.accounts.vault.key();
+        let seeds = &[
+            vault_key.as_ref(),
+            b"vaultAuthority",
+            &[ctx.accounts.vault.authority_bump_seed]
+        ];
+        let signer = &[&seeds[..]];
+
+        if burn_for_quote > 0 {
+            let cpi_accounts = BurnWriterForQuote {
+                user_authority: ctx.accounts.vault_authority.to_account_info(),
+                option_market: ctx.accounts.option_market.to_account_info(),
+                writer_token_mint: ctx.accounts.writer_token_mint.to_account_info(),
+                writer_token_src: ctx.accounts.vault_writer_tokens.to_account_info(),
+                quote_asset_pool: ctx.accounts.quote_asset_pool.to_account_info(),
+                writer_quote_dest: ctx.accounts.vault_quote.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.psy_american_program.clone(), cpi_accounts, signer);
+            psy_american::cpi::burn_writer_for_quote(cpi_ctx, burn_for_quote)?;
+        }
+
+        if close_for_underlying > 0 {
+            let cpi_accounts = ClosePostExp {
+                user_authority: ctx.accounts.vault_authority.to_account_info(),
+                option_market: ctx.accounts.option_market.to_account_info(),
+                writer_token_mint: ctx.accounts.writer_token_mint.to_account_info(),
+                writer_token_src: ctx.accounts.vault_writer_tokens.to_account_info(),
+                underlying_asset_pool: ctx.accounts.underlying_asset_pool.to_account_info(),
+                underlying_asset_dest: ctx.accounts.vault_underlying.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                clock: ctx.accounts.clock.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.psy_american_program.clone(), cpi_accounts, signer);
+            psy_american::cpi::close_post_expiration(cpi_ctx, close_for_underlying)?;
+        }
+
+        let vault = &mut ctx.accounts.vault;
+        vault.option_market = Pubkey::default();
+        vault.serum_market = Pubkey::default();
+        vault.auction_settled = false;
+        Ok(())
+    }
+}
+
+/// Split the vault's WriterTokens into those burned for the exercised quote assets and those
+/// closed for the unexercised underlying assets. Both are counted in option tokens, which are
+/// fractions of a contract on markets with option decimals. The vault's share of the exercised
+/// tokens is rounded down, and the remaining WriterTokens are always covered by the
+/// unexercised underlying assets in the pool.
+fn settlement_sizes(
+    option_market: &OptionMarket,
+    writer_tokens: u64,
+    writer_supply: u64,
+    quote_pool_amount: u64
+) -> Result<(u64, u64)> {
+    if writer_supply == 0 {
+        return Ok((0, writer_tokens))
+    }
+    let exercised = option_market.quote_pool_size(quote_pool_amount)?.min(writer_supply);
+    let burn_for_quote = ((writer_tokens as u128)
+        .checked_mul(exercised as u128)
+        .ok_or(errors::ErrorCode::NumberOverflow)? / writer_supply as u128) as u64;
+    Ok((burn_for_quote, writer_tokens - burn_for_quote))
+}
+
+/// Returns true if the OpenOrders account has any orders resting on the book
+fn has_open_orders(open_orders: &AccountInfo) -> Result<bool> {
+    let data = open_orders.try_borrow_data()?;
+    let bytes = data
+        .get(OPEN_ORDERS_FREE_SLOT_BITS_OFFSET..OPEN_ORDERS_FREE_SLOT_BITS_OFFSET + 16)
+        .ok_or(errors::ErrorCode::InvalidOpenOrders)?;
+    let free_slot_bits = u128::from_le_bytes(bytes.try_into().unwrap());
+    Ok(free_slot_bits != u128::MAX)
+}
+
+fn no_active_epoch(vault: &Vault) -> Result<()> {
+    if vault.option_market != Pubkey::default() {
+        return Err(errors::ErrorCode::EpochInProgress.into())
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub enum VaultStrategy {
+    /// Write calls on the deposited asset, auctioned for the option's quote asset
+    CoveredCall = 0,
+    /// Write puts secured by the deposited quote currency, auctioned for the same currency
+    CashSecuredPut = 1,
+}
+impl Default for VaultStrategy {
+    fn default() -> Self {
+        VaultStrategy::CoveredCall
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeVault<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// The collateral mint, which is the underlying asset of the options the vault writes
+    pub underlying_asset_mint: Box<Account<'info, Mint>>,
+    pub quote_asset_mint: Box<Account<'info, Mint>>,
+    #[account(init,
+        seeds = [b"vault", admin.key().as_ref(), underlying_asset_mint.key().as_ref(), quote_asset_mint.key().as_ref()],
+        bump,
+        payer = admin,
+        space = 8 + std::mem::size_of::<Vault>(),
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+    /// The PDA that owns the vault's token accounts and OpenOrders
+    /// CHECK: Validated by the seeds
+    #[account(seeds = [vault.key().as_ref(), b"vaultAuthority"], bump)]
+    pub vault_authority: AccountInfo<'info>,
+    #[account(init,
+        seeds = [vault.key().as_ref(), b"underlying"],
+        bump,
+        payer = admin,
+        token::mint = underlying_asset_mint,
+        token::authority = vault_authority,
+    )]
+    pub vault_underlying: Box<Account<'info, TokenAccount>>,
+    #[account(init,
+        seeds = [vault.key().as_ref(), b"quote"],
+        bump,
+        payer = admin,
+        token::mint = quote_asset_mint,
+        token::authority = vault_authority,
+    )]
+    pub vault_quote: Box<Account<'info, TokenAccount>>,
+    #[account(init,
+        seeds = [vault.key().as_ref(), b"shares"],
+        bump,
+        payer = admin,
+        mint::decimals = underlying_asset_mint.decimals,
+        mint::authority = vault_authority,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    /// The depositor must own the `underlying_asset_src` and `quote_asset_src`
+    pub depositor: Signer<'info>,
+    #[account(has_one = vault_underlying, has_one = vault_quote, has_one = share_mint)]
+    pub vault: Box<Account<'info, Vault>>,
+    /// CHECK: Validated by the seeds
+    #[account(seeds = [vault.key().as_ref(), b"vaultAuthority"], bump = vault.authority_bump_seed)]
+    pub vault_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub vault_underlying: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub vault_quote: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub share_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub underlying_asset_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub quote_asset_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub share_dest: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// The withdrawer must own the `share_src`
+    pub withdrawer: Signer<'info>,
+    #[account(has_one = vault_underlying, has_one = vault_quote, has_one = share_mint)]
+    pub vault: Box<Account<'info, Vault>>,
+    /// CHECK: Validated by the seeds
+    #[account(seeds = [vault.key().as_ref(), b"vaultAuthority"], bump = vault.authority_bump_seed)]
+    pub vault_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub vault_underlying: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub vault_quote: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub share_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub share_src: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub underlying_asset_dest: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub quote_asset_dest: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitOpenOrders<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(has_one = admin)]
+    pub vault: Box<Account<'info, Vault>>,
+    /// CHECK: Validated by the seeds
+    #[account(mut, seeds = [vault.key().as_ref(), b"vaultAuthority"], bump = vault.authority_bump_seed)]
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: Validated by the address
+    #[account(address = psy_american::ID)]
+    pub psy_american_program: AccountInfo<'info>,
+    pub dex_program: Program<'info, Dex>,
+    /// The vault authority's OpenOrders PDA
+    /// CHECK: Validated by the seeds
+    #[account(mut,
+        seeds = [b"open-orders", dex_program.key().as_ref(), serum_market.key().as_ref(), vault_authority.key().as_ref()],
+        bump,
+        seeds::program = psy_american_program.key(),
+    )]
+    pub open_orders: AccountInfo<'info>,
+    /// CHECK: Validated by the OpenOrdersPda middleware
+    pub serum_market: AccountInfo<'info>,
+    /// The PsyOptions market authority for the Serum market
+    /// CHECK: Validated by the seeds
+    #[account(
+        seeds = [b"open-orders-init", dex_program.key().as_ref(), serum_market.key().as_ref()],
+        bump,
+        seeds::program = psy_american_program.key(),
+    )]
+    pub market_authority: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct StartEpoch<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(mut, has_one = admin, has_one = vault_underlying, has_one = underlying_asset_mint, has_one = quote_asset_mint)]
+    pub vault: Box<Account<'info, Vault>>,
+    /// CHECK: Validated by the seeds
+    #[account(seeds = [vault.key().as_ref(), b"vaultAuthority"], bump = vault.authority_bump_seed)]
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: Validated by the address
+    #[account(address = psy_american::ID)]
+    pub psy_american_program: AccountInfo<'info>,
+    #[account(mut)]
+    pub vault_underlying: Box<Account<'info, TokenAccount>>,
+    #[account(init,
+        seeds = [vault.key().as_ref(), option_market.key().as_ref(), b"options"],
+        bump,
+        payer = admin,
+        token::mint = option_mint,
+        token::authority = vault_authority,
+    )]
+    pub vault_options: Box<Account<'info, TokenAccount>>,
+    #[account(init,
+        seeds = [vault.key().as_ref(), option_market.key().as_ref(), b"writerTokens"],
+        bump,
+        payer = admin,
+        token::mint = writer_token_mint,
+        token::authority = vault_authority,
+    )]
+    pub vault_writer_tokens: Box<Account<'info, TokenAccount>>,
+
+    // PsyOptions accounts
+    pub underlying_asset_mint: Box<Account<'info, Mint>>,
+    pub quote_asset_mint: Box<Account<'info, Mint>>,
+    /// The Serum market's pc mint, which must match the vault's strategy
+    /// CHECK: Validated in StartEpoch::accounts
+    pub pc_mint: AccountInfo<'info>,
+    /// CHECK: Validated by PsyOptions
+    #[account(mut)]
+    pub underlying_asset_pool: AccountInfo<'info>,
+    #[account(mut)]
+    pub option_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub writer_token_mint: Box<Account<'info, Mint>>,
+    pub option_market: Box<Account<'info, OptionMarket>>,
+
+    // Serum accounts
+    pub dex_program: Program<'info, Dex>,
+    /// CHECK: Validated by PsyOptions
+    #[account(mut)]
+    pub serum_market: AccountInfo<'info>,
+    /// CHECK: Validated by PsyOptions
+    #[account(mut)]
+    pub open_orders: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub request_queue: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub event_queue: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub market_bids: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub market_asks: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub coin_vault: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub pc_vault: AccountInfo<'info>,
+    /// CHECK: Validated by PsyOptions
+    pub price_band: AccountInfo<'info>,
+    /// CHECK: Validated by PsyOptions
+    pub price_oracle: AccountInfo<'info>,
+    /// CHECK: Validated by PsyOptions
+    pub pause_config: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+impl<'info> StartEpoch<'info> {
+    fn accounts(ctx: &Context<StartEpoch<'info>>) -> Result<()> {
+        // Validate the OptionMarket writes calls on the vault's assets
+        if ctx.accounts.option_market.underlying_asset_mint != ctx.accounts.vault.underlying_asset_mint {
+            return Err(errors::ErrorCode::UnderlyingAssetDoesNotMatchVault.into())
+        }
+        if ctx.accounts.option_market.quote_asset_mint != ctx.accounts.vault.quote_asset_mint {
+            return Err(errors::ErrorCode::QuoteAssetDoesNotMatchVault.into())
+        }
+        // Validate the options are auctioned for the strategy's premium asset
+        if ctx.accounts.pc_mint.key() != ctx.accounts.vault.pc_mint() {
+            return Err(errors::ErrorCode::PcMintDoesNotMatchStrategy.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
+    /// CHECK: Must sign until the options expire, validated in SettleAuction::accounts
+    pub admin: AccountInfo<'info>,
+    #[account(mut, has_one = admin, has_one = option_market, has_one = serum_market, has_one = vault_underlying, has_one = vault_quote)]
+    pub vault: Box<Account<'info, Vault>>,
+    /// CHECK: Validated by the seeds
+    #[account(seeds = [vault.key().as_ref(), b"vaultAuthority"], bump = vault.authority_bump_seed)]
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: Validated by the address
+    #[account(address = psy_american::ID)]
+    pub psy_american_program: AccountInfo<'info>,
+    #[account(mut)]
+    pub vault_underlying: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub vault_quote: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [vault.key().as_ref(), option_market.key().as_ref(), b"options"], bump)]
+    pub vault_options: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [vault.key().as_ref(), option_market.key().as_ref(), b"writerTokens"], bump)]
+    pub vault_writer_tokens: Box<Account<'info, TokenAccount>>,
+
+    // PsyOptions accounts
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    /// CHECK: Validated by PsyOptions
+    #[account(mut)]
+    pub option_mint: AccountInfo<'info>,
+    /// CHECK: Validated by PsyOptions
+    #[account(mut)]
+    pub writer_token_mint: AccountInfo<'info>,
+    /// CHECK: Validated by PsyOptions
+    #[account(mut)]
+    pub underlying_asset_pool: AccountInfo<'info>,
+
+    // Serum accounts
+    pub dex_program: Program<'info, Dex>,
+    /// CHECK: Validated by the vault
+    #[account(mut)]
+    pub serum_market: AccountInfo<'info>,
+    /// CHECK: Validated by the OpenOrdersPda middleware
+    #[account(mut)]
+    pub open_orders: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub event_queue: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub market_bids: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub market_asks: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub coin_vault: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    #[account(mut)]
+    pub pc_vault: AccountInfo<'info>,
+    /// CHECK: Handled by Serum
+    pub vault_signer: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+impl<'info> SettleAuction<'info> {
+    fn accounts(ctx: &Context<SettleAuction>) -> Result<()> {
+        if ctx.accounts.vault.auction_settled {
+            return Err(errors::ErrorCode::AuctionAlreadySettled.into())
+        }
+        // Anyone can settle once the options expire, so the collateral is not locked without
+        // the admin
+        let expired = ctx.accounts.option_market.expiration_unix_timestamp < Clock::get()?.unix_timestamp;
+        if !expired && !ctx.accounts.admin.is_signer {
+            return Err(errors::ErrorCode::AdminRequiredBeforeExpiration.into())
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SettleEpoch<'info> {
+    #[account(mut, has_one = option_market, has_one = vault_underlying, has_one = vault_quote)]
+    pub vault: Box<Account<'info, Vault>>,
+    /// CHECK: Validated by the seeds
+    #[account(seeds = [vault.key().as_ref(), b"vaultAuthority"], bump = vault.authority_bump_seed)]
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: Validated by the address
+    #[account(address = psy_american::ID)]
+    pub psy_american_program: AccountInfo<'info>,
+    #[account(mut)]
+    pub vault_underlying: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub vault_quote: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [vault.key().as_ref(), option_market.key().as_ref(), b"writerTokens"], bump)]
+    pub vault_writer_tokens: Box<Account<'info, TokenAccount>>,
+
+    // PsyOptions accounts
+    pub option_market: Box<Account<'info, OptionMarket>>,
+    #[account(mut, address = option_market.writer_token_mint)]
+    pub writer_token_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Validated by PsyOptions
+    #[account(mut)]
+    pub underlying_asset_pool: AccountInfo<'info>,
+    #[account(mut, address = option_market.quote_asset_pool)]
+    pub quote_asset_pool: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+impl<'info> SettleEpoch<'info> {
+    fn accounts(ctx: &Context<SettleEpoch>) -> Result<()> {
+        if !ctx.accounts.vault.auction_settled {
+            return Err(errors::ErrorCode::AuctionNotSettled.into())
+        }
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct Vault {
+    /// The admin that starts epochs and settles auctions before the options expire
+    pub admin: Pubkey,
+    /// The type of options the vault writes
+    pub strategy: VaultStrategy,
+    /// The mint of the vault's collateral, which is the underlying asset of its options
+    pub underlying_asset_mint: Pubkey,
+    /// The mint of the asset the options are exercised in
+    pub quote_asset_mint: Pubkey,
+    /// The vault's collateral account
+    pub vault_underlying: Pubkey,
+    /// The vault's quote asset account, which receives the exercised quote assets
+    pub vault_quote: Pubkey,
+    /// The mint of the vault shares
+    pub share_mint: Pubkey,
+    /// The OptionMarket for the active epoch, or the default key between epochs
+    pub option_market: Pubkey,
+    /// The Serum market the active epoch's options are auctioned on
+    pub serum_market: Pubkey,
+    /// The current epoch, also used as the client order id of the epoch's ask
+    pub epoch: u64,
+    /// Whether the active epoch's auction has been settled
+    pub auction_settled: bool,
+    pub bump_seed: u8,
+    pub authority_bump_seed: u8,
+}
+impl Vault {
+    /// The mint the vault's options are auctioned for
+    pub fn pc_mint(&self) -> Pubkey {
+        match self.strategy {
+            VaultStrategy::CoveredCall => self.quote_asset_mint,
+            VaultStrategy::CashSecuredPut => self.underlying_asset_mint,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(option_decimals: u8) -> OptionMarket {
+        OptionMarket {
+            underlying_amount_per_contract: 100,
+            quote_amount_per_contract: 2_500,
+            option_decimals,
+            ..OptionMarket::default()
+        }
+    }
+
+    #[test]
+    fn whole_contracts_settle_pro_rata() {
+        // 4 of 10 contracts exercised, the vault wrote half of them
+        assert_eq!(settlement_sizes(&market(0), 5, 10, 4 * 2_500).unwrap(), (2, 3));
+        assert_eq!(settlement_sizes(&market(0), 5, 0, 0).unwrap(), (0, 5));
+    }
+
+    #[test]
+    fn fractional_markets_settle_in_option_tokens() {
+        // 0.4 of 1 contract exercised, the vault wrote half of the 100 option tokens
+        let market = market(2);
+        let (burn_for_quote, close_for_underlying) = settlement_sizes(&market, 50, 100, 1_000).unwrap();
+        assert_eq!((burn_for_quote, close_for_underlying), (20, 30));
+        // The quote pool pays the burn and the underlying pool the close
+        assert_eq!(market.quote_amount_out(burn_for_quote).unwrap(), 500);
+        assert!(market.underlying_amount_out(close_for_underlying).unwrap() <= 60);
+    }
+}
@@ -14,7 +14,7 @@ import {
   TransactionInstruction,
 } from "@solana/web3.js";
 import { OptionMarketV2 } from "../../packages/psyoptions-ts/src/types";
-import { initSetup } from "../../utils/helpers";
+import { derivePauseConfig, initSetup } from "../../utils/helpers";
 import { FEE_OWNER_KEY } from "../../packages/psyoptions-ts/src/fees";
 import { CpiExamples } from "../../target/types/cpi_examples";
 import { Program } from "@project-serum/anchor";
@@ -78,6 +78,50 @@ describe("cpi_examples initOptionMarket", () => {
             rent: SYSVAR_RENT_PUBKEY,
             systemProgram: SystemProgram.programId,
             clock: SYSVAR_CLOCK_PUBKEY,
+            listingRegistry: (
+              await PublicKey.findProgramAddress(
+                [Buffer.from("listingRegistry")],
+                americanOptionsProgram.programId
+              )
+            )[0],
+            listedPair: (
+              await PublicKey.findProgramAddress(
+                [
+                  Buffer.from("listedPair"),
+                  optionMarket.underlyingAssetMint.toBuffer(),
+                  optionMarket.quoteAssetMint.toBuffer(),
+                ],
+                americanOptionsProgram.programId
+              )
+            )[0],
+            lister: (
+              await PublicKey.findProgramAddress(
+                [Buffer.from("lister"), provider.wallet.publicKey.toBuffer()],
+                americanOptionsProgram.programId
+              )
+            )[0],
+            seriesConfig: (
+              await PublicKey.findProgramAddress(
+                [
+                  Buffer.from("seriesConfig"),
+                  optionMarket.underlyingAssetMint.toBuffer(),
+                  optionMarket.quoteAssetMint.toBuffer(),
+                ],
+                americanOptionsProgram.programId
+              )
+            )[0],
+            optionChain: (
+              await PublicKey.findProgramAddress(
+                [
+                  Buffer.from("optionChain"),
+                  optionMarket.underlyingAssetMint.toBuffer(),
+                  optionMarket.quoteAssetMint.toBuffer(),
+                  Buffer.from([0, 0, 0, 0]),
+                ],
+                americanOptionsProgram.programId
+              )
+            )[0],
+            pauseConfig: (await derivePauseConfig(americanOptionsProgram))[0],
           },
           remainingAccounts,
           instructions,
@@ -18,7 +18,12 @@ import {
 import { OptionMarketV2 } from "../../packages/psyoptions-ts/src/types";
 import { CpiExamples } from "../../target/types/cpi_examples";
 import { PsyAmerican } from "../../target/types/psy_american";
-import { createMinter, initOptionMarket, initSetup } from "../../utils/helpers";
+import {
+  createMinter,
+  derivePauseConfig,
+  initOptionMarket,
+  initSetup,
+} from "../../utils/helpers";
 
 const textEncoder = new TextEncoder();
 let optionMarket: OptionMarketV2,
@@ -203,6 +208,9 @@ describe("cpi_examples mint", () => {
               clock: SYSVAR_CLOCK_PUBKEY,
               rent: SYSVAR_RENT_PUBKEY,
               systemProgram: SystemProgram.programId,
+              pauseConfig: (
+                await derivePauseConfig(americanOptionsProgram)
+              )[0],
             },
             signers: [user],
           });
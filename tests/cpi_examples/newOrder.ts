@@ -8,6 +8,7 @@ import { OptionMarketV2 } from "../../packages/psyoptions-ts/src/types";
 import { initOptionMarket, initSetup } from "../../utils/helpers";
 import {
   createMintAndVault,
+  derivePriceBand,
   DEX_PID,
   getMarketAndAuthorityInfo,
   initMarket,
@@ -66,7 +67,8 @@ describe("cpi_examples newOrder", () => {
     vault: anchor.web3.PublicKey,
     vaultBumpSeed: number,
     vaultAuthority: anchor.web3.PublicKey,
-    vaultAuthBump: number;
+    vaultAuthBump: number,
+    priceBand: PublicKey;
   before(async () => {
     // Setup - Create an OptionMarket
     const {
@@ -108,6 +110,10 @@ describe("cpi_examples newOrder", () => {
       optionMarket,
       usdcMint
     ));
+    [priceBand] = await derivePriceBand(
+      americanOptionsProgram,
+      marketProxy.market.address
+    );
     // Set the token variables for use in later tests
     underlyingToken = new Token(
       provider.connection,
@@ -245,6 +251,8 @@ describe("cpi_examples newOrder", () => {
               coinVault: marketProxy.market._decoded.baseVault,
               // @ts-ignore: Dumb serum stuff
               pcVault: marketProxy.market._decoded.quoteVault,
+              priceBand,
+              priceOracle: priceBand,
 
               systemProgram: SystemProgram.programId,
               tokenProgram: TOKEN_PROGRAM_ID,
@@ -330,6 +338,8 @@ describe("cpi_examples newOrder", () => {
                 coinVault: marketProxy.market._decoded.baseVault,
                 // @ts-ignore: Dumb serum stuff
                 pcVault: marketProxy.market._decoded.quoteVault,
+                priceBand,
+                priceOracle: priceBand,
 
                 systemProgram: SystemProgram.programId,
                 tokenProgram: TOKEN_PROGRAM_ID,
@@ -22,12 +22,10 @@ import {
   initNewTokenMint,
   initOptionMarket,
   initSetup,
+  mintOptionV2Instruction,
   wait,
 } from "../utils/helpers";
-import {
-  OptionMarketWithKey,
-  instructions as psyAmericanInstructions,
-} from "@mithraic-labs/psy-american";
+import { OptionMarketWithKey } from "@mithraic-labs/psy-american";
 import {
   AnchorError,
   AnchorProvider,
@@ -140,7 +138,7 @@ describe("exerciseOption", () => {
       );
       // Mint a bunch of contracts to the minter
       const { ix: mintOptionsIx } =
-        await psyAmericanInstructions.mintOptionV2Instruction(
+        await mintOptionV2Instruction(
           minterProgram,
           minterOptionAcct.publicKey,
           minterWriterAcct.publicKey,
@@ -565,7 +563,7 @@ describe("exerciseOption", () => {
       );
       // Mint a bunch of contracts to the minter
       const { ix: mintOptionsIx } =
-        await psyAmericanInstructions.mintOptionV2Instruction(
+        await mintOptionV2Instruction(
           minterProgram,
           minterOptionAcct.publicKey,
           minterWriterAcct.publicKey,
@@ -681,7 +679,7 @@ describe("exerciseOption", () => {
       );
       // Mint a bunch of contracts to the minter
       const { ix: mintOptionsIx } =
-        await psyAmericanInstructions.mintOptionV2Instruction(
+        await mintOptionV2Instruction(
           minterProgram,
           minterOptionAcct.publicKey,
           minterWriterAcct.publicKey,
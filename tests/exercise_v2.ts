@@ -22,6 +22,7 @@ import {
   initNewTokenMint,
   initOptionMarket,
   initSetup,
+  mintOptionV2Instruction,
   wait,
 } from "../utils/helpers";
 import {
@@ -152,7 +153,7 @@ describe("exerciseOption", () => {
       );
       // Mint a bunch of contracts to the minter
       const { ix: mintOptionsIx } =
-        await psyAmericanInstructions.mintOptionV2Instruction(
+        await mintOptionV2Instruction(
           minterProgram,
           minterOptionAcct.publicKey,
           minterWriterAcct.publicKey,
@@ -468,7 +469,7 @@ describe("exerciseOption", () => {
       );
       // Mint a bunch of contracts to the minter
       const { ix: mintOptionsIx } =
-        await psyAmericanInstructions.mintOptionV2Instruction(
+        await mintOptionV2Instruction(
           minterProgram,
           minterOptionAcct.publicKey,
           minterWriterAcct.publicKey,
@@ -22,6 +22,7 @@ import {
 } from "../packages/psyoptions-ts/src/fees";
 import {
   createMinter,
+  derivePauseConfig,
   initNewTokenAccount,
   initNewTokenMint,
   initOptionMarket,
@@ -85,6 +86,7 @@ describe("mintOption", () => {
       feeOwner?: PublicKey;
     } = {}
   ) => {
+    const [pauseConfig] = await derivePauseConfig(program);
     await program.rpc.mintOption(size, {
       accounts: {
         userAuthority: minter.publicKey,
@@ -103,6 +105,7 @@ describe("mintOption", () => {
         clock: SYSVAR_CLOCK_PUBKEY,
         rent: SYSVAR_RENT_PUBKEY,
         systemProgram: SystemProgram.programId,
+        pauseConfig,
       },
       remainingAccounts: opts.remainingAccounts
         ? opts.remainingAccounts
@@ -9,6 +9,7 @@ import {
 } from "@solana/web3.js";
 import {
   createMinter,
+  derivePauseConfig,
   initNewTokenAccount,
   initNewTokenMint,
   initOptionMarket,
@@ -71,6 +72,7 @@ describe("mintOption", () => {
       remainingAccounts?: AccountMeta[];
     } = {}
   ) => {
+    const [pauseConfig] = await derivePauseConfig(program);
     await program.rpc.mintOptionV2(size, {
       accounts: {
         userAuthority: minter.publicKey,
@@ -84,6 +86,7 @@ describe("mintOption", () => {
         mintedWriterTokenDest: writerTokenAccount.publicKey,
         optionMarket: optionMarket?.key,
         tokenProgram: TOKEN_PROGRAM_ID,
+        pauseConfig,
       },
       remainingAccounts: opts.remainingAccounts
         ? opts.remainingAccounts
@@ -0,0 +1,96 @@
+import * as anchor from "@project-serum/anchor";
+import assert from "assert";
+import { Program } from "@project-serum/anchor";
+import { Token, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+} from "@solana/web3.js";
+import { PsyVault } from "../../target/types/psy_vault";
+import { createUnderlyingAndQuoteMints } from "../../utils/helpers";
+
+const textEncoder = new TextEncoder();
+
+describe("psy_vault cash-secured put", () => {
+  const program = anchor.workspace.PsyVault as Program<PsyVault>;
+  const provider = program.provider;
+  const admin = Keypair.generate();
+  const mintAuthority = Keypair.generate();
+  // Put-style markets use the quote currency as the underlying asset
+  let usdcToken: Token, assetToken: Token;
+  let vault: PublicKey;
+
+  before(async () => {
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        admin.publicKey,
+        100 * LAMPORTS_PER_SOL
+      ),
+      "confirmed"
+    );
+    ({ underlyingToken: usdcToken, quoteToken: assetToken } =
+      await createUnderlyingAndQuoteMints(
+        provider,
+        (provider.wallet as anchor.Wallet).payer,
+        mintAuthority
+      ));
+    [vault] = await PublicKey.findProgramAddress(
+      [
+        textEncoder.encode("vault"),
+        admin.publicKey.toBuffer(),
+        usdcToken.publicKey.toBuffer(),
+        assetToken.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+  });
+
+  describe("initializeVault", () => {
+    it("should create a vault collateralized by the quote currency", async () => {
+      const [vaultAuthority] = await PublicKey.findProgramAddress(
+        [vault.toBuffer(), textEncoder.encode("vaultAuthority")],
+        program.programId
+      );
+      const [vaultUnderlying] = await PublicKey.findProgramAddress(
+        [vault.toBuffer(), textEncoder.encode("underlying")],
+        program.programId
+      );
+      const [vaultQuote] = await PublicKey.findProgramAddress(
+        [vault.toBuffer(), textEncoder.encode("quote")],
+        program.programId
+      );
+      const [shareMint] = await PublicKey.findProgramAddress(
+        [vault.toBuffer(), textEncoder.encode("shares")],
+        program.programId
+      );
+      await program.rpc.initializeVault(
+        { cashSecuredPut: {} },
+        {
+          accounts: {
+            admin: admin.publicKey,
+            underlyingAssetMint: usdcToken.publicKey,
+            quoteAssetMint: assetToken.publicKey,
+            vault,
+            vaultAuthority,
+            vaultUnderlying,
+            vaultQuote,
+            shareMint,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            rent: SYSVAR_RENT_PUBKEY,
+          },
+          signers: [admin],
+        }
+      );
+
+      const vaultAcct = await program.account.vault.fetch(vault);
+      assert.deepEqual(vaultAcct.strategy, { cashSecuredPut: {} });
+      assert.ok(vaultAcct.underlyingAssetMint.equals(usdcToken.publicKey));
+      assert.ok(vaultAcct.quoteAssetMint.equals(assetToken.publicKey));
+      assert.ok(vaultAcct.optionMarket.equals(PublicKey.default));
+    });
+  });
+});
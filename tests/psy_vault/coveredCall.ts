@@ -0,0 +1,175 @@
+import * as anchor from "@project-serum/anchor";
+import assert from "assert";
+import { AnchorError, Program } from "@project-serum/anchor";
+import { Token, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+} from "@solana/web3.js";
+import { PsyVault } from "../../target/types/psy_vault";
+import { createUnderlyingAndQuoteMints } from "../../utils/helpers";
+
+const textEncoder = new TextEncoder();
+
+describe("psy_vault covered call", () => {
+  const program = anchor.workspace.PsyVault as Program<PsyVault>;
+  const provider = program.provider;
+  const admin = Keypair.generate();
+  const depositor = Keypair.generate();
+  const mintAuthority = Keypair.generate();
+  let underlyingToken: Token, quoteToken: Token, shareToken: Token;
+  let vault: PublicKey,
+    vaultAuthority: PublicKey,
+    vaultUnderlying: PublicKey,
+    vaultQuote: PublicKey,
+    shareMint: PublicKey;
+  let underlyingSrc: PublicKey, quoteSrc: PublicKey, shareAccount: PublicKey;
+
+  const deposit = (amount: number) =>
+    program.rpc.deposit(new anchor.BN(amount), {
+      accounts: {
+        depositor: depositor.publicKey,
+        vault,
+        vaultAuthority,
+        vaultUnderlying,
+        vaultQuote,
+        shareMint,
+        underlyingAssetSrc: underlyingSrc,
+        quoteAssetSrc: quoteSrc,
+        shareDest: shareAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+      signers: [depositor],
+    });
+
+  before(async () => {
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        admin.publicKey,
+        100 * LAMPORTS_PER_SOL
+      ),
+      "confirmed"
+    );
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        depositor.publicKey,
+        100 * LAMPORTS_PER_SOL
+      ),
+      "confirmed"
+    );
+    ({ underlyingToken, quoteToken } = await createUnderlyingAndQuoteMints(
+      provider,
+      (provider.wallet as anchor.Wallet).payer,
+      mintAuthority
+    ));
+    [vault] = await PublicKey.findProgramAddress(
+      [
+        textEncoder.encode("vault"),
+        admin.publicKey.toBuffer(),
+        underlyingToken.publicKey.toBuffer(),
+        quoteToken.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    [vaultAuthority] = await PublicKey.findProgramAddress(
+      [vault.toBuffer(), textEncoder.encode("vaultAuthority")],
+      program.programId
+    );
+    [vaultUnderlying] = await PublicKey.findProgramAddress(
+      [vault.toBuffer(), textEncoder.encode("underlying")],
+      program.programId
+    );
+    [vaultQuote] = await PublicKey.findProgramAddress(
+      [vault.toBuffer(), textEncoder.encode("quote")],
+      program.programId
+    );
+    [shareMint] = await PublicKey.findProgramAddress(
+      [vault.toBuffer(), textEncoder.encode("shares")],
+      program.programId
+    );
+
+    await program.rpc.initializeVault(
+      { coveredCall: {} },
+      {
+        accounts: {
+          admin: admin.publicKey,
+          underlyingAssetMint: underlyingToken.publicKey,
+          quoteAssetMint: quoteToken.publicKey,
+          vault,
+          vaultAuthority,
+          vaultUnderlying,
+          vaultQuote,
+          shareMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        },
+        signers: [admin],
+      }
+    );
+
+    shareToken = new Token(
+      provider.connection,
+      shareMint,
+      TOKEN_PROGRAM_ID,
+      depositor
+    );
+    underlyingSrc = await underlyingToken.createAccount(depositor.publicKey);
+    quoteSrc = await quoteToken.createAccount(depositor.publicKey);
+    shareAccount = await shareToken.createAccount(depositor.publicKey);
+    await underlyingToken.mintTo(underlyingSrc, mintAuthority, [], 1_000_000);
+  });
+
+  describe("deposit", () => {
+    it("should error when depositing 0", async () => {
+      try {
+        await deposit(0);
+        assert.ok(false);
+      } catch (err) {
+        const errMsg = "The amount must be > 0";
+        assert.equal((err as AnchorError).error.errorMessage, errMsg);
+      }
+    });
+
+    it("should mint shares 1:1 for the first deposit", async () => {
+      await deposit(400_000);
+
+      const shareAcct = await shareToken.getAccountInfo(shareAccount);
+      assert.equal(shareAcct.amount.toString(), "400000");
+      const vaultUnderlyingAcct = await underlyingToken.getAccountInfo(
+        vaultUnderlying
+      );
+      assert.equal(vaultUnderlyingAcct.amount.toString(), "400000");
+    });
+  });
+
+  describe("withdraw", () => {
+    it("should return the pro rata underlying for the shares", async () => {
+      await program.rpc.withdraw(new anchor.BN(100_000), {
+        accounts: {
+          withdrawer: depositor.publicKey,
+          vault,
+          vaultAuthority,
+          vaultUnderlying,
+          vaultQuote,
+          shareMint,
+          shareSrc: shareAccount,
+          underlyingAssetDest: underlyingSrc,
+          quoteAssetDest: quoteSrc,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [depositor],
+      });
+
+      const shareAcct = await shareToken.getAccountInfo(shareAccount);
+      assert.equal(shareAcct.amount.toString(), "300000");
+      const underlyingAcct = await underlyingToken.getAccountInfo(
+        underlyingSrc
+      );
+      assert.equal(underlyingAcct.amount.toString(), "700000");
+    });
+  });
+});
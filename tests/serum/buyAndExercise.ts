@@ -0,0 +1,278 @@
+import * as anchor from "@project-serum/anchor";
+import assert from "assert";
+import { AnchorError, Program } from "@project-serum/anchor";
+import { MarketProxy } from "@project-serum/serum";
+import { Token, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SYSVAR_RENT_PUBKEY,
+  Transaction,
+} from "@solana/web3.js";
+import { mintOptionsTx } from "../../packages/psyoptions-ts/src";
+import { OptionMarketV2 } from "../../packages/psyoptions-ts/src/types";
+import {
+  createMinter,
+  derivePauseConfig,
+  initOptionMarket,
+  initSetup,
+} from "../../utils/helpers";
+import {
+  createMintAndVault,
+  derivePriceBand,
+  DEX_PID,
+  getMarketAndAuthorityInfo,
+  getVaultOwnerAndNonce,
+  initMarket,
+  marketLoader,
+  openOrdersSeed,
+} from "../../utils/serum";
+import { PsyAmerican } from "../../target/types/psy_american";
+
+describe("buyAndExercise", () => {
+  const program = anchor.workspace.PsyAmerican as Program<PsyAmerican>;
+  const provider = program.provider;
+  // @ts-ignore: TODO: Remove when anchor PR released
+  const wallet = provider.wallet as anchor.Wallet;
+  const mintAuthority = Keypair.generate();
+  const buyer = Keypair.generate();
+  let optionMarket: OptionMarketV2;
+  let marketProxy: MarketProxy;
+  let usdcMint: PublicKey, usdcAccount: PublicKey;
+  let priceBand: PublicKey;
+  let vaultSigner: PublicKey;
+  let buyerOpenOrders: PublicKey;
+  let buyerPcWallet: PublicKey;
+  let buyerOptionAccount: Keypair,
+    buyerUnderlyingAccount: Keypair,
+    buyerQuoteAccount: Keypair;
+  let underlyingToken: Token, optionToken: Token;
+
+  const buyAndExercise = (size: number, minFill: number) =>
+    program.rpc.buyAndExercise(
+      new anchor.BN(size),
+      marketProxy.market.priceNumberToLots(1),
+      new anchor.BN(1_000_000_000),
+      new anchor.BN(minFill),
+      {
+        accounts: {
+          userAuthority: buyer.publicKey,
+          optionMarket: optionMarket.key,
+          optionMint: optionMarket.optionMint,
+          optionTokenDest: buyerOptionAccount.publicKey,
+          underlyingAssetPool: optionMarket.underlyingAssetPool,
+          underlyingAssetDest: buyerUnderlyingAccount.publicKey,
+          quoteAssetPool: optionMarket.quoteAssetPool,
+          quoteAssetSrc: buyerQuoteAccount.publicKey,
+          pcWallet: buyerPcWallet,
+          dexProgram: DEX_PID,
+          pcMint: usdcMint,
+          serumMarket: marketProxy.market.address,
+          openOrders: buyerOpenOrders,
+          // @ts-ignore: Dumb serum stuff
+          requestQueue: marketProxy.market._decoded.requestQueue,
+          // @ts-ignore: Dumb serum stuff
+          eventQueue: marketProxy.market._decoded.eventQueue,
+          marketBids: marketProxy.market.bidsAddress,
+          marketAsks: marketProxy.market.asksAddress,
+          // @ts-ignore: Dumb serum stuff
+          coinVault: marketProxy.market._decoded.baseVault,
+          // @ts-ignore: Dumb serum stuff
+          pcVault: marketProxy.market._decoded.quoteVault,
+          vaultSigner,
+          priceBand,
+          priceOracle: priceBand,
+          psyAmericanProgram: program.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          pauseConfig: (await derivePauseConfig(program))[0],
+        },
+        signers: [buyer],
+      }
+    );
+
+  before(async () => {
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        buyer.publicKey,
+        100 * LAMPORTS_PER_SOL
+      ),
+      "confirmed"
+    );
+    const {
+      optionMarket: newOptionMarket,
+      quoteToken,
+      remainingAccounts,
+      instructions,
+    } = await initSetup(provider, wallet.payer, mintAuthority, program);
+    optionMarket = newOptionMarket;
+    await initOptionMarket(
+      program,
+      wallet.payer,
+      optionMarket,
+      remainingAccounts,
+      instructions
+    );
+    [usdcMint, usdcAccount] = await createMintAndVault(
+      provider,
+      new anchor.BN("1000000000000000000"),
+      undefined,
+      6
+    );
+    const { marketAuthorityBump } = await getMarketAndAuthorityInfo(
+      program,
+      optionMarket,
+      DEX_PID,
+      usdcMint
+    );
+    ({ marketA: marketProxy } = await initMarket(
+      provider,
+      program,
+      marketLoader(provider, program, optionMarket.key, marketAuthorityBump),
+      optionMarket,
+      usdcMint
+    ));
+    [priceBand] = await derivePriceBand(program, marketProxy.market.address);
+    [vaultSigner] = await getVaultOwnerAndNonce(
+      marketProxy.market.address,
+      DEX_PID
+    );
+    [buyerOpenOrders] = await PublicKey.findProgramAddress(
+      [
+        openOrdersSeed,
+        DEX_PID.toBuffer(),
+        marketProxy.market.address.toBuffer(),
+        buyer.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    underlyingToken = new Token(
+      provider.connection,
+      optionMarket.underlyingAssetMint,
+      TOKEN_PROGRAM_ID,
+      wallet.payer
+    );
+    optionToken = new Token(
+      provider.connection,
+      optionMarket.optionMint,
+      TOKEN_PROGRAM_ID,
+      wallet.payer
+    );
+    const usdcToken = new Token(
+      provider.connection,
+      usdcMint,
+      TOKEN_PROGRAM_ID,
+      wallet.payer
+    );
+
+    // The seller mints options and posts an ask for 1 of them
+    const {
+      optionAccount: sellerOptionAccount,
+      underlyingAccount: sellerUnderlyingAccount,
+      writerTokenAccount: sellerWriterTokenAccount,
+    } = await createMinter(
+      provider.connection,
+      wallet.payer,
+      mintAuthority,
+      underlyingToken,
+      optionMarket.underlyingAmountPerContract.muln(10).toNumber(),
+      optionMarket.optionMint,
+      optionMarket.writerTokenMint,
+      usdcToken
+    );
+    await mintOptionsTx(
+      program,
+      wallet.payer,
+      sellerOptionAccount,
+      sellerWriterTokenAccount,
+      sellerUnderlyingAccount,
+      new anchor.BN(1),
+      optionMarket
+    );
+    const dummy = new Keypair();
+    const tx = new Transaction();
+    tx.add(
+      await marketProxy.instruction.initOpenOrders(
+        wallet.publicKey,
+        marketProxy.market.address,
+        dummy.publicKey,
+        dummy.publicKey
+      )
+    );
+    tx.add(
+      marketProxy.instruction.newOrderV3({
+        owner: wallet.publicKey,
+        payer: sellerOptionAccount.publicKey,
+        side: "sell",
+        price: 1,
+        size: 1,
+        orderType: "postOnly",
+        clientId: new anchor.BN(1),
+        selfTradeBehavior: "abortTransaction",
+      })
+    );
+    await provider.sendAndConfirm!(tx);
+
+    // The buyer needs pc to buy and quote to exercise
+    ({
+      optionAccount: buyerOptionAccount,
+      underlyingAccount: buyerUnderlyingAccount,
+      quoteAccount: buyerQuoteAccount,
+    } = await createMinter(
+      provider.connection,
+      buyer,
+      mintAuthority,
+      underlyingToken,
+      0,
+      optionMarket.optionMint,
+      optionMarket.writerTokenMint,
+      quoteToken,
+      optionMarket.quoteAmountPerContract.muln(10).toNumber()
+    ));
+    buyerPcWallet = await usdcToken.createAccount(buyer.publicKey);
+    await usdcToken.transfer(
+      usdcAccount,
+      buyerPcWallet,
+      wallet.payer,
+      [],
+      1_000_000_000
+    );
+    const initTx = new Transaction();
+    initTx.add(
+      await marketProxy.instruction.initOpenOrders(
+        buyer.publicKey,
+        marketProxy.market.address,
+        dummy.publicKey,
+        dummy.publicKey
+      )
+    );
+    await provider.sendAndConfirm!(initTx, [buyer]);
+  });
+
+  it("should error when less than the minimum fill is available", async () => {
+    try {
+      await buyAndExercise(2, 2);
+      assert.ok(false);
+    } catch (err) {
+      const errMsg = "Fewer options were filled than the minimum fill";
+      assert.equal((err as AnchorError).error.errorMessage, errMsg);
+    }
+  });
+
+  it("should buy and exercise the filled options", async () => {
+    await buyAndExercise(2, 1);
+
+    const buyerOptionAcct = await optionToken.getAccountInfo(
+      buyerOptionAccount.publicKey
+    );
+    assert.equal(buyerOptionAcct.amount.toString(), "0");
+    const buyerUnderlyingAcct = await underlyingToken.getAccountInfo(
+      buyerUnderlyingAccount.publicKey
+    );
+    assert.equal(
+      buyerUnderlyingAcct.amount.toString(),
+      optionMarket.underlyingAmountPerContract.toString()
+    );
+  });
+});
@@ -0,0 +1,217 @@
+import * as anchor from "@project-serum/anchor";
+import assert from "assert";
+import { AnchorError, Program } from "@project-serum/anchor";
+import { MarketProxy, OpenOrders } from "@project-serum/serum";
+import { Token, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import {
+  Keypair,
+  PublicKey,
+  SYSVAR_RENT_PUBKEY,
+  Transaction,
+} from "@solana/web3.js";
+import { OptionMarketV2 } from "../../packages/psyoptions-ts/src/types";
+import {
+  createMinter,
+  derivePauseConfig,
+  initOptionMarket,
+  initSetup,
+} from "../../utils/helpers";
+import {
+  createMintAndVault,
+  derivePriceBand,
+  DEX_PID,
+  getMarketAndAuthorityInfo,
+  initMarket,
+  marketLoader,
+  openOrdersSeed,
+} from "../../utils/serum";
+import { PsyAmerican } from "../../target/types/psy_american";
+
+describe("mintAndSell", () => {
+  const program = anchor.workspace.PsyAmerican as Program<PsyAmerican>;
+  const provider = program.provider;
+  // @ts-ignore: TODO: Remove when anchor PR released
+  const wallet = provider.wallet as anchor.Wallet;
+  const mintAuthority = Keypair.generate();
+  let optionMarket: OptionMarketV2;
+  let marketProxy: MarketProxy;
+  let usdcMint: PublicKey;
+  let openOrdersKey: PublicKey;
+  let priceBand: PublicKey;
+  let optionToken: Token;
+  let writerToken: Token;
+  let optionAccount: Keypair,
+    underlyingAccount: Keypair,
+    writerTokenAccount: Keypair;
+
+  const mintAndSell = (size: anchor.BN, limitPrice: anchor.BN) =>
+    program.rpc.mintAndSell(
+      size,
+      limitPrice,
+      { limit: {} },
+      new anchor.BN(1),
+      {
+        accounts: {
+          userAuthority: wallet.publicKey,
+          underlyingAssetMint: optionMarket.underlyingAssetMint,
+          underlyingAssetPool: optionMarket.underlyingAssetPool,
+          underlyingAssetSrc: underlyingAccount.publicKey,
+          optionMint: optionMarket.optionMint,
+          mintedOptionDest: optionAccount.publicKey,
+          writerTokenMint: optionMarket.writerTokenMint,
+          mintedWriterTokenDest: writerTokenAccount.publicKey,
+          optionMarket: optionMarket.key,
+          dexProgram: DEX_PID,
+          pcMint: usdcMint,
+          serumMarket: marketProxy.market.address,
+          openOrders: openOrdersKey,
+          // @ts-ignore: Dumb serum stuff
+          requestQueue: marketProxy.market._decoded.requestQueue,
+          // @ts-ignore: Dumb serum stuff
+          eventQueue: marketProxy.market._decoded.eventQueue,
+          marketBids: marketProxy.market.bidsAddress,
+          marketAsks: marketProxy.market.asksAddress,
+          // @ts-ignore: Dumb serum stuff
+          coinVault: marketProxy.market._decoded.baseVault,
+          // @ts-ignore: Dumb serum stuff
+          pcVault: marketProxy.market._decoded.quoteVault,
+          priceBand,
+          priceOracle: priceBand,
+          psyAmericanProgram: program.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          pauseConfig: (await derivePauseConfig(program))[0],
+        },
+      }
+    );
+
+  before(async () => {
+    const {
+      optionMarket: newOptionMarket,
+      remainingAccounts,
+      instructions,
+    } = await initSetup(provider, wallet.payer, mintAuthority, program);
+    optionMarket = newOptionMarket;
+    await initOptionMarket(
+      program,
+      wallet.payer,
+      optionMarket,
+      remainingAccounts,
+      instructions
+    );
+    [usdcMint] = await createMintAndVault(
+      provider,
+      new anchor.BN("1000000000000000000"),
+      undefined,
+      6
+    );
+    const { marketAuthorityBump } = await getMarketAndAuthorityInfo(
+      program,
+      optionMarket,
+      DEX_PID,
+      usdcMint
+    );
+    ({ marketA: marketProxy } = await initMarket(
+      provider,
+      program,
+      marketLoader(provider, program, optionMarket.key, marketAuthorityBump),
+      optionMarket,
+      usdcMint
+    ));
+    [priceBand] = await derivePriceBand(program, marketProxy.market.address);
+    [openOrdersKey] = await PublicKey.findProgramAddress(
+      [
+        openOrdersSeed,
+        DEX_PID.toBuffer(),
+        marketProxy.market.address.toBuffer(),
+        wallet.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    const underlyingToken = new Token(
+      provider.connection,
+      optionMarket.underlyingAssetMint,
+      TOKEN_PROGRAM_ID,
+      wallet.payer
+    );
+    const usdcToken = new Token(
+      provider.connection,
+      usdcMint,
+      TOKEN_PROGRAM_ID,
+      wallet.payer
+    );
+    optionToken = new Token(
+      provider.connection,
+      optionMarket.optionMint,
+      TOKEN_PROGRAM_ID,
+      wallet.payer
+    );
+    writerToken = new Token(
+      provider.connection,
+      optionMarket.writerTokenMint,
+      TOKEN_PROGRAM_ID,
+      wallet.payer
+    );
+    ({ optionAccount, underlyingAccount, writerTokenAccount } =
+      await createMinter(
+        provider.connection,
+        wallet.payer,
+        mintAuthority,
+        underlyingToken,
+        optionMarket.underlyingAmountPerContract.muln(100).toNumber(),
+        optionMarket.optionMint,
+        optionMarket.writerTokenMint,
+        usdcToken
+      ));
+
+    // Initialize the OpenOrders account through the proxy
+    const dummy = new Keypair();
+    const tx = new Transaction();
+    tx.add(
+      await marketProxy.instruction.initOpenOrders(
+        wallet.publicKey,
+        marketProxy.market.address,
+        dummy.publicKey,
+        dummy.publicKey
+      )
+    );
+    await provider.sendAndConfirm!(tx);
+  });
+
+  it("should error when the limit price is 0", async () => {
+    try {
+      await mintAndSell(new anchor.BN(1), new anchor.BN(0));
+      assert.ok(false);
+    } catch (err) {
+      const errMsg = "Limit price must be > 0";
+      assert.equal((err as AnchorError).error.errorMessage, errMsg);
+    }
+  });
+
+  it("should mint options and post them as an ask", async () => {
+    const size = new anchor.BN(2);
+    await mintAndSell(size, new anchor.BN(100));
+
+    // The writer tokens stay with the user
+    const writerTokenAcct = await writerToken.getAccountInfo(
+      writerTokenAccount.publicKey
+    );
+    assert.equal(writerTokenAcct.amount.toString(), size.toString());
+    // The option tokens are posted to the book and the delegate is revoked
+    const optionTokenAcct = await optionToken.getAccountInfo(
+      optionAccount.publicKey
+    );
+    assert.equal(optionTokenAcct.amount.toString(), "0");
+    assert.equal(optionTokenAcct.delegate, null);
+
+    const openOrders = await OpenOrders.load(
+      provider.connection,
+      openOrdersKey,
+      DEX_PID
+    );
+    assert.equal(openOrders.orders.filter((id) => !id.isZero()).length, 1);
+    const asks = await marketProxy.market.loadAsks(provider.connection);
+    const [, askSize] = asks.getL2(1)[0];
+    assert.equal(askSize, size.toNumber());
+  });
+});
@@ -0,0 +1,103 @@
+import * as anchor from "@project-serum/anchor";
+import assert from "assert";
+import { AnchorError, Program } from "@project-serum/anchor";
+import { MarketProxy } from "@project-serum/serum";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { OptionMarketV2 } from "../../packages/psyoptions-ts/src/types";
+import {
+  deriveGovernance,
+  initOptionMarket,
+  initSetup,
+} from "../../utils/helpers";
+import {
+  createMintAndVault,
+  derivePriceBand,
+  DEX_PID,
+  getMarketAndAuthorityInfo,
+  initMarket,
+  marketLoader,
+} from "../../utils/serum";
+import { PsyAmerican } from "../../target/types/psy_american";
+
+describe("priceBand", () => {
+  const program = anchor.workspace.PsyAmerican as Program<PsyAmerican>;
+  const provider = program.provider;
+  // @ts-ignore: TODO: Remove when anchor PR released
+  const wallet = provider.wallet as anchor.Wallet;
+  const mintAuthority = Keypair.generate();
+  let optionMarket: OptionMarketV2;
+  let marketProxy: MarketProxy;
+  let usdcMint: PublicKey;
+  let priceBand: PublicKey;
+  let governance: PublicKey;
+
+  before(async () => {
+    const {
+      optionMarket: newOptionMarket,
+      remainingAccounts,
+      instructions,
+    } = await initSetup(provider, wallet.payer, mintAuthority, program);
+    optionMarket = newOptionMarket;
+    await initOptionMarket(
+      program,
+      wallet.payer,
+      optionMarket,
+      remainingAccounts,
+      instructions
+    );
+    [usdcMint] = await createMintAndVault(
+      provider,
+      new anchor.BN("1000000000000000000"),
+      undefined,
+      6
+    );
+    const { marketAuthorityBump } = await getMarketAndAuthorityInfo(
+      program,
+      optionMarket,
+      DEX_PID,
+      usdcMint
+    );
+    ({ marketA: marketProxy } = await initMarket(
+      provider,
+      program,
+      marketLoader(provider, program, optionMarket.key, marketAuthorityBump),
+      optionMarket,
+      usdcMint
+    ));
+    [priceBand] = await derivePriceBand(program, marketProxy.market.address);
+    [governance] = await deriveGovernance(program);
+  });
+
+  describe("initPriceBand", () => {
+    it("should error when not signed by the fee owner", async () => {
+      try {
+        await program.rpc.initPriceBand(1_000, 1_000, {
+          accounts: {
+            payer: wallet.publicKey,
+            feeOwner: wallet.publicKey,
+            governance,
+            optionMarket: optionMarket.key,
+            underlyingAssetMint: optionMarket.underlyingAssetMint,
+            quoteAssetMint: optionMarket.quoteAssetMint,
+            pcMint: usdcMint,
+            serumMarket: marketProxy.market.address,
+            oracle: priceBand,
+            priceBand,
+            systemProgram: SystemProgram.programId,
+          },
+        });
+        assert.ok(false);
+      } catch (err) {
+        const errMsg = "Fee owner does not match the program's fee owner";
+        assert.equal((err as AnchorError).error.errorMessage, errMsg);
+      }
+    });
+  });
+
+  describe("market without a price band", () => {
+    it("should not have a PriceBandConfig", async () => {
+      const priceBandAcct = await provider.connection.getAccountInfo(priceBand);
+      assert.equal(priceBandAcct, null);
+    });
+  });
+});
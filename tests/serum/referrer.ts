@@ -0,0 +1,138 @@
+import * as anchor from "@project-serum/anchor";
+import assert from "assert";
+import { AnchorError, Program } from "@project-serum/anchor";
+import { Token, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { FEE_OWNER_KEY } from "../../packages/psyoptions-ts/src/fees";
+import { PsyAmerican } from "../../target/types/psy_american";
+import { deriveGovernance } from "../../utils/helpers";
+import { deriveReferrer } from "../../utils/serum";
+
+describe("referrer", () => {
+  const program = anchor.workspace.PsyAmerican as Program<PsyAmerican>;
+  const provider = program.provider;
+  const frontend = Keypair.generate();
+  const mintAuthority = Keypair.generate();
+  let referrer: PublicKey;
+  let governance: PublicKey;
+  let usdcToken: Token;
+  let referralFeeSrc: PublicKey;
+  let frontendFeeDest: PublicKey;
+  let protocolFeeDest: PublicKey;
+
+  before(async () => {
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        frontend.publicKey,
+        100 * LAMPORTS_PER_SOL
+      ),
+      "confirmed"
+    );
+    [referrer] = await deriveReferrer(program, frontend.publicKey);
+    [governance] = await deriveGovernance(program);
+    usdcToken = await Token.createMint(
+      provider.connection,
+      frontend,
+      mintAuthority.publicKey,
+      null,
+      6,
+      TOKEN_PROGRAM_ID
+    );
+    referralFeeSrc = await usdcToken.createAccount(referrer);
+    frontendFeeDest = await usdcToken.createAccount(frontend.publicKey);
+    protocolFeeDest = await usdcToken.createAccount(FEE_OWNER_KEY);
+  });
+
+  describe("registerReferrer", () => {
+    it("should create the Referrer with the default share", async () => {
+      await program.rpc.registerReferrer({
+        accounts: {
+          authority: frontend.publicKey,
+          referrer,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        },
+        signers: [frontend],
+      });
+
+      const referrerAcct = await program.account.referrer.fetch(referrer);
+      assert.ok(referrerAcct.authority.equals(frontend.publicKey));
+      assert.equal(referrerAcct.frontendShareBps, 5_000);
+    });
+  });
+
+  describe("setReferrerShare", () => {
+    it("should error when not signed by the fee owner", async () => {
+      try {
+        await program.rpc.setReferrerShare(8_000, {
+          accounts: {
+            feeOwner: frontend.publicKey,
+            governance,
+            referrer,
+          },
+          signers: [frontend],
+        });
+        assert.ok(false);
+      } catch (err) {
+        const errMsg = "Fee owner does not match the program's fee owner";
+        assert.equal((err as AnchorError).error.errorMessage, errMsg);
+      }
+    });
+  });
+
+  describe("claimReferralFees", () => {
+    before(async () => {
+      await usdcToken.mintTo(
+        referralFeeSrc,
+        mintAuthority,
+        [],
+        new anchor.BN(1_000_000).toNumber()
+      );
+    });
+
+    it("should error when the frontend destination is not owned by the authority", async () => {
+      try {
+        await program.rpc.claimReferralFees({
+          accounts: {
+            authority: frontend.publicKey,
+            referrer,
+            referralFeeSrc,
+            frontendFeeDest: protocolFeeDest,
+            protocolFeeDest,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          },
+          signers: [frontend],
+        });
+        assert.ok(false);
+      } catch (err) {
+        const errMsg =
+          "Referral fee destination must be owned by the Referrer authority";
+        assert.equal((err as AnchorError).error.errorMessage, errMsg);
+      }
+    });
+
+    it("should split the referral fees between the frontend and protocol", async () => {
+      await program.rpc.claimReferralFees({
+        accounts: {
+          authority: frontend.publicKey,
+          referrer,
+          referralFeeSrc,
+          frontendFeeDest,
+          protocolFeeDest,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [frontend],
+      });
+
+      const referralFeeSrcAcct = await usdcToken.getAccountInfo(referralFeeSrc);
+      const frontendFeeDestAcct = await usdcToken.getAccountInfo(
+        frontendFeeDest
+      );
+      const protocolFeeDestAcct = await usdcToken.getAccountInfo(
+        protocolFeeDest
+      );
+      assert.equal(referralFeeSrcAcct.amount.toString(), "0");
+      assert.equal(frontendFeeDestAcct.amount.toString(), "500000");
+      assert.equal(protocolFeeDestAcct.amount.toString(), "500000");
+    });
+  });
+});
@@ -31,6 +31,82 @@ import { PsyAmerican } from "../target/types/psy_american";
 export const wait = (delayMS: number) =>
   new Promise((resolve) => setTimeout(resolve, delayMS));
 
+/** The PauseConfig PDA. Pausable instructions take it whether or not it exists. */
+export const derivePauseConfig = (program: Program<PsyAmerican>) =>
+  PublicKey.findProgramAddress(
+    [new TextEncoder().encode("pauseConfig")],
+    program.programId
+  );
+
+export const deriveGovernance = (program: Program<PsyAmerican>) =>
+  PublicKey.findProgramAddress(
+    [new TextEncoder().encode("governance")],
+    program.programId
+  );
+
+export const deriveListingRegistry = (program: Program<PsyAmerican>) =>
+  PublicKey.findProgramAddress(
+    [new TextEncoder().encode("listingRegistry")],
+    program.programId
+  );
+
+export const deriveListedPair = (
+  program: Program<PsyAmerican>,
+  underlyingAssetMint: PublicKey,
+  quoteAssetMint: PublicKey
+) =>
+  PublicKey.findProgramAddress(
+    [
+      new TextEncoder().encode("listedPair"),
+      underlyingAssetMint.toBuffer(),
+      quoteAssetMint.toBuffer(),
+    ],
+    program.programId
+  );
+
+export const deriveLister = (
+  program: Program<PsyAmerican>,
+  authority: PublicKey
+) =>
+  PublicKey.findProgramAddress(
+    [new TextEncoder().encode("lister"), authority.toBuffer()],
+    program.programId
+  );
+
+export const deriveSeriesConfig = (
+  program: Program<PsyAmerican>,
+  underlyingAssetMint: PublicKey,
+  quoteAssetMint: PublicKey
+) =>
+  PublicKey.findProgramAddress(
+    [
+      new TextEncoder().encode("seriesConfig"),
+      underlyingAssetMint.toBuffer(),
+      quoteAssetMint.toBuffer(),
+    ],
+    program.programId
+  );
+
+/**
+ * A page of a pair's OptionChain. Until the chain is created initializeMarket takes the
+ * address of page 0 and the market is not indexed.
+ */
+export const deriveOptionChain = (
+  program: Program<PsyAmerican>,
+  underlyingAssetMint: PublicKey,
+  quoteAssetMint: PublicKey,
+  page = 0
+) =>
+  PublicKey.findProgramAddress(
+    [
+      new TextEncoder().encode("optionChain"),
+      underlyingAssetMint.toBuffer(),
+      quoteAssetMint.toBuffer(),
+      new BN(page).toArrayLike(Buffer, "le", 4),
+    ],
+    program.programId
+  );
+
 export const createUnderlyingAndQuoteMints = async (
   provider: Provider,
   wallet: Keypair,
@@ -546,13 +622,33 @@ export const initOptionMarket = async (
   payer: Keypair,
   optionMarket: OptionMarketV2,
   remainingAccounts: AccountMeta[],
-  instructions: TransactionInstruction[]
+  instructions: TransactionInstruction[],
+  optionDecimals = 0
 ) => {
+  const [listingRegistry] = await deriveListingRegistry(program);
+  const [listedPair] = await deriveListedPair(
+    program,
+    optionMarket.underlyingAssetMint,
+    optionMarket.quoteAssetMint
+  );
+  const [lister] = await deriveLister(program, payer.publicKey);
+  const [seriesConfig] = await deriveSeriesConfig(
+    program,
+    optionMarket.underlyingAssetMint,
+    optionMarket.quoteAssetMint
+  );
+  const [optionChain] = await deriveOptionChain(
+    program,
+    optionMarket.underlyingAssetMint,
+    optionMarket.quoteAssetMint
+  );
+  const [pauseConfig] = await derivePauseConfig(program);
   await program.rpc.initializeMarket(
     optionMarket.underlyingAmountPerContract,
     optionMarket.quoteAmountPerContract,
     optionMarket.expirationUnixTimestamp,
     optionMarket.bumpSeed,
+    optionDecimals,
     {
       accounts: {
         authority: payer.publicKey,
@@ -569,6 +665,12 @@ export const initOptionMarket = async (
         rent: SYSVAR_RENT_PUBKEY,
         systemProgram: SystemProgram.programId,
         clock: SYSVAR_CLOCK_PUBKEY,
+        listingRegistry,
+        listedPair,
+        lister,
+        seriesConfig,
+        optionChain,
+        pauseConfig,
       },
       remainingAccounts,
       signers: [payer],
@@ -577,6 +679,45 @@ export const initOptionMarket = async (
   );
 };
 
+/**
+ * Build a MintOptionV2 instruction signed by the program's wallet, with the PauseConfig the
+ * instruction takes.
+ */
+export const mintOptionV2Instruction = async (
+  program: anchor.Program<PsyAmerican>,
+  mintedOptionDest: PublicKey,
+  mintedWriterTokenDest: PublicKey,
+  underlyingAssetSrc: PublicKey,
+  size: anchor.BN,
+  optionMarket: Pick<
+    OptionMarketV2,
+    | "key"
+    | "optionMint"
+    | "writerTokenMint"
+    | "underlyingAssetMint"
+    | "underlyingAssetPool"
+  >
+) => {
+  const [pauseConfig] = await derivePauseConfig(program);
+  const ix = program.instruction.mintOptionV2(size, {
+    accounts: {
+      // @ts-ignore: TODO: Remove when anchor PR released
+      userAuthority: program.provider.wallet.publicKey,
+      underlyingAssetMint: optionMarket.underlyingAssetMint,
+      underlyingAssetPool: optionMarket.underlyingAssetPool,
+      underlyingAssetSrc,
+      optionMint: optionMarket.optionMint,
+      mintedOptionDest,
+      writerTokenMint: optionMarket.writerTokenMint,
+      mintedWriterTokenDest,
+      optionMarket: optionMarket.key,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      pauseConfig,
+    },
+  });
+  return { ix };
+};
+
 /**
  *
  * @param program
@@ -30,6 +30,7 @@ import {
 import { Token, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { OptionMarketV2 } from "../packages/psyoptions-ts/src/types";
 import { PsyAmerican } from "../target/types/psy_american";
+import { derivePauseConfig } from "./helpers";
 
 const MARKET_MAKER = new Keypair();
 export const DEX_PID = new PublicKey(
@@ -53,7 +54,8 @@ export const marketLoader =
     provider: anchor.Provider,
     program: Program<PsyAmerican>,
     optionMarketKey: PublicKey,
-    marketAuthorityBump: number
+    marketAuthorityBump: number,
+    priceOracle?: PublicKey
   ) =>
   async (marketKey: PublicKey) => {
     return new MarketProxyBuilder()
@@ -64,6 +66,12 @@ export const marketLoader =
         })
       )
       .middleware(new Validation(optionMarketKey, marketAuthorityBump))
+      .middleware(
+        new PriceBand(
+          (await derivePriceBand(program, marketKey))[0],
+          priceOracle
+        )
+      )
       .middleware(new Logger())
       .middleware(new ReferralFees())
       .load({
@@ -305,6 +313,75 @@ export class Validation implements Middleware {
   }
 }
 
+/**
+ * Prepends the market's PriceBandConfig PDA and its oracle to NewOrderV3. When
+ * the market has no price band the PDA is passed in place of the oracle.
+ */
+export class PriceBand implements Middleware {
+  priceBand: PublicKey;
+  oracle: PublicKey;
+
+  constructor(priceBand: PublicKey, oracle?: PublicKey) {
+    this.priceBand = priceBand;
+    this.oracle = oracle ?? priceBand;
+  }
+  initOpenOrders(_ix: TransactionInstruction) {}
+  newOrderV3(ix: TransactionInstruction) {
+    ix.keys = [
+      { pubkey: this.priceBand, isWritable: false, isSigner: false },
+      { pubkey: this.oracle, isWritable: false, isSigner: false },
+      ...ix.keys,
+    ];
+  }
+  cancelOrderV2(_ix: TransactionInstruction) {}
+  cancelOrderByClientIdV2(_ix: TransactionInstruction) {}
+  settleFunds(_ix: TransactionInstruction) {}
+  closeOpenOrders(_ix: TransactionInstruction) {}
+  prune(_ix: TransactionInstruction) {}
+}
+
+export const derivePriceBand = (
+  program: Program<PsyAmerican>,
+  serumMarket: PublicKey
+) =>
+  PublicKey.findProgramAddress(
+    [serumMarket.toBuffer(), new TextEncoder().encode("priceBand")],
+    program.programId
+  );
+
+/**
+ * Appends a registered PsyOptions Referrer to SettleFunds so the proxy accepts
+ * a referral wallet owned by the Referrer PDA.
+ */
+export class ReferrerMiddleware implements Middleware {
+  referrer: PublicKey;
+
+  constructor(referrer: PublicKey) {
+    this.referrer = referrer;
+  }
+  initOpenOrders(_ix: TransactionInstruction) {}
+  newOrderV3(_ix: TransactionInstruction) {}
+  cancelOrderV2(_ix: TransactionInstruction) {}
+  cancelOrderByClientIdV2(_ix: TransactionInstruction) {}
+  settleFunds(ix: TransactionInstruction) {
+    ix.keys = [
+      ...ix.keys,
+      { pubkey: this.referrer, isWritable: false, isSigner: false },
+    ];
+  }
+  closeOpenOrders(_ix: TransactionInstruction) {}
+  prune(_ix: TransactionInstruction) {}
+}
+
+export const deriveReferrer = (
+  program: Program<PsyAmerican>,
+  authority: PublicKey
+) =>
+  PublicKey.findProgramAddress(
+    [new TextEncoder().encode("referrer"), authority.toBuffer()],
+    program.programId
+  );
+
 export const initSerum = async (
   provider: anchor.Provider,
   program: Program<PsyAmerican>,
@@ -350,6 +427,7 @@ export const initSerum = async (
   const coinLotSize = new anchor.BN(1);
   const pcLotSize = new anchor.BN(100);
   const pcDustThreshold = new anchor.BN(100);
+  const [pauseConfig] = await derivePauseConfig(program);
   await program.rpc.initSerumMarket(
     new BN(MARKET_STATE_LAYOUT_V3.span),
     vaultSignerNonce,
@@ -375,6 +453,7 @@ export const initSerum = async (
         rent: SYSVAR_RENT_PUBKEY,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        pauseConfig,
       },
       signers: [wallet.payer],
     }